@@ -1,15 +1,122 @@
-use crate::compiler::types::TypeBase;
+use crate::compiler::types::{get_base_type, BaseTypes, TypeBase};
+use cyclang_parser::Type;
+use llvm_sys::prelude::LLVMValueRef;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 #[derive(Clone)]
 struct Container {
     pub trait_object: Box<dyn TypeBase>,
 }
-pub struct VariableCache {
-    map: HashMap<String, Container>,
+
+/// The bindings in scope, reached through `VariableCache::read` and `VariableCache::write`
+pub struct Bindings {
+    // every binding of a name from the outermost scope in, the last one is the one in scope
+    map: HashMap<String, Vec<Container>>,
     local: HashMap<i32, Vec<String>>,
 }
 
+impl Bindings {
+    pub fn set(&mut self, key: &str, trait_object: Box<dyn TypeBase>, depth: i32) {
+        self.map
+            .entry(key.to_string())
+            .or_default()
+            .push(Container { trait_object });
+        self.local.entry(depth).or_default().push(key.to_string());
+    }
+
+    pub fn get(&self, key: &str) -> Option<Box<dyn TypeBase>> {
+        match self.map.get(key).and_then(|bindings| bindings.last()) {
+            Some(v) => Some(dyn_clone::clone_box(&*v.trait_object)),
+            None => None,
+        }
+    }
+
+    /// Replaces the binding in scope for `key`, the new value keeps the depth the binding was
+    /// set at
+    pub fn replace(&mut self, key: &str, trait_object: Box<dyn TypeBase>) {
+        if let Some(container) = self
+            .map
+            .get_mut(key)
+            .and_then(|bindings| bindings.last_mut())
+        {
+            container.trait_object = trait_object;
+        }
+    }
+
+    /// The binding in scope for every name
+    pub fn snapshot(&self) -> HashMap<String, Box<dyn TypeBase>> {
+        self.map
+            .iter()
+            .filter_map(|(key, bindings)| {
+                let container = bindings.last()?;
+                Some((key.clone(), dyn_clone::clone_box(&*container.trait_object)))
+            })
+            .collect()
+    }
+
+    /// Moves the binding in scope for `old_key` to `new_key` at the same depth, the value
+    /// isn't cloned and no new LLVM value is built for it
+    pub fn rename(&mut self, old_key: &str, new_key: &str) {
+        let container = match self
+            .map
+            .get_mut(old_key)
+            .and_then(|bindings| bindings.pop())
+        {
+            Some(container) => container,
+            None => return,
+        };
+        if self
+            .map
+            .get(old_key)
+            .is_some_and(|bindings| bindings.is_empty())
+        {
+            self.map.remove(old_key);
+        }
+        self.map
+            .entry(new_key.to_string())
+            .or_default()
+            .push(container);
+        // the binding in scope was set at the deepest depth that declared the name
+        if let Some((_, locals)) = self
+            .local
+            .iter_mut()
+            .filter(|(_, locals)| locals.iter().any(|local| local == old_key))
+            .max_by_key(|(depth, _)| **depth)
+        {
+            if let Some(local) = locals.iter_mut().rev().find(|local| *local == old_key) {
+                *local = new_key.to_string();
+            }
+        }
+    }
+
+    // drops every binding of key, whatever scope it was set in
+    pub fn del(&mut self, key: &str) {
+        self.map.remove(key);
+    }
+
+    // pops the bindings set at depth, so a variable an inner block shadowed is back in scope
+    pub fn del_locals(&mut self, depth: i32) {
+        if let Some(locals) = self.local.remove(&depth) {
+            for local in locals {
+                if let Some(bindings) = self.map.get_mut(&local) {
+                    bindings.pop();
+                    if bindings.is_empty() {
+                        self.map.remove(&local);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Variables and functions by name. Clones share the same bindings, the bindings hold LLVM
+/// values that belong to one thread's context so the cache isn't `Send`
+#[derive(Clone)]
+pub struct VariableCache {
+    bindings: Arc<RwLock<Bindings>>,
+}
+
 impl Default for VariableCache {
     fn default() -> Self {
         Self::new()
@@ -17,47 +124,200 @@ impl Default for VariableCache {
 }
 
 impl VariableCache {
+    // the lock is kept for when each thread compiling a module has a context of its own
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new() -> Self {
         VariableCache {
-            map: HashMap::new(),
-            local: HashMap::new(),
+            bindings: Arc::new(RwLock::new(Bindings {
+                map: HashMap::new(),
+                local: HashMap::new(),
+            })),
         }
     }
 
-    pub fn set(&mut self, key: &str, trait_object: Box<dyn TypeBase>, depth: i32) {
-        let mut locals: HashMap<i32, bool> = HashMap::new();
-        locals.insert(depth, true);
-        self.map.insert(key.to_string(), Container { trait_object });
-        match self.local.get(&depth) {
-            Some(val) => {
-                let mut val_clone = val.clone();
-                val_clone.push(key.to_string());
-                self.local.insert(depth, val_clone);
-            }
-            None => {
-                self.local.insert(depth, vec![key.to_string()]);
-            }
-        }
+    /// Shared access for lookups, any number of readers can hold it at once
+    pub fn read(&self) -> RwLockReadGuard<'_, Bindings> {
+        self.bindings
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
-    pub fn get(&self, key: &str) -> Option<Box<dyn TypeBase>> {
-        match self.map.get(key) {
-            Some(v) => Some(dyn_clone::clone_box(&*v.trait_object)),
-            None => None,
-        }
+    /// Exclusive access for declaring variables and leaving scopes
+    pub fn write(&self) -> RwLockWriteGuard<'_, Bindings> {
+        self.bindings
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
+}
 
-    #[allow(dead_code)]
-    fn del(&mut self, key: &str) {
-        self.map.remove(key);
+// the argument types an overload is declared with and its functions, a function shadowing
+// another with the same signature is last
+type Overload = (Vec<Type>, Vec<Container>);
+
+/// User functions, a name has one overload for each list of argument types it's declared with
+pub struct Overloads {
+    // the overloads of each name in the order they were declared. Like `Bindings`, a function
+    // declared in a block shadows one with the same signature until the block ends
+    map: HashMap<String, Vec<Overload>>,
+    local: HashMap<i32, Vec<(String, Vec<Type>)>>,
+}
+
+impl Overloads {
+    pub fn set(
+        &mut self,
+        key: &str,
+        arg_types: Vec<Type>,
+        trait_object: Box<dyn TypeBase>,
+        depth: i32,
+    ) {
+        let overloads = self.map.entry(key.to_string()).or_default();
+        let container = Container { trait_object };
+        match overloads
+            .iter_mut()
+            .find(|(params, _)| *params == arg_types)
+        {
+            Some((_, containers)) => containers.push(container),
+            None => overloads.push((arg_types.clone(), vec![container])),
+        }
+        self.local
+            .entry(depth)
+            .or_default()
+            .push((key.to_string(), arg_types));
     }
 
+    // pops the functions declared at depth, so a function declared inside another one can't
+    // be called once the enclosing function's body ends
     pub fn del_locals(&mut self, depth: i32) {
-        if let Some(v) = self.local.get(&depth) {
-            for local in v.iter() {
-                self.map.remove(&local.to_string());
+        if let Some(locals) = self.local.remove(&depth) {
+            for (name, arg_types) in locals {
+                let Some(overloads) = self.map.get_mut(&name) else {
+                    continue;
+                };
+                if let Some(index) = overloads
+                    .iter()
+                    .position(|(params, _)| *params == arg_types)
+                {
+                    overloads[index].1.pop();
+                    if overloads[index].1.is_empty() {
+                        overloads.remove(index);
+                    }
+                }
+                if overloads.is_empty() {
+                    self.map.remove(&name);
+                }
             }
-            self.local.remove(&depth);
         }
     }
+
+    // drops the overloads built as function, for a function the repl discarded
+    pub fn del_function(&mut self, function: LLVMValueRef) {
+        self.map.retain(|_, overloads| {
+            overloads.retain_mut(|(_, containers)| {
+                containers.retain(|overload| overload.trait_object.get_value() != function);
+                !containers.is_empty()
+            });
+            !overloads.is_empty()
+        });
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// The overload of `key` to call with arguments of `arg_types`, picked by `pick_overload`.
+    /// A function that isn't overloaded is picked whatever the arguments are
+    pub fn resolve(&self, key: &str, arg_types: &[BaseTypes]) -> Option<Box<dyn TypeBase>> {
+        self.resolve_with_params(key, arg_types)
+            .map(|(_, function)| function)
+    }
+
+    /// Like `resolve`, with the argument types the picked overload was declared with so the
+    /// arguments of a function that isn't overloaded can be checked against them
+    pub fn resolve_with_params(
+        &self,
+        key: &str,
+        arg_types: &[BaseTypes],
+    ) -> Option<(Vec<Type>, Box<dyn TypeBase>)> {
+        let overloads: Vec<(&Vec<Type>, &Container)> = self
+            .map
+            .get(key)?
+            .iter()
+            .filter_map(|(params, containers)| Some((params, containers.last()?)))
+            .collect();
+        let overload = match overloads.as_slice() {
+            [overload] => Some(overload),
+            _ => pick_overload(&overloads, |(params, _)| params, arg_types),
+        };
+        overload.map(|(params, container)| {
+            (
+                params.to_vec(),
+                dyn_clone::clone_box(&*container.trait_object),
+            )
+        })
+    }
+}
+
+/// The overload a call with arguments of `arg_types` picks from `overloads`, which are in the
+/// order they were declared. The first one that takes exactly those types is picked, then the
+/// first one an `i32` argument can be widened to an `i64` for. The type checker picks with this
+/// too, so it agrees with codegen on which overload is called
+pub fn pick_overload<'a, T>(
+    overloads: &'a [T],
+    params: fn(&T) -> &[Type],
+    arg_types: &[BaseTypes],
+) -> Option<&'a T> {
+    let takes = |overload: &&T, widen: bool| {
+        let params = params(overload);
+        params.len() == arg_types.len()
+            && params
+                .iter()
+                .zip(arg_types)
+                .all(|(param, arg)| match (get_base_type(param), arg) {
+                    (Some(BaseTypes::Number64), BaseTypes::Number) => widen,
+                    (param, arg) => param.as_ref() == Some(arg),
+                })
+    };
+    overloads
+        .iter()
+        .find(|overload| takes(overload, false))
+        .or_else(|| overloads.iter().find(|overload| takes(overload, true)))
+}
+
+/// Functions by name and argument types, clones share the same overloads like
+/// `VariableCache`
+#[derive(Clone)]
+pub struct FunctionCache {
+    overloads: Arc<RwLock<Overloads>>,
+}
+
+impl Default for FunctionCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FunctionCache {
+    // like `VariableCache`, the lock is kept for when each thread compiling a module has a
+    // context of its own
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new() -> Self {
+        FunctionCache {
+            overloads: Arc::new(RwLock::new(Overloads {
+                map: HashMap::new(),
+                local: HashMap::new(),
+            })),
+        }
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, Overloads> {
+        self.overloads
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, Overloads> {
+        self.overloads
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
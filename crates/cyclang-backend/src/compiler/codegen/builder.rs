@@ -1,52 +1,98 @@
 use crate::compiler::codegen::context::{LLVMFunction, LLVMFunctionCache};
+use crate::compiler::codegen::debug::DebugInfoBuilder;
+use crate::compiler::codegen::stdlib::float::load_float_helper_funcs;
+use crate::compiler::codegen::stdlib::io::load_io_helper_funcs;
 use crate::compiler::codegen::stdlib::list::load_list_helper_funcs;
 use crate::compiler::codegen::stdlib::load_bitcode_and_set_stdlib_funcs;
 use crate::compiler::codegen::stdlib::string::load_string_helper_funcs;
+use crate::compiler::codegen::target::{Target, TargetArch, TargetInfo};
 use crate::compiler::codegen::{
-    cstr_from_string, int1_type, int32_ptr_type, int64_type, int8_ptr_type,
+    cstr_from_string, float_type, int1_type, int32_ptr_type, int32_type, int64_ptr_type,
+    int64_type, int8_ptr_type, int8_type,
 };
 use crate::compiler::context::{ASTContext, LLVMCodegenVisitor};
+use crate::compiler::error::CycloError;
 use crate::compiler::types::bool::BoolType;
+use crate::compiler::types::float::FloatType;
 use crate::compiler::types::list::ListType;
 use crate::compiler::types::num::NumberType;
+use crate::compiler::types::num64::NumberType64;
 use crate::compiler::types::return_type::ReturnType;
+use crate::compiler::types::string::StringType;
+use crate::compiler::types::struct_type::{StructLayout, StructType};
+use crate::compiler::types::tuple::{check_component_type, TupleType};
 use crate::compiler::types::void::VoidType;
-use crate::compiler::types::{BaseTypes, TypeBase};
+use crate::compiler::types::{get_base_type, BaseTypes, TypeBase};
 use crate::compiler::visitor::Visitor;
-use crate::compiler::CompileOptions;
+use crate::compiler::{CompileOptions, ExecutionMode};
 use anyhow::{anyhow, Result};
-use cyclang_parser::{Expression, Type};
-use libc::{c_uint};
+use cyclang_parser::{Expression, FuncAttribute, Type};
+use libc::c_uint;
+use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyModule};
 use llvm_sys::core::{
-    LLVMAddFunction, LLVMAppendBasicBlock, LLVMAppendBasicBlockInContext, LLVMArrayType2,
-    LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildBr, LLVMBuildCall2, LLVMBuildCondBr, LLVMBuildGEP2,
-    LLVMBuildGlobalStringPtr, LLVMBuildICmp, LLVMBuildLoad2, LLVMBuildMul, LLVMBuildRet,
-    LLVMBuildRetVoid, LLVMBuildSDiv, LLVMBuildSExt, LLVMBuildStore, LLVMBuildSub, LLVMConstArray2,
-    LLVMConstInt, LLVMContextCreate, LLVMContextDispose, LLVMCreateBuilderInContext,
+    LLVMAddAttributeAtIndex, LLVMAddCase, LLVMAddFunction, LLVMAddGlobal, LLVMAddIncoming,
+    LLVMAppendBasicBlock, LLVMAppendBasicBlockInContext, LLVMArrayType2, LLVMBuildAdd,
+    LLVMBuildAlloca, LLVMBuildAnd, LLVMBuildBr, LLVMBuildCall2, LLVMBuildCondBr,
+    LLVMBuildExtractValue, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv, LLVMBuildFMul,
+    LLVMBuildFPToSI, LLVMBuildFSub, LLVMBuildGEP2, LLVMBuildGlobalStringPtr, LLVMBuildICmp,
+    LLVMBuildInsertValue, LLVMBuildLoad2, LLVMBuildMul, LLVMBuildNot, LLVMBuildPhi,
+    LLVMBuildPtrToInt, LLVMBuildRet, LLVMBuildRetVoid, LLVMBuildSDiv, LLVMBuildSExt,
+    LLVMBuildSIToFP, LLVMBuildSelect, LLVMBuildStore, LLVMBuildStructGEP2, LLVMBuildSub,
+    LLVMBuildSwitch, LLVMBuildTrunc, LLVMBuildUnreachable, LLVMBuildZExt, LLVMCloneModule,
+    LLVMConstArray2, LLVMConstInt, LLVMConstNull, LLVMConstPointerNull, LLVMConstReal,
+    LLVMConstStringInContext2, LLVMContextCreate, LLVMContextDispose, LLVMCreateBuilderInContext,
+    LLVMCreateEnumAttribute, LLVMCreateStringAttribute, LLVMDeleteBasicBlock, LLVMDeleteFunction,
     LLVMDisposeBuilder, LLVMDisposeMessage, LLVMDisposeModule, LLVMFunctionType,
-    LLVMGetIntTypeWidth, LLVMGetNamedFunction, LLVMGetParam, LLVMGetTypeByName2,
-    LLVMInt8TypeInContext, LLVMModuleCreateWithName, LLVMPointerType,
-    LLVMPositionBuilderAtEnd, LLVMPrintModuleToFile, LLVMSetTarget, LLVMTypeOf,
-    LLVMVoidTypeInContext,
+    LLVMGetBasicBlockTerminator, LLVMGetEnumAttributeKindForName, LLVMGetFirstBasicBlock,
+    LLVMGetFirstFunction, LLVMGetFirstGlobal, LLVMGetFirstInstruction, LLVMGetInsertBlock,
+    LLVMGetIntTypeWidth, LLVMGetLastInstruction, LLVMGetModuleContext, LLVMGetNamedFunction,
+    LLVMGetNextBasicBlock, LLVMGetNextFunction, LLVMGetNextGlobal, LLVMGetNextInstruction,
+    LLVMGetParam, LLVMGetTarget, LLVMGetTypeByName2, LLVMGetTypeKind, LLVMGetUndef,
+    LLVMGetValueName2, LLVMInstructionEraseFromParent, LLVMInt8TypeInContext,
+    LLVMIsAGlobalVariable, LLVMIsDeclaration, LLVMModuleCreateWithName, LLVMPointerType,
+    LLVMPositionBuilderAtEnd, LLVMPrintModuleToFile, LLVMPrintModuleToString,
+    LLVMReplaceAllUsesWith, LLVMSetGlobalConstant, LLVMSetInitializer, LLVMSetLinkage,
+    LLVMSetTarget, LLVMSetUnnamedAddress, LLVMSetValueName2, LLVMSetVisibility, LLVMStructType,
+    LLVMTypeOf, LLVMVoidTypeInContext,
 };
+use llvm_sys::error::{LLVMDisposeErrorMessage, LLVMGetErrorMessage};
 use llvm_sys::execution_engine::{
-    LLVMCreateExecutionEngineForModule, LLVMDisposeExecutionEngine, LLVMGetFunctionAddress,
-    LLVMLinkInMCJIT,
+    LLVMAddModule, LLVMCreateExecutionEngineForModule, LLVMDisposeExecutionEngine,
+    LLVMExecutionEngineRef, LLVMGetFunctionAddress, LLVMLinkInMCJIT,
 };
 use llvm_sys::prelude::{
-    LLVMBasicBlockRef, LLVMBool, LLVMBuilderRef, LLVMContextRef, LLVMModuleRef, LLVMTypeRef,
-    LLVMValueRef,
+    LLVMBasicBlockRef, LLVMBool, LLVMBuilderRef, LLVMContextRef, LLVMMetadataRef, LLVMModuleRef,
+    LLVMTypeRef, LLVMValueRef,
 };
-use llvm_sys::target::{LLVM_InitializeNativeAsmPrinter, LLVM_InitializeNativeTarget};
+use llvm_sys::target::{
+    LLVMDisposeTargetData, LLVMSetModuleDataLayout, LLVM_InitializeNativeAsmPrinter,
+    LLVM_InitializeNativeTarget,
+};
+use llvm_sys::target_machine::{
+    LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetDataLayout,
+    LLVMCreateTargetMachine, LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple,
+    LLVMGetTargetFromTriple, LLVMRelocMode, LLVMTargetMachineEmitToFile, LLVMTargetRef,
+};
+use llvm_sys::transforms::pass_builder::{
+    LLVMCreatePassBuilderOptions, LLVMDisposePassBuilderOptions, LLVMRunPasses,
+};
+use llvm_sys::LLVMAttributeFunctionIndex;
 use llvm_sys::LLVMIntPredicate;
 use llvm_sys::LLVMIntPredicate::{
-    LLVMIntEQ, LLVMIntNE, LLVMIntSGE, LLVMIntSGT, LLVMIntSLE, LLVMIntSLT,
+    LLVMIntEQ, LLVMIntNE, LLVMIntSGE, LLVMIntSGT, LLVMIntSLE, LLVMIntSLT, LLVMIntULE,
+};
+use llvm_sys::LLVMRealPredicate;
+use llvm_sys::LLVMRealPredicate::{
+    LLVMRealOEQ, LLVMRealOGE, LLVMRealOGT, LLVMRealOLE, LLVMRealOLT, LLVMRealUNE,
 };
-use std::collections::HashMap;
+use llvm_sys::{LLVMLinkage, LLVMTypeKind, LLVMUnnamedAddr, LLVMVisibility};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
 use std::process::Command;
 use std::ptr;
-use cyclang_parser::Expression::{BlockStmt, LetStmt, Number};
+
+// the block an if arm ends in and the variable bindings in scope at its end
+type ArmEnd = (LLVMBasicBlockRef, HashMap<String, Box<dyn TypeBase>>);
 
 pub struct LLVMCodegenBuilder {
     pub builder: LLVMBuilderRef,
@@ -57,7 +103,21 @@ pub struct LLVMCodegenBuilder {
     pub printf_str_value: LLVMValueRef,
     pub printf_str_num_value: LLVMValueRef,
     pub printf_str_num64_value: LLVMValueRef,
-    is_execution_engine: bool,
+    string_constant_cache: HashMap<Vec<u8>, LLVMValueRef>,
+    debug_info: Option<DebugInfoBuilder>,
+    optimization_level: u8,
+    emit_object: bool,
+    print_ir: bool,
+    verify_module: bool,
+    // exit blocks of the enclosing loops, innermost last, so `break` knows where to branch
+    loop_exit_blocks: Vec<LLVMBasicBlockRef>,
+    // the function and loop nesting each enclosing if arm was started in, innermost last
+    if_arms: Vec<(LLVMValueRef, usize)>,
+    execution_mode: ExecutionMode,
+    target: Option<Target>,
+    // the repl builds every statement into its own function and hands it to this engine to
+    // run, the module only keeps declarations of what the engine already has
+    repl_engine: Option<LLVMExecutionEngineRef>,
 }
 
 macro_rules! llvm_build_fn {
@@ -70,33 +130,60 @@ impl LLVMCodegenBuilder {
     // Initialise execution engine and LLVM IR constructs
     pub fn init(compile_options: Option<CompileOptions>) -> Result<LLVMCodegenBuilder> {
         unsafe {
-            let mut is_execution_engine = false;
-            let mut is_default_target: bool = true;
+            let mut execution_mode = ExecutionMode::default();
+            let mut is_debug_info = false;
+            let mut optimization_level = 0;
+            let mut emit_object = false;
+            let mut print_ir = false;
+            let mut verify_module = cfg!(debug_assertions);
+            let mut target = None;
+            let mut source_file = None;
 
             if let Some(compile_options) = compile_options {
-                is_execution_engine = compile_options.is_execution_engine;
-                is_default_target = compile_options.target.is_none();
+                execution_mode = compile_options.execution_mode;
+                is_debug_info = compile_options.debug_info;
+                optimization_level = compile_options.optimization_level;
+                emit_object = compile_options.emit_object;
+                print_ir = compile_options.print_ir;
+                verify_module |= compile_options.verify_module;
+                target = compile_options.target;
+                source_file = compile_options.source_file;
+            }
+            if optimization_level > 3 {
+                return Err(anyhow!(
+                    "optimization level {} is not between 0 and 3",
+                    optimization_level
+                ));
             }
 
-            if is_execution_engine {
-                LLVMLinkInMCJIT();
+            if let Some(target) = &target {
+                if execution_mode == ExecutionMode::Jit {
+                    return Err(anyhow!(
+                        "unable to JIT compile for target {}, compile it ahead of time instead",
+                        target.get_llvm_target_name()
+                    ));
+                }
             }
 
-            if is_default_target {
-                LLVM_InitializeNativeTarget();
-                LLVM_InitializeNativeAsmPrinter();
+            if execution_mode == ExecutionMode::Jit {
+                LLVMLinkInMCJIT();
             }
-            if !is_default_target {
-                compile_options.unwrap().target.unwrap().initialize();
+
+            match &target {
+                Some(target) => target.initialize()?,
+                None => {
+                    LLVM_InitializeNativeTarget();
+                    LLVM_InitializeNativeAsmPrinter();
+                }
             }
 
             let context = LLVMContextCreate();
             let module = LLVMModuleCreateWithName(cstr_from_string("main").as_ptr());
             let builder = LLVMCreateBuilderInContext(context);
-            if !is_default_target {
+            if let Some(target) = &target {
                 LLVMSetTarget(
                     module,
-                    cstr_from_string("wasm32-unknown-unknown-wasm").as_ptr(),
+                    cstr_from_string(&target.get_llvm_target_name()).as_ptr(),
                 );
             }
 
@@ -118,6 +205,15 @@ impl LLVMCodegenBuilder {
             );
             LLVMPositionBuilderAtEnd(builder, main_block);
 
+            let mut debug_info = None;
+            if is_debug_info {
+                let mut debug_info_builder =
+                    DebugInfoBuilder::init(context, module, source_file.as_deref());
+                debug_info_builder.scope = debug_info_builder.create_subprogram(main_func, "main");
+                debug_info_builder.set_location(builder);
+                debug_info = Some(debug_info_builder);
+            }
+
             // Define common functions
 
             let format_str = "%d\n";
@@ -128,7 +224,7 @@ impl LLVMCodegenBuilder {
             );
             let printf_str_num64_value = LLVMBuildGlobalStringPtr(
                 builder,
-                cstr_from_string("%llu\n").as_ptr(),
+                cstr_from_string("%lld\n").as_ptr(),
                 cstr_from_string("number64_printf_val").as_ptr(),
             );
             let printf_str_value = LLVMBuildGlobalStringPtr(
@@ -154,67 +250,618 @@ impl LLVMCodegenBuilder {
                 printf_str_value,
                 printf_str_num_value,
                 printf_str_num64_value,
-                is_execution_engine,
+                string_constant_cache: HashMap::new(),
+                debug_info,
+                optimization_level,
+                emit_object,
+                print_ir,
+                verify_module,
+                loop_exit_blocks: vec![],
+                if_arms: vec![],
+                execution_mode,
+                target,
+                repl_engine: None,
             };
+            codegen_builder.set_data_layout()?;
             codegen_builder.build_helper_funcs(main_block);
             Ok(codegen_builder)
         }
     }
 
     pub fn dispose_and_get_module_str(&self) -> Result<String> {
-        unsafe {
+        let result = unsafe {
             self.build_ret_void();
+            if let Some(debug_info) = self.debug_info {
+                debug_info.finalize();
+            }
+            let result = match self
+                .verify()
+                .and_then(|_| self.optimize(self.optimization_level))
+            {
+                Ok(()) => {
+                    if self.print_ir {
+                        self.print_module_to_stdout();
+                    }
+                    match self.execution_mode {
+                        ExecutionMode::Jit => self.run_execution_engine(),
+                        // cross compiled modules are always compiled with the target machine
+                        ExecutionMode::Aot if self.emit_object || self.target.is_some() => {
+                            self.emit_object_file()
+                        }
+                        ExecutionMode::Aot => self.print_module_to_file(),
+                    }
+                }
+                Err(e) => {
+                    LLVMDisposeModule(self.module);
+                    Err(e)
+                }
+            };
+            // clean up, the module has already been disposed of by the execution engine
+            // or after writing it out
+            LLVMDisposeBuilder(self.builder);
+            LLVMContextDispose(self.context);
+            result
+        };
+        result?;
+        self.emit_binary()
+    }
 
-            // Run execution engine
-            let mut engine = ptr::null_mut();
+    /// verify
+    ///
+    /// Checks the module is valid LLVM IR before it's optimized and run, so invalid IR is
+    /// reported as an error instead of crashing the JIT or clang. Skipped unless
+    /// `verify_module` is set or this is a debug build.
+    ///
+    pub fn verify(&self) -> Result<()> {
+        if !self.verify_module {
+            return Ok(());
+        }
+        self.verify_ir()
+    }
+
+    // the repl checks every statement, invalid IR would crash the JIT and end the session
+    fn verify_ir(&self) -> Result<()> {
+        unsafe {
             let mut error = ptr::null_mut();
+            let failed = LLVMVerifyModule(
+                self.module,
+                LLVMVerifierFailureAction::LLVMReturnStatusAction,
+                &mut error,
+            );
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            if failed != 0 {
+                return Err(anyhow!(
+                    "module verification failed: {}",
+                    message.trim_end()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// optimize
+    ///
+    /// Runs the standard LLVM optimization passes over the module, more passes are added as the
+    /// level increases and level 0 leaves the module untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The optimization level, from 0 to 3
+    ///
+    pub fn optimize(&self, level: u8) -> Result<()> {
+        // the same pipelines clang runs for -O1 to -O3, they start with the always inliner so
+        // #[inline] functions are inlined before the rest of the pipeline simplifies the call
+        // sites and promote the stack slots every value is stored in to registers
+        let passes = match level {
+            0 => return Ok(()),
+            1 => "default<O1>",
+            2 => "default<O2>",
+            3 => "default<O3>",
+            _ => {
+                return Err(anyhow!(
+                    "optimization level {} is not between 0 and 3",
+                    level
+                ))
+            }
+        };
+        unsafe {
+            let options = LLVMCreatePassBuilderOptions();
+            let error = LLVMRunPasses(
+                self.module,
+                cstr_from_string(passes).as_ptr(),
+                ptr::null_mut(),
+                options,
+            );
+            LLVMDisposePassBuilderOptions(options);
+            if !error.is_null() {
+                let message_ptr = LLVMGetErrorMessage(error);
+                let message = std::ffi::CStr::from_ptr(message_ptr)
+                    .to_string_lossy()
+                    .to_string();
+                LLVMDisposeErrorMessage(message_ptr);
+                return Err(anyhow!("failed to run optimization passes: {}", message));
+            }
+        }
+        Ok(())
+    }
+
+    // Call the main function with the execution engine, which takes ownership of the module
+    unsafe fn run_execution_engine(&self) -> Result<()> {
+        let mut engine = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        if LLVMCreateExecutionEngineForModule(&mut engine, self.module, &mut error) != 0 {
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            return Err(anyhow!("failed to create execution engine: {}", message));
+        }
+        let main_func: extern "C" fn() =
+            std::mem::transmute(LLVMGetFunctionAddress(engine, c"main".as_ptr() as *const _));
+        main_func();
+        LLVMDisposeExecutionEngine(engine);
+        Ok(())
+    }
+
+    /// enable_repl
+    ///
+    /// Closes `main` and hands the runtime functions to the repl's execution engine, so each
+    /// statement can be built into a function of its own. Must be called before any
+    /// statements are compiled.
+    ///
+    pub fn enable_repl(&mut self) -> Result<()> {
+        if self.execution_mode != ExecutionMode::Jit {
+            return Err(anyhow!("the repl can only be used with the JIT"));
+        }
+        self.build_ret_void();
+        self.verify_ir()?;
+        unsafe { self.add_module_to_repl_engine() }?;
+        Ok(())
+    }
+
+    /// start_repl_statement
+    ///
+    /// Adds the function a repl statement is built into and moves the builder to its entry
+    /// block.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The index of the statement in the repl session
+    ///
+    pub fn start_repl_statement(&mut self, line: u32) -> Result<()> {
+        if self.repl_engine.is_none() {
+            return Err(anyhow!("the repl has not been enabled"));
+        }
+        unsafe {
+            let func_type =
+                LLVMFunctionType(LLVMVoidTypeInContext(self.context), ptr::null_mut(), 0, 0);
+            let function = LLVMAddFunction(
+                self.module,
+                cstr_from_string(&format!("repl_{}", line)).as_ptr(),
+                func_type,
+            );
+            let block = self.append_basic_block(function, "entry");
+            self.current_function = LLVMFunction {
+                function,
+                func_type,
+                entry_block: block,
+                block,
+                symbol_table: HashMap::new(),
+                args: vec![],
+                return_type: Type::None,
+            };
+            self.position_builder_at_end(block);
+        }
+        Ok(())
+    }
+
+    /// run_repl_statement
+    ///
+    /// Hands the function of a repl statement, with any functions it defined, to the execution
+    /// engine and calls it. The statements before it aren't run again.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The index of the statement in the repl session
+    ///
+    pub fn run_repl_statement(&mut self, line: u32) -> Result<()> {
+        if self.repl_engine.is_none() {
+            return Err(anyhow!("the repl has not been enabled"));
+        }
+        self.build_ret_void();
+        self.verify_ir()?;
+        unsafe {
+            let engine = self.add_module_to_repl_engine()?;
+            let name = cstr_from_string(&format!("repl_{}", line));
+            let address = LLVMGetFunctionAddress(engine, name.as_ptr());
+            if address == 0 {
+                return Err(anyhow!("unable to find repl statement {}", line));
+            }
+            let statement: extern "C" fn() = std::mem::transmute(address);
+            statement();
+            libc::fflush(ptr::null_mut());
+        }
+        Ok(())
+    }
 
-            // Call the main function. It should execute its code.
-            if self.is_execution_engine {
-                if LLVMCreateExecutionEngineForModule(&mut engine, self.module, &mut error) != 0 {
-                    LLVMDisposeMessage(error);
-                    panic!("Failed to create execution engine");
+    /// discard_repl_statement
+    ///
+    /// Deletes the function of a repl statement that failed to compile, along with every
+    /// function defined by it, so the next statement starts from a valid module. Returns the
+    /// deleted functions so they can be dropped from the function cache.
+    ///
+    pub fn discard_repl_statement(&mut self) -> Vec<LLVMValueRef> {
+        self.loop_exit_blocks.clear();
+        self.if_arms.clear();
+        // everything defined before the statement was handed to the engine and is only
+        // declared in the module, so any function with a body comes from this statement
+        let mut defined = vec![];
+        unsafe {
+            let mut function = LLVMGetFirstFunction(self.module);
+            while !function.is_null() {
+                if LLVMIsDeclaration(function) == 0 {
+                    defined.push(function);
                 }
-                let main_func: extern "C" fn() = std::mem::transmute(LLVMGetFunctionAddress(
-                    engine,
-                    c"main".as_ptr() as *const _,
-                ));
-                main_func();
+                function = LLVMGetNextFunction(function);
+            }
+            for function in &defined {
+                delete_function_body(*function);
+            }
+            for function in &defined {
+                LLVMDeleteFunction(*function);
             }
+        }
+        defined
+    }
 
-            if !self.is_execution_engine {
-                LLVMPrintModuleToFile(
-                    self.module,
-                    cstr_from_string("bin/main.ll").as_ptr(),
-                    ptr::null_mut(),
-                );
+    // moves what's been defined in the module since the last call into a copy owned by the
+    // repl's engine, the module keeps declarations of them for the next statement to link to
+    unsafe fn add_module_to_repl_engine(&mut self) -> Result<LLVMExecutionEngineRef> {
+        // the copies in the engine are linked by name, so nothing can stay internal
+        let mut function = LLVMGetFirstFunction(self.module);
+        while !function.is_null() {
+            if LLVMIsDeclaration(function) == 0 {
+                LLVMSetLinkage(function, LLVMLinkage::LLVMExternalLinkage);
             }
-            // clean up
-            LLVMDisposeBuilder(self.builder);
-            if self.is_execution_engine {
-                LLVMDisposeExecutionEngine(engine);
+            function = LLVMGetNextFunction(function);
+        }
+        let mut global = LLVMGetFirstGlobal(self.module);
+        while !global.is_null() {
+            if LLVMIsDeclaration(global) == 0 && !is_llvm_global(global) {
+                LLVMSetLinkage(global, LLVMLinkage::LLVMExternalLinkage);
+                let mut len = 0;
+                LLVMGetValueName2(global, &mut len);
+                if len == 0 {
+                    let name = c"repl.global";
+                    LLVMSetValueName2(global, name.as_ptr(), name.count_bytes());
+                }
             }
-            if !self.is_execution_engine {
-                LLVMDisposeModule(self.module);
+            global = LLVMGetNextGlobal(global);
+        }
+
+        let module = LLVMCloneModule(self.module);
+
+        let mut function = LLVMGetFirstFunction(self.module);
+        while !function.is_null() {
+            delete_function_body(function);
+            function = LLVMGetNextFunction(function);
+        }
+        let mut global = LLVMGetFirstGlobal(self.module);
+        while !global.is_null() {
+            if !is_llvm_global(global) {
+                LLVMSetInitializer(global, ptr::null_mut());
             }
-            LLVMContextDispose(self.context);
-            self.emit_binary()
+            global = LLVMGetNextGlobal(global);
+        }
+
+        if let Some(engine) = self.repl_engine {
+            LLVMAddModule(engine, module);
+            return Ok(engine);
+        }
+        let mut engine = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        if LLVMCreateExecutionEngineForModule(&mut engine, module, &mut error) != 0 {
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            return Err(anyhow!("failed to create execution engine: {}", message));
         }
+        self.repl_engine = Some(engine);
+        Ok(engine)
     }
 
-    pub fn emit_binary(&self) -> Result<String> {
-        if !self.is_execution_engine {
-            Command::new("clang")
-                .arg("bin/main.ll")
-                .arg("-o")
-                .arg("bin/main")
-                .output()?;
-            let output = Command::new("bin/main").output()?;
-            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    /// build_const_number
+    ///
+    /// Builds a numeric constant without storing it on the stack, so it has no pointer and is
+    /// always read from the constant itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A Number, Number64 or Float expression
+    ///
+    pub fn build_const_number(&self, value: &Expression) -> Result<Box<dyn TypeBase>> {
+        match value {
+            Expression::Number(val) => Ok(Box::new(NumberType {
+                name: "num32".to_string(),
+                llvm_value: self.const_int(int32_type(), *val as u64, 0),
+                llvm_value_pointer: None,
+            })),
+            Expression::Number64(val) => Ok(Box::new(NumberType64 {
+                name: "num64".to_string(),
+                llvm_value: self.const_int(int64_type(), *val as u64, 0),
+                llvm_value_pointer: None,
+            })),
+            Expression::Float(val) => Ok(Box::new(FloatType {
+                name: "float".to_string(),
+                llvm_value: self.const_real(float_type(), *val),
+                llvm_value_pointer: None,
+            })),
+            _ => Err(anyhow!("type is not a number (i32,i64,float)")),
+        }
+    }
+
+    /// new_print
+    ///
+    /// Prints a value followed by a newline.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to print
+    ///
+    pub fn new_print(&mut self, value: &dyn TypeBase) -> Result<()> {
+        value.print(self)
+    }
+
+    /// new_repl_result
+    ///
+    /// Prints the result of a repl statement with its type, e.g. `i32 = 3`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The result of the statement
+    ///
+    pub fn new_repl_result(&mut self, value: &dyn TypeBase) -> Result<()> {
+        let print_func = self
+            .llvm_func_cache
+            .get("printf")
+            .ok_or(anyhow!("unable to call print function"))?;
+        let prefix = unsafe {
+            LLVMBuildGlobalStringPtr(
+                self.builder,
+                cstr_from_string(&format!("{} = ", value.get_type())).as_ptr(),
+                cstr_from_string("repl_prefix").as_ptr(),
+            )
+        };
+        self.build_call(print_func, vec![prefix], 1, "");
+        value.print(self)
+    }
+
+    /// new_repl_variable
+    ///
+    /// Adds the global a repl variable is kept in between statements.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable
+    /// * `llvm_type` - The type of the value stored in it
+    ///
+    pub fn new_repl_variable(&self, name: &str, llvm_type: LLVMTypeRef) -> LLVMValueRef {
+        unsafe {
+            let name = cstr_from_string(&format!("repl.{}", name));
+            let global = LLVMAddGlobal(self.module, llvm_type, name.as_ptr());
+            LLVMSetInitializer(global, LLVMConstNull(llvm_type));
+            global
+        }
+    }
+
+    // the JIT never writes bin/main.ll, so this is the only way to see its IR
+    unsafe fn print_module_to_stdout(&self) {
+        let ir = LLVMPrintModuleToString(self.module);
+        println!("{}", std::ffi::CStr::from_ptr(ir).to_string_lossy());
+        LLVMDisposeMessage(ir);
+    }
+
+    unsafe fn print_module_to_file(&self) -> Result<()> {
+        let mut error = ptr::null_mut();
+        let failed = LLVMPrintModuleToFile(
+            self.module,
+            cstr_from_string("bin/main.ll").as_ptr(),
+            &mut error,
+        );
+        LLVMDisposeModule(self.module);
+        if failed != 0 {
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            return Err(anyhow!("failed to write bin/main.ll: {}", message));
+        }
+        Ok(())
+    }
+
+    // Compile the module straight to bin/main.o with the target machine, skipping the
+    // bin/main.ll round trip through clang
+    unsafe fn emit_object_file(&self) -> Result<()> {
+        let result = self.write_object_file("bin/main.o");
+        LLVMDisposeModule(self.module);
+        result
+    }
+
+    unsafe fn write_object_file(&self, path: &str) -> Result<()> {
+        let mut error = ptr::null_mut();
+        let triple = self.get_target_triple();
+        let target = Self::get_target(&triple)?;
+        let opt_level = match self.optimization_level {
+            0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            2 => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            _ => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+        };
+        // wasm-ld only links position independent objects with --experimental-pic
+        let reloc_mode = if self.is_wasm_target() {
+            LLVMRelocMode::LLVMRelocStatic
+        } else {
+            LLVMRelocMode::LLVMRelocPIC
+        };
+        let target_machine = LLVMCreateTargetMachine(
+            target,
+            triple.as_ptr(),
+            cstr_from_string("").as_ptr(),
+            cstr_from_string("").as_ptr(),
+            opt_level,
+            reloc_mode,
+            LLVMCodeModel::LLVMCodeModelDefault,
+        );
+        LLVMSetTarget(self.module, triple.as_ptr());
+        let target_data = LLVMCreateTargetDataLayout(target_machine);
+        LLVMSetModuleDataLayout(self.module, target_data);
+        LLVMDisposeTargetData(target_data);
+        let filename = cstr_from_string(path);
+        let failed = LLVMTargetMachineEmitToFile(
+            target_machine,
+            self.module,
+            filename.as_ptr() as *mut _,
+            LLVMCodeGenFileType::LLVMObjectFile,
+            &mut error,
+        );
+        LLVMDisposeTargetMachine(target_machine);
+        if failed != 0 {
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            return Err(anyhow!("failed to write {}: {}", path, message));
+        }
+        Ok(())
+    }
+
+    unsafe fn get_target(triple: &CString) -> Result<LLVMTargetRef> {
+        let mut error = ptr::null_mut();
+        let mut target = ptr::null_mut();
+        if LLVMGetTargetFromTriple(triple.as_ptr(), &mut target, &mut error) != 0 {
+            let message = std::ffi::CStr::from_ptr(error)
+                .to_string_lossy()
+                .to_string();
+            LLVMDisposeMessage(error);
+            return Err(anyhow!(
+                "failed to get target for {:?}: {}",
+                triple,
+                message
+            ));
+        }
+        Ok(target)
+    }
+
+    // the module gets the data layout of its target up front, so sizes and alignments are
+    // known while it's built and not only once it's emitted
+    unsafe fn set_data_layout(&self) -> Result<()> {
+        let triple = self.get_target_triple();
+        let target_machine = LLVMCreateTargetMachine(
+            Self::get_target(&triple)?,
+            triple.as_ptr(),
+            cstr_from_string("").as_ptr(),
+            cstr_from_string("").as_ptr(),
+            LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            LLVMRelocMode::LLVMRelocDefault,
+            LLVMCodeModel::LLVMCodeModelDefault,
+        );
+        let target_data = LLVMCreateTargetDataLayout(target_machine);
+        LLVMSetModuleDataLayout(self.module, target_data);
+        LLVMDisposeTargetData(target_data);
+        LLVMDisposeTargetMachine(target_machine);
+        Ok(())
+    }
+
+    /// target_info
+    ///
+    /// The pointer size, byte order and type layouts of the target the module is compiled
+    /// for, read from the module's data layout.
+    pub fn target_info(&self) -> TargetInfo {
+        TargetInfo::from_module(self.module)
+    }
+
+    // the triple set on the module for a cross compile target, otherwise the host triple
+    unsafe fn get_target_triple(&self) -> CString {
+        let module_triple = std::ffi::CStr::from_ptr(LLVMGetTarget(self.module));
+        if !module_triple.to_bytes().is_empty() {
+            return module_triple.to_owned();
+        }
+        let default_triple = LLVMGetDefaultTargetTriple();
+        let triple = std::ffi::CStr::from_ptr(default_triple).to_owned();
+        LLVMDisposeMessage(default_triple);
+        triple
+    }
+
+    fn is_wasm_target(&self) -> bool {
+        self.target.as_ref().is_some_and(|target| target.is_wasm())
+    }
+
+    // links bin/main.o for a cross compile target, the output can't be run on the host
+    // so there is no output to return
+    fn link_target_binary(&self, target: &Target) -> Result<String> {
+        let (linker, args) = match target.arch {
+            // printf and the other libc functions the stdlib calls are left as imports
+            // for the wasm runtime to provide
+            TargetArch::wasm => (
+                "wasm-ld",
+                vec![
+                    "bin/main.o",
+                    "-o",
+                    "bin/main.wasm",
+                    "--no-entry",
+                    "--export=main",
+                    "--allow-undefined",
+                ],
+            ),
+            TargetArch::arm64 => (
+                "aarch64-linux-gnu-gcc",
+                vec!["bin/main.o", "-o", "bin/main"],
+            ),
+            TargetArch::x86_64 => ("x86_64-linux-gnu-gcc", vec!["bin/main.o", "-o", "bin/main"]),
+            _ => {
+                return Err(anyhow!(
+                    "unable to link for target {}",
+                    target.get_llvm_target_name()
+                ))
+            }
+        };
+        let output = Command::new(linker).args(args).output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "failed to link bin/main.o with {}: {}",
+                linker,
+                String::from_utf8_lossy(&output.stderr)
+            ));
         }
         Ok("".to_string())
     }
 
+    pub fn emit_binary(&self) -> Result<String> {
+        match (self.execution_mode, &self.target) {
+            // output has already been written to stdout by the execution engine
+            (ExecutionMode::Jit, _) => Ok("".to_string()),
+            (ExecutionMode::Aot, Some(target)) => self.link_target_binary(target),
+            (ExecutionMode::Aot, None) => {
+                let input = if self.emit_object {
+                    "bin/main.o"
+                } else {
+                    "bin/main.ll"
+                };
+                Command::new("clang")
+                    .arg(input)
+                    .arg("-o")
+                    .arg("bin/main")
+                    .output()?;
+                let output = Command::new("bin/main").output()?;
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+        }
+    }
+
     /// build_load
     ///
     /// This reads a value from one memory location via the LLVMBuildLoad instruction
@@ -254,7 +901,110 @@ impl LLVMCodegenBuilder {
     /// * `name` - The LLVM name of the alloca
     ///
     pub fn build_alloca(&self, ptr_type: LLVMTypeRef, name: &str) -> LLVMValueRef {
-        unsafe { LLVMBuildAlloca(self.builder, ptr_type, cstr_from_string(name).as_ptr()) }
+        unsafe {
+            let alloca = LLVMBuildAlloca(self.builder, ptr_type, cstr_from_string(name).as_ptr());
+            if let Some(debug_info) = self.debug_info {
+                if !name.is_empty() {
+                    debug_info.declare_variable(self.builder, alloca, ptr_type, name);
+                }
+            }
+            alloca
+        }
+    }
+
+    /// add_function_attribute
+    ///
+    /// Maps a `#[...]` attribute from the source onto the matching LLVM function attribute,
+    /// `#[inline]` becomes alwaysinline and `#[noinline]` becomes noinline. `#[export]` keeps
+    /// the function externally visible and marks it with wasm-export-name so wasm-ld adds it
+    /// to the module's exports.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The LLVM function being defined
+    /// * `name` - The name of the function in the source
+    /// * `attribute` - The attribute written before the function declaration
+    ///
+    pub fn add_function_attribute(
+        &self,
+        function: LLVMValueRef,
+        name: &str,
+        attribute: FuncAttribute,
+    ) {
+        let kind_name = match attribute {
+            FuncAttribute::Inline => "alwaysinline",
+            FuncAttribute::NoInline => "noinline",
+            FuncAttribute::Export => {
+                self.export_function(function, name);
+                return;
+            }
+        };
+        unsafe {
+            let kind = LLVMGetEnumAttributeKindForName(
+                cstr_from_string(kind_name).as_ptr(),
+                kind_name.len(),
+            );
+            let context = LLVMGetModuleContext(self.module);
+            let llvm_attribute = LLVMCreateEnumAttribute(context, kind, 0);
+            LLVMAddAttributeAtIndex(function, LLVMAttributeFunctionIndex, llvm_attribute);
+        }
+    }
+
+    fn export_function(&self, function: LLVMValueRef, name: &str) {
+        let key = "wasm-export-name";
+        unsafe {
+            LLVMSetLinkage(function, LLVMLinkage::LLVMExternalLinkage);
+            LLVMSetVisibility(function, LLVMVisibility::LLVMDefaultVisibility);
+            let context = LLVMGetModuleContext(self.module);
+            let llvm_attribute = LLVMCreateStringAttribute(
+                context,
+                cstr_from_string(key).as_ptr(),
+                key.len() as c_uint,
+                cstr_from_string(name).as_ptr(),
+                name.len() as c_uint,
+            );
+            LLVMAddAttributeAtIndex(function, LLVMAttributeFunctionIndex, llvm_attribute);
+        }
+    }
+
+    /// enter_debug_scope
+    ///
+    /// When debug info is enabled this attaches a DISubprogram to the function and makes it
+    /// the scope of every instruction built until exit_debug_scope is called. It returns the
+    /// previous scope to restore.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The LLVM function being defined
+    /// * `name` - The name of the function in the source
+    ///
+    pub fn enter_debug_scope(
+        &mut self,
+        function: LLVMValueRef,
+        name: &str,
+    ) -> Option<LLVMMetadataRef> {
+        let debug_info = self.debug_info.as_mut()?;
+        let previous_scope = debug_info.scope;
+        unsafe {
+            debug_info.scope = debug_info.create_subprogram(function, name);
+            debug_info.set_location(self.builder);
+        }
+        Some(previous_scope)
+    }
+
+    /// Sets the source line of the instructions built from now on, when debug info is enabled
+    pub fn set_debug_line(&mut self, line: u32) {
+        if let Some(debug_info) = self.debug_info.as_mut() {
+            debug_info.line = line;
+            unsafe { debug_info.set_location(self.builder) };
+        }
+    }
+
+    pub fn exit_debug_scope(&mut self, previous_scope: Option<LLVMMetadataRef>) {
+        if let (Some(debug_info), Some(scope)) = (self.debug_info.as_mut(), previous_scope) {
+            debug_info.scope = scope;
+            unsafe { debug_info.set_location(self.builder) };
+        }
     }
 
     /// build_alloca_store
@@ -279,6 +1029,119 @@ impl LLVMCodegenBuilder {
         ptr
     }
 
+    /// build_global_string
+    ///
+    /// This returns a pointer to an immutable `[N x i8]` global holding the bytes of the string
+    /// constant. The bytes can contain nulls, a null is added after them for the libc functions
+    /// that take a `char *`. Globals are interned by content so every use of the same literal
+    /// shares a single global in the module.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The UTF-8 bytes of the string constant
+    ///
+    pub fn build_global_string(&mut self, value: &[u8]) -> LLVMValueRef {
+        if let Some(global) = self.string_constant_cache.get(value) {
+            return *global;
+        }
+        let global = unsafe {
+            let bytes = LLVMConstStringInContext2(
+                self.context,
+                value.as_ptr() as *const ::libc::c_char,
+                value.len(),
+                0,
+            );
+            let global = LLVMAddGlobal(
+                self.module,
+                LLVMTypeOf(bytes),
+                cstr_from_string("str_const").as_ptr(),
+            );
+            LLVMSetInitializer(global, bytes);
+            LLVMSetGlobalConstant(global, 1);
+            LLVMSetLinkage(global, LLVMLinkage::LLVMPrivateLinkage);
+            LLVMSetUnnamedAddress(global, LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+            global
+        };
+        self.string_constant_cache.insert(value.to_vec(), global);
+        global
+    }
+
+    /// new_global
+    ///
+    /// Adds a module level variable every function can load and store. A literal is the
+    /// global's initializer, so it holds the value before `main` reaches the declaration, any
+    /// other value is stored to it where it's declared.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the global
+    /// * `value` - The value the global starts with
+    /// * `is_literal` - Whether `value` was built from a literal, its value is a constant
+    ///
+    pub fn new_global(
+        &self,
+        name: &str,
+        value: &dyn TypeBase,
+        is_literal: bool,
+    ) -> Result<Box<dyn TypeBase>> {
+        let llvm_type = value.get_llvm_type();
+        let initial = if is_literal {
+            value.get_value()
+        } else {
+            self.load_value(value)
+        };
+        let global = unsafe {
+            let global = LLVMAddGlobal(self.module, llvm_type, cstr_from_string(name).as_ptr());
+            if is_literal {
+                LLVMSetInitializer(global, initial);
+            } else {
+                LLVMSetInitializer(global, LLVMConstNull(llvm_type));
+            }
+            // internal so it can't clash with a symbol from a linked C library
+            LLVMSetLinkage(global, LLVMLinkage::LLVMInternalLinkage);
+            global
+        };
+        if !is_literal {
+            self.build_store(initial, global);
+        }
+        match value.get_type() {
+            BaseTypes::Number => Ok(Box::new(NumberType {
+                name: name.to_string(),
+                llvm_value: initial,
+                llvm_value_pointer: Some(global),
+            })),
+            BaseTypes::Number64 => Ok(Box::new(NumberType64 {
+                name: name.to_string(),
+                llvm_value: initial,
+                llvm_value_pointer: Some(global),
+            })),
+            BaseTypes::Float => Ok(Box::new(FloatType {
+                name: name.to_string(),
+                llvm_value: initial,
+                llvm_value_pointer: Some(global),
+            })),
+            BaseTypes::Bool => Ok(Box::new(BoolType {
+                name: name.to_string(),
+                builder: self.builder,
+                llvm_value: initial,
+                llvm_value_pointer: global,
+            })),
+            _ => Err(anyhow!(
+                "global `{}` can only be an i32, i64, float or bool, got {}",
+                name,
+                value.get_type()
+            )),
+        }
+    }
+
+    // a global is stored to when it's assigned rather than rebound, so every function that
+    // loads it sees the new value
+    pub fn is_global(&self, value: &dyn TypeBase) -> bool {
+        value
+            .get_ptr()
+            .is_some_and(|ptr| unsafe { !LLVMIsAGlobalVariable(ptr).is_null() })
+    }
+
     /// build_load_store
     ///
     /// This reads a value from one memory location via the LLVMBuildLoad instruction
@@ -370,6 +1233,26 @@ impl LLVMCodegenBuilder {
         }
     }
 
+    // integers are printed with the format for the width of their LLVM type rather than
+    // their BaseTypes, so a value widened to i64 isn't printed as an i32
+    pub fn get_printf_str_for_value(
+        &mut self,
+        val: BaseTypes,
+        value: LLVMValueRef,
+    ) -> LLVMValueRef {
+        unsafe {
+            let value_type = LLVMTypeOf(value);
+            if LLVMGetTypeKind(value_type) == LLVMTypeKind::LLVMIntegerTypeKind {
+                match LLVMGetIntTypeWidth(value_type) {
+                    64 => return self.printf_str_num64_value,
+                    32 => return self.printf_str_num_value,
+                    _ => {}
+                }
+            }
+        }
+        self.get_printf_str(val)
+    }
+
     pub fn build_br(&self, block: LLVMBasicBlockRef) -> LLVMValueRef {
         unsafe { LLVMBuildBr(self.builder, block) }
     }
@@ -408,6 +1291,10 @@ impl LLVMCodegenBuilder {
         unsafe { LLVMConstInt(int_type, val, sign_extend) }
     }
 
+    pub fn const_real(&self, real_type: LLVMTypeRef, val: f64) -> LLVMValueRef {
+        unsafe { LLVMConstReal(real_type, val) }
+    }
+
     pub fn const_array(
         &self,
         element_type: LLVMTypeRef,
@@ -451,9 +1338,14 @@ impl LLVMCodegenBuilder {
         let then_block = self.append_basic_block(function, "then_block");
         let merge_block = self.append_basic_block(function, "merge_block");
 
+        // both arms start from the bindings before the if, the block each arm that doesn't
+        // return ends in is kept with its bindings so they can be merged
+        let bindings_before = context.var_cache.read().snapshot();
+        let mut arm_ends = vec![];
+
         self.set_current_block(then_block);
 
-        let stmt = context.match_ast(if_stmt, visitor, self)?;
+        let stmt = self.build_if_arm(context, if_stmt, visitor)?;
 
         match stmt.get_type() {
             BaseTypes::Return => {
@@ -461,10 +1353,13 @@ impl LLVMCodegenBuilder {
                 return_type = Box::new(ReturnType {});
             }
             _ => {
-                self.build_br(merge_block); // Branch to merge_block
+                arm_ends.push((
+                    self.current_function.block,
+                    context.var_cache.read().snapshot(),
+                ));
             }
         }
-        // Each
+        Self::restore_bindings(context, &bindings_before);
 
         // Build Else Block
         let else_block = self.append_basic_block(function, "else_block");
@@ -472,25 +1367,27 @@ impl LLVMCodegenBuilder {
 
         match else_stmt {
             Some(v_stmt) => {
-                let stmt = context.match_ast(v_stmt, visitor, self)?;
+                let stmt = self.build_if_arm(context, v_stmt, visitor)?;
                 match stmt.get_type() {
                     BaseTypes::Return => {
                         // if its a return type we will skip branching in the LLVM IR
                         return_type = Box::new(ReturnType {});
                     }
                     _ => {
-                        self.build_br(merge_block);
+                        arm_ends.push((
+                            self.current_function.block,
+                            context.var_cache.read().snapshot(),
+                        ));
                     }
                 }
+                Self::restore_bindings(context, &bindings_before);
             }
             _ => {
-                self.position_builder_at_end(else_block);
-                self.build_br(merge_block);
+                arm_ends.push((else_block, context.var_cache.read().snapshot()));
             }
         }
 
-        self.position_builder_at_end(merge_block);
-        self.set_current_block(merge_block);
+        self.build_if_merge(&bindings_before, arm_ends, merge_block);
 
         self.set_current_block(if_entry_block);
 
@@ -501,77 +1398,2295 @@ impl LLVMCodegenBuilder {
         Ok(return_type)
     }
 
-    pub fn new_while_stmt(
+    fn build_if_arm(
+        &mut self,
+        context: &mut ASTContext,
+        stmt: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        self.if_arms
+            .push((self.current_function.function, self.loop_exit_blocks.len()));
+        let value = context.match_ast(stmt, visitor, self);
+        self.if_arms.pop();
+        value
+    }
+
+    // an assignment directly in an if arm, not in a loop or function inside the arm, rebinds
+    // the variable to a new value instead of storing over the old one
+    pub fn in_if_arm(&self) -> bool {
+        self.if_arms.last() == Some(&(self.current_function.function, self.loop_exit_blocks.len()))
+    }
+
+    fn restore_bindings(context: &ASTContext, bindings: &HashMap<String, Box<dyn TypeBase>>) {
+        let mut var_cache = context.var_cache.write();
+        for (name, binding) in bindings {
+            var_cache.replace(name, binding.clone());
+        }
+    }
+
+    // a variable rebound in either arm gets the value from the arm that ran with a phi in the
+    // merge block, which is stored back to the variable so its binding from before the if is
+    // still the one in scope
+    fn build_if_merge(
+        &mut self,
+        bindings_before: &HashMap<String, Box<dyn TypeBase>>,
+        arm_ends: Vec<ArmEnd>,
+        merge_block: LLVMBasicBlockRef,
+    ) {
+        let rebound: Vec<(&String, &Box<dyn TypeBase>)> = bindings_before
+            .iter()
+            .filter(|(name, before)| {
+                matches!(
+                    before.get_type(),
+                    BaseTypes::Number | BaseTypes::Number64 | BaseTypes::Float | BaseTypes::Bool
+                ) && arm_ends.iter().any(|(_, after)| {
+                    after.get(*name).map(|after| after.get_ptr()) != Some(before.get_ptr())
+                })
+            })
+            .collect();
+
+        let mut incoming_values: Vec<Vec<LLVMValueRef>> = vec![vec![]; rebound.len()];
+        let mut incoming_blocks: Vec<LLVMBasicBlockRef> = vec![];
+        for (block, after) in &arm_ends {
+            self.set_current_block(*block);
+            for ((name, before), values) in rebound.iter().zip(incoming_values.iter_mut()) {
+                let value = after.get(*name).unwrap_or(before);
+                values.push(self.load_value(value.as_ref()));
+            }
+            incoming_blocks.push(*block);
+            self.build_br(merge_block);
+        }
+
+        // the phis have to come before any other instruction in the block
+        self.set_current_block(merge_block);
+        let mut merged = vec![];
+        for ((name, before), mut values) in rebound.into_iter().zip(incoming_values) {
+            unsafe {
+                let phi = LLVMBuildPhi(
+                    self.builder,
+                    before.get_llvm_type(),
+                    cstr_from_string(name).as_ptr(),
+                );
+                LLVMAddIncoming(
+                    phi,
+                    values.as_mut_ptr(),
+                    incoming_blocks.as_mut_ptr(),
+                    values.len() as c_uint,
+                );
+                merged.push((phi, before.get_ptr()));
+            }
+        }
+        for (phi, ptr) in merged {
+            if let Some(ptr) = ptr {
+                self.build_store(phi, ptr);
+            }
+        }
+    }
+
+    // an if used as a value, the value of the branch that ran is picked with a phi in the
+    // merge block so both branches have to produce the same type
+    pub fn new_if_expr(
+        &mut self,
+        context: &mut ASTContext,
+        condition: Expression,
+        if_stmt: Expression,
+        else_stmt: Option<Expression>,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let else_stmt = else_stmt.ok_or(anyhow!("an if used as a value needs an else branch"))?;
+        let function = self.current_function.function;
+
+        let cond: Box<dyn TypeBase> = context.match_ast(condition, visitor, self)?;
+        let cmp = self.build_load(cond.get_ptr().unwrap(), int1_type(), "cmp");
+        let then_block = self.append_basic_block(function, "if_then");
+        let else_block = self.append_basic_block(function, "if_else");
+        let merge_block = self.append_basic_block(function, "if_merge");
+        self.build_cond_br(cmp, then_block, else_block);
+
+        let mut branch_type: Option<BaseTypes> = None;
+        let mut incoming_values: Vec<LLVMValueRef> = vec![];
+        let mut incoming_blocks: Vec<LLVMBasicBlockRef> = vec![];
+        for (block, stmt) in [(then_block, if_stmt), (else_block, else_stmt)] {
+            self.set_current_block(block);
+            let value = context.match_ast(stmt, visitor, self)?;
+            let value_type = match value.get_type() {
+                // if its a return type we will skip branching in the LLVM IR
+                BaseTypes::Return => continue,
+                // a branch without a value has nothing to add to the phi
+                BaseTypes::Void | BaseTypes::Func => BaseTypes::Void,
+                value_type => value_type,
+            };
+            if let Some(expected) = &branch_type {
+                if *expected != value_type {
+                    return Err(CycloError::BranchTypeMismatch {
+                        expected: expected.clone(),
+                        found: value_type,
+                        span: context.span,
+                    }
+                    .into());
+                }
+            }
+            if value_type != BaseTypes::Void {
+                incoming_values.push(self.load_value(value.as_ref()));
+                incoming_blocks.push(self.current_function.block);
+            }
+            branch_type = Some(value_type);
+            self.build_br(merge_block);
+        }
+
+        self.set_current_block(merge_block);
+        match branch_type {
+            Some(branch_type) if branch_type != BaseTypes::Void => unsafe {
+                let phi = LLVMBuildPhi(
+                    self.builder,
+                    self.get_llvm_type_for(&branch_type),
+                    cstr_from_string("if_value").as_ptr(),
+                );
+                LLVMAddIncoming(
+                    phi,
+                    incoming_values.as_mut_ptr(),
+                    incoming_blocks.as_mut_ptr(),
+                    incoming_values.len() as c_uint,
+                );
+                self.build_value_for_type(&branch_type, phi, "if_value")
+            },
+            _ => Ok(Box::new(VoidType {})),
+        }
+    }
+
+    pub fn new_while_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        condition: Expression,
+        while_block_stmt: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+
+        let loop_cond_block = self.append_basic_block(function, "loop_cond");
+        let loop_body_block = self.append_basic_block(function, "loop_body");
+        let loop_exit_block = self.append_basic_block(function, "loop_exit");
+
+        let bool_type_ptr = self.build_alloca(int1_type(), "while_value_bool_var");
+
+
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_body_block);
+        // Check if the global variable already exists
+
+        self.loop_exit_blocks.push(loop_exit_block);
+        let while_block = context.match_ast(while_block_stmt, visitor, self);
+        self.loop_exit_blocks.pop();
+        while_block?;
+
+        self.build_br(loop_cond_block); // Jump back to loop condition
+
+        self.set_current_block(loop_cond_block);
+        let value_condition = context.match_ast(condition, visitor, self)?;
+        let cmp = self.build_load(value_condition.get_ptr().unwrap(), int1_type(), "cmp");
+
+        self.build_store(cmp, bool_type_ptr);
+        // Build loop condition block
+        let value_cond_load = self.build_load(
+            value_condition.get_ptr().unwrap(),
+            int1_type(),
+            "while_value_bool_var",
+        );
+
+        self.build_cond_br(value_cond_load, loop_body_block, loop_exit_block);
+
+        // Position builder at loop exit block
+        self.set_current_block(loop_exit_block);
+        Ok(value_condition)
+    }
+
+    // same blocks as a while loop, but we branch straight into the body so it runs once
+    pub fn new_do_while_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        do_block_stmt: Expression,
+        condition: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+
+        let loop_cond_block = self.append_basic_block(function, "do_loop_cond");
+        let loop_body_block = self.append_basic_block(function, "do_loop_body");
+        let loop_exit_block = self.append_basic_block(function, "do_loop_exit");
+
+        self.build_br(loop_body_block);
+
+        self.set_current_block(loop_body_block);
+        self.loop_exit_blocks.push(loop_exit_block);
+        let do_block = context.match_ast(do_block_stmt, visitor, self);
+        self.loop_exit_blocks.pop();
+        do_block?;
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_cond_block);
+        let value_condition = context.match_ast(condition, visitor, self)?;
+        let condition_ptr = value_condition
+            .get_ptr()
+            .ok_or(anyhow!("the do while condition must be a bool"))?;
+        let value_cond_load =
+            self.build_load(condition_ptr, int1_type(), "do_while_value_bool_var");
+
+        self.build_cond_br(value_cond_load, loop_body_block, loop_exit_block);
+
+        self.set_current_block(loop_exit_block);
+        Ok(value_condition)
+    }
+
+    // inverse of a do while loop, the body repeats while the condition is false
+    pub fn new_repeat_until_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        repeat_block_stmt: Expression,
+        condition: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+
+        let loop_cond_block = self.append_basic_block(function, "repeat_loop_cond");
+        let loop_body_block = self.append_basic_block(function, "repeat_loop_body");
+        let loop_exit_block = self.append_basic_block(function, "repeat_loop_exit");
+
+        self.build_br(loop_body_block);
+
+        self.set_current_block(loop_body_block);
+        self.loop_exit_blocks.push(loop_exit_block);
+        let repeat_block = context.match_ast(repeat_block_stmt, visitor, self);
+        self.loop_exit_blocks.pop();
+        repeat_block?;
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_cond_block);
+        let value_condition = context.match_ast(condition, visitor, self)?;
+        let condition_ptr = value_condition
+            .get_ptr()
+            .ok_or(anyhow!("the repeat until condition must be a bool"))?;
+        let value_cond_load =
+            self.build_load(condition_ptr, int1_type(), "repeat_until_value_bool_var");
+
+        self.build_cond_br(value_cond_load, loop_exit_block, loop_body_block);
+
+        self.set_current_block(loop_exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    // loop has no condition, the body branches back to itself until a `break`
+    // branches to loop_exit
+    pub fn new_loop_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        loop_block_stmt: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+
+        let loop_body_block = self.append_basic_block(function, "loop_body");
+        let loop_exit_block = self.append_basic_block(function, "loop_exit");
+
+        self.build_br(loop_body_block);
+
+        self.set_current_block(loop_body_block);
+        self.loop_exit_blocks.push(loop_exit_block);
+        let loop_block = context.match_ast(loop_block_stmt, visitor, self);
+        self.loop_exit_blocks.pop();
+        loop_block?;
+        self.build_br(loop_body_block);
+
+        self.set_current_block(loop_exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    // swaps out the enclosing loops, used when building a function body
+    pub fn replace_loop_exit_blocks(
+        &mut self,
+        loop_exit_blocks: Vec<LLVMBasicBlockRef>,
+    ) -> Vec<LLVMBasicBlockRef> {
+        std::mem::replace(&mut self.loop_exit_blocks, loop_exit_blocks)
+    }
+
+    pub fn new_break_stmt(&mut self) -> Result<Box<dyn TypeBase>> {
+        let loop_exit_block = *self
+            .loop_exit_blocks
+            .last()
+            .ok_or(anyhow!("`break` can only be used inside a loop"))?;
+        self.build_br(loop_exit_block);
+
+        // anything built after the break is unreachable, but it still needs an
+        // unterminated block to go in
+        let function = self.current_function.function;
+        let after_break_block = self.append_basic_block(function, "after_break");
+        self.set_current_block(after_break_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    // the counter is carried between iterations by a phi in for_cond rather than being
+    // reassigned in place, the body reads and assigns it through an alloca holding the value
+    // for the current iteration
+    pub fn new_for_loop(
+        &mut self,
+        context: &mut ASTContext,
+        var_name: String,
+        init: i32,
+        length: i32,
+        increment: i32,
+        for_block_expr: Expression
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        // the counter's first version feeds the phi from the preheader
+        let entry_value = self.const_int(int32_type(), init as u64, 1);
+        let counter_ptr = self.build_alloca(int32_type(), &var_name);
+        let preheader_block = self.current_function.block;
+
+        let function = self.current_function.function;
+        let loop_cond_block = self.append_basic_block(function, "for_cond");
+        let loop_body_block = self.append_basic_block(function, "for_body");
+        let loop_exit_block = self.append_basic_block(function, "for_exit");
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_cond_block);
+        let counter = unsafe {
+            LLVMBuildPhi(
+                self.builder,
+                int32_type(),
+                cstr_from_string(&var_name).as_ptr(),
+            )
+        };
+        self.add_phi_incoming(counter, entry_value, preheader_block);
+        self.build_store(counter, counter_ptr);
+        let predicate = if Self::get_while_cond_loop(increment) == ">" {
+            LLVMIntSGT
+        } else {
+            LLVMIntSLT
+        };
+        let bound = self.const_int(int32_type(), length as u64, 1);
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                predicate,
+                counter,
+                bound,
+                cstr_from_string("for_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, loop_body_block, loop_exit_block);
+
+        self.set_current_block(loop_body_block);
+        // the counter only lives for the body of the loop, it shadows a variable with the same
+        // name until the body ends
+        context.incr();
+        context.var_cache.write().set(
+            &var_name,
+            Box::new(NumberType {
+                name: var_name.clone(),
+                llvm_value: counter,
+                llvm_value_pointer: Some(counter_ptr),
+            }),
+            context.get_depth(),
+        );
+        self.loop_exit_blocks.push(loop_exit_block);
+        let for_block = context.match_ast(for_block_expr, &mut visitor, self);
+        self.loop_exit_blocks.pop();
+        context.var_cache.write().del_locals(context.get_depth());
+        context.decr();
+        for_block?;
+
+        //increment after each loop pass, the next version flows back into the phi
+        let current = self.build_load(counter_ptr, int32_type(), &var_name);
+        let step = self.const_int(int32_type(), increment as u64, 1);
+        let next = self.llvm_build_fn(current, step, "+".to_string());
+        self.build_store(next, counter_ptr);
+        self.add_phi_incoming(counter, next, self.current_function.block);
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    // reversed(start..end) carries the exclusive upper bound in the phi rather than the counter,
+    // it starts at `end` and the body sees one less than it. Neither bound is ever stepped past
+    // so the loop can't overflow at the ends of i32
+    pub fn new_reversed_for_loop(
+        &mut self,
+        context: &mut ASTContext,
+        var_name: &str,
+        start: i32,
+        end: i32,
+        for_block_expr: Expression,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        let counter_ptr = self.build_alloca(int32_type(), var_name);
+        let preheader_block = self.current_function.block;
+
+        let function = self.current_function.function;
+        let loop_cond_block = self.append_basic_block(function, "reversed_for_cond");
+        let loop_body_block = self.append_basic_block(function, "reversed_for_body");
+        let loop_exit_block = self.append_basic_block(function, "reversed_for_exit");
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_cond_block);
+        let upper = unsafe { LLVMBuildPhi(self.builder, int32_type(), c"upper".as_ptr()) };
+        let end = self.const_int(int32_type(), end as u64, 1);
+        self.add_phi_incoming(upper, end, preheader_block);
+        let start = self.const_int(int32_type(), start as u64, 1);
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSGT,
+                upper,
+                start,
+                cstr_from_string("reversed_for_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, loop_body_block, loop_exit_block);
+
+        self.set_current_block(loop_body_block);
+        let one = self.const_int(int32_type(), 1, 0);
+        let counter = self.llvm_build_fn(upper, one, "-".to_string());
+        self.build_store(counter, counter_ptr);
+
+        // the loop variable only lives for the body of the loop
+        context.incr();
+        context.var_cache.write().set(
+            var_name,
+            Box::new(NumberType {
+                name: var_name.to_string(),
+                llvm_value: counter,
+                llvm_value_pointer: Some(counter_ptr),
+            }),
+            context.get_depth(),
+        );
+        self.loop_exit_blocks.push(loop_exit_block);
+        let for_block = context.match_ast(for_block_expr, &mut visitor, self);
+        self.loop_exit_blocks.pop();
+        context.var_cache.write().del_locals(context.get_depth());
+        context.decr();
+        for_block?;
+
+        // the counter the body finished with is the next upper bound
+        let next = self.build_load(counter_ptr, int32_type(), var_name);
+        self.add_phi_incoming(upper, next, self.current_function.block);
+        self.build_br(loop_cond_block);
+
+        self.set_current_block(loop_exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    fn add_phi_incoming(&self, phi: LLVMValueRef, value: LLVMValueRef, block: LLVMBasicBlockRef) {
+        let mut values = [value];
+        let mut blocks = [block];
+        unsafe { LLVMAddIncoming(phi, values.as_mut_ptr(), blocks.as_mut_ptr(), 1) }
+    }
+
+    // loops over the elements of a list by index, counting down from the last element
+    // when reversed so the condition is always `index >= 0` rather than switching predicates
+    pub fn new_for_each(
+        &mut self,
+        context: &mut ASTContext,
+        var_name: &str,
+        list: Expression,
+        reversed: bool,
+        for_block_expr: Expression,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        let list_value = context.match_ast(list, &mut visitor, self)?;
+        let (get_value_func_name, inner_type) = match list_value.get_type() {
+            BaseTypes::List(inner_type) => match *inner_type {
+                BaseTypes::Number => ("get_int32_tValue", BaseTypes::Number),
+                BaseTypes::Number64 => ("get_int64_tValue", BaseTypes::Number64),
+                BaseTypes::String => ("getStringValue", BaseTypes::String),
+                _ => return Err(anyhow!("unable to loop over List<{:?}>", inner_type)),
+            },
+            _ => {
+                return Err(anyhow!(
+                    "unable to loop over type {:?}",
+                    list_value.get_type()
+                ))
+            }
+        };
+        let get_value_func = self
+            .llvm_func_cache
+            .get(get_value_func_name)
+            .ok_or(anyhow!("unable to find {} function", get_value_func_name))?;
+
+        let length = list_value.len(self)?.get_value();
+        let one = self.const_int(int32_type(), 1, 0);
+        let (start, predicate, bound, step) = if reversed {
+            let last = self.llvm_build_fn(length, one, "-".to_string());
+            (last, LLVMIntSGE, self.const_int(int32_type(), 0, 0), "-")
+        } else {
+            (self.const_int(int32_type(), 0, 0), LLVMIntSLT, length, "+")
+        };
+        let index_ptr = self.build_alloca_store(start, int32_type(), "for_each_index");
+
+        let function = self.current_function.function;
+        let cond_block = self.append_basic_block(function, "for_each_cond");
+        let body_block = self.append_basic_block(function, "for_each_body");
+        let exit_block = self.append_basic_block(function, "for_each_exit");
+        self.build_br(cond_block);
+
+        self.set_current_block(cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                predicate,
+                index,
+                bound,
+                cstr_from_string("for_each_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, body_block, exit_block);
+
+        self.set_current_block(body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let element = self.build_call(
+            get_value_func,
+            vec![list_value.get_value(), index],
+            2,
+            "element",
+        );
+        let element: Box<dyn TypeBase> = match inner_type {
+            BaseTypes::String => Box::new(StringType {
+                name: var_name.to_string(),
+                llvm_value: element,
+                llvm_value_pointer: Some(element),
+            }),
+            _ => self.build_value_for_type(&inner_type, element, var_name)?,
+        };
+
+        // the loop variable only lives for the body of the loop
+        context.incr();
+        context
+            .var_cache
+            .write()
+            .set(var_name, element, context.get_depth());
+        self.loop_exit_blocks.push(exit_block);
+        let for_block = context.match_ast(for_block_expr, &mut visitor, self);
+        self.loop_exit_blocks.pop();
+        context.var_cache.write().del_locals(context.get_depth());
+        context.decr();
+        for_block?;
+
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let next_index = self.llvm_build_fn(index, one, step.to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(cond_block);
+
+        self.set_current_block(exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    // fold is lowered to the same cond/body/exit loop as a for loop, with the accumulator
+    // kept in a stack slot and the function called once per element
+    pub fn new_fold(
+        &mut self,
+        context: &mut ASTContext,
+        list: Expression,
+        init: Expression,
+        func_name: String,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        let list_value = context.match_ast(list, &mut visitor, self)?;
+        let init_value = context.match_ast(init, &mut visitor, self)?;
+        // the function is called with the accumulator and an element
+        let arg_types = match list_value.get_type() {
+            BaseTypes::List(inner_type) => vec![init_value.get_type(), *inner_type],
+            _ => vec![],
+        };
+        let func = context
+            .func_cache
+            .read()
+            .resolve(&func_name, &arg_types)
+            .ok_or(anyhow!("call does not exist for function {:?}", func_name))?;
+        if func.get_return_type() == Type::None {
+            return Err(anyhow!("fold function {:?} must return a value", func_name));
+        }
+
+        let get_value_func_name = match list_value.get_type() {
+            BaseTypes::List(inner_type) => match *inner_type {
+                BaseTypes::Number => "get_int32_tValue",
+                BaseTypes::Number64 => "get_int64_tValue",
+                BaseTypes::String => "getStringValue",
+                _ => {
+                    return Err(anyhow!(
+                        "fold is not implemented for List<{:?}>",
+                        inner_type
+                    ))
+                }
+            },
+            _ => {
+                return Err(anyhow!(
+                    "unable to fold over type {:?}",
+                    list_value.get_type()
+                ))
+            }
+        };
+        let get_value_func = self
+            .llvm_func_cache
+            .get(get_value_func_name)
+            .ok_or(anyhow!("unable to find {} function", get_value_func_name))?;
+
+        // the call is built straight from the accumulator and element, so check the function
+        // takes exactly those and gives back the accumulator
+        let acc_type = init_value.get_llvm_type();
+        let (param_types, return_type) = unsafe {
+            use llvm_sys::core::{LLVMCountParamTypes, LLVMGetParamTypes, LLVMGetReturnType};
+            let func_type = func.get_llvm_type();
+            let mut param_types = vec![ptr::null_mut(); LLVMCountParamTypes(func_type) as usize];
+            LLVMGetParamTypes(func_type, param_types.as_mut_ptr());
+            (param_types, LLVMGetReturnType(func_type))
+        };
+        let element_type = unsafe { llvm_sys::core::LLVMGetReturnType(get_value_func.func_type) };
+        if param_types != vec![acc_type, element_type] {
+            return Err(anyhow!(
+                "fold function {:?} must take the accumulator and a list element",
+                func_name
+            ));
+        }
+        if return_type != acc_type {
+            return Err(anyhow!(
+                "fold function {:?} must return the type of the accumulator",
+                func_name
+            ));
+        }
+
+        let acc_ptr = self.build_alloca_store(init_value.get_value(), acc_type, "fold_acc");
+        let length = list_value.len(self)?;
+        let zero = self.const_int(int32_type(), 0, 0);
+        let index_ptr = self.build_alloca_store(zero, int32_type(), "fold_index");
+
+        let function = self.current_function.function;
+        let fold_cond_block = self.append_basic_block(function, "fold_cond");
+        let fold_body_block = self.append_basic_block(function, "fold_body");
+        let fold_exit_block = self.append_basic_block(function, "fold_exit");
+        self.build_br(fold_cond_block);
+
+        self.set_current_block(fold_cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                index,
+                length.get_value(),
+                cstr_from_string("fold_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, fold_body_block, fold_exit_block);
+
+        self.set_current_block(fold_body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let element = self.build_call(
+            get_value_func,
+            vec![list_value.get_value(), index],
+            2,
+            "element",
+        );
+        let acc = self.build_load(acc_ptr, acc_type, "acc");
+        let mut call_args = vec![acc, element];
+        let result = unsafe {
+            LLVMBuildCall2(
+                self.builder,
+                func.get_llvm_type(),
+                func.get_value(),
+                call_args.as_mut_ptr(),
+                call_args.len() as c_uint,
+                cstr_from_string("fold_call").as_ptr(),
+            )
+        };
+        self.build_store(result, acc_ptr);
+        let one = self.const_int(int32_type(), 1, 0);
+        let next_index = self.llvm_build_fn(index, one, "+".to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(fold_cond_block);
+
+        self.set_current_block(fold_exit_block);
+        let acc = self.build_load(acc_ptr, acc_type, "fold_result");
+        self.build_value_for_type(&init_value.get_type(), acc, "fold_result")
+    }
+
+    /// new_sum
+    ///
+    /// Sums a List<i32>, the total is accumulated and returned as an i64 so it can't overflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_value` - The list being summed
+    ///
+    pub fn new_sum(&mut self, list_value: &dyn TypeBase) -> Result<Box<dyn TypeBase>> {
+        let (sum, _) = self.build_list_sum("sum", list_value)?;
+        self.build_value_for_type(&BaseTypes::Number64, sum, "sum")
+    }
+
+    /// new_avg
+    ///
+    /// Averages a List<i32> as a float, the average of an empty list is NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_value` - The list being averaged
+    ///
+    pub fn new_avg(&mut self, list_value: &dyn TypeBase) -> Result<Box<dyn TypeBase>> {
+        let (sum, length) = self.build_list_sum("avg", list_value)?;
+        let avg = unsafe {
+            let sum = LLVMBuildSIToFP(
+                self.builder,
+                sum,
+                float_type(),
+                cstr_from_string("sum_float").as_ptr(),
+            );
+            let length = LLVMBuildSIToFP(
+                self.builder,
+                length,
+                float_type(),
+                cstr_from_string("length_float").as_ptr(),
+            );
+            LLVMBuildFDiv(self.builder, sum, length, cstr_from_string("avg").as_ptr())
+        };
+        self.build_value_for_type(&BaseTypes::Float, avg, "avg")
+    }
+
+    /// new_clamp
+    ///
+    /// Clamps a float between lo and hi.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The float being clamped
+    /// * `lo` - The lower bound
+    /// * `hi` - The upper bound
+    ///
+    pub fn new_clamp(
+        &mut self,
+        value: &dyn TypeBase,
+        lo: &dyn TypeBase,
+        hi: &dyn TypeBase,
+    ) -> Result<Box<dyn TypeBase>> {
+        let [value, lo, hi] = self.load_float_args("clamp", [value, lo, hi])?;
+        let clamped = unsafe {
+            let below = LLVMBuildFCmp(
+                self.builder,
+                LLVMRealOLT,
+                value,
+                lo,
+                cstr_from_string("below_lo").as_ptr(),
+            );
+            let value = LLVMBuildSelect(
+                self.builder,
+                below,
+                lo,
+                value,
+                cstr_from_string("clamp_lo").as_ptr(),
+            );
+            let above = LLVMBuildFCmp(
+                self.builder,
+                LLVMRealOGT,
+                value,
+                hi,
+                cstr_from_string("above_hi").as_ptr(),
+            );
+            LLVMBuildSelect(
+                self.builder,
+                above,
+                hi,
+                value,
+                cstr_from_string("clamp").as_ptr(),
+            )
+        };
+        self.build_value_for_type(&BaseTypes::Float, clamped, "clamp")
+    }
+
+    /// new_lerp
+    ///
+    /// Linearly interpolates between two floats, `a + (b - a) * t`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The value when t is 0.0
+    /// * `b` - The value when t is 1.0
+    /// * `t` - How far to interpolate from a to b
+    ///
+    pub fn new_lerp(
+        &mut self,
+        a: &dyn TypeBase,
+        b: &dyn TypeBase,
+        t: &dyn TypeBase,
+    ) -> Result<Box<dyn TypeBase>> {
+        let [a, b, t] = self.load_float_args("lerp", [a, b, t])?;
+        let lerp = unsafe {
+            let diff = LLVMBuildFSub(self.builder, b, a, cstr_from_string("lerp_diff").as_ptr());
+            let scaled = LLVMBuildFMul(
+                self.builder,
+                diff,
+                t,
+                cstr_from_string("lerp_scaled").as_ptr(),
+            );
+            LLVMBuildFAdd(self.builder, a, scaled, cstr_from_string("lerp").as_ptr())
+        };
+        self.build_value_for_type(&BaseTypes::Float, lerp, "lerp")
+    }
+
+    /// new_exit
+    ///
+    /// Ends the program with an exit code by calling libc `exit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The i32 exit code
+    ///
+    pub fn new_exit(&mut self, code: &dyn TypeBase) -> Result<Box<dyn TypeBase>> {
+        if code.get_type() != BaseTypes::Number {
+            return Err(anyhow!(
+                "exit is only implemented for i32 codes, found {}",
+                code.get_type()
+            ));
+        }
+        let exit_func = self
+            .llvm_func_cache
+            .get("exit")
+            .ok_or(anyhow!("unable to find exit function"))?;
+        let code = self.load_value(code);
+        self.build_call(exit_func, vec![code], 1, "");
+        unsafe {
+            LLVMBuildUnreachable(self.builder);
+        }
+
+        // like after a break, anything built after the exit goes in an unreachable block
+        let function = self.current_function.function;
+        let after_exit_block = self.append_basic_block(function, "after_exit");
+        self.set_current_block(after_exit_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    /// new_assert
+    ///
+    /// Prints the message to stderr and exits with code 1 when the condition is false, the
+    /// program carries on when it's true.
+    ///
+    /// # Arguments
+    ///
+    /// * `cond` - The bool condition
+    /// * `message` - What's printed when the condition is false
+    ///
+    pub fn new_assert(&mut self, cond: &dyn TypeBase, message: &str) -> Result<Box<dyn TypeBase>> {
+        if cond.get_type() != BaseTypes::Bool {
+            return Err(anyhow!(
+                "assert is only implemented for bool conditions, found {}",
+                cond.get_type()
+            ));
+        }
+        let print_func = self
+            .llvm_func_cache
+            .get("dprintf")
+            .ok_or(anyhow!("unable to find dprintf function"))?;
+        let exit_func = self
+            .llvm_func_cache
+            .get("exit")
+            .ok_or(anyhow!("unable to find exit function"))?;
+        let cond = self.load_value(cond);
+        let function = self.current_function.function;
+        let failed_block = self.append_basic_block(function, "assert_failed");
+        let passed_block = self.append_basic_block(function, "assert_passed");
+        self.build_cond_br(cond, passed_block, failed_block);
+
+        self.set_current_block(failed_block);
+        // the message is passed as an argument so a `%` in the condition isn't a format
+        let stderr = self.const_int(int32_type(), 2, 0);
+        let format = self.build_global_string(b"%s\n");
+        let message = self.build_global_string(message.as_bytes());
+        self.build_call(print_func, vec![stderr, format, message], 3, "");
+        let code = self.const_int(int32_type(), 1, 0);
+        self.build_call(exit_func, vec![code], 1, "");
+        unsafe {
+            LLVMBuildUnreachable(self.builder);
+        }
+
+        self.set_current_block(passed_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    fn load_float_args(&self, op: &str, args: [&dyn TypeBase; 3]) -> Result<[LLVMValueRef; 3]> {
+        for arg in args {
+            if arg.get_type() != BaseTypes::Float {
+                return Err(anyhow!(
+                    "{} is only implemented for floats, found {}",
+                    op,
+                    arg.get_type()
+                ));
+            }
+        }
+        Ok(args.map(|arg| self.load_value(arg)))
+    }
+
+    // loops over a List<i32> sign extending each element to an i64 before adding it to
+    // the total, returns the total and the length of the list
+    fn build_list_sum(
+        &mut self,
+        op: &str,
+        list_value: &dyn TypeBase,
+    ) -> Result<(LLVMValueRef, LLVMValueRef)> {
+        if list_value.get_type() != BaseTypes::List(Box::new(BaseTypes::Number)) {
+            return Err(anyhow!(
+                "{} is only implemented for List<i32>, found {}",
+                op,
+                list_value.get_type()
+            ));
+        }
+        let get_value_func = self
+            .llvm_func_cache
+            .get("get_int32_tValue")
+            .ok_or(anyhow!("unable to find get_int32_tValue function"))?;
+
+        let zero = self.const_int(int64_type(), 0, 0);
+        let sum_ptr = self.build_alloca_store(zero, int64_type(), "sum_acc");
+        let length = list_value.len(self)?.get_value();
+        let zero = self.const_int(int32_type(), 0, 0);
+        let index_ptr = self.build_alloca_store(zero, int32_type(), "sum_index");
+
+        let function = self.current_function.function;
+        let sum_cond_block = self.append_basic_block(function, "sum_cond");
+        let sum_body_block = self.append_basic_block(function, "sum_body");
+        let sum_exit_block = self.append_basic_block(function, "sum_exit");
+        self.build_br(sum_cond_block);
+
+        self.set_current_block(sum_cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                index,
+                length,
+                cstr_from_string("sum_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, sum_body_block, sum_exit_block);
+
+        self.set_current_block(sum_body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let element = self.build_call(
+            get_value_func,
+            vec![list_value.get_value(), index],
+            2,
+            "element",
+        );
+        let total = unsafe {
+            let element = LLVMBuildSExt(
+                self.builder,
+                element,
+                int64_type(),
+                cstr_from_string("element_i64").as_ptr(),
+            );
+            let acc = self.build_load(sum_ptr, int64_type(), "acc");
+            LLVMBuildAdd(
+                self.builder,
+                acc,
+                element,
+                cstr_from_string("total").as_ptr(),
+            )
+        };
+        self.build_store(total, sum_ptr);
+        let one = self.const_int(int32_type(), 1, 0);
+        let next_index = self.llvm_build_fn(index, one, "+".to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(sum_cond_block);
+
+        self.set_current_block(sum_exit_block);
+        let sum = self.build_load(sum_ptr, int64_type(), "sum");
+        Ok((sum, length))
+    }
+
+    // the size on the target the module is compiled for, a bool is a single bit but takes
+    // up a byte
+    pub fn new_sizeof(&self, llvm_type: LLVMTypeRef) -> Result<Box<dyn TypeBase>> {
+        let size = unsafe { self.target_info().size_of(llvm_type) };
+        Ok(Box::new(NumberType64 {
+            llvm_value: self.const_int(int64_type(), size, 0),
+            llvm_value_pointer: None,
+            name: "sizeof".to_string(),
+        }))
+    }
+
+    pub fn new_read_line(&self) -> Result<Box<dyn TypeBase>> {
+        let read_line_func = self
+            .llvm_func_cache
+            .get("readLine")
+            .ok_or(anyhow!("unable to find readLine function"))?;
+        let line = self.build_call(read_line_func, vec![], 0, "read_line");
+        Ok(Box::new(StringType {
+            name: "read_line".to_string(),
+            llvm_value: line,
+            llvm_value_pointer: Some(line),
+        }))
+    }
+
+    pub fn new_read_int(&self) -> Result<Box<dyn TypeBase>> {
+        let read_int_func = self
+            .llvm_func_cache
+            .get("readInt")
+            .ok_or(anyhow!("unable to find readInt function"))?;
+        let value = self.build_call(read_int_func, vec![], 0, "read_int");
+        self.build_value_for_type(&BaseTypes::Number, value, "read_int")
+    }
+
+    // format is checked against its arguments at compile time, the result is sized with
+    // snprintf(NULL, 0, ..), written into a heap buffer with sprintf and copied into a
+    // StringType
+    pub fn new_format(
+        &mut self,
+        context: &mut ASTContext,
+        format: String,
+        args: Vec<Expression>,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let specifiers = Self::get_format_specifiers(&format)?;
+        if specifiers.len() != args.len() {
+            return Err(anyhow!(
+                "format string {:?} has {} format specifiers but {} arguments were given",
+                format,
+                specifiers.len(),
+                args.len()
+            ));
+        }
+
+        let mut format_args = vec![];
+        for (specifier, arg) in specifiers.into_iter().zip(args) {
+            let value = context.match_ast(arg.clone(), visitor, self)?;
+            let expected_type = match specifier {
+                's' => BaseTypes::String,
+                'f' => BaseTypes::Float,
+                _ => BaseTypes::Number,
+            };
+            if value.get_type() != expected_type {
+                return Err(anyhow!(
+                    "format specifier `%{}` expected `{}` to have type {}, but it was inferred as {}",
+                    specifier,
+                    arg,
+                    expected_type,
+                    value.get_type()
+                ));
+            }
+            match value.get_type() {
+                BaseTypes::String => format_args.push(self.load_string_buffer(value.as_ref())?),
+                _ => format_args.push(self.load_value(value.as_ref())),
+            }
+        }
+
+        let snprintf_func = self
+            .llvm_func_cache
+            .get("snprintf")
+            .ok_or(anyhow!("unable to find snprintf function"))?;
+        let sprintf_func = self
+            .llvm_func_cache
+            .get("sprintf")
+            .ok_or(anyhow!("unable to find sprintf function"))?;
+        let malloc_func = self
+            .llvm_func_cache
+            .get("malloc")
+            .ok_or(anyhow!("unable to find malloc function"))?;
+        let free_func = self
+            .llvm_func_cache
+            .get("free")
+            .ok_or(anyhow!("unable to find free function"))?;
+        let string_init_func = self
+            .llvm_func_cache
+            .get("stringInit")
+            .ok_or(anyhow!("unable to find stringInit function"))?;
+
+        let format_str = unsafe {
+            LLVMBuildGlobalStringPtr(
+                self.builder,
+                CString::new(format)?.as_ptr(),
+                cstr_from_string("format_str").as_ptr(),
+            )
+        };
+
+        let null_ptr = unsafe { LLVMConstPointerNull(int8_ptr_type()) };
+        let mut snprintf_args = vec![null_ptr, self.const_int(int64_type(), 0, 0), format_str];
+        snprintf_args.extend(format_args.iter());
+        let num_args = snprintf_args.len() as c_uint;
+        let length = self.build_call(snprintf_func, snprintf_args, num_args, "format_len");
+
+        let one = self.const_int(int32_type(), 1, 0);
+        let size = self.llvm_build_fn(length, one, "+".to_string());
+        let size = unsafe {
+            LLVMBuildSExt(
+                self.builder,
+                size,
+                int64_type(),
+                cstr_from_string("format_size").as_ptr(),
+            )
+        };
+        let buffer = self.build_call(malloc_func, vec![size], 1, "format_buffer");
+
+        let mut sprintf_args = vec![buffer, format_str];
+        sprintf_args.extend(format_args.iter());
+        let num_args = sprintf_args.len() as c_uint;
+        self.build_call(sprintf_func, sprintf_args, num_args, "");
+
+        let string = self.build_call(string_init_func, vec![buffer, length], 2, "format_string");
+        self.build_call(free_func, vec![buffer], 1, "");
+        Ok(Box::new(StringType {
+            name: "format_string".to_string(),
+            llvm_value: string,
+            llvm_value_pointer: Some(string),
+        }))
+    }
+
+    fn get_format_specifiers(format: &str) -> Result<Vec<char>> {
+        let mut specifiers = vec![];
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                continue;
+            }
+            match chars.next() {
+                Some('%') => {}
+                Some(specifier @ ('d' | 's' | 'f')) => specifiers.push(specifier),
+                Some(specifier) => {
+                    return Err(anyhow!(
+                        "unsupported format specifier `%{}` in {:?}, expected %d, %s or %f",
+                        specifier,
+                        format
+                    ))
+                }
+                None => {
+                    return Err(anyhow!(
+                        "format string {:?} ends with an incomplete format specifier",
+                        format
+                    ))
+                }
+            }
+        }
+        Ok(specifiers)
+    }
+
+    // each window is copied into its own list by windowsInt32List in types.c
+    pub fn new_windows(
+        &mut self,
+        context: &mut ASTContext,
+        list: Expression,
+        size: Expression,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let list_value = context.match_ast(list, visitor, self)?;
+        let size_value = context.match_ast(size, visitor, self)?;
+        if list_value.get_type() != BaseTypes::List(Box::new(BaseTypes::Number)) {
+            return Err(anyhow!(
+                "windows is only implemented for List<i32>, got {}",
+                list_value.get_type()
+            ));
+        }
+        if size_value.get_type() != BaseTypes::Number {
+            return Err(anyhow!(
+                "windows size must have type i32, got {}",
+                size_value.get_type()
+            ));
+        }
+        let windows_func = self
+            .llvm_func_cache
+            .get("windowsInt32List")
+            .ok_or(anyhow!("unable to find windowsInt32List function"))?;
+        let size = self.load_value(size_value.as_ref());
+        let windows = self.build_call(
+            windows_func,
+            vec![list_value.get_value(), size],
+            2,
+            "windows",
+        );
+        let list_type = unsafe { LLVMPointerType(self.get_list_int32_ptr_type(), 0) };
+        let ptr = self.build_alloca_store(windows, list_type, "windows");
+        Ok(Box::new(ListType {
+            llvm_value: windows,
+            llvm_value_ptr: ptr,
+            llvm_type: list_type,
+            inner_type: BaseTypes::List(Box::new(BaseTypes::Number)),
+        }))
+    }
+
+    /// new_slice
+    ///
+    /// Copies the elements of a list from `start` up to but not including `end` into a new
+    /// list. The bounds are clamped to the list, so a slice past the end stops at the end and
+    /// a start after the end gives an empty list.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_value` - The list to slice
+    /// * `start` - The index of the first element to copy
+    /// * `end` - The index after the last element to copy
+    ///
+    pub fn new_slice(
+        &mut self,
+        list_value: &dyn TypeBase,
+        start: &dyn TypeBase,
+        end: &dyn TypeBase,
+    ) -> Result<Box<dyn TypeBase>> {
+        let (func_names, inner_type, list_type) = match list_value.get_type() {
+            BaseTypes::List(inner_type) => match *inner_type {
+                BaseTypes::Number => (
+                    ("create_int32_tList", "get_int32_tValue", "set_int32_tValue"),
+                    BaseTypes::Number,
+                    self.get_list_int32_ptr_type(),
+                ),
+                BaseTypes::Number64 => (
+                    ("create_int64_tList", "get_int64_tValue", "set_int64_tValue"),
+                    BaseTypes::Number64,
+                    self.get_list_int64_ptr_type(),
+                ),
+                BaseTypes::String => (
+                    ("createStringList", "getStringValue", "setStringValue"),
+                    BaseTypes::String,
+                    self.get_list_string_ptr_type(),
+                ),
+                _ => return Err(anyhow!("unable to slice List<{}>", inner_type)),
+            },
+            other => return Err(anyhow!("unable to slice type {}", other)),
+        };
+        for bound in [start, end] {
+            if bound.get_type() != BaseTypes::Number {
+                return Err(anyhow!(
+                    "slice bounds must have type i32, got {}",
+                    bound.get_type()
+                ));
+            }
+        }
+        let find_func = |func_name: &str| {
+            self.llvm_func_cache
+                .get(func_name)
+                .ok_or(anyhow!("unable to find {} function", func_name))
+        };
+        let (create_func, get_func, set_func) = (
+            find_func(func_names.0)?,
+            find_func(func_names.1)?,
+            find_func(func_names.2)?,
+        );
+
+        let length = list_value.len(self)?.get_value();
+        let one = self.const_int(int32_type(), 1, 0);
+        let start = self.load_value(start);
+        let end = self.load_value(end);
+        self.build_slice_bounds_check(start, end, length)?;
+        let slice_length = self.llvm_build_fn(end, start, "-".to_string());
+        let slice = self.build_call(create_func, vec![slice_length], 1, "slice");
+        let index_ptr = self.build_alloca_store(start, int32_type(), "slice_index");
+
+        let function = self.current_function.function;
+        let cond_block = self.append_basic_block(function, "slice_cond");
+        let body_block = self.append_basic_block(function, "slice_body");
+        let exit_block = self.append_basic_block(function, "slice_exit");
+        self.build_br(cond_block);
+
+        self.set_current_block(cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                index,
+                end,
+                cstr_from_string("slice_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, body_block, exit_block);
+
+        self.set_current_block(body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let element = self.build_call(get_func, vec![list_value.get_value(), index], 2, "element");
+        let slice_index = self.llvm_build_fn(index, start, "-".to_string());
+        self.build_call(set_func, vec![slice, element, slice_index], 3, "");
+        let next_index = self.llvm_build_fn(index, one, "+".to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(cond_block);
+
+        self.set_current_block(exit_block);
+        let ptr = self.build_alloca_store(slice, list_type, "slice");
+        Ok(Box::new(ListType {
+            llvm_value: slice,
+            llvm_value_ptr: ptr,
+            llvm_type: list_type,
+            inner_type,
+        }))
+    }
+
+    /// new_chars
+    ///
+    /// Copies each byte of a string into a new `List<string>` of one byte strings. Every byte
+    /// is treated as an ASCII character, so a multi-byte UTF-8 character is split across
+    /// several elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `string_value` - The string to split into characters
+    ///
+    pub fn new_chars(&mut self, string_value: &dyn TypeBase) -> Result<Box<dyn TypeBase>> {
+        let find_func = |func_name: &str| {
+            self.llvm_func_cache
+                .get(func_name)
+                .ok_or(anyhow!("unable to find {} function", func_name))
+        };
+        let (create_func, set_func, string_init_func) = (
+            find_func("createStringList")?,
+            find_func("setStringValue")?,
+            find_func("stringInit")?,
+        );
+        let list_type = self.get_list_string_ptr_type();
+
+        let buffer = self.load_string_buffer(string_value)?;
+        let length = self.load_string_length(string_value)?;
+        let one = self.const_int(int32_type(), 1, 0);
+        let chars = self.build_call(create_func, vec![length], 1, "chars");
+        let zero = self.const_int(int32_type(), 0, 0);
+        let index_ptr = self.build_alloca_store(zero, int32_type(), "chars_index");
+
+        let function = self.current_function.function;
+        let cond_block = self.append_basic_block(function, "chars_cond");
+        let body_block = self.append_basic_block(function, "chars_body");
+        let exit_block = self.append_basic_block(function, "chars_exit");
+        self.build_br(cond_block);
+
+        self.set_current_block(cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                index,
+                length,
+                cstr_from_string("chars_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, body_block, exit_block);
+
+        self.set_current_block(body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let byte_ptr = unsafe {
+            LLVMBuildGEP2(
+                self.builder,
+                int8_type(),
+                buffer,
+                [index].as_mut_ptr(),
+                1,
+                cstr_from_string("char_ptr").as_ptr(),
+            )
+        };
+        let char_string = self.build_call(string_init_func, vec![byte_ptr, one], 2, "char");
+        self.build_call(set_func, vec![chars, char_string, index], 3, "");
+        let next_index = self.llvm_build_fn(index, one, "+".to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(cond_block);
+
+        self.set_current_block(exit_block);
+        let ptr = self.build_alloca_store(chars, list_type, "chars");
+        Ok(Box::new(ListType {
+            llvm_value: chars,
+            llvm_value_ptr: ptr,
+            llvm_type: list_type,
+            inner_type: BaseTypes::String,
+        }))
+    }
+
+    /// new_list_sort
+    ///
+    /// Sorts a `List<i32>` in place with the `listSortI32` runtime helper, or
+    /// `listSortDescI32` to sort from largest to smallest.
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - The list to sort
+    /// * `descending` - Whether the largest value goes first
+    ///
+    pub fn new_list_sort(
+        &mut self,
+        list: &dyn TypeBase,
+        descending: bool,
+    ) -> Result<Box<dyn TypeBase>> {
+        let func_name = if descending {
+            "listSortDescI32"
+        } else {
+            "listSortI32"
+        };
+        let length = list.len(self)?;
+        let count = self.build_cast(length.as_ref(), &Type::i64)?.get_value();
+        let sort_func = self
+            .llvm_func_cache
+            .get(func_name)
+            .ok_or(anyhow!("unable to find {} function", func_name))?;
+        self.build_call(sort_func, vec![list.get_value(), count], 2, "");
+        Ok(Box::new(VoidType {}))
+    }
+
+    /// new_list_contains
+    ///
+    /// Searches a `List<i32>` or `List<string>` for a value. The loop is built in place
+    /// like fold's, and it branches straight out of the loop at the first element equal
+    /// to the value. Strings are compared with memcmp over their buffers and then by length,
+    /// so strings holding NULs are matched in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - The list to search
+    /// * `needle` - The value to look for, it has the list's element type
+    ///
+    pub fn new_list_contains(
+        &mut self,
+        list: &dyn TypeBase,
+        needle: &(dyn TypeBase + 'static),
+    ) -> Result<Box<dyn TypeBase>> {
+        let (get_value_func_name, inner_type) = match list.get_type() {
+            BaseTypes::List(inner_type) if *inner_type == needle.get_type() => match *inner_type {
+                BaseTypes::Number => ("get_int32_tValue", BaseTypes::Number),
+                BaseTypes::String => ("getStringValue", BaseTypes::String),
+                _ => {
+                    return Err(anyhow!(
+                        "contains is not implemented for List<{}>",
+                        inner_type
+                    ))
+                }
+            },
+            list_type => {
+                return Err(anyhow!(
+                    "unable to check if {} contains {}",
+                    list_type,
+                    needle.get_type()
+                ))
+            }
+        };
+        let get_value_func = self
+            .llvm_func_cache
+            .get(get_value_func_name)
+            .ok_or(anyhow!("unable to find {} function", get_value_func_name))?;
+
+        let not_found = self.const_int(int1_type(), 0, 0);
+        let found_ptr = self.build_alloca_store(not_found, int1_type(), "contains_found");
+        let length = list.len(self)?;
+        let zero = self.const_int(int32_type(), 0, 0);
+        let index_ptr = self.build_alloca_store(zero, int32_type(), "contains_index");
+
+        let function = self.current_function.function;
+        let cond_block = self.append_basic_block(function, "contains_cond");
+        let body_block = self.append_basic_block(function, "contains_body");
+        let found_block = self.append_basic_block(function, "contains_match");
+        let next_block = self.append_basic_block(function, "contains_next");
+        let exit_block = self.append_basic_block(function, "contains_exit");
+        self.build_br(cond_block);
+
+        self.set_current_block(cond_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let cmp = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                index,
+                length.get_value(),
+                cstr_from_string("contains_cmp").as_ptr(),
+            )
+        };
+        self.build_cond_br(cmp, body_block, exit_block);
+
+        self.set_current_block(body_block);
+        let index = self.build_load(index_ptr, int32_type(), "index");
+        let element = self.build_call(get_value_func, vec![list.get_value(), index], 2, "element");
+        let is_equal = match inner_type {
+            BaseTypes::String => {
+                let element = StringType {
+                    name: "element".to_string(),
+                    llvm_value: element,
+                    llvm_value_pointer: Some(element),
+                };
+                self.build_strcmp(&element, needle, LLVMIntEQ)?
+            }
+            _ => {
+                let element = self.build_value_for_type(&inner_type, element, "element")?;
+                self.cmp(element, dyn_clone::clone_box(needle), "==".to_string())?
+                    .get_value()
+            }
+        };
+        self.build_cond_br(is_equal, found_block, next_block);
+
+        self.set_current_block(found_block);
+        let found = self.const_int(int1_type(), 1, 0);
+        self.build_store(found, found_ptr);
+        self.build_br(exit_block);
+
+        self.set_current_block(next_block);
+        let one = self.const_int(int32_type(), 1, 0);
+        let next_index = self.llvm_build_fn(index, one, "+".to_string());
+        self.build_store(next_index, index_ptr);
+        self.build_br(cond_block);
+
+        self.set_current_block(exit_block);
+        let found = self.build_load(found_ptr, int1_type(), "contains");
+        self.build_value_for_type(&BaseTypes::Bool, found, "contains")
+    }
+
+    /// new_string_from_helper
+    ///
+    /// Calls a runtime helper that takes a string and returns a new one, such as
+    /// `stringToUpper`. The string passed in is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `func_name` - The name of the runtime helper
+    /// * `string_value` - The string to pass to it
+    ///
+    pub fn new_string_from_helper(
+        &mut self,
+        func_name: &str,
+        string_value: &dyn TypeBase,
+    ) -> Result<Box<dyn TypeBase>> {
+        let func = self
+            .llvm_func_cache
+            .get(func_name)
+            .ok_or(anyhow!("unable to find {} function", func_name))?;
+        let result = self.build_call(func, vec![string_value.get_value()], 1, func_name);
+        Ok(Box::new(StringType {
+            name: func_name.to_string(),
+            llvm_value: result,
+            llvm_value_pointer: Some(result),
+        }))
+    }
+
+    /// new_split
+    ///
+    /// Splits a string into a new `List<string>` with the `stringSplit` runtime helper. Each
+    /// occurrence of the whole delimiter separates two parts and empty parts are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `string_value` - The string to split
+    /// * `delimiter` - The string to split on
+    ///
+    pub fn new_split(
+        &mut self,
+        string_value: &dyn TypeBase,
+        delimiter: &dyn TypeBase,
+    ) -> Result<Box<dyn TypeBase>> {
+        let split_func = self
+            .llvm_func_cache
+            .get("stringSplit")
+            .ok_or(anyhow!("unable to find stringSplit function"))?;
+        let list_type = self.get_list_string_ptr_type();
+        let parts = self.build_call(
+            split_func,
+            vec![string_value.get_value(), delimiter.get_value()],
+            2,
+            "split",
+        );
+        let ptr = self.build_alloca_store(parts, list_type, "split");
+        Ok(Box::new(ListType {
+            llvm_value: parts,
+            llvm_value_ptr: ptr,
+            llvm_type: list_type,
+            inner_type: BaseTypes::String,
+        }))
+    }
+
+    // exits with an error when the bounds of a slice aren't `0 <= start <= end <= length`,
+    // compared unsigned so a negative bound is out of range too
+    fn build_slice_bounds_check(
+        &mut self,
+        start: LLVMValueRef,
+        end: LLVMValueRef,
+        length: LLVMValueRef,
+    ) -> Result<()> {
+        let print_func = self
+            .llvm_func_cache
+            .get("dprintf")
+            .ok_or(anyhow!("unable to find dprintf function"))?;
+        let exit_func = self
+            .llvm_func_cache
+            .get("exit")
+            .ok_or(anyhow!("unable to find exit function"))?;
+        let in_range = unsafe {
+            let start_in_range = LLVMBuildICmp(
+                self.builder,
+                LLVMIntULE,
+                start,
+                end,
+                cstr_from_string("start_in_range").as_ptr(),
+            );
+            let end_in_range = LLVMBuildICmp(
+                self.builder,
+                LLVMIntULE,
+                end,
+                length,
+                cstr_from_string("end_in_range").as_ptr(),
+            );
+            LLVMBuildAnd(
+                self.builder,
+                start_in_range,
+                end_in_range,
+                cstr_from_string("in_range").as_ptr(),
+            )
+        };
+        let function = self.current_function.function;
+        let out_of_range_block = self.append_basic_block(function, "slice_out_of_range");
+        let in_range_block = self.append_basic_block(function, "slice_in_range");
+        self.build_cond_br(in_range, in_range_block, out_of_range_block);
+
+        self.set_current_block(out_of_range_block);
+        let stderr = self.const_int(int32_type(), 2, 0);
+        let format = self.build_global_string(b"slice bounds %d..%d out of range for length %d\n");
+        self.build_call(print_func, vec![stderr, format, start, end, length], 5, "");
+        let code = self.const_int(int32_type(), 1, 0);
+        self.build_call(exit_func, vec![code], 1, "");
+        unsafe {
+            LLVMBuildUnreachable(self.builder);
+        }
+
+        self.set_current_block(in_range_block);
+        Ok(())
+    }
+
+    // match arms are tested in order with `cmp`, each arm gets its own block and
+    // the arm values are merged with a phi node in the merge block
+    pub fn new_match_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        scrutinee: Expression,
+        arms: Vec<(Expression, Expression)>,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+        let scrutinee_value = context.match_ast(scrutinee, visitor, self)?;
+        let merge_block = self.append_basic_block(function, "match_merge");
+
+        let mut arm_type: Option<BaseTypes> = None;
+        let mut incoming_values: Vec<LLVMValueRef> = vec![];
+        let mut incoming_blocks: Vec<LLVMBasicBlockRef> = vec![];
+        let mut has_wildcard = false;
+        let mut bool_patterns = vec![];
+
+        for (pattern, body) in arms {
+            if has_wildcard {
+                return Err(anyhow!(
+                    "unreachable match arm `{}` after wildcard `_`",
+                    pattern
+                ));
+            }
+            let arm_block = self.append_basic_block(function, "match_arm");
+            let next_block = match pattern {
+                Expression::Wildcard => {
+                    has_wildcard = true;
+                    self.build_br(arm_block);
+                    None
+                }
+                _ => {
+                    if let Expression::Bool(b) = pattern {
+                        bool_patterns.push(b);
+                    }
+                    let pattern_value = context.match_ast(pattern.clone(), visitor, self)?;
+                    if scrutinee_value.get_type() != pattern_value.get_type() {
+                        return Err(anyhow!(
+                            "match pattern `{}` has type {} but the value being matched has type {}",
+                            pattern,
+                            pattern_value.get_type(),
+                            scrutinee_value.get_type()
+                        ));
+                    }
+                    let next_block = self.append_basic_block(function, "match_next");
+                    let cond = match pattern_value.get_type() {
+                        BaseTypes::String => self.build_strcmp(
+                            scrutinee_value.as_ref(),
+                            pattern_value.as_ref(),
+                            LLVMIntEQ,
+                        )?,
+                        _ => self
+                            .cmp(scrutinee_value.clone(), pattern_value, "==".to_string())?
+                            .get_value(),
+                    };
+                    self.build_cond_br(cond, arm_block, next_block);
+                    Some(next_block)
+                }
+            };
+
+            self.set_current_block(arm_block);
+            self.build_match_arm(
+                context,
+                body,
+                merge_block,
+                &mut arm_type,
+                &mut incoming_values,
+                &mut incoming_blocks,
+                visitor,
+            )?;
+            if let Some(next_block) = next_block {
+                self.set_current_block(next_block);
+            }
+        }
+
+        // no arm matched, a match without a value falls through to the merge block. A match with
+        // a value has nothing to give the phi, so it needs a `_` arm unless it matches both bools
+        if !has_wildcard {
+            let has_value = arm_type.as_ref().is_some_and(|t| *t != BaseTypes::Void);
+            if !has_value {
+                self.build_br(merge_block);
+            } else if bool_patterns.contains(&true) && bool_patterns.contains(&false) {
+                unsafe { LLVMBuildUnreachable(self.builder) };
+            } else {
+                return Err(anyhow!("a match with a value needs a wildcard `_` arm"));
+            }
+        }
+
+        self.set_current_block(merge_block);
+        match arm_type {
+            Some(arm_type) if !incoming_values.is_empty() => unsafe {
+                let phi = LLVMBuildPhi(
+                    self.builder,
+                    self.get_llvm_type_for(&arm_type),
+                    cstr_from_string("match_value").as_ptr(),
+                );
+                LLVMAddIncoming(
+                    phi,
+                    incoming_values.as_mut_ptr(),
+                    incoming_blocks.as_mut_ptr(),
+                    incoming_values.len() as c_uint,
+                );
+                self.build_value_for_type(&arm_type, phi, "match_value")
+            },
+            _ => Ok(Box::new(VoidType {})),
+        }
+    }
+
+    // string patterns and orderings compare the underlying char buffers rather than the
+    // StringType pointers. Strings can hold NULs so the buffers are compared with memcmp over
+    // the shorter length, a string that's a prefix of the other sorts first. The result is
+    // compared to zero with the predicate like a strcmp result
+    fn build_strcmp(
+        &self,
+        lhs: &dyn TypeBase,
+        rhs: &dyn TypeBase,
+        predicate: LLVMIntPredicate,
+    ) -> Result<LLVMValueRef> {
+        let memcmp_func = self
+            .llvm_func_cache
+            .get("memcmp")
+            .ok_or(anyhow!("unable to find memcmp function"))?;
+        let lhs_buffer = self.load_string_buffer(lhs)?;
+        let rhs_buffer = self.load_string_buffer(rhs)?;
+        let lhs_length = self.load_string_length(lhs)?;
+        let rhs_length = self.load_string_length(rhs)?;
+        let zero = self.const_int(int32_type(), 0, 0);
+        unsafe {
+            let lhs_shorter = LLVMBuildICmp(
+                self.builder,
+                LLVMIntSLT,
+                lhs_length,
+                rhs_length,
+                cstr_from_string("lhs_shorter").as_ptr(),
+            );
+            let length = LLVMBuildSelect(
+                self.builder,
+                lhs_shorter,
+                lhs_length,
+                rhs_length,
+                cstr_from_string("common_length").as_ptr(),
+            );
+            let length = LLVMBuildSExt(
+                self.builder,
+                length,
+                int64_type(),
+                cstr_from_string("common_length_ext").as_ptr(),
+            );
+            let result = self.build_call(
+                memcmp_func,
+                vec![lhs_buffer, rhs_buffer, length],
+                3,
+                "memcmp",
+            );
+            let same_prefix = LLVMBuildICmp(
+                self.builder,
+                LLVMIntEQ,
+                result,
+                zero,
+                cstr_from_string("same_prefix").as_ptr(),
+            );
+            let length_diff = LLVMBuildSub(
+                self.builder,
+                lhs_length,
+                rhs_length,
+                cstr_from_string("length_diff").as_ptr(),
+            );
+            let result = LLVMBuildSelect(
+                self.builder,
+                same_prefix,
+                length_diff,
+                result,
+                cstr_from_string("string_cmp").as_ptr(),
+            );
+            Ok(LLVMBuildICmp(
+                self.builder,
+                predicate,
+                result,
+                zero,
+                cstr_from_string("string_cmp_result").as_ptr(),
+            ))
+        }
+    }
+
+    /// load_string_length
+    ///
+    /// Loads the `int32_t length` field from a struct.StringType pointer, the number of bytes
+    /// in the string not counting the null after them.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The string to load the length of
+    ///
+    pub fn load_string_length(&self, value: &dyn TypeBase) -> Result<LLVMValueRef> {
+        let string_ptr = value.get_ptr().ok_or(anyhow!(
+            "unable to get pointer for string {}",
+            value.get_name_as_str()
+        ))?;
+        let length_ptr = unsafe {
+            LLVMBuildStructGEP2(
+                self.builder,
+                self.get_string_type(),
+                string_ptr,
+                1,
+                cstr_from_string("string_length_ptr").as_ptr(),
+            )
+        };
+        Ok(self.build_load(length_ptr, int32_type(), "string_length"))
+    }
+
+    /// store_string_length
+    ///
+    /// Stores to the `int32_t length` field of a struct.StringType pointer, the buffer is left
+    /// as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The string to set the length of
+    /// * `length` - The i32 length to store
+    ///
+    pub fn store_string_length(&self, value: &dyn TypeBase, length: LLVMValueRef) -> Result<()> {
+        let string_ptr = value.get_ptr().ok_or(anyhow!(
+            "unable to get pointer for string {}",
+            value.get_name_as_str()
+        ))?;
+        let length_ptr = unsafe {
+            LLVMBuildStructGEP2(
+                self.builder,
+                self.get_string_type(),
+                string_ptr,
+                1,
+                cstr_from_string("string_length_ptr").as_ptr(),
+            )
+        };
+        self.build_store(length, length_ptr);
+        Ok(())
+    }
+
+    /// load_string_buffer
+    ///
+    /// Loads the `char *buffer` field from a struct.StringType pointer.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The string to load the buffer of
+    ///
+    pub fn load_string_buffer(&self, value: &dyn TypeBase) -> Result<LLVMValueRef> {
+        let string_ptr = value.get_ptr().ok_or(anyhow!(
+            "unable to get pointer for string {}",
+            value.get_name_as_str()
+        ))?;
+        let buffer_ptr = unsafe {
+            LLVMBuildStructGEP2(
+                self.builder,
+                self.get_string_type(),
+                string_ptr,
+                0,
+                cstr_from_string("string_buffer_ptr").as_ptr(),
+            )
+        };
+        Ok(self.build_load(buffer_ptr, int8_ptr_type(), "string_buffer"))
+    }
+
+    /// free_strings
+    ///
+    /// Frees the buffer and then the struct.StringType of each string, skipped when the
+    /// current block already ends in a `return` or `break` as nothing can be built after it.
+    /// The strings are leaked in that case rather than freed before the branch.
+    ///
+    /// # Arguments
+    ///
+    /// * `strings` - The struct.StringType pointers to free
+    ///
+    pub fn free_strings(&self, strings: &[LLVMValueRef]) -> Result<()> {
+        let terminator = unsafe { LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(self.builder)) };
+        if !terminator.is_null() {
+            return Ok(());
+        }
+        let free_func = self
+            .llvm_func_cache
+            .get("free")
+            .ok_or(anyhow!("unable to find free function"))?;
+        for string in strings {
+            let string_type = StringType {
+                name: "".to_string(),
+                llvm_value: *string,
+                llvm_value_pointer: Some(*string),
+            };
+            let buffer = self.load_string_buffer(&string_type)?;
+            self.build_call(free_func.clone(), vec![buffer], 1, "");
+            self.build_call(free_func.clone(), vec![*string], 1, "");
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_match_arm(
+        &mut self,
+        context: &mut ASTContext,
+        body: Expression,
+        merge_block: LLVMBasicBlockRef,
+        arm_type: &mut Option<BaseTypes>,
+        incoming_values: &mut Vec<LLVMValueRef>,
+        incoming_blocks: &mut Vec<LLVMBasicBlockRef>,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<()> {
+        let value = context.match_ast(body, visitor, self)?;
+        let value_type = match value.get_type() {
+            // if its a return type we will skip branching in the LLVM IR
+            BaseTypes::Return => return Ok(()),
+            // an arm without a value has nothing to add to the phi, so it can't be mixed with
+            // arms that have one
+            BaseTypes::Void | BaseTypes::Func => BaseTypes::Void,
+            value_type => value_type,
+        };
+        if let Some(expected) = arm_type {
+            if *expected != value_type {
+                return Err(anyhow!(
+                    "match arms must all have the same type, expected {} but found {}",
+                    expected,
+                    value_type
+                ));
+            }
+        }
+        if value_type != BaseTypes::Void {
+            incoming_values.push(self.load_value(value.as_ref()));
+            incoming_blocks.push(self.current_function.block);
+        }
+        *arm_type = Some(value_type);
+        self.build_br(merge_block);
+        Ok(())
+    }
+
+    /// new_switch_stmt
+    ///
+    /// Builds a `switch` on an integer as a single LLVM switch instruction, each case jumps
+    /// to its own block and anything else to the default block, or straight past the switch
+    /// when there's no default. Cases don't fall through into the next one.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The AST context the scrutinee and case bodies are built in
+    /// * `scrutinee` - The i32 or i64 value being switched on
+    /// * `cases` - Each case value with the body to run for it
+    /// * `default` - The body to run when no case matches
+    /// * `visitor` - The visitor used to build the expressions
+    ///
+    pub fn new_switch_stmt(
+        &mut self,
+        context: &mut ASTContext,
+        scrutinee: Expression,
+        cases: Vec<(i32, Expression)>,
+        default: Option<Expression>,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let function = self.current_function.function;
+        let scrutinee_value = context.match_ast(scrutinee, visitor, self)?;
+        let case_type = match scrutinee_value.get_type() {
+            BaseTypes::Number => int32_type(),
+            BaseTypes::Number64 => int64_type(),
+            other => {
+                return Err(anyhow!(
+                    "unable to switch on type {}, switch cases are integers",
+                    other
+                ))
+            }
+        };
+        let merge_block = self.append_basic_block(function, "switch_merge");
+        let default_block = match default {
+            Some(_) => self.append_basic_block(function, "switch_default"),
+            None => merge_block,
+        };
+        let switch = unsafe {
+            LLVMBuildSwitch(
+                self.builder,
+                self.load_value(scrutinee_value.as_ref()),
+                default_block,
+                cases.len() as c_uint,
+            )
+        };
+
+        let mut seen_cases = HashSet::new();
+        for (case, body) in cases {
+            if !seen_cases.insert(case) {
+                return Err(anyhow!("duplicate switch case {}", case));
+            }
+            let case_block = self.append_basic_block(function, "switch_case");
+            // i32 to u64 sign extends, so negative cases are the same value in an i64 switch
+            let case_value = self.const_int(case_type, case as u64, 1);
+            unsafe { LLVMAddCase(switch, case_value, case_block) };
+            self.set_current_block(case_block);
+            self.build_switch_case(context, body, merge_block, visitor)?;
+        }
+        if let Some(default) = default {
+            self.set_current_block(default_block);
+            self.build_switch_case(context, default, merge_block, visitor)?;
+        }
+
+        self.set_current_block(merge_block);
+        Ok(Box::new(VoidType {}))
+    }
+
+    fn build_switch_case(
         &mut self,
         context: &mut ASTContext,
-        condition: Expression,
-        while_block_stmt: Expression,
+        body: Expression,
+        merge_block: LLVMBasicBlockRef,
         visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
-    ) -> Result<Box<dyn TypeBase>> {
-        let function = self.current_function.function;
-
-        let loop_cond_block = self.append_basic_block(function, "loop_cond");
-        let loop_body_block = self.append_basic_block(function, "loop_body");
-        let loop_exit_block = self.append_basic_block(function, "loop_exit");
-
-        let bool_type_ptr = self.build_alloca(int1_type(), "while_value_bool_var");
-
-
-        self.build_br(loop_cond_block);
-
-        self.set_current_block(loop_body_block);
-        // Check if the global variable already exists
+    ) -> Result<()> {
+        let value = context.match_ast(body, visitor, self)?;
+        // if its a return type we will skip branching in the LLVM IR
+        if value.get_type() != BaseTypes::Return {
+            self.build_br(merge_block);
+        }
+        Ok(())
+    }
 
-        context.match_ast(while_block_stmt, visitor, self)?;
+    // reads the current value of a TypeBase, strings are passed around as a pointer to
+    // the StringType struct so don't need a load
+    pub fn load_value(&self, value: &dyn TypeBase) -> LLVMValueRef {
+        match (value.get_type(), value.get_ptr()) {
+            (BaseTypes::String, _) | (BaseTypes::List(_), _) => value.get_value(),
+            (_, Some(ptr)) => self.build_load(ptr, value.get_llvm_type(), "load_value"),
+            (_, None) => value.get_value(),
+        }
+    }
 
-        self.build_br(loop_cond_block); // Jump back to loop condition
+    // wraps a raw LLVM value in the TypeBase for base_type, storing it on the stack
+    // for the types that are read back through a pointer
+    pub fn build_value_for_type(
+        &self,
+        base_type: &BaseTypes,
+        value: LLVMValueRef,
+        name: &str,
+    ) -> Result<Box<dyn TypeBase>> {
+        match base_type {
+            BaseTypes::Number => Ok(Box::new(NumberType {
+                name: name.to_string(),
+                llvm_value: value,
+                llvm_value_pointer: Some(self.build_alloca_store(value, int32_type(), name)),
+            })),
+            BaseTypes::Number64 => Ok(Box::new(NumberType64 {
+                name: name.to_string(),
+                llvm_value: value,
+                llvm_value_pointer: Some(self.build_alloca_store(value, int64_type(), name)),
+            })),
+            BaseTypes::Float => Ok(Box::new(FloatType {
+                name: name.to_string(),
+                llvm_value: value,
+                llvm_value_pointer: Some(self.build_alloca_store(value, float_type(), name)),
+            })),
+            BaseTypes::Bool => Ok(Box::new(BoolType {
+                name: name.to_string(),
+                builder: self.builder,
+                llvm_value: value,
+                llvm_value_pointer: self.build_alloca_store(value, int1_type(), name),
+            })),
+            BaseTypes::String => Ok(Box::new(StringType {
+                name: name.to_string(),
+                llvm_value: value,
+                llvm_value_pointer: Some(value),
+            })),
+            BaseTypes::List(inner_type) => {
+                let llvm_type = unsafe { LLVMTypeOf(value) };
+                Ok(Box::new(ListType {
+                    llvm_value: value,
+                    llvm_value_ptr: self.build_alloca_store(value, llvm_type, name),
+                    llvm_type,
+                    inner_type: *inner_type.clone(),
+                }))
+            }
+            _ => Err(anyhow!("unable to build value for type {}", base_type)),
+        }
+    }
 
-        self.set_current_block(loop_cond_block);
-        let value_condition = context.match_ast(condition, visitor, self)?;
-        let cmp = self.build_load(value_condition.get_ptr().unwrap(), int1_type(), "cmp");
+    // tuples and structs are both an anonymous LLVM struct with a field per component
+    pub fn get_struct_type(&self, component_types: &[BaseTypes]) -> LLVMTypeRef {
+        let mut llvm_types: Vec<LLVMTypeRef> = component_types
+            .iter()
+            .map(|component_type| self.get_llvm_type_for(component_type))
+            .collect();
+        unsafe { LLVMStructType(llvm_types.as_mut_ptr(), llvm_types.len() as c_uint, 0) }
+    }
 
-        self.build_store(cmp, bool_type_ptr);
-        // Build loop condition block
-        let value_cond_load = self.build_load(
-            value_condition.get_ptr().unwrap(),
-            int1_type(),
-            "while_value_bool_var",
-        );
+    /// build_tuple
+    ///
+    /// Inserts the current value of each component into a new tuple struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `components` - The values making up the tuple, in order
+    ///
+    pub fn build_tuple(&self, components: Vec<Box<dyn TypeBase>>) -> Result<TupleType> {
+        let component_types: Vec<BaseTypes> = components.iter().map(|c| c.get_type()).collect();
+        for component_type in &component_types {
+            check_component_type(component_type)?;
+        }
+        let llvm_type = self.get_struct_type(&component_types);
+        let mut llvm_value = unsafe { LLVMGetUndef(llvm_type) };
+        for (i, component) in components.iter().enumerate() {
+            llvm_value = unsafe {
+                LLVMBuildInsertValue(
+                    self.builder,
+                    llvm_value,
+                    self.load_value(component.as_ref()),
+                    i as c_uint,
+                    cstr_from_string("tuple_insert").as_ptr(),
+                )
+            };
+        }
+        Ok(TupleType {
+            llvm_value,
+            llvm_value_pointer: self.build_alloca_store(llvm_value, llvm_type, "tuple"),
+            llvm_type,
+            components,
+        })
+    }
 
-        self.build_cond_br(value_cond_load, loop_body_block, loop_exit_block);
+    /// build_tuple_from_value
+    ///
+    /// Extracts the components of a tuple struct, i.e. one returned from a function call.
+    ///
+    /// # Arguments
+    ///
+    /// * `llvm_value` - The LLVM struct value
+    /// * `component_types` - The type of each field in the struct
+    ///
+    pub fn build_tuple_from_value(
+        &self,
+        llvm_value: LLVMValueRef,
+        component_types: &[BaseTypes],
+    ) -> Result<TupleType> {
+        let mut components = vec![];
+        for (i, component_type) in component_types.iter().enumerate() {
+            let component = unsafe {
+                LLVMBuildExtractValue(
+                    self.builder,
+                    llvm_value,
+                    i as c_uint,
+                    cstr_from_string("tuple_extract").as_ptr(),
+                )
+            };
+            components.push(self.build_value_for_type(
+                component_type,
+                component,
+                "tuple_component",
+            )?);
+        }
+        let llvm_type = self.get_struct_type(component_types);
+        Ok(TupleType {
+            llvm_value,
+            llvm_value_pointer: self.build_alloca_store(llvm_value, llvm_type, "tuple"),
+            llvm_type,
+            components,
+        })
+    }
 
-        // Position builder at loop exit block
-        self.set_current_block(loop_exit_block);
-        Ok(value_condition)
+    /// build_struct
+    ///
+    /// Inserts the current value of each field into a new struct of the given layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The layout of the struct being built
+    /// * `fields` - The value of each field, in the order of the layout
+    ///
+    pub fn build_struct(
+        &self,
+        layout: &StructLayout,
+        fields: Vec<Box<dyn TypeBase>>,
+    ) -> StructType {
+        let mut llvm_value = unsafe { LLVMGetUndef(layout.llvm_type) };
+        for (i, field) in fields.iter().enumerate() {
+            llvm_value = unsafe {
+                LLVMBuildInsertValue(
+                    self.builder,
+                    llvm_value,
+                    self.load_value(field.as_ref()),
+                    i as c_uint,
+                    cstr_from_string("struct_insert").as_ptr(),
+                )
+            };
+        }
+        StructType {
+            name: layout.name.clone(),
+            llvm_value,
+            llvm_value_pointer: self.build_alloca_store(llvm_value, layout.llvm_type, "struct"),
+            llvm_type: layout.llvm_type,
+        }
     }
 
-    // here we "desugar" a for loop to a while loop
-    pub fn new_for_loop(
-        &mut self,
-        context: &mut ASTContext,
-        var_name: String,
-        init: i32,
-        length: i32,
-        increment: i32,
-        for_block_expr: Expression
+    /// build_field_access
+    ///
+    /// Reads a field of a struct through a GEP into the struct's stack allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The struct value
+    /// * `layout` - The layout of the struct
+    /// * `field` - The name of the field being read
+    ///
+    pub fn build_field_access(
+        &self,
+        value: &dyn TypeBase,
+        layout: &StructLayout,
+        field: &str,
     ) -> Result<Box<dyn TypeBase>> {
-        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
-        // initiate variable
-        let variable = Expression::Variable(var_name.clone());
-        let value = LetStmt(var_name.clone(), Type::i32, Box::new(Number(init)));
-        context.match_ast(value, &mut visitor, self)?;
-
-        // create condition for while loop
-        let condition_for_while_loop = Self::get_while_cond_loop(increment);
-        let cond = Expression::Binary(Box::new(variable.clone()), condition_for_while_loop.into(), Box::new(Number(length)));
-
-        //increment after each while loop pass
-        let add_to_value =  Expression::Binary(Box::new(variable.clone()), "+".into(), Box::new(Number(increment)));
-        let add_to_value = LetStmt(var_name, Type::i32, Box::new(add_to_value.clone()));
+        let (index, field_type) = layout.get_field(field).ok_or(anyhow!(
+            "struct {} has no field `{}`",
+            layout.name,
+            field
+        ))?;
+        let struct_ptr = value
+            .get_ptr()
+            .ok_or(anyhow!("unable to get pointer for struct {}", layout.name))?;
+        let field_ptr = unsafe {
+            LLVMBuildStructGEP2(
+                self.builder,
+                layout.llvm_type,
+                struct_ptr,
+                index as c_uint,
+                cstr_from_string("field_ptr").as_ptr(),
+            )
+        };
+        let field_value = self.build_load(field_ptr, self.get_llvm_type_for(field_type), field);
+        self.build_value_for_type(field_type, field_value, field)
+    }
 
-        // add at the end of the block stmt and then pass through as a while loop
-        let new_block_stmt = BlockStmt(vec![for_block_expr, add_to_value]);
-        self.new_while_stmt(context, cond, new_block_stmt, &mut visitor)
+    fn get_llvm_type_for(&self, base_type: &BaseTypes) -> LLVMTypeRef {
+        match base_type {
+            BaseTypes::Number => int32_type(),
+            BaseTypes::Number64 => int64_type(),
+            BaseTypes::Float => float_type(),
+            BaseTypes::Bool => int1_type(),
+            BaseTypes::String => self.get_string_ptr_type(),
+            BaseTypes::List(_) => int32_ptr_type(),
+            BaseTypes::Tuple(component_types) => self.get_struct_type(component_types),
+            _ => {
+                unreachable!("LLVMType for Type {:?} not found", base_type)
+            }
+        }
     }
 
     fn get_while_cond_loop(increment: i32) -> &'static str {
@@ -607,6 +3722,59 @@ impl LLVMCodegenBuilder {
                     return_type: Type::None,
                 },
             );
+
+            // libc functions used directly from codegen
+            let memcmp_args = vec![int8_ptr_type(), int8_ptr_type(), int64_type()];
+            self.declare_libc_function(
+                main_block,
+                "memcmp",
+                memcmp_args,
+                int32_type(),
+                Type::i32,
+                false,
+            );
+            let snprintf_args = vec![int8_ptr_type(), int64_type(), int8_ptr_type()];
+            self.declare_libc_function(
+                main_block,
+                "snprintf",
+                snprintf_args,
+                int32_type(),
+                Type::i32,
+                true,
+            );
+            let sprintf_args = vec![int8_ptr_type(), int8_ptr_type()];
+            self.declare_libc_function(
+                main_block,
+                "sprintf",
+                sprintf_args,
+                int32_type(),
+                Type::i32,
+                true,
+            );
+            let malloc_args = vec![int64_type()];
+            // there is no cyclang type for a raw pointer, callers cast the result themselves
+            self.declare_libc_function(
+                main_block,
+                "malloc",
+                malloc_args,
+                int8_ptr_type(),
+                Type::None,
+                false,
+            );
+            let free_args = vec![int8_ptr_type()];
+            self.declare_libc_function(main_block, "free", free_args, void_type, Type::None, false);
+            // writes formatted output to a file descriptor, runtime errors go to stderr with it
+            let dprintf_args = vec![int32_type(), int8_ptr_type()];
+            self.declare_libc_function(
+                main_block,
+                "dprintf",
+                dprintf_args,
+                int32_type(),
+                Type::i32,
+                true,
+            );
+            let exit_args = vec![int32_type()];
+            self.declare_libc_function(main_block, "exit", exit_args, void_type, Type::None, false);
             load_string_helper_funcs(
                 self.context,
                 self.module,
@@ -619,9 +3787,57 @@ impl LLVMCodegenBuilder {
                 &mut self.llvm_func_cache,
                 main_block,
             );
+            load_float_helper_funcs(
+                self.context,
+                self.module,
+                &mut self.llvm_func_cache,
+                main_block,
+            );
+            load_io_helper_funcs(
+                self.context,
+                self.module,
+                &mut self.llvm_func_cache,
+                main_block,
+            );
         }
     }
 
+    // libc functions are only in the module if the stdlib bitcode happens to use them,
+    // otherwise they are declared here and resolved when linking
+    unsafe fn declare_libc_function(
+        &mut self,
+        block: LLVMBasicBlockRef,
+        name: &str,
+        mut args: Vec<LLVMTypeRef>,
+        llvm_return_type: LLVMTypeRef,
+        return_type: Type,
+        is_var_arg: bool,
+    ) {
+        let function_name = CString::new(name).expect("CString::new failed");
+        let func_type = LLVMFunctionType(
+            llvm_return_type,
+            args.as_mut_ptr(),
+            args.len() as c_uint,
+            is_var_arg as i32,
+        );
+        let mut function = LLVMGetNamedFunction(self.module, function_name.as_ptr());
+        if function.is_null() {
+            function = LLVMAddFunction(self.module, function_name.as_ptr(), func_type);
+        }
+        self.llvm_func_cache.set(
+            name,
+            LLVMFunction {
+                function,
+                func_type,
+                block,
+                entry_block: block,
+                symbol_table: HashMap::new(),
+                args,
+                return_type,
+            },
+        );
+    }
+
     pub unsafe fn build_bool_to_str_func(&self) -> LLVMFunction {
         // Create the function
         let char_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
@@ -719,6 +3935,30 @@ impl LLVMCodegenBuilder {
                         llvm_value_pointer: alloca,
                     }))
                 }
+                // bools always have a pointer, loading through it picks up reassignments
+                // and the stored result of a function call rather than a stale value
+                (Some(lhs_ptr), BaseTypes::Bool) => {
+                    let rhs_ptr = rhs.get_ptr().ok_or(anyhow!(
+                        "unable to compare bool {:?} without a pointer",
+                        rhs.get_name_as_str()
+                    ))?;
+                    let lhs_val = self.build_load(lhs_ptr, int1_type(), lhs.get_name_as_str());
+                    let rhs_val = self.build_load(rhs_ptr, int1_type(), rhs.get_name_as_str());
+                    let cmp = LLVMBuildICmp(
+                        self.builder,
+                        op,
+                        lhs_val,
+                        rhs_val,
+                        cstr_from_string("result").as_ptr(),
+                    );
+                    let alloca = self.build_alloca_store(cmp, int1_type(), "bool_cmp");
+                    Ok(Box::new(BoolType {
+                        name: lhs.get_name_as_str().to_string(),
+                        builder: self.builder,
+                        llvm_value: cmp,
+                        llvm_value_pointer: alloca,
+                    }))
+                }
                 _ => {
                     let mut lhs_val = lhs.get_value();
                     let mut rhs_val = rhs.get_value();
@@ -743,6 +3983,101 @@ impl LLVMCodegenBuilder {
         }
     }
 
+    pub fn bitwise_not(
+        &self,
+        value: Box<dyn TypeBase>,
+        expr: &Expression,
+    ) -> Result<Box<dyn TypeBase>> {
+        let base_type = value.get_type();
+        match base_type {
+            BaseTypes::Number | BaseTypes::Number64 => {
+                let loaded = self.load_value(value.as_ref());
+                let not_value =
+                    unsafe { LLVMBuildNot(self.builder, loaded, cstr_from_string("not").as_ptr()) };
+                self.build_value_for_type(&base_type, not_value, "bitwise_not")
+            }
+            _ => Err(anyhow!(
+                "operator `~` expected `{}` to have type i32 or i64, but it was inferred as {}",
+                expr,
+                base_type
+            )),
+        }
+    }
+
+    /// build_cast
+    ///
+    /// Converts a value to another type for `cast<T>(expr)`. Integers are sign extended or
+    /// truncated, floats are truncated towards zero when converted to an integer and a bool
+    /// is 0 or 1. A list is cast to an i64 as the address of its value. Strings can't be cast
+    /// to or from an integer, an integer isn't a valid string to print or free.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to convert
+    /// * `target_type` - The type to convert it to
+    ///
+    pub fn build_cast(
+        &self,
+        value: &(dyn TypeBase + 'static),
+        target_type: &Type,
+    ) -> Result<Box<dyn TypeBase>> {
+        let from = value.get_type();
+        let to = get_base_type(target_type).ok_or(anyhow!(
+            "unable to cast {} to {}",
+            from,
+            target_type
+        ))?;
+        if from == to {
+            return Ok(dyn_clone::clone_box(value));
+        }
+        let loaded = self.load_value(value);
+        let name = cstr_from_string("cast");
+        let cast = unsafe {
+            match (&from, &to) {
+                (BaseTypes::Number, BaseTypes::Number64) => {
+                    LLVMBuildSExt(self.builder, loaded, int64_type(), name.as_ptr())
+                }
+                (BaseTypes::Number64, BaseTypes::Number) => {
+                    LLVMBuildTrunc(self.builder, loaded, int32_type(), name.as_ptr())
+                }
+                (BaseTypes::Bool, BaseTypes::Number | BaseTypes::Number64) => LLVMBuildZExt(
+                    self.builder,
+                    loaded,
+                    self.get_llvm_type_for(&to),
+                    name.as_ptr(),
+                ),
+                (BaseTypes::Number | BaseTypes::Number64, BaseTypes::Bool) => LLVMBuildICmp(
+                    self.builder,
+                    LLVMIntNE,
+                    loaded,
+                    LLVMConstInt(LLVMTypeOf(loaded), 0, 0),
+                    name.as_ptr(),
+                ),
+                (BaseTypes::Number | BaseTypes::Number64, BaseTypes::Float) => {
+                    LLVMBuildSIToFP(self.builder, loaded, float_type(), name.as_ptr())
+                }
+                (BaseTypes::Float, BaseTypes::Number | BaseTypes::Number64) => LLVMBuildFPToSI(
+                    self.builder,
+                    loaded,
+                    self.get_llvm_type_for(&to),
+                    name.as_ptr(),
+                ),
+                (BaseTypes::List(_), BaseTypes::Number64) => {
+                    LLVMBuildPtrToInt(self.builder, loaded, int64_type(), name.as_ptr())
+                }
+                _ => {
+                    return Err(CycloError::InvalidCast {
+                        from,
+                        to,
+                        span: None,
+                    }
+                    .into())
+                }
+            }
+        };
+        self.build_value_for_type(&to, cast, "cast")
+    }
+
     pub fn llvm_build_fn(&self, lhs: LLVMValueRef, rhs: LLVMValueRef, op: String) -> LLVMValueRef {
         unsafe {
             match op.as_str() {
@@ -789,6 +4124,119 @@ impl LLVMCodegenBuilder {
         }
     }
 
+    pub fn fcmp(
+        &self,
+        lhs: Box<dyn TypeBase>,
+        rhs: Box<dyn TypeBase>,
+        op: LLVMRealPredicate,
+    ) -> Result<Box<dyn TypeBase>> {
+        let lhs_val = self.load_float_value(lhs.as_ref());
+        let rhs_val = self.load_float_value(rhs.as_ref());
+        let cmp = unsafe {
+            LLVMBuildFCmp(
+                self.builder,
+                op,
+                lhs_val,
+                rhs_val,
+                cstr_from_string("result").as_ptr(),
+            )
+        };
+        let alloca = self.build_alloca_store(cmp, int1_type(), "bool_cmp");
+        Ok(Box::new(BoolType {
+            name: lhs.get_name_as_str().to_string(),
+            builder: self.builder,
+            llvm_value: cmp,
+            llvm_value_pointer: alloca,
+        }))
+    }
+
+    pub fn llvm_build_float_fn(
+        &self,
+        lhs: LLVMValueRef,
+        rhs: LLVMValueRef,
+        op: String,
+    ) -> LLVMValueRef {
+        unsafe {
+            match op.as_str() {
+                "+" => {
+                    llvm_build_fn!(
+                        LLVMBuildFAdd,
+                        self.builder,
+                        lhs,
+                        rhs,
+                        cstr_from_string("addFloatType").as_ptr()
+                    )
+                }
+                "-" => {
+                    llvm_build_fn!(
+                        LLVMBuildFSub,
+                        self.builder,
+                        lhs,
+                        rhs,
+                        cstr_from_string("subFloatType").as_ptr()
+                    )
+                }
+                "*" => {
+                    llvm_build_fn!(
+                        LLVMBuildFMul,
+                        self.builder,
+                        lhs,
+                        rhs,
+                        cstr_from_string("mulFloatType").as_ptr()
+                    )
+                }
+                "/" => {
+                    llvm_build_fn!(
+                        LLVMBuildFDiv,
+                        self.builder,
+                        lhs,
+                        rhs,
+                        cstr_from_string("divFloatType").as_ptr()
+                    )
+                }
+                _ => {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    fn load_float_value(&self, value: &dyn TypeBase) -> LLVMValueRef {
+        match value.get_ptr() {
+            Some(ptr) => self.build_load(ptr, float_type(), "float"),
+            None => value.get_value(),
+        }
+    }
+
+    // an i32 and an i64 operand are widened to i64, the result is a NumberType64 whenever
+    // the LLVM value is 64 bits wide so it's loaded and printed as an i64
+    fn build_int_arithmetic(
+        &self,
+        lhs_val: LLVMValueRef,
+        rhs_val: LLVMValueRef,
+        op: String,
+        name: &str,
+    ) -> Box<dyn TypeBase> {
+        let lhs_val = self.cast_i32_to_i64(lhs_val, rhs_val);
+        let rhs_val = self.cast_i32_to_i64(rhs_val, lhs_val);
+        let result = self.llvm_build_fn(lhs_val, rhs_val, op);
+        let width = unsafe { LLVMGetIntTypeWidth(LLVMTypeOf(result)) };
+        if width == 64 {
+            let alloca = self.build_alloca_store(result, int64_type(), name);
+            return Box::new(NumberType64 {
+                name: name.to_string(),
+                llvm_value: result,
+                llvm_value_pointer: Some(alloca),
+            });
+        }
+        let alloca = self.build_alloca_store(result, int32_type(), name);
+        Box::new(NumberType {
+            name: name.to_string(),
+            llvm_value: result,
+            llvm_value_pointer: Some(alloca),
+        })
+    }
+
     pub fn arithmetic(
         &self,
         lhs: Box<dyn TypeBase>,
@@ -796,46 +4244,30 @@ impl LLVMCodegenBuilder {
         op: String,
     ) -> Result<Box<dyn TypeBase>> {
         match rhs.get_type() {
-            BaseTypes::String => {
-                let add_string_func = self.llvm_func_cache.get("stringAdd").unwrap();
-                let lhs_value = lhs.get_value();
-                let rhs_value = rhs.get_value();
-                let args = vec![lhs_value, rhs_value];
-                self.build_call(add_string_func, args, 2, "");
-                Ok(lhs)
-            }
+            BaseTypes::String => StringType::add(self, lhs, rhs),
             BaseTypes::Number | BaseTypes::Number64 => match (lhs.get_ptr(), rhs.get_ptr()) {
                 (Some(ptr), Some(rhs_ptr)) => {
-                    let mut lhs_val = self.build_load(ptr, lhs.get_llvm_type(), "lhs");
-                    let mut rhs_val = self.build_load(rhs_ptr, rhs.get_llvm_type(), "rhs");
-                    lhs_val = self.cast_i32_to_i64(lhs_val, rhs_val);
-                    rhs_val = self.cast_i32_to_i64(rhs_val, lhs_val);
-                    let result = self.llvm_build_fn(lhs_val, rhs_val, op);
-                    let alloca = self.build_alloca_store(result, lhs.get_llvm_ptr_type(), rhs.get_name_as_str());
-                    // self.build_store(result, ptr);
-                    let name = lhs.get_name_as_str().to_string();
-                    Ok(Box::new(NumberType {
-                        name,
-                        llvm_value: result,
-                        llvm_value_pointer: Some(alloca),
-                    }))
+                    let lhs_val = self.build_load(ptr, lhs.get_llvm_type(), "lhs");
+                    let rhs_val = self.build_load(rhs_ptr, rhs.get_llvm_type(), "rhs");
+                    Ok(self.build_int_arithmetic(lhs_val, rhs_val, op, lhs.get_name_as_str()))
                 }
                 _ => {
-                    let mut lhs_val = lhs.get_value();
-                    let mut rhs_val = rhs.get_value();
-                    lhs_val = self.cast_i32_to_i64(lhs_val, rhs_val);
-                    rhs_val = self.cast_i32_to_i64(rhs_val, lhs_val);
-                    let result = self.llvm_build_fn(lhs_val, rhs_val, op);
-                    let alloca =
-                        self.build_alloca_store(result, lhs.get_llvm_ptr_type(), rhs.get_name_as_str());
-                    let name = lhs.get_name_as_str().to_string();
-                    Ok(Box::new(NumberType {
-                        name,
-                        llvm_value: result,
-                        llvm_value_pointer: Some(alloca),
-                    }))
+                    let lhs_val = lhs.get_value();
+                    let rhs_val = rhs.get_value();
+                    Ok(self.build_int_arithmetic(lhs_val, rhs_val, op, lhs.get_name_as_str()))
                 }
             },
+            BaseTypes::Float => {
+                let lhs_val = self.load_float_value(lhs.as_ref());
+                let rhs_val = self.load_float_value(rhs.as_ref());
+                let result = self.llvm_build_float_fn(lhs_val, rhs_val, op);
+                let alloca = self.build_alloca_store(result, float_type(), "float_result");
+                Ok(Box::new(FloatType {
+                    name: lhs.get_name_as_str().to_string(),
+                    llvm_value: result,
+                    llvm_value_pointer: Some(alloca),
+                }))
+            }
             BaseTypes::List(value) => match *value {
                 BaseTypes::Number => {
                     let llvm_func = self.llvm_func_cache.get("concatInt32List").unwrap();
@@ -891,10 +4323,35 @@ impl LLVMCodegenBuilder {
     ) -> Result<Box<dyn TypeBase>> {
         match rhs.get_type() {
             BaseTypes::String => {
-                let is_string_equal_func = self.llvm_func_cache.get("isStringEqual").ok_or(anyhow!("unable to get function isStringEqual"))?;
-                let is_string_equal_args = vec![lhs.get_ptr().unwrap(), rhs.get_ptr().unwrap()];
+                let bool_value = match op.as_str() {
+                    "==" | "!=" => {
+                        let is_string_equal_func = self
+                            .llvm_func_cache
+                            .get("isStringEqual")
+                            .ok_or(anyhow!("unable to get function isStringEqual"))?;
+                        let is_string_equal_args =
+                            vec![lhs.get_ptr().unwrap(), rhs.get_ptr().unwrap()];
 
-                let bool_value = self.build_call(is_string_equal_func, is_string_equal_args, 2, "");
+                        let is_equal =
+                            self.build_call(is_string_equal_func, is_string_equal_args, 2, "");
+                        if op == "!=" {
+                            unsafe {
+                                LLVMBuildNot(
+                                    self.builder,
+                                    is_equal,
+                                    cstr_from_string("is_not_equal").as_ptr(),
+                                )
+                            }
+                        } else {
+                            is_equal
+                        }
+                    }
+                    "<" => self.build_strcmp(lhs.as_ref(), rhs.as_ref(), LLVMIntSLT)?,
+                    "<=" => self.build_strcmp(lhs.as_ref(), rhs.as_ref(), LLVMIntSLE)?,
+                    ">" => self.build_strcmp(lhs.as_ref(), rhs.as_ref(), LLVMIntSGT)?,
+                    ">=" => self.build_strcmp(lhs.as_ref(), rhs.as_ref(), LLVMIntSGE)?,
+                    _ => return Err(anyhow!("unable to compare strings with {}", op)),
+                };
                 let alloca = self.build_alloca_store(bool_value, int1_type(), "");
                 return Ok(Box::new(BoolType {
                     name: "bool_type".to_string(),
@@ -903,6 +4360,17 @@ impl LLVMCodegenBuilder {
                     llvm_value_pointer: alloca,
                 }));
             }
+            BaseTypes::Float => {
+                return match op.as_str() {
+                    "==" => self.fcmp(lhs, rhs, LLVMRealOEQ),
+                    "!=" => self.fcmp(lhs, rhs, LLVMRealUNE),
+                    "<" => self.fcmp(lhs, rhs, LLVMRealOLT),
+                    "<=" => self.fcmp(lhs, rhs, LLVMRealOLE),
+                    ">" => self.fcmp(lhs, rhs, LLVMRealOGT),
+                    ">=" => self.fcmp(lhs, rhs, LLVMRealOGE),
+                    _ => Err(anyhow!("unsupported float comparison {op}")),
+                };
+            }
             BaseTypes::Number | BaseTypes::Bool => {}
             _ => {
                 unreachable!(
@@ -926,18 +4394,21 @@ impl LLVMCodegenBuilder {
     }
 
     pub fn assign(
-        &self,
+        &mut self,
         lhs: Box<dyn TypeBase>,
         rhs: Box<dyn TypeBase>,
     ) -> Result<Box<dyn TypeBase>> {
         if rhs.get_type() != lhs.get_type() {
             return Err(anyhow!(
-                "Can't reassign variable {:?} that has type {:?} to type {:?}",
+                "Can't reassign variable {:?} that has type {} to type {}",
                 lhs.get_name_as_str(),
                 lhs.get_type(),
                 rhs.get_type()
             ));
         }
+        if lhs.get_type() == BaseTypes::String {
+            return StringType::assign(self, lhs, rhs);
+        }
         self.build_load_store(
             rhs.get_ptr().unwrap(),
             lhs.get_ptr().unwrap(),
@@ -960,7 +4431,49 @@ impl LLVMCodegenBuilder {
         int32_ptr_type()
     }
 
+    pub fn get_list_int64_ptr_type(&self) -> LLVMTypeRef {
+        int64_ptr_type()
+    }
+
     pub fn get_list_string_ptr_type(&self) -> LLVMTypeRef {
         unsafe { LLVMPointerType(self.get_string_ptr_type(), 0) }
     }
 }
+
+// empties a function, leaving a declaration. Every instruction's uses are dropped first so
+// the blocks can be deleted in any order
+unsafe fn delete_function_body(function: LLVMValueRef) {
+    let mut block = LLVMGetFirstBasicBlock(function);
+    while !block.is_null() {
+        let mut instruction = LLVMGetFirstInstruction(block);
+        while !instruction.is_null() {
+            let instruction_type = LLVMTypeOf(instruction);
+            if LLVMGetTypeKind(instruction_type) != LLVMTypeKind::LLVMVoidTypeKind {
+                LLVMReplaceAllUsesWith(instruction, LLVMGetUndef(instruction_type));
+            }
+            instruction = LLVMGetNextInstruction(instruction);
+        }
+        block = LLVMGetNextBasicBlock(block);
+    }
+    let mut block = LLVMGetFirstBasicBlock(function);
+    while !block.is_null() {
+        let mut instruction = LLVMGetLastInstruction(block);
+        while !instruction.is_null() {
+            LLVMInstructionEraseFromParent(instruction);
+            instruction = LLVMGetLastInstruction(block);
+        }
+        block = LLVMGetNextBasicBlock(block);
+    }
+    let mut block = LLVMGetFirstBasicBlock(function);
+    while !block.is_null() {
+        LLVMDeleteBasicBlock(block);
+        block = LLVMGetFirstBasicBlock(function);
+    }
+}
+
+// globals like `llvm.used` belong to LLVM and keep their linkage and initializer
+unsafe fn is_llvm_global(global: LLVMValueRef) -> bool {
+    let mut len = 0;
+    let name = LLVMGetValueName2(global, &mut len);
+    std::slice::from_raw_parts(name as *const u8, len).starts_with(b"llvm.")
+}
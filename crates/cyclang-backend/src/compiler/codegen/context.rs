@@ -1,7 +1,8 @@
 use crate::compiler::codegen::{
-    cstr_from_string, int1_type, int32_ptr_type, int32_type, int64_type, int8_ptr_type,
+    cstr_from_string, float_type, int1_type, int32_ptr_type, int32_type, int64_type, int8_ptr_type,
 };
 use crate::compiler::types::bool::BoolType;
+use crate::compiler::types::float::FloatType;
 use crate::compiler::types::num::NumberType;
 use crate::compiler::types::{BaseTypes, TypeBase};
 use std::collections::HashMap;
@@ -13,12 +14,14 @@ use crate::compiler::types::func::FuncType;
 use crate::compiler::types::num64::NumberType64;
 use crate::compiler::visitor::Visitor;
 use anyhow::{anyhow, Result};
-use cyclang_parser::{Expression, Type};
+use cyclang_parser::{Expression, FuncAttribute, Type};
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
 use llvm_sys::LLVMType;
 use cyclang_parser::Expression::Variable;
 use crate::compiler::types::list::ListType;
+use crate::compiler::types::string::StringType;
+use crate::compiler::types::tuple::get_component_types;
 
 pub struct LLVMFunctionCache {
     map: HashMap<String, LLVMFunction>,
@@ -49,7 +52,7 @@ impl LLVMFunction {
         args: Vec<Expression>,
         return_type: Type,
         body: Expression,
-        block: LLVMBasicBlockRef,
+        attribute: Option<FuncAttribute>,
         codegen: &mut LLVMCodegenBuilder,
     ) -> Result<Self> {
         unsafe {
@@ -57,24 +60,28 @@ impl LLVMFunction {
             let param_types: &mut Vec<*mut LLVMType> =
                 &mut LLVMFunction::get_arg_types(args.clone());
 
-            let function_type = Self::get_function_type(codegen, &args, &return_type, param_types);
-            // get correct function return type
-            let function = LLVMAddFunction(
-                codegen.module,
-                cstr_from_string(&name).as_ptr(),
-                function_type,
-            );
-
-            let func = FuncType {
-                llvm_type: function_type,
-                llvm_func: function,
-                return_type: return_type.clone(),
+            // a function declared ahead of the block it's defined in only needs its body built
+            let declared = context
+                .declared_functions
+                .remove(&(name.clone(), Self::get_signature(&args)));
+            let func = match declared {
+                Some(func) => func,
+                None => Self::declare(context, codegen, &name, &args, &return_type)?,
             };
-            context.func_cache.set(&name, Box::new(func), context.depth);
+            let function_type = func.llvm_type;
+            let function = func.llvm_func;
+            if let Some(attribute) = attribute {
+                codegen.add_function_attribute(function, &name, attribute);
+            }
 
             let function_entry_block = codegen.append_basic_block(function, "entry");
+            let previous_debug_scope = codegen.enter_debug_scope(function, &name);
 
+            // the enclosing function (main for top level definitions) is restored once the body
+            // is built, even if building it fails, so the caller carries on in its own block
             let previous_func = codegen.current_function.clone();
+            // a `break` in the body can't jump to a loop in the enclosing function
+            let previous_loop_exit_blocks = codegen.replace_loop_exit_blocks(vec![]);
             let mut new_function = LLVMFunction {
                 function,
                 func_type: function_type,
@@ -84,38 +91,84 @@ impl LLVMFunction {
                 args: param_types.to_vec(),
                 return_type: return_type.clone(),
             };
-
-            Self::map_args_to_func_call(context, args.clone(), codegen, function, &mut new_function, codegen.current_function.block, function_entry_block)?;
-
             codegen.current_function = new_function.clone();
 
-            codegen.position_builder_at_end(function_entry_block);
-
-            // Set func args here
-            context.match_ast(body.clone(), &mut visitor, codegen)?;
+            // args are scoped to the function so they don't leak into the enclosing scope
+            // or get reused by the next function with the same arg names
+            context.incr();
+            let built = Self::map_args_to_func_call(
+                context,
+                args.clone(),
+                codegen,
+                function,
+                &mut new_function,
+                previous_func.block,
+                function_entry_block,
+            )
+            .and_then(|_| {
+                codegen.current_function = new_function.clone();
+                codegen.position_builder_at_end(function_entry_block);
+                context.match_ast(body.clone(), &mut visitor, codegen)
+            });
+            context.var_cache.write().del_locals(context.get_depth());
+            context.decr();
 
-            // Delete func args here
-            // // Check to see if there is a Return type
-            if return_type == Type::None {
+            // Check to see if there is a Return type
+            if built.is_ok() && return_type == Type::None {
                 codegen.build_ret_void();
             }
 
-            codegen.set_current_block(block);
-            context.var_cache.set(
-                name.as_str(),
-                Box::new(FuncType {
-                    llvm_type: function_type,
-                    llvm_func: function,
-                    return_type,
-                }),
-                context.depth,
-            );
-            //reset previous function
+            codegen.exit_debug_scope(previous_debug_scope);
+            let previous_block = previous_func.block;
             codegen.current_function = previous_func;
+            codegen.set_current_block(previous_block);
+            codegen.replace_loop_exit_blocks(previous_loop_exit_blocks);
+            built?;
             Ok(new_function)
         }
     }
 
+    /// Adds a function to the module without its body, so calls to it can be built before
+    /// the body is
+    pub fn declare(
+        context: &mut ASTContext,
+        codegen: &mut LLVMCodegenBuilder,
+        name: &str,
+        args: &[Expression],
+        return_type: &Type,
+    ) -> Result<FuncType> {
+        unsafe {
+            let param_types = &mut Self::get_arg_types(args.to_vec());
+            let function_type =
+                Self::get_function_type(context, codegen, args, return_type, param_types)?;
+            let signature = Self::get_signature(args);
+            // an overload of a function that's already declared gets its own symbol, the first
+            // declaration keeps the plain name so it can still be exported
+            let symbol = if context.func_cache.read().contains(name) {
+                Self::get_overload_symbol(name, &signature)
+            } else {
+                name.to_string()
+            };
+            let function = LLVMAddFunction(
+                codegen.module,
+                cstr_from_string(&symbol).as_ptr(),
+                function_type,
+            );
+            let func = FuncType {
+                llvm_type: function_type,
+                llvm_func: function,
+                return_type: return_type.clone(),
+                default_args: Self::get_default_args(args),
+                is_extern: false,
+            };
+            context
+                .func_cache
+                .write()
+                .set(name, signature, Box::new(func.clone()), context.depth);
+            Ok(func)
+        }
+    }
+
     // get arg then assign a pointer at the beginning of the func call i.e
     // fn example(var i32) -> i32 {
     //    return var
@@ -129,7 +182,7 @@ impl LLVMFunction {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
         for (i, val) in args.iter().enumerate() {
             match val {
-                Expression::FuncArg(v, t) => match t {
+                Expression::FuncArg(v, t, _) => match t {
                     Type::i32 => {
                         let val = LLVMGetParam(function, i as u32);
                         codegen.position_builder_at_end(entry_block);
@@ -154,17 +207,40 @@ impl LLVMFunction {
                         };
                         new_function.set_func_var(v, Box::new(num));
                     }
-                    Type::String => {}
+                    Type::String => {
+                        let val = LLVMGetParam(function, i as u32);
+                        let string_type = StringType {
+                            name: "string_param".into(),
+                            llvm_value: val,
+                            llvm_value_pointer: Some(val),
+                        };
+                        new_function.set_func_var(v, Box::new(string_type));
+                    }
                     Type::Bool => {
                         let val = LLVMGetParam(function, i as u32);
+                        codegen.position_builder_at_end(entry_block);
+                        let ptr = codegen.build_alloca_store(val, int1_type(), &v.clone());
+                        codegen.position_builder_at_end(current_block);
                         let bool_type = BoolType {
                             builder: codegen.builder,
                             llvm_value: val,
-                            llvm_value_pointer: val,
+                            llvm_value_pointer: ptr,
                             name: "bool_param".into(),
                         };
                         new_function.set_func_var(v, Box::new(bool_type));
                     }
+                    Type::Float => {
+                        let val = LLVMGetParam(function, i as u32);
+                        codegen.position_builder_at_end(entry_block);
+                        let ptr = codegen.build_alloca_store(val, float_type(), &v.clone());
+                        codegen.position_builder_at_end(current_block);
+                        let float_param = FloatType {
+                            llvm_value: val,
+                            llvm_value_pointer: Some(ptr),
+                            name: "float_param".into(),
+                        };
+                        new_function.set_func_var(v, Box::new(float_param));
+                    }
                     Type::List(inner_type) => {
                         Self::map_list_args_to_func(codegen, function, new_function, i, v, t, inner_type)?
                     }
@@ -210,18 +286,22 @@ impl LLVMFunction {
     }
 
     unsafe fn get_function_type(
+        context: &ASTContext,
         codegen: &mut LLVMCodegenBuilder,
         args: &[Expression],
         return_type: &Type,
         param_types: &mut Vec<*mut LLVMType>,
-    ) -> LLVMTypeRef {
-        match return_type {
+    ) -> Result<LLVMTypeRef> {
+        Ok(match return_type {
             Type::i32 => {
                 LLVMFunctionType(int32_type(), param_types.as_mut_ptr(), args.len() as u32, 0)
             }
             Type::i64 => {
                 LLVMFunctionType(int64_type(), param_types.as_mut_ptr(), args.len() as u32, 0)
             }
+            Type::Float => {
+                LLVMFunctionType(float_type(), param_types.as_mut_ptr(), args.len() as u32, 0)
+            }
             Type::Bool => {
                 LLVMFunctionType(int1_type(), param_types.as_mut_ptr(), args.len() as u32, 0)
             }
@@ -254,17 +334,139 @@ impl LLVMFunction {
                     unimplemented!("inner type List<{:?}>", inner_type)
                 }
             },
+            Type::Tuple(component_types) => LLVMFunctionType(
+                codegen.get_struct_type(&get_component_types(component_types)?),
+                param_types.as_mut_ptr(),
+                args.len() as u32,
+                0,
+            ),
+            // structs are returned by value as the LLVM struct of their layout
+            Type::Struct(name) => LLVMFunctionType(
+                context
+                    .struct_layouts
+                    .get(name)
+                    .ok_or(anyhow!("unknown struct `{}`", name))?
+                    .llvm_type,
+                param_types.as_mut_ptr(),
+                args.len() as u32,
+                0,
+            ),
+            // aliases are resolved with the statement they're in, this catches any that weren't
+            Type::Alias(_) => {
+                let resolved = context.resolve_type(return_type)?;
+                return Self::get_function_type(context, codegen, args, &resolved, param_types);
+            }
+        })
+    }
+
+    // an `extern fn` is only declared, the symbol is resolved by the JIT or the linker. A
+    // string argument is passed as its null terminated buffer, a `char *` in C. Lists are
+    // pointers to cyclang's own layout and a returned `char *` has no length, so neither can
+    // be passed or returned
+    pub fn declare_extern(
+        context: &mut ASTContext,
+        codegen: &mut LLVMCodegenBuilder,
+        name: &str,
+        args: &[(String, Type)],
+        return_type: &Type,
+    ) -> Result<FuncType> {
+        let is_scalar =
+            |arg_type: &Type| matches!(arg_type, Type::i32 | Type::i64 | Type::Float | Type::Bool);
+        if !args
+            .iter()
+            .all(|(_, arg_type)| is_scalar(arg_type) || *arg_type == Type::String)
+        {
+            return Err(anyhow!(
+                "extern function `{}` can only take i32, i64, float, bool and string",
+                name
+            ));
+        }
+        if !(is_scalar(return_type) || *return_type == Type::None) {
+            return Err(anyhow!(
+                "extern function `{}` can only return i32, i64, float and bool",
+                name
+            ));
+        }
+        let args: Vec<Expression> = args
+            .iter()
+            .map(|(arg_name, arg_type)| {
+                Expression::FuncArg(arg_name.clone(), arg_type.clone(), None)
+            })
+            .collect();
+        unsafe {
+            let param_types = &mut Self::get_arg_types(args.clone());
+            let function_type =
+                Self::get_function_type(context, codegen, &args, return_type, param_types)?;
+            // declaring the same extern twice, or one the runtime already declared like
+            // `printf`, reuses the declaration in the module as long as the types match
+            let symbol = cstr_from_string(name);
+            let mut function = LLVMGetNamedFunction(codegen.module, symbol.as_ptr());
+            if function.is_null() {
+                function = LLVMAddFunction(codegen.module, symbol.as_ptr(), function_type);
+            } else if LLVMGlobalGetValueType(function) != function_type {
+                return Err(anyhow!(
+                    "extern function `{}` doesn't match the function `{}` already defined",
+                    name,
+                    name
+                ));
+            }
+            let func = FuncType {
+                llvm_type: function_type,
+                llvm_func: function,
+                return_type: return_type.clone(),
+                default_args: vec![],
+                is_extern: true,
+            };
+            context.func_cache.write().set(
+                name,
+                Self::get_signature(&args),
+                Box::new(func.clone()),
+                context.depth,
+            );
+            Ok(func)
+        }
+    }
+
+    // the argument types a function is declared with, which overload a call picks
+    pub fn get_signature(args: &[Expression]) -> Vec<Type> {
+        args.iter()
+            .filter_map(|arg| match arg {
+                Expression::FuncArg(_, arg_type, _) => Some(arg_type.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // the name and default value of each argument, `None` for an argument a call has to pass
+    pub fn get_default_args(args: &[Expression]) -> Vec<(String, Option<Expression>)> {
+        args.iter()
+            .filter_map(|arg| match arg {
+                Expression::FuncArg(name, _, default) => {
+                    Some((name.clone(), default.as_deref().cloned()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // `add` taking an i64 and a float is `add.i64.float`
+    fn get_overload_symbol(name: &str, signature: &[Type]) -> String {
+        let mut symbol = name.to_string();
+        for arg_type in signature {
+            symbol.push_str(&format!(".{}", arg_type));
         }
+        symbol
     }
 
     fn get_arg_types(args: Vec<Expression>) -> Vec<*mut LLVMType> {
         let mut args_vec = vec![];
         for arg in args.into_iter() {
             match arg {
-                Expression::FuncArg(_, t) => match t {
+                Expression::FuncArg(_, t, _) => match t {
                     Type::Bool => args_vec.push(int1_type()),
                     Type::i32 => args_vec.push(int32_type()),
                     Type::i64 => args_vec.push(int64_type()),
+                    Type::Float => args_vec.push(float_type()),
                     Type::String => args_vec.push(int8_ptr_type()),
                     Type::List(inner_type) => match *inner_type {
                         Type::i32 => args_vec.push(int32_ptr_type()),
@@ -0,0 +1,281 @@
+extern crate llvm_sys;
+
+use crate::compiler::codegen::cstr_from_string;
+use llvm_sys::core::{
+    LLVMAddMetadataToInst, LLVMAddModuleFlag, LLVMConstInt, LLVMGetInsertBlock,
+    LLVMGetIntTypeWidth, LLVMGetTypeKind, LLVMInt32TypeInContext, LLVMSetCurrentDebugLocation2,
+    LLVMValueAsMetadata,
+};
+use llvm_sys::debuginfo::{
+    LLVMCreateDIBuilder, LLVMDIBuilderCreateAutoVariable, LLVMDIBuilderCreateBasicType,
+    LLVMDIBuilderCreateCompileUnit, LLVMDIBuilderCreateDebugLocation,
+    LLVMDIBuilderCreateExpression, LLVMDIBuilderCreateFile, LLVMDIBuilderCreateFunction,
+    LLVMDIBuilderCreatePointerType, LLVMDIBuilderCreateSubroutineType, LLVMDIBuilderFinalize,
+    LLVMDIBuilderInsertDeclareRecordAtEnd, LLVMDIFlagZero, LLVMDWARFEmissionKind,
+    LLVMDWARFSourceLanguage, LLVMDebugMetadataVersion, LLVMDisposeDIBuilder, LLVMSetSubprogram,
+};
+use llvm_sys::prelude::{
+    LLVMBuilderRef, LLVMContextRef, LLVMDIBuilderRef, LLVMMetadataRef, LLVMModuleRef, LLVMTypeRef,
+    LLVMValueRef,
+};
+use llvm_sys::{LLVMModuleFlagBehavior, LLVMTypeKind};
+use std::path::Path;
+use std::ptr;
+
+// a program compiled from a string has no file, the module is always named main so the
+// source file is too
+const DEFAULT_SOURCE_FILE: &str = "main.cyclo";
+
+// DW_ATE_* type encodings
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DebugInfoBuilder {
+    di_builder: LLVMDIBuilderRef,
+    context: LLVMContextRef,
+    file: LLVMMetadataRef,
+    pub scope: LLVMMetadataRef,
+    // the source line of the statement being built, 0 until the first statement with a span
+    pub line: u32,
+}
+
+impl DebugInfoBuilder {
+    /// # Safety
+    ///
+    /// The context and module must be valid and outlive the builder, which owns the DIBuilder
+    /// it creates for the module until `finalize`
+    pub unsafe fn init(
+        context: LLVMContextRef,
+        module: LLVMModuleRef,
+        source_file: Option<&str>,
+    ) -> Self {
+        let source_path = Path::new(source_file.unwrap_or(DEFAULT_SOURCE_FILE));
+        let file_name = source_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(DEFAULT_SOURCE_FILE.to_string());
+        let directory = source_path
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or(".".to_string());
+        let di_builder = LLVMCreateDIBuilder(module);
+        let file = LLVMDIBuilderCreateFile(
+            di_builder,
+            cstr_from_string(&file_name).as_ptr(),
+            file_name.len(),
+            cstr_from_string(&directory).as_ptr(),
+            directory.len(),
+        );
+        let producer = "cyclang";
+        let compile_unit = LLVMDIBuilderCreateCompileUnit(
+            di_builder,
+            LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC,
+            file,
+            cstr_from_string(producer).as_ptr(),
+            producer.len(),
+            0,
+            cstr_from_string("").as_ptr(),
+            0,
+            0,
+            cstr_from_string("").as_ptr(),
+            0,
+            LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+            0,
+            0,
+            0,
+            cstr_from_string("").as_ptr(),
+            0,
+            cstr_from_string("").as_ptr(),
+            0,
+        );
+        Self::add_module_flag(
+            context,
+            module,
+            "Debug Info Version",
+            LLVMDebugMetadataVersion(),
+        );
+        Self::add_module_flag(context, module, "Dwarf Version", 4);
+        DebugInfoBuilder {
+            di_builder,
+            context,
+            file,
+            scope: compile_unit,
+            line: 0,
+        }
+    }
+
+    unsafe fn add_module_flag(
+        context: LLVMContextRef,
+        module: LLVMModuleRef,
+        key: &str,
+        value: u32,
+    ) {
+        let int_type = LLVMInt32TypeInContext(context);
+        let value = LLVMValueAsMetadata(LLVMConstInt(int_type, value as u64, 0));
+        LLVMAddModuleFlag(
+            module,
+            LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+            cstr_from_string(key).as_ptr(),
+            key.len(),
+            value,
+        );
+    }
+
+    /// create_subprogram
+    ///
+    /// Attaches a DISubprogram to the function and returns it so it can be used as the scope
+    /// for the instructions and variables built inside the function.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The LLVM function being defined
+    /// * `name` - The name of the function in the source
+    ///
+    /// # Safety
+    ///
+    /// `function` must be a function in the module the builder was created for
+    ///
+    pub unsafe fn create_subprogram(&self, function: LLVMValueRef, name: &str) -> LLVMMetadataRef {
+        let subroutine_type = LLVMDIBuilderCreateSubroutineType(
+            self.di_builder,
+            self.file,
+            ptr::null_mut(),
+            0,
+            LLVMDIFlagZero,
+        );
+        let subprogram = LLVMDIBuilderCreateFunction(
+            self.di_builder,
+            self.file,
+            cstr_from_string(name).as_ptr(),
+            name.len(),
+            cstr_from_string(name).as_ptr(),
+            name.len(),
+            self.file,
+            self.line,
+            subroutine_type,
+            0,
+            1,
+            self.line,
+            LLVMDIFlagZero,
+            0,
+        );
+        LLVMSetSubprogram(function, subprogram);
+        subprogram
+    }
+
+    /// set_location
+    ///
+    /// Sets the debug location for every instruction the builder creates from now on to the
+    /// current line and scope. Every call in a function with a DISubprogram must have a location.
+    ///
+    /// # Safety
+    ///
+    /// `builder` must be a valid builder for the module the builder was created for
+    ///
+    pub unsafe fn set_location(&self, builder: LLVMBuilderRef) {
+        let location = self.location();
+        LLVMSetCurrentDebugLocation2(builder, location);
+    }
+
+    unsafe fn location(&self) -> LLVMMetadataRef {
+        LLVMDIBuilderCreateDebugLocation(self.context, self.line, 0, self.scope, ptr::null_mut())
+    }
+
+    /// declare_variable
+    ///
+    /// Emits a DILocalVariable for the stack allocation and a declare record at the end of
+    /// the current block so debuggers can find the variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The LLVM builder positioned where the alloca was built
+    /// * `alloca` - The LLVM alloca instruction
+    /// * `ptr_type` - The LLVM Type stored in the alloca
+    /// * `name` - The name of the variable
+    ///
+    /// # Safety
+    ///
+    /// `builder` must be positioned in a block of the module the builder was created for and
+    /// `alloca` must be an alloca instruction in that module
+    ///
+    pub unsafe fn declare_variable(
+        &self,
+        builder: LLVMBuilderRef,
+        alloca: LLVMValueRef,
+        ptr_type: LLVMTypeRef,
+        name: &str,
+    ) {
+        LLVMAddMetadataToInst(builder, alloca);
+        let variable = LLVMDIBuilderCreateAutoVariable(
+            self.di_builder,
+            self.scope,
+            cstr_from_string(name).as_ptr(),
+            name.len(),
+            self.file,
+            self.line,
+            self.get_di_type(ptr_type),
+            1,
+            LLVMDIFlagZero,
+            0,
+        );
+        let expression = LLVMDIBuilderCreateExpression(self.di_builder, ptr::null_mut(), 0);
+        LLVMDIBuilderInsertDeclareRecordAtEnd(
+            self.di_builder,
+            alloca,
+            variable,
+            expression,
+            self.location(),
+            LLVMGetInsertBlock(builder),
+        );
+    }
+
+    unsafe fn get_di_type(&self, llvm_type: LLVMTypeRef) -> LLVMMetadataRef {
+        match LLVMGetTypeKind(llvm_type) {
+            LLVMTypeKind::LLVMIntegerTypeKind => match LLVMGetIntTypeWidth(llvm_type) {
+                1 => self.create_basic_type("bool", 8, DW_ATE_BOOLEAN),
+                width => {
+                    self.create_basic_type(&format!("i{}", width), width as u64, DW_ATE_SIGNED)
+                }
+            },
+            LLVMTypeKind::LLVMDoubleTypeKind => self.create_basic_type("f64", 64, DW_ATE_FLOAT),
+            _ => {
+                let char_type = self.create_basic_type("char", 8, DW_ATE_UNSIGNED_CHAR);
+                let name = "ptr";
+                LLVMDIBuilderCreatePointerType(
+                    self.di_builder,
+                    char_type,
+                    64,
+                    0,
+                    0,
+                    cstr_from_string(name).as_ptr(),
+                    name.len(),
+                )
+            }
+        }
+    }
+
+    unsafe fn create_basic_type(&self, name: &str, size: u64, encoding: u32) -> LLVMMetadataRef {
+        LLVMDIBuilderCreateBasicType(
+            self.di_builder,
+            cstr_from_string(name).as_ptr(),
+            name.len(),
+            size,
+            encoding,
+            LLVMDIFlagZero,
+        )
+    }
+
+    /// The DIBuilder has to be finalized before the module is verified, printed or run
+    ///
+    /// # Safety
+    ///
+    /// The DIBuilder is disposed, so nothing can be built with this builder afterwards
+    pub unsafe fn finalize(&self) {
+        LLVMDIBuilderFinalize(self.di_builder);
+        LLVMDisposeDIBuilder(self.di_builder);
+    }
+}
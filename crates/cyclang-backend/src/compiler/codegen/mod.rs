@@ -6,6 +6,7 @@ use std::ffi::CString;
 
 pub(crate) mod builder;
 pub mod context;
+pub mod debug;
 pub mod stdlib;
 pub mod target;
 
@@ -33,6 +34,14 @@ pub fn int64_type() -> LLVMTypeRef {
     unsafe { LLVMInt64Type() }
 }
 
+pub fn float_type() -> LLVMTypeRef {
+    unsafe { LLVMDoubleType() }
+}
+
+pub fn float_ptr_type() -> LLVMTypeRef {
+    unsafe { LLVMPointerType(LLVMDoubleType(), 0) }
+}
+
 pub fn int64_ptr_type() -> LLVMTypeRef {
     unsafe { LLVMPointerType(LLVMInt64Type(), 0) }
 }
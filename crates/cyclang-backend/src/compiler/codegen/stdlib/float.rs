@@ -0,0 +1,28 @@
+use crate::compiler::codegen::context::LLVMFunctionCache;
+use crate::compiler::codegen::float_type;
+use crate::compiler::codegen::stdlib::list::create_and_set_llvm_function;
+use llvm_sys::core::LLVMVoidTypeInContext;
+use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMContextRef, LLVMModuleRef};
+
+/// # Safety
+///
+/// Load Float Helper funcs
+pub unsafe fn load_float_helper_funcs(
+    context: LLVMContextRef,
+    module: LLVMModuleRef,
+    llvm_func_cache: &mut LLVMFunctionCache,
+    block: LLVMBasicBlockRef,
+) {
+    let void_type = LLVMVoidTypeInContext(context);
+
+    // printFloat
+    let mut print_float_args = vec![float_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "printFloat",
+        &mut print_float_args,
+        void_type,
+    );
+}
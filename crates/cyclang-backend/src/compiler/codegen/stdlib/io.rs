@@ -0,0 +1,40 @@
+use crate::compiler::codegen::context::LLVMFunctionCache;
+use crate::compiler::codegen::int32_type;
+use crate::compiler::codegen::stdlib::list::create_and_set_llvm_function;
+use llvm_sys::core::{LLVMGetTypeByName2, LLVMPointerType};
+use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMContextRef, LLVMModuleRef};
+use std::ffi::CString;
+
+/// # Safety
+///
+/// Load stdin helper funcs, readLine and readInt wrap fgets and strtol in types.c
+pub unsafe fn load_io_helper_funcs(
+    context: LLVMContextRef,
+    module: LLVMModuleRef,
+    llvm_func_cache: &mut LLVMFunctionCache,
+    block: LLVMBasicBlockRef,
+) {
+    let string_struct_name = CString::new("struct.StringType").expect("CString::new failed");
+    let string_type = LLVMGetTypeByName2(context, string_struct_name.as_ptr());
+    let string_ptr_type = LLVMPointerType(string_type, 0);
+
+    // readLine
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "readLine",
+        &mut vec![],
+        string_ptr_type,
+    );
+
+    // readInt
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "readInt",
+        &mut vec![],
+        int32_type(),
+    );
+}
@@ -1,5 +1,5 @@
 use crate::compiler::codegen::context::{LLVMFunction, LLVMFunctionCache};
-use crate::compiler::codegen::{int32_ptr_type, int32_type};
+use crate::compiler::codegen::{int32_ptr_type, int32_type, int64_ptr_type, int64_type};
 use cyclang_parser::Type;
 use llvm_sys::core::{
     LLVMFunctionType, LLVMGetNamedFunction, LLVMGetTypeByName2, LLVMPointerType,
@@ -48,7 +48,7 @@ pub unsafe fn load_list_helper_funcs(
         block,
         "get_int32_tValue",
         &mut list_get_int32_args,
-        int32_ptr_type(),
+        int32_type(),
     );
     // printInt32List
     let mut print_list_int32_args = vec![int32_ptr_type()];
@@ -81,6 +81,100 @@ pub unsafe fn load_list_helper_funcs(
         int32_ptr_type(),
     );
 
+    // listSortI32 and listSortDescI32 sort in place with qsort
+    for func_name in ["listSortI32", "listSortDescI32"] {
+        let mut sort_int32_args = vec![int32_ptr_type(), int64_type()];
+        create_and_set_llvm_function(
+            module,
+            llvm_func_cache,
+            block,
+            func_name,
+            &mut sort_int32_args,
+            void_type,
+        );
+    }
+
+    // * List<i64> * //
+    let mut list_create_int64_args = vec![int32_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "create_int64_tList",
+        &mut list_create_int64_args,
+        int64_ptr_type(),
+    );
+    let mut list_set_int64_args = vec![int64_ptr_type(), int64_type(), int32_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "set_int64_tValue",
+        &mut list_set_int64_args,
+        void_type,
+    );
+    let mut list_get_int64_args = vec![int64_ptr_type(), int32_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "get_int64_tValue",
+        &mut list_get_int64_args,
+        int64_type(),
+    );
+    let mut print_list_int64_args = vec![int64_ptr_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "printInt64List",
+        &mut print_list_int64_args,
+        void_type,
+    );
+    let mut len_list_int64_args = vec![int64_ptr_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "lenInt64List",
+        &mut len_list_int64_args,
+        int32_type(),
+    );
+
+    // * List<List<i32>> * //
+    let int32_ptr_ptr_type = LLVMPointerType(int32_ptr_type(), 0);
+
+    // windowsInt32List
+    let mut windows_int32_args = vec![int32_ptr_type(), int32_type()];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "windowsInt32List",
+        &mut windows_int32_args,
+        int32_ptr_ptr_type,
+    );
+
+    let mut print_list_int32_list_args = vec![int32_ptr_ptr_type];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "printInt32ListList",
+        &mut print_list_int32_list_args,
+        void_type,
+    );
+
+    let mut len_list_int32_list_args = vec![int32_ptr_ptr_type];
+    create_and_set_llvm_function(
+        module,
+        llvm_func_cache,
+        block,
+        "lenInt32ListList",
+        &mut len_list_int32_list_args,
+        int32_type(),
+    );
+
     // * String * //
     let string_struct_name = CString::new("struct.StringType").expect("CString::new failed");
     let string_type = LLVMGetTypeByName2(context, string_struct_name.as_ptr());
@@ -148,7 +242,7 @@ pub unsafe fn load_list_helper_funcs(
     );
 }
 
-unsafe fn create_and_set_llvm_function(
+pub(crate) unsafe fn create_and_set_llvm_function(
     module: LLVMModuleRef,
     llvm_func_cache: &mut LLVMFunctionCache,
     block: LLVMBasicBlockRef,
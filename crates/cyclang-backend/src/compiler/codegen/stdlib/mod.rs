@@ -1,3 +1,5 @@
+pub mod float;
+pub mod io;
 pub mod list;
 pub mod string;
 
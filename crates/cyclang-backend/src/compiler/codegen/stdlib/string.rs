@@ -1,5 +1,5 @@
 use crate::compiler::codegen::context::{LLVMFunction, LLVMFunctionCache};
-use crate::compiler::codegen::{int1_type, int8_ptr_type};
+use crate::compiler::codegen::{int1_type, int32_type, int8_ptr_type};
 use cyclang_parser::Type;
 use llvm_sys::core::{
     LLVMFunctionType, LLVMGetNamedFunction, LLVMGetTypeByName2, LLVMPointerType,
@@ -26,8 +26,8 @@ pub unsafe fn load_string_helper_funcs(
     let string_init_function_name = CString::new("stringInit").expect("CString::new failed");
     let string_init_function = LLVMGetNamedFunction(module, string_init_function_name.as_ptr());
 
-    // todo: load array correctly
-    let mut string_init_args = [int8_ptr_type()];
+    // the bytes and how many there are, the bytes can contain nulls
+    let mut string_init_args = [int8_ptr_type(), int32_type()];
     let string_init_func_type = LLVMFunctionType(
         string_ptr_type,
         string_init_args.as_mut_ptr(),
@@ -42,7 +42,7 @@ pub unsafe fn load_string_helper_funcs(
             block,
             entry_block: block,
             symbol_table: HashMap::new(),
-            args: vec![int8_ptr_type()],
+            args: vec![int8_ptr_type(), int32_type()],
             return_type: Type::None,
         },
     );
@@ -50,7 +50,7 @@ pub unsafe fn load_string_helper_funcs(
     let string_add_function_name = CString::new("stringAdd").expect("CString::new failed");
     let string_add_function = LLVMGetNamedFunction(module, string_add_function_name.as_ptr());
 
-    let mut string_add_args = [string_ptr_type, string_ptr_type];
+    let mut string_add_args = [string_ptr_type, int8_ptr_type(), int32_type()];
     let string_add_func_type = LLVMFunctionType(
         void_type,
         string_add_args.as_mut_ptr(),
@@ -65,7 +65,7 @@ pub unsafe fn load_string_helper_funcs(
             block,
             entry_block: block,
             symbol_table: HashMap::new(),
-            args: vec![string_ptr_type, string_ptr_type],
+            args: vec![string_ptr_type, int8_ptr_type(), int32_type()],
             return_type: Type::None,
         },
     );
@@ -116,4 +116,55 @@ pub unsafe fn load_string_helper_funcs(
             return_type: Type::None,
         },
     );
+
+    // helpers that return a new string made from the one they're given
+    for func_name in [
+        "stringToUpper",
+        "stringToLower",
+        "stringTrim",
+        "stringTrimStart",
+        "stringTrimEnd",
+    ] {
+        let function_name = CString::new(func_name).expect("CString::new failed");
+        let function = LLVMGetNamedFunction(module, function_name.as_ptr());
+        let mut args = [string_ptr_type];
+        let func_type = LLVMFunctionType(string_ptr_type, args.as_mut_ptr(), args.len() as u32, 0);
+        llvm_func_cache.set(
+            func_name,
+            LLVMFunction {
+                function,
+                func_type,
+                block,
+                entry_block: block,
+                symbol_table: HashMap::new(),
+                args: vec![string_ptr_type],
+                return_type: Type::None,
+            },
+        );
+    }
+
+    let string_split_function_name = CString::new("stringSplit").expect("CString::new failed");
+    let string_split_function = LLVMGetNamedFunction(module, string_split_function_name.as_ptr());
+
+    // returns a NULL terminated List<string>
+    let string_ptr_ptr_type = LLVMPointerType(string_ptr_type, 0);
+    let mut string_split_args = [string_ptr_type, string_ptr_type];
+    let string_split_func_type = LLVMFunctionType(
+        string_ptr_ptr_type,
+        string_split_args.as_mut_ptr(),
+        string_split_args.len() as u32,
+        0,
+    );
+    llvm_func_cache.set(
+        "stringSplit",
+        LLVMFunction {
+            function: string_split_function,
+            func_type: string_split_func_type,
+            block,
+            entry_block: block,
+            symbol_table: HashMap::new(),
+            args: vec![string_ptr_type, string_ptr_type],
+            return_type: Type::None,
+        },
+    );
 }
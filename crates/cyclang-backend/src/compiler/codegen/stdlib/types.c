@@ -2,9 +2,14 @@
 #include <stdlib.h>
 #include <string.h>
 #include <stdbool.h>
+#include <ctype.h>
 
 
 // * MACROS * // 
+// An i32 or i64 list stores its length in an int64_t just before its first element, so an
+// element can hold any value, -1 included
+#define LIST_LENGTH(arr) (((int64_t*)(arr))[-1])
+
 #define DEFINE_GET_VALUE_FUNC(type) \
 type get_##type##Value(type* arr, int index) { \
     return arr[index]; \
@@ -17,9 +22,13 @@ void set_##type##Value(type* arr, type value, int index) { \
 
 #define DEFINE_CREATE_VALUE_FUNC(type) \
 type* create_##type##List(int size) { \
-    type* arr = (type*)malloc((size + 1) * sizeof(type)); \
-    arr[size] = -1; \
-    return arr; \
+    int64_t* header = (int64_t*)malloc(sizeof(int64_t) + size * sizeof(type)); \
+    if (header == NULL) { \
+        printf("Memory allocation failed\n"); \
+        exit(1); \
+    } \
+    header[0] = size; \
+    return (type*)(header + 1); \
 } 
 
 DEFINE_CREATE_VALUE_FUNC(int32_t)
@@ -30,6 +39,8 @@ DEFINE_GET_VALUE_FUNC(int64_t)
 DEFINE_SET_VALUE_FUNC(int64_t)
 
 // * STRING IMPLEMENTATION * // 
+// strings are UTF-8 bytes with an explicit length, so they can hold null bytes. The buffer is
+// still null terminated after length for the libc functions that take a char *
 typedef struct {
     char *buffer;
     int32_t length;
@@ -38,11 +49,15 @@ typedef struct {
 } StringType;
 
 void stringPrint(StringType *this) {
-    printf("\"%s\"\n", this->buffer);
+    printf("\"");
+    fwrite(this->buffer, 1, this->length, stdout);
+    printf("\"\n");
 }
 
 void stringPrintList(StringType *this) {
-    printf("\"%s\"", this->buffer);
+    printf("\"");
+    fwrite(this->buffer, 1, this->length, stdout);
+    printf("\"");
 }
 
 void stringCreateDefault(StringType *this) {
@@ -82,15 +97,34 @@ void stringAddChar(StringType *this, char value) {
     this->length++;
 }
 
-void stringAdd(StringType *this, const StringType *other) {
-    for (int i = 0; i < other->length; i++) {
-        stringAddChar(this, other->buffer[i]);
+// appends length bytes of data, data can point into this string's own buffer as the old
+// buffer is only freed once the bytes have been copied out of it
+void stringAdd(StringType *this, const char *data, int32_t length) {
+    int32_t new_length = this->length + length;
+    // keep the buffer null terminated, without counting it in length
+    if (new_length + 1 > this->maxlen) {
+        int new_size = new_length + 1 + this->factor;
+        char *new_buffer = (char *)malloc(new_size);
+        if (new_buffer == NULL) {
+            fprintf(stderr, "Failed to allocate memory\n");
+            return;
+        }
+        if (this->buffer) {
+            memcpy(new_buffer, this->buffer, this->length);
+        }
+        memcpy(new_buffer + this->length, data, length);
+        free(this->buffer);
+        this->buffer = new_buffer;
+        this->maxlen = new_size;
+    } else {
+        memmove(this->buffer + this->length, data, length);
     }
+    this->length = new_length;
+    this->buffer[this->length] = '\0';
 }
 
-StringType* stringInit(const char *data) {
+StringType* stringInit(const char *data, int32_t len) {
     StringType *this = malloc(sizeof(StringType));
-    int len = strlen(data);
     stringCreateDefault(this);
     this->buffer = (char *)malloc(len + 1);
     if (this->buffer) {
@@ -116,38 +150,175 @@ bool isStringEqual(StringType *stringOne, StringType* stringTwo) {
     return true;
 }
 
+// POSIX has no strupr/strlwr, so each byte is converted with toupper/tolower
+// into a new string. Bytes that aren't ASCII letters are copied unchanged.
+StringType* stringToUpper(StringType *this) {
+    StringType *result = stringInit(this->buffer, this->length);
+    for (int32_t i = 0; i < result->length; i++) {
+        result->buffer[i] = (char)toupper((unsigned char)result->buffer[i]);
+    }
+    return result;
+}
+
+StringType* stringToLower(StringType *this) {
+    StringType *result = stringInit(this->buffer, this->length);
+    for (int32_t i = 0; i < result->length; i++) {
+        result->buffer[i] = (char)tolower((unsigned char)result->buffer[i]);
+    }
+    return result;
+}
+
+// Only space, tab and newline are stripped by the trim helpers. Each helper
+// walks past them from its end(s) and copies what's left into a new string.
+static bool isTrimSpace(char c) {
+    return c == ' ' || c == '\t' || c == '\n';
+}
+
+StringType* stringTrimStart(StringType *this) {
+    int32_t start = 0;
+    while (start < this->length && isTrimSpace(this->buffer[start])) {
+        start++;
+    }
+    return stringInit(this->buffer + start, this->length - start);
+}
+
+StringType* stringTrimEnd(StringType *this) {
+    int32_t end = this->length;
+    while (end > 0 && isTrimSpace(this->buffer[end - 1])) {
+        end--;
+    }
+    return stringInit(this->buffer, end);
+}
+
+StringType* stringTrim(StringType *this) {
+    int32_t start = 0;
+    int32_t end = this->length;
+    while (start < end && isTrimSpace(this->buffer[start])) {
+        start++;
+    }
+    while (end > start && isTrimSpace(this->buffer[end - 1])) {
+        end--;
+    }
+    return stringInit(this->buffer + start, end - start);
+}
+
+// * INPUT IMPLEMENTATION * //
+// Reads a single line from stdin with fgets, doubling the buffer until the
+// newline is found. The trailing newline is stripped and an empty string is
+// returned at EOF.
+StringType* readLine(void) {
+    size_t size = 128;
+    size_t length = 0;
+    char *line = (char *)malloc(size);
+    if (line == NULL) {
+        fprintf(stderr, "Failed to allocate memory\n");
+        return stringInit("", 0);
+    }
+    line[0] = '\0';
+    while (fgets(line + length, size - length, stdin) != NULL) {
+        length += strlen(line + length);
+        if (length > 0 && line[length - 1] == '\n') {
+            line[--length] = '\0';
+            break;
+        }
+        size *= 2;
+        char *new_line = (char *)realloc(line, size);
+        if (new_line == NULL) {
+            fprintf(stderr, "Failed to allocate memory\n");
+            break;
+        }
+        line = new_line;
+    }
+    StringType *result = stringInit(line, length);
+    free(line);
+    return result;
+}
+
+int32_t readInt(void) {
+    StringType *line = readLine();
+    int32_t value = (int32_t)strtol(line->buffer, NULL, 10);
+    stringDelete(line);
+    free(line);
+    return value;
+}
+
+// * FLOAT IMPLEMENTATION * //
+// Floats are formatted with a fixed algorithm instead of printf's %f/%g so that
+// output is identical across libc implementations: the value is rounded to
+// FLOAT_PRINT_PRECISION decimal places and trailing zeros are trimmed, keeping
+// at least one digit after the point (0.1 + 0.2 prints as 0.3, 2.0 as 2.0).
+// Values too large to scale into an int64_t fall back to scientific notation.
+#define FLOAT_PRINT_PRECISION 6
+#define FLOAT_PRINT_SCALE 1000000
+#define FLOAT_PRINT_MAX 9.0e12
+
+void formatFloat(double value, char *buffer, size_t size) {
+    if (value != value) {
+        snprintf(buffer, size, "nan");
+        return;
+    }
+    int negative = value < 0;
+    double magnitude = negative ? -value : value;
+    if (magnitude >= FLOAT_PRINT_MAX) {
+        if (magnitude - magnitude != 0) {
+            snprintf(buffer, size, negative ? "-inf" : "inf");
+            return;
+        }
+        snprintf(buffer, size, "%.6e", value);
+        return;
+    }
+
+    // round half away from zero
+    int64_t fixed = (int64_t)(magnitude * FLOAT_PRINT_SCALE + 0.5);
+    int64_t whole = fixed / FLOAT_PRINT_SCALE;
+    int64_t fraction = fixed % FLOAT_PRINT_SCALE;
+
+    char digits[FLOAT_PRINT_PRECISION + 1];
+    for (int i = FLOAT_PRINT_PRECISION - 1; i >= 0; i--) {
+        digits[i] = '0' + (fraction % 10);
+        fraction /= 10;
+    }
+    int length = FLOAT_PRINT_PRECISION;
+    while (length > 1 && digits[length - 1] == '0') {
+        length--;
+    }
+    digits[length] = '\0';
+
+    snprintf(buffer, size, "%s%lld.%s", negative && fixed != 0 ? "-" : "", (long long)whole, digits);
+}
+
+void printFloat(double value) {
+    char buffer[64];
+    formatFloat(value, buffer, sizeof(buffer));
+    printf("%s\n", buffer);
+}
+
 // * LIST IMPLEMENTATION * //
-void printInt32List(int32_t* arr) {
-    int i = 0;
+// Prints the list without a newline so it can be an element of a nested list
+static void printInt32ListInline(int32_t* arr) {
     printf("[");
-    while (arr[i] != -1) {
+    for (int64_t i = 0; i < LIST_LENGTH(arr); i++) {
         if (i != 0) {
-            printf(",");
+            printf(", ");
         }
         printf("%d", arr[i]);
-        i++;
     }
     printf("]");
 }
 
+void printInt32List(int32_t* arr) {
+    printInt32ListInline(arr);
+    printf("\n");
+}
+
 int32_t lenInt32List(int32_t* arr) {
-    int i = 0;
-    while (arr[i] != -1) {
-        i++;
-    }
-    return i;
+    return (int32_t)LIST_LENGTH(arr);
 }
 
 int32_t* concatInt32List(int32_t* arrOne, int32_t* arrTwo) {
     int sizeOne = lenInt32List(arrOne);
     int sizeTwo = lenInt32List(arrTwo);
-    // add a -1 terminator
-    int *result = (int32_t*)malloc((sizeOne + sizeTwo + 1) * sizeof(int32_t));
-    result[sizeOne + sizeTwo] = -1;
-    if (result == NULL) {
-        printf("Memory allocation failed\n");
-        exit(1);
-    }
+    int32_t *result = create_int32_tList(sizeOne + sizeTwo);
 
     // Copy over first elements
     for (int i = 0; i < sizeOne; i++) {
@@ -162,16 +333,82 @@ int32_t* concatInt32List(int32_t* arrOne, int32_t* arrTwo) {
 }
 
 void printInt64List(int64_t* arr) {
-    int i = 0;
     printf("[");
-    while (arr[i] != -1) {
+    for (int64_t i = 0; i < LIST_LENGTH(arr); i++) {
         if (i != 0) {
-            printf(",");
+            printf(", ");
         }
         printf("%lld", arr[i]);
+    }
+    printf("]\n");
+}
+
+int32_t lenInt64List(int64_t* arr) {
+    return (int32_t)LIST_LENGTH(arr);
+}
+
+// Sorts the list in place, count is the length of the list
+static int compareInt32(const void *a, const void *b) {
+    int32_t x = *(const int32_t *)a;
+    int32_t y = *(const int32_t *)b;
+    return (x > y) - (x < y);
+}
+
+static int compareInt32Desc(const void *a, const void *b) {
+    return compareInt32(b, a);
+}
+
+void listSortI32(int32_t* arr, int64_t count) {
+    qsort(arr, (size_t)count, sizeof(int32_t), compareInt32);
+}
+
+void listSortDescI32(int32_t* arr, int64_t count) {
+    qsort(arr, (size_t)count, sizeof(int32_t), compareInt32Desc);
+}
+
+// * NESTED LIST IMPLEMENTATION * //
+// A List<List<i32>> is a NULL terminated array of int32_t lists
+
+// Returns every overlapping window of `size` consecutive elements, each window
+// is copied into its own list. An empty list is returned if size is out of range.
+int32_t** windowsInt32List(int32_t* arr, int32_t size) {
+    int length = lenInt32List(arr);
+    int count = (size > 0 && size <= length) ? length - size + 1 : 0;
+    int32_t **windows = malloc((count + 1) * sizeof(int32_t *));
+    if (windows == NULL) {
+        printf("Memory allocation failed\n");
+        exit(1);
+    }
+    for (int i = 0; i < count; i++) {
+        int32_t *window = create_int32_tList(size);
+        for (int j = 0; j < size; j++) {
+            window[j] = arr[i + j];
+        }
+        windows[i] = window;
+    }
+    windows[count] = NULL;
+    return windows;
+}
+
+void printInt32ListList(int32_t** arr) {
+    int i = 0;
+    printf("[");
+    while (arr[i] != NULL) {
+        if (i != 0) {
+            printf(", ");
+        }
+        printInt32ListInline(arr[i]);
         i++;
     }
-    printf("]");
+    printf("]\n");
+}
+
+int32_t lenInt32ListList(int32_t** arr) {
+    int i = 0;
+    while (arr[i] != NULL) {
+        i++;
+    }
+    return i;
 }
 
 StringType** createStringList(int size) {
@@ -194,12 +431,12 @@ void printStringList(StringType** arr) {
     printf("[");
     while (arr[i] != NULL) {
         if (i != 0) {
-            printf(",");
+            printf(", ");
         }
-        printf("\"%s\"", arr[i]->buffer);
+        stringPrintList(arr[i]);
         i++;
     }
-    printf("]");
+    printf("]\n");
 }
 
 int32_t lenStringList(StringType** arr) {
@@ -210,6 +447,50 @@ int32_t lenStringList(StringType** arr) {
     return i;
 }
 
+// The first match of needle in the haystack, or NULL if there isn't one. memchr
+// finds each candidate first byte, so null bytes in either are compared like any
+// other byte.
+static const char* findBytes(const char *haystack, int32_t length, const char *needle, int32_t needle_length) {
+    const char *end = haystack + length;
+    const char *candidate = haystack;
+    while (end - candidate >= needle_length) {
+        candidate = memchr(candidate, needle[0], (end - candidate) - needle_length + 1);
+        if (candidate == NULL) {
+            return NULL;
+        }
+        if (memcmp(candidate, needle, needle_length) == 0) {
+            return candidate;
+        }
+        candidate++;
+    }
+    return NULL;
+}
+
+// Splits a string at each occurrence of the whole delimiter, empty parts are
+// skipped. The bytes are compared with their length, so either string can hold
+// null bytes. Each part is copied into its own string, the string being split is
+// left unchanged. An empty delimiter gives the whole string as the only part.
+StringType** stringSplit(StringType *this, StringType *delimiter) {
+    // a string of n bytes has at most (n + 1) / 2 non empty parts
+    StringType **parts = createStringList((this->length + 1) / 2);
+    int count = 0;
+    const char *start = this->buffer;
+    const char *end = this->buffer + this->length;
+    while (start < end) {
+        const char *match = NULL;
+        if (delimiter->length > 0) {
+            match = findBytes(start, end - start, delimiter->buffer, delimiter->length);
+        }
+        const char *part_end = match != NULL ? match : end;
+        if (part_end > start) {
+            parts[count++] = stringInit(start, part_end - start);
+        }
+        start = match != NULL ? match + delimiter->length : end;
+    }
+    parts[count] = NULL;
+    return parts;
+}
+
 StringType** concatStringList(StringType** arrOne, StringType** arrTwo) {
     int sizeOne = lenStringList(arrOne);
     int sizeTwo = lenStringList(arrTwo);
@@ -1,8 +1,31 @@
-use llvm_sys::target::{LLVMInitializeWebAssemblyAsmPrinter, LLVMInitializeWebAssemblyTarget};
+use crate::compiler::codegen::{
+    cstr_from_string, float_type, int1_type, int32_type, int64_type, int8_ptr_type,
+};
+use anyhow::{anyhow, Result};
+use cyclang_parser::Type;
+use libc::c_uint;
+use llvm_sys::core::{LLVMDisposeMessage, LLVMStructType};
+use llvm_sys::prelude::{LLVMModuleRef, LLVMTypeRef};
+use llvm_sys::target::{
+    LLVMByteOrder, LLVMByteOrdering, LLVMCopyStringRepOfTargetData, LLVMCreateTargetData,
+    LLVMDisposeTargetData, LLVMGetModuleDataLayout, LLVMInitializeAArch64AsmPrinter,
+    LLVMInitializeAArch64Target, LLVMInitializeAArch64TargetInfo, LLVMInitializeAArch64TargetMC,
+    LLVMInitializeWebAssemblyAsmPrinter, LLVMInitializeWebAssemblyTarget,
+    LLVMInitializeWebAssemblyTargetInfo, LLVMInitializeWebAssemblyTargetMC,
+    LLVMInitializeX86AsmPrinter, LLVMInitializeX86Target, LLVMInitializeX86TargetInfo,
+    LLVMInitializeX86TargetMC, LLVMPointerSize, LLVMPreferredAlignmentOfType, LLVMSizeOfTypeInBits,
+    LLVMTargetDataRef, LLVM_InitializeAllTargetInfos, LLVM_InitializeNativeTarget,
+};
+use llvm_sys::target_machine::{
+    LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetDataLayout, LLVMCreateTargetMachine,
+    LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple, LLVMGetTargetFromTriple, LLVMRelocMode,
+};
+use std::ffi::CStr;
+use std::ptr;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(non_camel_case_types)]
-pub enum Target {
+pub enum TargetArch {
     wasm,
     arm32,
     arm64,
@@ -10,48 +33,233 @@ pub enum Target {
     x86_64,
 }
 
+/// A cross compile target, the architecture picks the LLVM backend and linker and the
+/// triple is set on the module as it was given
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub arch: TargetArch,
+    triple: String,
+}
+
 impl Target {
     pub fn from_target_name(s: &str) -> Option<Self> {
-        match s {
-            "wasm" => Some(Target::wasm),
-            "arm32" => Some(Target::arm32),
-            "arm64" => Some(Target::arm64),
-            "x86_32" => Some(Target::x86_32),
-            "x86_64" => Some(Target::x86_64),
-            _ => None,
+        let arch = match s {
+            "wasm" | "wasm32" => TargetArch::wasm,
+            "arm32" => TargetArch::arm32,
+            "arm64" | "aarch64-linux" => TargetArch::arm64,
+            "x86_32" => TargetArch::x86_32,
+            "x86_64" => TargetArch::x86_64,
+            _ => return None,
+        };
+        let triple = match arch {
+            TargetArch::wasm => "wasm32-unknown-unknown-wasm",
+            TargetArch::arm32 => "arm-unknown-linux-gnueabihf",
+            TargetArch::arm64 => "aarch64-unknown-linux-gnu",
+            TargetArch::x86_32 => "i386-unknown-unknown-elf",
+            TargetArch::x86_64 => "x86_64-unknown-linux-gnu",
+        };
+        Some(Target {
+            arch,
+            triple: triple.to_string(),
+        })
+    }
+
+    /// Parses a `--target` value, either a short name like `wasm32` or an LLVM target triple
+    /// like `x86_64-unknown-linux-gnu`. A triple has to be one LLVM recognises, its
+    /// architecture picks the target the module is compiled for.
+    pub fn from_triple(triple: &str) -> Result<Self> {
+        if let Some(target) = Self::from_target_name(triple) {
+            return Ok(target);
+        }
+        unsafe {
+            LLVM_InitializeAllTargetInfos();
+            let mut llvm_target = ptr::null_mut();
+            let mut error = ptr::null_mut();
+            if LLVMGetTargetFromTriple(
+                cstr_from_string(triple).as_ptr(),
+                &mut llvm_target,
+                &mut error,
+            ) != 0
+            {
+                let message = std::ffi::CStr::from_ptr(error)
+                    .to_string_lossy()
+                    .to_string();
+                LLVMDisposeMessage(error);
+                return Err(anyhow!("invalid target triple {}: {}", triple, message));
+            }
         }
+        let arch = match triple.split('-').next() {
+            Some("wasm32") => TargetArch::wasm,
+            Some("aarch64" | "arm64") => TargetArch::arm64,
+            Some("x86_64") => TargetArch::x86_64,
+            _ => return Err(anyhow!("target {} is not supported", triple)),
+        };
+        Ok(Target {
+            arch,
+            triple: triple.to_string(),
+        })
     }
 
     pub fn get_llvm_target_name(&self) -> String {
-        match self {
-            Target::wasm => "wasm32-unknown-unknown-wasm".to_string(),
-            Target::arm32 => "arm-unknown-linux-gnueabihf".to_string(),
-            Target::arm64 => "aarch64-unknown-linux-gnu".to_string(),
-            Target::x86_32 => "i386-unknown-unknown-elf".to_string(),
-            Target::x86_64 => "x86_64-unknown-unknown-elf".to_string(),
-        }
+        self.triple.clone()
+    }
+
+    pub fn is_wasm(&self) -> bool {
+        matches!(self.arch, TargetArch::wasm)
     }
 
-    pub fn initialize(&self) {
+    pub fn initialize(&self) -> Result<()> {
         unsafe {
-            match self {
-                Target::wasm => {
+            match self.arch {
+                TargetArch::wasm => {
+                    // the target info and MC layer are needed to create a target machine
+                    LLVMInitializeWebAssemblyTargetInfo();
                     LLVMInitializeWebAssemblyTarget();
+                    LLVMInitializeWebAssemblyTargetMC();
                     LLVMInitializeWebAssemblyAsmPrinter();
                 }
-                Target::arm32 => {
-                    unimplemented!("arm32 not implemented yet ")
+                TargetArch::arm64 => {
+                    LLVMInitializeAArch64TargetInfo();
+                    LLVMInitializeAArch64Target();
+                    LLVMInitializeAArch64TargetMC();
+                    LLVMInitializeAArch64AsmPrinter();
                 }
-                Target::arm64 => {
-                    unimplemented!("arm64 not implemented yet ")
+                TargetArch::x86_64 => {
+                    LLVMInitializeX86TargetInfo();
+                    LLVMInitializeX86Target();
+                    LLVMInitializeX86TargetMC();
+                    LLVMInitializeX86AsmPrinter();
                 }
-                Target::x86_32 => {
-                    unimplemented!("x86_32 not implemented yet ")
+                TargetArch::arm32 | TargetArch::x86_32 => {
+                    return Err(anyhow!("target {} is not supported yet", self.triple))
                 }
-                Target::x86_64 => {
-                    unimplemented!("x86_64 not implemented yet ")
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The layout of a target: its pointer size, byte order and the size and alignment of
+/// cyclang types on it. A module gets its target's data layout when the builder is created, so
+/// `LLVMCodegenBuilder::target_info` reports the same layout as `TargetInfo::new`
+pub struct TargetInfo {
+    /// The size of a pointer in bytes
+    pub pointer_size: u32,
+    pub is_little_endian: bool,
+    target_data: LLVMTargetDataRef,
+}
+
+impl TargetInfo {
+    /// Reads the data layout of `target`, or of the host when it's `None`
+    pub fn new(target: Option<&Target>) -> Result<Self> {
+        unsafe {
+            let triple = match target {
+                Some(target) => {
+                    target.initialize()?;
+                    cstr_from_string(&target.get_llvm_target_name())
                 }
+                None => {
+                    LLVM_InitializeNativeTarget();
+                    let default_triple = LLVMGetDefaultTargetTriple();
+                    let triple = CStr::from_ptr(default_triple).to_owned();
+                    LLVMDisposeMessage(default_triple);
+                    triple
+                }
+            };
+            let mut llvm_target = ptr::null_mut();
+            let mut error = ptr::null_mut();
+            if LLVMGetTargetFromTriple(triple.as_ptr(), &mut llvm_target, &mut error) != 0 {
+                let message = CStr::from_ptr(error).to_string_lossy().to_string();
+                LLVMDisposeMessage(error);
+                return Err(anyhow!(
+                    "failed to get target for {:?}: {}",
+                    triple,
+                    message
+                ));
+            }
+            let target_machine = LLVMCreateTargetMachine(
+                llvm_target,
+                triple.as_ptr(),
+                cstr_from_string("").as_ptr(),
+                cstr_from_string("").as_ptr(),
+                LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+                LLVMRelocMode::LLVMRelocDefault,
+                LLVMCodeModel::LLVMCodeModelDefault,
+            );
+            let target_data = LLVMCreateTargetDataLayout(target_machine);
+            LLVMDisposeTargetMachine(target_machine);
+            Ok(Self::from_target_data(target_data))
+        }
+    }
+
+    // takes a copy of the module's data layout, so the info can outlive the module
+    pub(crate) fn from_module(module: LLVMModuleRef) -> Self {
+        unsafe {
+            let layout = LLVMCopyStringRepOfTargetData(LLVMGetModuleDataLayout(module));
+            let target_data = LLVMCreateTargetData(layout);
+            LLVMDisposeMessage(layout);
+            Self::from_target_data(target_data)
+        }
+    }
+
+    // the info owns `target_data` and disposes it when it's dropped
+    fn from_target_data(target_data: LLVMTargetDataRef) -> Self {
+        unsafe {
+            TargetInfo {
+                pointer_size: LLVMPointerSize(target_data),
+                is_little_endian: matches!(
+                    LLVMByteOrder(target_data),
+                    LLVMByteOrdering::LLVMLittleEndian
+                ),
+                target_data,
             }
         }
     }
+
+    /// The size in bytes of a value of `cyclo_type`, a bool is a single bit but takes up a byte
+    pub fn type_size(&self, cyclo_type: &Type) -> Result<u64> {
+        let llvm_type = Self::get_llvm_type(cyclo_type)?;
+        Ok(unsafe { self.size_of(llvm_type) })
+    }
+
+    /// The alignment in bytes the target prefers for a value of `cyclo_type`
+    pub fn type_alignment(&self, cyclo_type: &Type) -> Result<u32> {
+        let llvm_type = Self::get_llvm_type(cyclo_type)?;
+        Ok(unsafe { LLVMPreferredAlignmentOfType(self.target_data, llvm_type) })
+    }
+
+    /// The size in bytes of `llvm_type`, rounded up to a whole byte
+    ///
+    /// # Safety
+    ///
+    /// `llvm_type` must be a valid, sized LLVM type
+    pub(crate) unsafe fn size_of(&self, llvm_type: LLVMTypeRef) -> u64 {
+        LLVMSizeOfTypeInBits(self.target_data, llvm_type).div_ceil(8)
+    }
+
+    // the same types codegen uses, strings and lists are pointers to their buffers. A struct
+    // is only known to the module that defines it so its layout can't be looked up here
+    fn get_llvm_type(cyclo_type: &Type) -> Result<LLVMTypeRef> {
+        Ok(match cyclo_type {
+            Type::i32 => int32_type(),
+            Type::i64 => int64_type(),
+            Type::Float => float_type(),
+            Type::Bool => int1_type(),
+            Type::String | Type::List(_) => int8_ptr_type(),
+            Type::Tuple(component_types) => {
+                let mut llvm_types = component_types
+                    .iter()
+                    .map(Self::get_llvm_type)
+                    .collect::<Result<Vec<_>>>()?;
+                unsafe { LLVMStructType(llvm_types.as_mut_ptr(), llvm_types.len() as c_uint, 0) }
+            }
+            other => return Err(anyhow!("unable to get the layout of type {}", other)),
+        })
+    }
+}
+
+impl Drop for TargetInfo {
+    fn drop(&mut self) {
+        unsafe { LLVMDisposeTargetData(self.target_data) }
+    }
 }
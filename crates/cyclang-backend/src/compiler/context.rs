@@ -1,82 +1,320 @@
-use crate::compiler::cache::VariableCache;
+use crate::compiler::cache::{FunctionCache, VariableCache};
 use crate::compiler::codegen::builder::LLVMCodegenBuilder;
 use crate::compiler::codegen::context::LLVMFunction;
 use crate::compiler::codegen::{
-    cstr_from_string, int1_ptr_type, int1_type, int32_ptr_type, int32_type, int64_ptr_type,
-    int64_type, int8_ptr_type,
+    cstr_from_string, float_type, int1_type, int32_ptr_type, int32_type, int64_ptr_type, int64_type,
 };
+use crate::compiler::error::CycloError;
+use crate::compiler::optimizer::prune_dead_code;
 use crate::compiler::types::bool::BoolType;
+use crate::compiler::types::float::FloatType;
 use crate::compiler::types::func::FuncType;
 use crate::compiler::types::list::ListType;
 use crate::compiler::types::num::NumberType;
 use crate::compiler::types::num64::NumberType64;
 use crate::compiler::types::return_type::ReturnType;
 use crate::compiler::types::string::StringType;
+use crate::compiler::types::struct_type::{StructLayout, StructType};
+use crate::compiler::types::tuple::get_component_types;
 use crate::compiler::types::void::VoidType;
-use crate::compiler::types::{BaseTypes, TypeBase};
+use crate::compiler::types::{get_base_type, get_scalar_base_type, BaseTypes, TypeBase};
 use crate::compiler::visitor::Visitor;
-use crate::compiler::Expression;
+use crate::compiler::{locate_error, Expression};
 use anyhow::anyhow;
 use anyhow::Result;
-use cyclang_parser::Type;
+use cyclang_parser::{unescape, Span, Type};
 use libc::c_ulonglong;
-use llvm_sys::core::{LLVMBuildCall2, LLVMConstStringInContext2, LLVMCountParamTypes};
-use std::ffi::CString;
+use llvm_sys::core::{LLVMBuildCall2, LLVMBuildSExt, LLVMCountParamTypes};
 use llvm_sys::prelude::LLVMValueRef;
+use std::collections::HashMap;
 
 pub struct ASTContext {
     pub var_cache: VariableCache,
-    pub func_cache: VariableCache,
+    pub func_cache: FunctionCache,
+    pub type_aliases: HashMap<String, Type>,
+    pub struct_layouts: HashMap<String, StructLayout>,
+    // functions added to the module ahead of their definition, by name and argument types
+    pub declared_functions: HashMap<(String, Vec<Type>), FuncType>,
+    // the strings a `let` built in each open block by depth, freed when the block ends
+    pub owned_strings: HashMap<i32, Vec<LLVMValueRef>>,
     pub depth: i32,
+    // where the top level statement being compiled starts, attached to errors
+    pub span: Option<Span>,
 }
 
 impl ASTContext {
     pub fn init() -> Result<ASTContext> {
         let var_cache = VariableCache::new();
-        let func_cache = VariableCache::new();
+        let func_cache = FunctionCache::new();
         Ok(ASTContext {
             var_cache,
             func_cache,
+            type_aliases: HashMap::new(),
+            struct_layouts: HashMap::new(),
+            declared_functions: HashMap::new(),
+            owned_strings: HashMap::new(),
             depth: 0,
+            span: None,
         })
     }
 
+    /// Declares the functions defined in a block before any of its statements are built, so a
+    /// function can call one defined after it, like two functions that call each other
+    pub fn declare_functions(
+        &mut self,
+        exprs: &[Expression],
+        codegen: &mut LLVMCodegenBuilder,
+    ) -> Result<()> {
+        for expr in exprs {
+            let (name, args) = match expr {
+                Expression::FuncStmt(name, args, _, _, _) => (name, args),
+                _ => continue,
+            };
+            let signature = LLVMFunction::get_signature(args);
+            // a function redefined in the same block is declared where each definition is
+            let definitions = exprs
+                .iter()
+                .filter(|other| {
+                    matches!(other, Expression::FuncStmt(other_name, other_args, ..)
+                        if other_name == name && LLVMFunction::get_signature(other_args) == signature)
+                })
+                .count();
+            if definitions > 1 {
+                continue;
+            }
+            // a function with a type that isn't known yet, like a struct defined further on,
+            // is declared where it's defined instead
+            if let Ok(Expression::FuncStmt(name, args, return_type, _, _)) =
+                self.resolve_type_aliases(expr.clone())
+            {
+                let func = LLVMFunction::declare(self, codegen, &name, &args, &return_type)?;
+                let signature = LLVMFunction::get_signature(&args);
+                self.declared_functions.insert((name, signature), func);
+            }
+        }
+        Ok(())
+    }
+
     pub fn match_ast(
         &mut self,
         input: Expression,
         visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
         codegen: &mut LLVMCodegenBuilder,
     ) -> Result<Box<dyn TypeBase>> {
+        let input = self.resolve_builtin_call(self.resolve_type_aliases(input)?);
         match input {
             Expression::Number(_) => visitor.visit_number(&input, codegen),
             Expression::Number64(_) => visitor.visit_number(&input, codegen),
+            Expression::Float(_) => visitor.visit_number(&input, codegen),
             Expression::String(_) => visitor.visit_string(&input, codegen),
             Expression::Bool(_) => visitor.visit_bool(&input, codegen),
             Expression::Variable(_) => visitor.visit_variable_expr(&input, codegen, self),
             Expression::List(_) => visitor.visit_list_expr(&input, codegen, self),
+            Expression::Tuple(_) => visitor.visit_tuple_expr(&input, codegen, self),
+            Expression::StructDef(_, _) => visitor.visit_struct_stmt(&input, codegen, self),
+            Expression::StructInit(_, _) => visitor.visit_struct_init_expr(&input, codegen, self),
+            Expression::FieldAccess(_, _) => visitor.visit_field_access_expr(&input, codegen, self),
+            Expression::MethodCall(_, _, _) => {
+                visitor.visit_method_call_expr(&input, codegen, self)
+            }
             Expression::ListIndex(_, _) => visitor.visit_list_index_expr(&input, codegen, self),
+            Expression::Slice(_, _, _) => visitor.visit_slice_expr(&input, codegen, self),
             Expression::ListAssign(_, _, _) => {
                 visitor.visit_list_assign_expr(&input, codegen, self)
             }
             Expression::Nil => visitor.visit_nil(),
             Expression::Binary(_, _, _) => visitor.visit_binary_stmt(&input, codegen, self),
+            Expression::Unary(_, _) => visitor.visit_unary_stmt(&input, codegen, self),
             Expression::Grouping(_) => visitor.visit_grouping_stmt(input, codegen, self),
             Expression::LetStmt(_, _, _) => visitor.visit_let_stmt(&input, codegen, self),
-            Expression::BlockStmt(_) => visitor.visit_block_stmt(&input, codegen, self),
+            Expression::AssignStmt(_, _) => visitor.visit_assign_stmt(&input, codegen, self),
+            Expression::GlobalStmt(_, _, _) => visitor.visit_global_stmt(&input, codegen, self),
+            Expression::LetTupleStmt(_, _) => visitor.visit_let_tuple_stmt(&input, codegen, self),
+            Expression::BlockStmt(exprs, spans) => {
+                let input = Expression::BlockStmt(prune_dead_code(exprs), spans);
+                visitor.visit_block_stmt(&input, codegen, self)
+            }
             Expression::CallStmt(_, _) => visitor.visit_call_stmt(&input, codegen, self),
-            Expression::FuncStmt(_, _, _, _) => visitor.visit_func_stmt(&input, codegen, self),
+            Expression::FuncStmt(_, _, _, _, _) => visitor.visit_func_stmt(&input, codegen, self),
+            Expression::ExternFunc(_, _, _) => visitor.visit_extern_func(&input, codegen, self),
             Expression::IfStmt(_, _, _) => visitor.visit_if_stmt(&input, codegen, self),
             Expression::WhileStmt(_, _) => visitor.visit_while_stmt(&input, codegen, self),
+            Expression::DoWhileStmt(_, _) => visitor.visit_do_while_stmt(&input, codegen, self),
+            Expression::RepeatUntilStmt(_, _) => {
+                visitor.visit_repeat_until_stmt(&input, codegen, self)
+            }
+            Expression::LoopStmt(_) => visitor.visit_loop_stmt(&input, codegen, self),
+            Expression::BreakStmt => visitor.visit_break_stmt(codegen),
             Expression::ForStmt(_, _, _, _, _) => {
                 visitor.visit_for_loop_stmt(&input, codegen, self)
             }
+            Expression::ReversedForStmt(_, _, _, _) => {
+                visitor.visit_reversed_for_stmt(&input, codegen, self)
+            }
+            Expression::ForEachStmt(_, _, _, _) => {
+                visitor.visit_for_each_stmt(&input, codegen, self)
+            }
             Expression::Len(_) => visitor.visit_len_stmt(&input, codegen, self),
+            Expression::Sum(_) => visitor.visit_sum_stmt(&input, codegen, self),
+            Expression::Avg(_) => visitor.visit_avg_stmt(&input, codegen, self),
+            Expression::Fold(_, _, _) => visitor.visit_fold_stmt(&input, codegen, self),
+            Expression::Windows(_, _) => visitor.visit_windows_stmt(&input, codegen, self),
+            Expression::Clamp(_, _, _) => visitor.visit_clamp_stmt(&input, codegen, self),
+            Expression::Lerp(_, _, _) => visitor.visit_lerp_stmt(&input, codegen, self),
+            Expression::Exit(_) => visitor.visit_exit_stmt(&input, codegen, self),
+            Expression::Assert(_) => visitor.visit_assert_stmt(&input, codegen, self),
+            Expression::Match(_, _) => visitor.visit_match_stmt(&input, codegen, self),
+            Expression::Switch(_, _, _) => visitor.visit_switch_stmt(&input, codegen, self),
+            Expression::Wildcard => Err(CycloError::MisplacedWildcard { span: self.span }.into()),
+            Expression::ReadLine => visitor.visit_read_line_stmt(codegen),
+            Expression::ReadInt => visitor.visit_read_int_stmt(codegen),
+            Expression::SizeOf(_) => visitor.visit_sizeof_stmt(&input, codegen, self),
+            Expression::Cast(_, _) => visitor.visit_cast_stmt(&input, codegen, self),
+            Expression::Is(_, _) => visitor.visit_is_expr(&input, codegen, self),
+            Expression::Format(_, _) => visitor.visit_format_stmt(&input, codegen, self),
             Expression::Print(_) => visitor.visit_print_stmt(&input, codegen, self),
+            Expression::TypeAlias(name, alias_type) => {
+                if self.type_aliases.contains_key(&name) {
+                    return Err(CycloError::AlreadyDefined {
+                        kind: "type alias".to_string(),
+                        name,
+                        span: self.span,
+                    }
+                    .into());
+                }
+                let alias_type = self.resolve_type(&alias_type)?;
+                self.type_aliases.insert(name, alias_type);
+                Ok(Box::new(VoidType {}))
+            }
             Expression::ReturnStmt(_) => visitor.visit_return_stmt(&input, codegen, self),
             _ => Err(anyhow!("this should be unreachable code, for {:?}", input)),
         }
     }
 
+    // type aliases don't introduce a new LLVM type, they are replaced by the type they
+    // refer to before codegen so an aliased value is interchangeable with its underlying type
+    fn resolve_type_aliases(&self, input: Expression) -> Result<Expression> {
+        match input {
+            Expression::LetStmt(var, var_type, lhs) => {
+                Ok(Expression::LetStmt(var, self.resolve_type(&var_type)?, lhs))
+            }
+            Expression::GlobalStmt(var, var_type, lhs) => Ok(Expression::GlobalStmt(
+                var,
+                self.resolve_type(&var_type)?,
+                lhs,
+            )),
+            Expression::FuncStmt(name, args, return_type, body, attribute) => {
+                let mut resolved_args = vec![];
+                for arg in args {
+                    match arg {
+                        Expression::FuncArg(arg_name, arg_type, default) => resolved_args.push(
+                            Expression::FuncArg(arg_name, self.resolve_type(&arg_type)?, default),
+                        ),
+                        _ => resolved_args.push(arg),
+                    }
+                }
+                Ok(Expression::FuncStmt(
+                    name,
+                    resolved_args,
+                    self.resolve_type(&return_type)?,
+                    body,
+                    attribute,
+                ))
+            }
+            Expression::ExternFunc(name, args, return_type) => {
+                let mut resolved_args = vec![];
+                for (arg_name, arg_type) in args {
+                    resolved_args.push((arg_name, self.resolve_type(&arg_type)?));
+                }
+                Ok(Expression::ExternFunc(
+                    name,
+                    resolved_args,
+                    self.resolve_type(&return_type)?,
+                ))
+            }
+            _ => Ok(input),
+        }
+    }
+
+    // user functions shadow the builtin with the same name, builtins are only called when
+    // there's no user function to call or through `builtin.name(...)`
+    fn resolve_builtin_call(&self, input: Expression) -> Expression {
+        match input {
+            Expression::Builtin(builtin) => *builtin,
+            Expression::CallStmt(ref name, ref args) if !self.func_cache.read().contains(name) => {
+                Expression::from_builtin_call(name, args).unwrap_or(input)
+            }
+            _ => match input.as_builtin_call() {
+                Some((name, args)) if self.func_cache.read().contains(name) => {
+                    Expression::CallStmt(name.to_string(), args)
+                }
+                _ => input,
+            },
+        }
+    }
+
+    pub fn resolve_type(&self, input_type: &Type) -> Result<Type> {
+        match input_type {
+            // struct names are parsed as aliases as they're only known once the struct is defined
+            Type::Alias(name) if self.struct_layouts.contains_key(name) => {
+                Ok(Type::Struct(name.clone()))
+            }
+            Type::Alias(name) => self.type_aliases.get(name).cloned().ok_or(
+                CycloError::UnknownType {
+                    name: name.clone(),
+                    span: self.span,
+                }
+                .into(),
+            ),
+            Type::List(inner) => Ok(Type::List(Box::new(self.resolve_type(inner)?))),
+            Type::Tuple(component_types) => Ok(Type::Tuple(
+                component_types
+                    .iter()
+                    .map(|component_type| self.resolve_type(component_type))
+                    .collect::<Result<Vec<Type>>>()?,
+            )),
+            _ => Ok(input_type.clone()),
+        }
+    }
+
+    // top level strings live until the program exits, so only strings built in a block are owned
+    pub fn own_string(&mut self, value: LLVMValueRef) {
+        if self.depth > 0 {
+            self.owned_strings
+                .entry(self.depth)
+                .or_default()
+                .push(value);
+        }
+    }
+
+    /// Stops a string from being freed when the block that owns it ends, for a string that can
+    /// still be reached afterwards like one put in a list or returned
+    pub fn escape_string(&mut self, value: &dyn TypeBase) {
+        if value.get_type() == BaseTypes::String {
+            let value = value.get_value();
+            for strings in self.owned_strings.values_mut() {
+                strings.retain(|owned| *owned != value);
+            }
+        }
+    }
+
+    /// Runs when a block ends: frees the strings the block owns and drops its locals. The value
+    /// the block evaluates to leaves the block, so it is never freed here even if it was built
+    /// in the block, which keeps an if or match arm from freeing the string it returns.
+    ///
+    /// This leaks in a few cases for now: a string passed to any call escapes, nothing is freed
+    /// when the block ends in a `return` or `break`, and a block ending with the `let` itself
+    /// evaluates to the string so it's never freed.
+    pub fn exit_block(&mut self, value: &dyn TypeBase, codegen: &LLVMCodegenBuilder) -> Result<()> {
+        self.escape_string(value);
+        let owned_strings = self.owned_strings.remove(&self.depth).unwrap_or_default();
+        codegen.free_strings(&owned_strings)?;
+        self.var_cache.write().del_locals(self.depth);
+        self.func_cache.write().del_locals(self.depth);
+        self.decr();
+        Ok(())
+    }
+
     pub fn get_depth(&self) -> i32 {
         self.depth
     }
@@ -118,42 +356,35 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                     llvm_value_pointer: Some(ptr),
                 }))
             }
-            _ => Err(anyhow!("type is not a number (i32,i64)")),
+            Expression::Float(val) => {
+                let name = "float";
+                let value = codegen.const_real(float_type(), *val);
+                let ptr = codegen.build_alloca_store(value, float_type(), name);
+                Ok(Box::new(FloatType {
+                    name: name.to_string(),
+                    llvm_value: value,
+                    llvm_value_pointer: Some(ptr),
+                }))
+            }
+            _ => Err(anyhow!("type is not a number (i32,i64,float)")),
         }
     }
 
     fn visit_string(
         &mut self,
         left: &Expression,
-        codegen: &LLVMCodegenBuilder,
+        codegen: &mut LLVMCodegenBuilder,
     ) -> Result<Box<dyn TypeBase>> {
         if let Expression::String(val) = left {
-            let name = "str_val";
-            let val = val.replace('"', "");
-            let string: CString = CString::new(val.clone()).unwrap();
-            unsafe {
-                let value = LLVMConstStringInContext2(
-                    codegen.context,
-                    string.as_ptr(),
-                    string.as_bytes().len(),
-                    0,
-                );
-                let string_init_func_llvm = codegen.llvm_func_cache.get("stringInit").unwrap();
-                let string_ptr =
-                    codegen.build_alloca_store(value, int8_ptr_type(), "stringPtrExample");
-
-                let return_value = codegen.build_call(
-                    string_init_func_llvm.clone(),
-                    vec![string_ptr],
-                    1,
-                    "stringInitExample",
-                );
-                return Ok(Box::new(StringType {
-                    name: name.to_string(),
-                    llvm_value: return_value,
-                    llvm_value_pointer: Some(return_value),
-                }));
-            }
+            // only the quotes around the literal are dropped, an escaped quote is kept
+            let val = val
+                .strip_prefix('"')
+                .and_then(|val| val.strip_suffix('"'))
+                .unwrap_or(val);
+            let val =
+                unescape(val).map_err(|escape| CycloError::InvalidEscape { escape, span: None })?;
+            let string = StringType::init(codegen, "str_val", val.as_bytes())?;
+            return Ok(Box::new(string));
         }
         Err(anyhow!("type is not a string"))
     }
@@ -190,9 +421,13 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                 None => {
                     // check if variable is in function
                     // TODO: should this be reversed i.e check func var first then global
-                    match context.var_cache.get(input) {
+                    match context.var_cache.read().get(input) {
                         Some(val) => Ok(val),
-                        None => Err(anyhow!(format!("Unknown variable {}", input))),
+                        None => Err(CycloError::UndefinedVariable {
+                            name: input.clone(),
+                            span: context.span,
+                        }
+                        .into()),
                     }
                 }
             };
@@ -200,6 +435,223 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         Err(anyhow!("type is not an i32"))
     }
 
+    fn visit_tuple_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Tuple(exprs) = left {
+            let mut components = vec![];
+            for expr in exprs {
+                let component = context.match_ast(expr.clone(), &mut visitor, codegen)?;
+                context.escape_string(component.as_ref());
+                components.push(component);
+            }
+            return Ok(Box::new(codegen.build_tuple(components)?));
+        }
+        Err(anyhow!("unable to visit tuple"))
+    }
+
+    fn visit_struct_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::StructDef(name, fields) = left {
+            if context.struct_layouts.contains_key(name) {
+                return Err(CycloError::AlreadyDefined {
+                    kind: "struct".to_string(),
+                    name: name.clone(),
+                    span: context.span,
+                }
+                .into());
+            }
+            let mut layout_fields: Vec<(String, BaseTypes)> = vec![];
+            for (field, field_type) in fields {
+                if layout_fields.iter().any(|(existing, _)| existing == field) {
+                    return Err(CycloError::DuplicateField {
+                        name: name.clone(),
+                        field: field.clone(),
+                        span: context.span,
+                    }
+                    .into());
+                }
+                let field_type = context.resolve_type(field_type)?;
+                let field_type = get_scalar_base_type(&field_type).ok_or_else(|| {
+                    CycloError::InvalidFieldType {
+                        name: name.clone(),
+                        field_type: field_type.clone(),
+                        span: context.span,
+                    }
+                })?;
+                layout_fields.push((field.clone(), field_type));
+            }
+            let field_types: Vec<BaseTypes> = layout_fields
+                .iter()
+                .map(|(_, field_type)| field_type.clone())
+                .collect();
+            let layout = StructLayout {
+                name: name.clone(),
+                fields: layout_fields,
+                llvm_type: codegen.get_struct_type(&field_types),
+            };
+            context.struct_layouts.insert(name.clone(), layout);
+            return Ok(Box::new(VoidType {}));
+        }
+        Err(anyhow!("unable to visit struct stmt"))
+    }
+
+    fn visit_struct_init_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::StructInit(name, fields) = left {
+            let layout =
+                context
+                    .struct_layouts
+                    .get(name)
+                    .cloned()
+                    .ok_or(CycloError::UnknownStruct {
+                        name: name.clone(),
+                        span: context.span,
+                    })?;
+            for (field, _) in fields {
+                if layout.get_field(field).is_none() {
+                    return Err(CycloError::UnknownField {
+                        name: name.clone(),
+                        field: field.clone(),
+                        span: context.span,
+                    }
+                    .into());
+                }
+            }
+            let mut values = vec![];
+            for (field, field_type) in &layout.fields {
+                let (_, value) = fields
+                    .iter()
+                    .find(|(init_field, _)| init_field == field)
+                    .ok_or_else(|| CycloError::MissingField {
+                        field: field.clone(),
+                        expr: left.to_string(),
+                        span: context.span,
+                    })?;
+                let value = context.match_ast(value.clone(), &mut visitor, codegen)?;
+                if value.get_type() != *field_type {
+                    return Err(CycloError::FieldTypeMismatch {
+                        name: name.clone(),
+                        field: field.clone(),
+                        expected: Box::new(field_type.clone()),
+                        found: Box::new(value.get_type()),
+                        span: context.span,
+                    }
+                    .into());
+                }
+                context.escape_string(value.as_ref());
+                values.push(value);
+            }
+            return Ok(Box::new(codegen.build_struct(&layout, values)));
+        }
+        Err(anyhow!("unable to visit struct init"))
+    }
+
+    fn visit_field_access_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::FieldAccess(value, field) = left {
+            let value = context.match_ast(*value.clone(), &mut visitor, codegen)?;
+            let layout = match value.get_type() {
+                BaseTypes::Struct(name) => {
+                    context
+                        .struct_layouts
+                        .get(&name)
+                        .ok_or(CycloError::UnknownStruct {
+                            name,
+                            span: context.span,
+                        })?
+                }
+                value_type => {
+                    return Err(CycloError::InvalidFieldAccess {
+                        field: field.clone(),
+                        expr: left.to_string(),
+                        found: Box::new(value_type),
+                        span: context.span,
+                    }
+                    .into())
+                }
+            };
+            return codegen.build_field_access(value.as_ref(), layout, field);
+        }
+        Err(anyhow!("unable to visit field access"))
+    }
+
+    fn visit_method_call_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::MethodCall(value, method, args) = left {
+            let value = context.match_ast(*value.clone(), &mut visitor, codegen)?;
+            let mut arg_values = vec![];
+            for arg in args {
+                arg_values.push(context.match_ast(arg.clone(), &mut visitor, codegen)?);
+            }
+            return match (value.get_type(), method.as_str(), arg_values.as_slice()) {
+                (BaseTypes::String, "chars", []) => codegen.new_chars(value.as_ref()),
+                (BaseTypes::String, "to_upper", []) => {
+                    codegen.new_string_from_helper("stringToUpper", value.as_ref())
+                }
+                (BaseTypes::String, "to_lower", []) => {
+                    codegen.new_string_from_helper("stringToLower", value.as_ref())
+                }
+                (BaseTypes::String, "trim", []) => {
+                    codegen.new_string_from_helper("stringTrim", value.as_ref())
+                }
+                (BaseTypes::String, "trim_start", []) => {
+                    codegen.new_string_from_helper("stringTrimStart", value.as_ref())
+                }
+                (BaseTypes::String, "trim_end", []) => {
+                    codegen.new_string_from_helper("stringTrimEnd", value.as_ref())
+                }
+                (BaseTypes::List(inner_type), "sort", []) if *inner_type == BaseTypes::Number => {
+                    codegen.new_list_sort(value.as_ref(), false)
+                }
+                (BaseTypes::List(inner_type), "sort_desc", [])
+                    if *inner_type == BaseTypes::Number =>
+                {
+                    codegen.new_list_sort(value.as_ref(), true)
+                }
+                (BaseTypes::List(_), "contains", [needle]) => {
+                    codegen.new_list_contains(value.as_ref(), needle.as_ref())
+                }
+                (BaseTypes::String, "split", [delimiter])
+                    if delimiter.get_type() == BaseTypes::String =>
+                {
+                    codegen.new_split(value.as_ref(), delimiter.as_ref())
+                }
+                (value_type, _, _) => Err(CycloError::UnknownMethod {
+                    method: method.clone(),
+                    expr: left.to_string(),
+                    found: Box::new(value_type),
+                    span: context.span,
+                }
+                .into()),
+            };
+        }
+        Err(anyhow!("unable to visit method call"))
+    }
+
     fn visit_list_expr(
         &mut self,
         left: &Expression,
@@ -212,10 +664,21 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
             let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
             for x in v {
                 let expr = context.match_ast(x.clone(), &mut visitor, codegen)?;
+                context.escape_string(expr.as_ref());
                 vec_expr.push(expr)
             }
 
-            let first_type = vec_expr.first().ok_or(anyhow!("unable to get first element from vec"))?.get_type();
+            let mut first_type = vec_expr
+                .first()
+                .ok_or(anyhow!("unable to get first element from vec"))?
+                .get_type();
+            // a list of integers with any i64 elements is a List<i64>, the i32 elements are widened
+            let is_integer = |t: BaseTypes| matches!(t, BaseTypes::Number | BaseTypes::Number64);
+            if vec_expr.iter().all(|x| is_integer(x.get_type()))
+                && vec_expr.iter().any(|x| x.get_type() == BaseTypes::Number64)
+            {
+                first_type = BaseTypes::Number64;
+            }
 
             // todo: refactor this
             let list_init_func_name = Self::get_list_init_func_name(&first_type);
@@ -226,16 +689,25 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
             let list = codegen.build_call(list_init_func, vec![length.unwrap().get_value()], 1, "");
 
             let set_int32_func = codegen.llvm_func_cache.get("set_int32_tValue").unwrap();
+            let set_int64_func = codegen.llvm_func_cache.get("set_int64_tValue").unwrap();
             let set_string_func = codegen.llvm_func_cache.get("setStringValue").unwrap();
 
             for (i, x) in vec_expr.iter().enumerate() {
                 let index = self.visit_number(&Expression::Number(i as i32), codegen);
                 let func_args = vec![list, x.get_value(), index.unwrap().get_value()];
-                match x.get_type() {
-                    BaseTypes::Number => {
+                match (&first_type, x.get_type()) {
+                    (BaseTypes::Number64, BaseTypes::Number) => {
+                        let value = codegen.build_cast(x.as_ref(), &Type::i64)?.get_value();
+                        let func_args = vec![list, value, func_args[2]];
+                        codegen.build_call(set_int64_func.clone(), func_args, 3, "");
+                    }
+                    (_, BaseTypes::Number64) => {
+                        codegen.build_call(set_int64_func.clone(), func_args, 3, "");
+                    }
+                    (_, BaseTypes::Number) => {
                         codegen.build_call(set_int32_func.clone(), func_args, 3, "");
                     }
-                    BaseTypes::String => {
+                    (_, BaseTypes::String) => {
                         codegen.build_call(set_string_func.clone(), func_args, 3, "");
                     }
                     _ => {
@@ -279,6 +751,13 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                             name: "".to_string(),
                         }));
                     }
+                    BaseTypes::Number64 => {
+                        let get_int64_value_func =
+                            codegen.llvm_func_cache.get("get_int64_tValue").unwrap();
+                        let i_val =
+                            codegen.build_call(get_int64_value_func, get_index_value_args, 2, "");
+                        return codegen.build_value_for_type(&BaseTypes::Number64, i_val, "");
+                    }
                     _ => unreachable!("not implement for {:?}", inner),
                 }
             }
@@ -286,6 +765,22 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         Err(anyhow!("not a list index"))
     }
 
+    fn visit_slice_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Slice(list, start, end) = left {
+            let list = context.match_ast(*list.clone(), &mut visitor, codegen)?;
+            let start = context.match_ast(*start.clone(), &mut visitor, codegen)?;
+            let end = context.match_ast(*end.clone(), &mut visitor, codegen)?;
+            return codegen.new_slice(list.as_ref(), start.as_ref(), end.as_ref());
+        }
+        Err(anyhow!("not a slice"))
+    }
+
     fn visit_list_assign_expr(
         &mut self,
         left: &Expression,
@@ -294,9 +789,12 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
         if let Expression::ListAssign(var, i, rhs) = left {
-            if let Some(val) = context.var_cache.get(var) {
+            // the lookup is bound first so the read lock is released before the value is built
+            let existing = context.var_cache.read().get(var);
+            if let Some(val) = existing {
                 let lhs: Box<dyn TypeBase> =
                     context.match_ast(*rhs.clone(), &mut visitor, codegen)?;
+                context.escape_string(lhs.as_ref());
                 let index = context.match_ast(*i.clone(), &mut visitor, codegen)?;
                 if let BaseTypes::List(inner) = val.get_type() {
                     match *inner {
@@ -307,6 +805,13 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                                 vec![val.get_value(), lhs.get_value(), index.get_value()];
                             codegen.build_call(set_int32_value_func, set_int32_args, 3, "");
                         }
+                        BaseTypes::Number64 => {
+                            let set_int64_value_func =
+                                codegen.llvm_func_cache.get("set_int64_tValue").unwrap();
+                            let value = codegen.build_cast(lhs.as_ref(), &Type::i64)?.get_value();
+                            let set_int64_args = vec![val.get_value(), value, index.get_value()];
+                            codegen.build_call(set_int64_value_func, set_int64_args, 3, "");
+                        }
                         _ => {
                             unreachable!()
                         }
@@ -329,20 +834,67 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
-        if let Expression::Binary(lhs, op, rhs) = left {
-            let lhs = context.match_ast(*lhs.clone(), &mut visitor, codegen)?;
-            let rhs = context.match_ast(*rhs.clone(), &mut visitor, codegen)?;
+        if let Expression::Binary(lhs_expr, op, rhs_expr) = left {
+            if op == "/" && matches!(**rhs_expr, Expression::Number(0) | Expression::Number64(0)) {
+                return Err(CycloError::DivisionByZero {
+                    expr: left.to_string(),
+                    span: context.span,
+                }
+                .into());
+            }
+            let lhs = context.match_ast(*lhs_expr.clone(), &mut visitor, codegen)?;
+            let rhs = context.match_ast(*rhs_expr.clone(), &mut visitor, codegen)?;
+            if !Self::is_compatible_binary_type(&lhs.get_type(), &rhs.get_type()) {
+                return Err(CycloError::OperandMismatch {
+                    op: op.clone(),
+                    lhs: lhs_expr.to_string(),
+                    rhs: rhs_expr.to_string(),
+                    expected: Box::new(lhs.get_type()),
+                    found: Box::new(rhs.get_type()),
+                    span: context.span,
+                }
+                .into());
+            }
             return match op.as_str() {
                 "+" | "-" | "/" | "*" => codegen.arithmetic(lhs, rhs, op.to_string()),
-                "^" => Err(anyhow!("^ is not implemented yet")),
+                "^" => Err(CycloError::UnsupportedOperator {
+                    op: op.to_string(),
+                    span: context.span,
+                }
+                .into()),
                 "==" | "!=" | "<" | "<=" | ">" | ">=" => codegen.cmp(lhs, rhs, op.to_string()),
 
-                _ => Err(anyhow!("Operator: {} not implement", op.clone())),
+                _ => Err(CycloError::UnsupportedOperator {
+                    op: op.clone(),
+                    span: context.span,
+                }
+                .into()),
             };
         }
         Err(anyhow!("unable to apply binary operation"))
     }
 
+    fn visit_unary_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Unary(op, expr) = left {
+            let value = context.match_ast(*expr.clone(), &mut visitor, codegen)?;
+            return match op.as_str() {
+                "~" => codegen.bitwise_not(value, expr),
+                _ => Err(CycloError::UnsupportedOperator {
+                    op: op.clone(),
+                    span: context.span,
+                }
+                .into()),
+            };
+        }
+        Err(anyhow!("unable to apply unary operation"))
+    }
+
     fn visit_grouping_stmt(
         &mut self,
         left: Expression,
@@ -363,21 +915,173 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
-        if let Expression::LetStmt(var, _, lhs) = left {
-            let lhs: Box<dyn TypeBase> = context.match_ast(*lhs.clone(), &mut visitor, codegen)?;
-            match context.var_cache.get(var) {
+        if let Expression::LetStmt(var, var_type, lhs) = left {
+            let is_new_string = Self::is_new_string(lhs);
+            // an i32 literal annotated as i64 is built as an i64 rather than truncated
+            let lhs = match (var_type, lhs.as_ref()) {
+                (Type::i64, Expression::Number(val)) => Expression::Number64(*val as i64),
+                _ => *lhs.clone(),
+            };
+            let lhs = Self::build_assigned_value(lhs, codegen, context, &mut visitor)?;
+            if let Some(expected_type) = get_base_type(var_type) {
+                if lhs.get_type() != expected_type {
+                    return Err(CycloError::TypeMismatch {
+                        name: var.clone(),
+                        expected: expected_type,
+                        found: lhs.get_type(),
+                        span: context.span,
+                    }
+                    .into());
+                }
+            }
+            if is_new_string && lhs.get_type() == BaseTypes::String {
+                context.own_string(lhs.get_value());
+            }
+            // a new binding, shadowing any variable with the same name until the block ends
+            context
+                .var_cache
+                .write()
+                .set(&var.clone(), lhs.clone(), context.depth);
+            return Ok(lhs);
+        }
+        Err(anyhow!("unable to visit let statement"))
+    }
+
+    fn visit_global_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::GlobalStmt(var, var_type, lhs) = left {
+            let expected_type = match var_type {
+                Type::i32 | Type::i64 | Type::Float | Type::Bool => get_base_type(var_type),
+                _ => None,
+            }
+            .ok_or_else(|| CycloError::InvalidGlobalType {
+                name: var.clone(),
+                span: context.span,
+            })?;
+            let lhs = match (var_type, lhs.as_ref()) {
+                (Type::i64, Expression::Number(val)) => Expression::Number64(*val as i64),
+                _ => *lhs.clone(),
+            };
+            let is_literal = matches!(
+                lhs,
+                Expression::Number(_)
+                    | Expression::Number64(_)
+                    | Expression::Float(_)
+                    | Expression::Bool(_)
+            );
+            let lhs = Self::build_assigned_value(lhs, codegen, context, &mut visitor)?;
+            if lhs.get_type() != expected_type {
+                return Err(CycloError::TypeMismatch {
+                    name: var.clone(),
+                    expected: expected_type,
+                    found: lhs.get_type(),
+                    span: context.span,
+                }
+                .into());
+            }
+            // bound at the top level so functions reach it through the var cache, they load
+            // and store it through the global rather than a stack slot in main
+            let global = codegen.new_global(var, lhs.as_ref(), is_literal)?;
+            context
+                .var_cache
+                .write()
+                .set(var, global.clone(), context.depth);
+            return Ok(global);
+        }
+        Err(anyhow!("unable to visit global statement"))
+    }
+
+    fn visit_assign_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::AssignStmt(var, lhs) = left {
+            let lhs = Self::build_assigned_value(*lhs.clone(), codegen, context, &mut visitor)?;
+            let existing = context.var_cache.read().get(var);
+            match existing {
+                // the if this arm belongs to merges the new binding back with a phi
+                Some(val)
+                    if codegen.in_if_arm()
+                        && !codegen.is_global(val.as_ref())
+                        && val.get_type() == lhs.get_type()
+                        && matches!(
+                            val.get_type(),
+                            BaseTypes::Number
+                                | BaseTypes::Number64
+                                | BaseTypes::Float
+                                | BaseTypes::Bool
+                        ) =>
+                {
+                    let value = codegen.load_value(lhs.as_ref());
+                    let rebound = codegen.build_value_for_type(&val.get_type(), value, var)?;
+                    context.var_cache.write().replace(var, rebound.clone());
+                    return Ok(rebound);
+                }
                 Some(val) => {
                     return codegen.assign(val.clone(), lhs);
                 }
                 _ => {
                     context
                         .var_cache
+                        .write()
                         .set(&var.clone(), lhs.clone(), context.depth);
                     return Ok(lhs);
                 }
             }
         }
-        Err(anyhow!("unable to visit let statement"))
+        Err(anyhow!("unable to visit assign statement"))
+    }
+
+    fn visit_let_tuple_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::LetTupleStmt(vars, lhs) = left {
+            let lhs: Box<dyn TypeBase> = context.match_ast(*lhs.clone(), &mut visitor, codegen)?;
+            let component_types = match lhs.get_type() {
+                BaseTypes::Tuple(component_types) if component_types.len() == vars.len() => {
+                    component_types
+                }
+                lhs_type => {
+                    return Err(CycloError::InvalidDestructure {
+                        names: vars.clone(),
+                        found: Box::new(lhs_type),
+                        span: context.span,
+                    }
+                    .into())
+                }
+            };
+            let tuple = codegen
+                .build_tuple_from_value(codegen.load_value(lhs.as_ref()), &component_types)?;
+            for (var, component) in vars.iter().zip(tuple.components.iter()) {
+                let existing = context.var_cache.read().get(var);
+                match existing {
+                    Some(val) => {
+                        codegen.assign(val.clone(), component.clone())?;
+                    }
+                    _ => {
+                        context.var_cache.write().set(
+                            &var.clone(),
+                            component.clone(),
+                            context.depth,
+                        );
+                    }
+                }
+            }
+            return Ok(Box::new(tuple));
+        }
+        Err(anyhow!("unable to visit let tuple statement"))
     }
 
     fn visit_block_stmt(
@@ -387,18 +1091,29 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
-        if let Expression::BlockStmt(exprs) = left {
+        if let Expression::BlockStmt(exprs, spans) = left {
             // Set Variable Depth
             // Each Block Stmt, Incr and Decr
             // Clearing all the "Local" Variables That Have Been Assigned
             context.incr();
+            // strings left by a block at this depth that failed to build aren't this block's
+            context.owned_strings.remove(&context.get_depth());
+            context.declare_functions(exprs, codegen)?;
             let mut val: Box<dyn TypeBase> = Box::new(VoidType {});
-            for expr in exprs {
-                val = context.match_ast(expr.clone(), &mut visitor, codegen)?;
+            // errors are located at the statement in the block, or the statement the block is
+            // in when it was built without spans
+            let outer_span = context.span;
+            for (i, expr) in exprs.iter().enumerate() {
+                context.span = spans.get(i).or(outer_span);
+                if let Some(span) = context.span {
+                    codegen.set_debug_line(span.line);
+                }
+                val = context
+                    .match_ast(expr.clone(), &mut visitor, codegen)
+                    .map_err(|e| locate_error(e, context.span))?;
             }
-            // Delete Variables
-            context.var_cache.del_locals(context.get_depth());
-            context.decr();
+            context.span = outer_span;
+            context.exit_block(val.as_ref(), codegen)?;
             return Ok(val);
         }
         Err(anyhow!("unable to visit block stmt"))
@@ -412,13 +1127,121 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
         if let Expression::CallStmt(name, args) = left {
-            let val = context.func_cache.get(name).ok_or(anyhow!("call does not exist for function {:?}", name))?;
+            if !context.func_cache.read().contains(name) {
+                return Err(CycloError::UndefinedFunction {
+                    name: name.clone(),
+                    span: context.span,
+                }
+                .into());
+            }
             unsafe {
                 // need to build up call with actual LLVMValue
                 let call_args = &mut vec![];
-                self.add_args_to_function(codegen, context, &mut visitor, args, call_args)?;
+                let mut arg_values =
+                    self.add_args_to_function(codegen, context, &mut visitor, args, call_args)?;
+                let arg_types: Vec<BaseTypes> =
+                    arg_values.iter().map(|value| value.get_type()).collect();
+                // the overload is picked by the types of the arguments
+                let resolved = context
+                    .func_cache
+                    .read()
+                    .resolve_with_params(name, &arg_types);
+                let (params, val) = resolved.ok_or_else(|| CycloError::NoMatchingOverload {
+                    name: name.clone(),
+                    args: arg_types.clone(),
+                    span: context.span,
+                })?;
                 let llvm_type = val.get_llvm_type();
                 let value = val.get_value();
+                // trailing arguments the call leaves out take their default values. A default
+                // only sees the arguments before it, the parser stops it naming anything else,
+                // so they stand in for the caller's arguments while it's built
+                let default_args = val.get_default_args();
+                let bound_args = default_args
+                    .iter()
+                    .zip(&arg_values)
+                    .map(|((arg_name, _), value)| (arg_name.clone(), value.clone()))
+                    .collect();
+                let caller_args =
+                    std::mem::replace(&mut codegen.current_function.symbol_table, bound_args);
+                let mut built = Ok(());
+                for (arg_name, default) in default_args.iter().skip(call_args.len()) {
+                    let Some(default) = default else {
+                        break;
+                    };
+                    match self.add_args_to_function(
+                        codegen,
+                        context,
+                        &mut visitor,
+                        std::slice::from_ref(default),
+                        call_args,
+                    ) {
+                        Ok(values) => {
+                            for value in values {
+                                codegen
+                                    .current_function
+                                    .symbol_table
+                                    .insert(arg_name.clone(), value.clone());
+                                arg_values.push(value);
+                            }
+                        }
+                        Err(e) => {
+                            built = Err(e);
+                            break;
+                        }
+                    }
+                }
+                codegen.current_function.symbol_table = caller_args;
+                built?;
+                let arg_types: Vec<BaseTypes> =
+                    arg_values.iter().map(|value| value.get_type()).collect();
+                // a function with one overload is resolved whatever the arguments are, so
+                // the arity is checked here before it can build a call LLVM would reject
+                let expected = LLVMCountParamTypes(llvm_type) as usize;
+                if call_args.len() != expected {
+                    return Err(CycloError::ArgumentCountMismatch {
+                        name: name.clone(),
+                        expected,
+                        found: call_args.len(),
+                        span: context.span,
+                    }
+                    .into());
+                }
+                // likewise its argument types, an i32 argument to an i64 parameter is widened
+                for (position, (param, arg_type)) in params.iter().zip(&arg_types).enumerate() {
+                    match get_base_type(param) {
+                        Some(BaseTypes::Number64) if *arg_type == BaseTypes::Number => {
+                            call_args[position] = LLVMBuildSExt(
+                                codegen.builder,
+                                call_args[position],
+                                int64_type(),
+                                cstr_from_string("widen_arg").as_ptr(),
+                            );
+                        }
+                        // C takes the buffer, which is kept null terminated
+                        Some(BaseTypes::String)
+                            if val.is_extern() && *arg_type == BaseTypes::String =>
+                        {
+                            let string = StringType {
+                                name: "extern_arg".to_string(),
+                                llvm_value: call_args[position],
+                                llvm_value_pointer: Some(call_args[position]),
+                            };
+                            call_args[position] = codegen.load_string_buffer(&string)?;
+                        }
+                        Some(expected) if expected != *arg_type => {
+                            return Err(CycloError::ArgumentTypeMismatch {
+                                name: name.clone(),
+                                position: position + 1,
+                                expected,
+                                found: arg_type.clone(),
+                                span: context.span,
+                            }
+                            .into());
+                        }
+                        _ => {}
+                    }
+                }
                 let call_value = LLVMBuildCall2(
                     codegen.builder,
                     llvm_type,
@@ -439,11 +1262,6 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                             llvm_value_pointer: None,
                             name: "call_value".into(),
                         });
-                        context.var_cache.set(
-                            name.as_str(),
-                            call_val.clone(),
-                            context.depth,
-                        );
                         return Ok(call_val)
                     }
                     Type::i64 => {
@@ -452,22 +1270,17 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                             int64_ptr_type(),
                             "call_value_int64",
                         );
-                        let call_val = Box::new(NumberType {
+                        let call_val = Box::new(NumberType64 {
                             llvm_value: call_value,
                             llvm_value_pointer: None,
                             name: "call_value".into(),
                         });
-                        context.var_cache.set(
-                            name.as_str(),
-                            call_val.clone(),
-                            context.depth,
-                        );
                         return Ok(call_val)
                     }
                     Type::Bool => {
                         let ptr = codegen.build_alloca_store(
                             call_value,
-                            int1_ptr_type(),
+                            int1_type(),
                             "bool_value",
                         );
                         let call_val = Box::new(BoolType {
@@ -476,13 +1289,18 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                             llvm_value_pointer: ptr,
                             name: "call_value".into(),
                         });
-                        context.var_cache.set(
-                            name.as_str(),
-                            call_val.clone(),
-                            context.depth,
-                        );
                         return Ok(call_val)
                     }
+                    Type::Float => {
+                        let ptr =
+                            codegen.build_alloca_store(call_value, float_type(), "float_value");
+                        let call_val = Box::new(FloatType {
+                            llvm_value: call_value,
+                            llvm_value_pointer: Some(ptr),
+                            name: "call_value".into(),
+                        });
+                        return Ok(call_val);
+                    }
                     Type::String => {
                         let ptr = codegen.build_alloca_store(
                             call_value,
@@ -494,11 +1312,6 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                             llvm_value_pointer: Some(ptr),
                             name: "call_value".into(),
                         });
-                        context.var_cache.set(
-                            name.as_str(),
-                            call_val.clone(),
-                            context.depth,
-                        );
                         return Ok(call_val)
                     }
                     Type::List(inner) => match *inner {
@@ -514,11 +1327,6 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                                 llvm_type: codegen.get_list_int32_ptr_type(),
                                 inner_type: BaseTypes::Number,
                             });
-                            context.var_cache.set(
-                                name.as_str(),
-                                call_val.clone(),
-                                context.depth,
-                            );
                             return Ok(call_val)
                         }
                         Type::String => {
@@ -533,24 +1341,46 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                                 llvm_type: codegen.get_list_string_ptr_type(),
                                 inner_type: BaseTypes::String,
                             });
-                            context.var_cache.set(
-                                name.as_str(),
-                                call_val.clone(),
-                                context.depth,
-                            );
                             return Ok(call_val)
                         }
                         _ => return Err(anyhow!("call does not exist for type List<{:?}>", inner)),
                     },
+                    Type::Tuple(component_types) => {
+                        let component_types = get_component_types(&component_types)?;
+                        let call_val =
+                            Box::new(codegen.build_tuple_from_value(call_value, &component_types)?);
+                        return Ok(call_val);
+                    }
+                    Type::Struct(struct_name) => {
+                        let layout = context.struct_layouts.get(&struct_name).ok_or(
+                            CycloError::UnknownStruct {
+                                name: struct_name.clone(),
+                                span: context.span,
+                            },
+                        )?;
+                        let call_val = Box::new(StructType {
+                            name: struct_name,
+                            llvm_value: call_value,
+                            llvm_value_pointer: codegen.build_alloca_store(
+                                call_value,
+                                layout.llvm_type,
+                                "struct_value",
+                            ),
+                            llvm_type: layout.llvm_type,
+                        });
+                        return Ok(call_val);
+                    }
                     Type::None => {
-                        //Return void
-                        let call_val = Box::new(VoidType {});
-                        context.var_cache.set(
-                            name.as_str(),
-                            call_val.clone(),
-                            context.depth,
-                        );
-                        return Ok(call_val)
+                        // a void call is only made for its side effects, there's no value to
+                        // bind so the function name isn't shadowed by a variable
+                        return Ok(Box::new(VoidType {}));
+                    }
+                    Type::Alias(alias) => {
+                        return Err(anyhow!(
+                            "unresolved type alias `{}` for call {}",
+                            alias,
+                            name
+                        ))
                     }
                 }
             }
@@ -564,14 +1394,14 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         codegen: &mut LLVMCodegenBuilder,
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>> {
-        if let Expression::FuncStmt(name, args, _return_type, body) = left {
+        if let Expression::FuncStmt(name, args, _return_type, body, attribute) = left {
             let llvm_func = LLVMFunction::new(
                 context,
                 name.clone(),
                 args.clone(),
                 _return_type.clone(),
                 *body.clone(),
-                codegen.current_function.block,
+                *attribute,
                 codegen,
             )?;
 
@@ -579,16 +1409,112 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
                 llvm_type: llvm_func.func_type,
                 llvm_func: llvm_func.function,
                 return_type: _return_type.clone(),
+                default_args: LLVMFunction::get_default_args(args),
+                is_extern: false,
             };
             // Set Func as a variable
             context
                 .func_cache
-                .set(name, Box::new(func.clone()), context.depth);
+                .write()
+                .set(
+                    name,
+                    LLVMFunction::get_signature(args),
+                    Box::new(func.clone()),
+                    context.depth,
+                );
             return Ok(Box::new(func));
         }
         Err(anyhow!("unable to visit func stmt"))
     }
 
+    fn visit_extern_func(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::ExternFunc(name, args, return_type) = left {
+            let func = LLVMFunction::declare_extern(context, codegen, name, args, return_type)?;
+            return Ok(Box::new(func));
+        }
+        Err(anyhow!("unable to visit extern func"))
+    }
+
+    fn visit_sizeof_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::SizeOf(size_type) = left {
+            // strings and lists are pointers to their values, so they're the size of a pointer
+            let llvm_type = match context.resolve_type(size_type)? {
+                Type::i32 => int32_type(),
+                Type::i64 => int64_type(),
+                Type::Float => float_type(),
+                Type::Bool => int1_type(),
+                Type::String => codegen.get_string_ptr_type(),
+                Type::List(_) => codegen.get_list_int32_ptr_type(),
+                Type::Tuple(component_types) => {
+                    codegen.get_struct_type(&get_component_types(&component_types)?)
+                }
+                Type::Struct(name) => {
+                    context
+                        .struct_layouts
+                        .get(&name)
+                        .ok_or(CycloError::UnknownStruct {
+                            name,
+                            span: context.span,
+                        })?
+                        .llvm_type
+                }
+                other => {
+                    return Err(CycloError::InvalidSizeOf {
+                        size_type: other,
+                        span: context.span,
+                    }
+                    .into())
+                }
+            };
+            return codegen.new_sizeof(llvm_type);
+        }
+        Err(anyhow!("unable to visit sizeof stmt"))
+    }
+
+    fn visit_cast_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Cast(cast_type, value) = left {
+            let cast_type = context.resolve_type(cast_type)?;
+            let value = context.match_ast(*value.clone(), &mut visitor, codegen)?;
+            return codegen.build_cast(value.as_ref(), &cast_type);
+        }
+        Err(anyhow!("unable to visit cast stmt"))
+    }
+
+    fn visit_is_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Is(value, is_type) = left {
+            // every value's type is known when it's built, so the check is a constant. The
+            // value is still built for any calls in it
+            let is_type = context.resolve_type(is_type)?;
+            let value = context.match_ast(*value.clone(), &mut visitor, codegen)?;
+            let matches = get_base_type(&is_type) == Some(value.get_type());
+            let is_value = codegen.const_int(int1_type(), matches.into(), 0);
+            return codegen.build_value_for_type(&BaseTypes::Bool, is_value, "is");
+        }
+        Err(anyhow!("unable to visit is expr"))
+    }
+
     fn visit_if_stmt(
         &mut self,
         left: &Expression,
@@ -625,6 +1551,56 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         Err(anyhow!("unable to visit while stmt"))
     }
 
+    fn visit_do_while_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::DoWhileStmt(do_block_stmt, condition) = left {
+            let cond = *condition.clone();
+            return codegen.new_do_while_stmt(context, *do_block_stmt.clone(), cond, &mut visitor);
+        }
+        Err(anyhow!("unable to visit do while stmt"))
+    }
+
+    fn visit_repeat_until_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::RepeatUntilStmt(repeat_block_stmt, condition) = left {
+            let cond = *condition.clone();
+            return codegen.new_repeat_until_stmt(
+                context,
+                *repeat_block_stmt.clone(),
+                cond,
+                &mut visitor,
+            );
+        }
+        Err(anyhow!("unable to visit repeat until stmt"))
+    }
+
+    fn visit_loop_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::LoopStmt(loop_block_stmt) = left {
+            return codegen.new_loop_stmt(context, *loop_block_stmt.clone(), &mut visitor);
+        }
+        Err(anyhow!("unable to visit loop stmt"))
+    }
+
+    fn visit_break_stmt(&mut self, codegen: &mut LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>> {
+        codegen.new_break_stmt()
+    }
+
     fn visit_for_loop_stmt(
         &mut self,
         left: &Expression,
@@ -645,6 +1621,42 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         Err(anyhow!("unable to visit for loop"))
     }
 
+    fn visit_reversed_for_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::ReversedForStmt(var_name, start, end, for_block_expr) = left {
+            return codegen.new_reversed_for_loop(
+                context,
+                var_name,
+                *start,
+                *end,
+                *for_block_expr.clone(),
+            );
+        }
+        Err(anyhow!("unable to visit reversed for loop"))
+    }
+
+    fn visit_for_each_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::ForEachStmt(var_name, list, reversed, for_block_expr) = left {
+            return codegen.new_for_each(
+                context,
+                var_name,
+                *list.clone(),
+                *reversed,
+                *for_block_expr.clone(),
+            );
+        }
+        Err(anyhow!("unable to visit for each loop"))
+    }
+
     fn visit_print_stmt(
         &mut self,
         left: &Expression,
@@ -653,8 +1665,14 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
     ) -> Result<Box<dyn TypeBase>> {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
         if let Expression::Print(input) = left {
-            let expression_value = context.match_ast(*input.clone(), &mut visitor, codegen)?;
-            expression_value.print(codegen)?;
+            let expression_value = match input.as_ref() {
+                // numeric constants are printed straight from the constant, skipping the stack slot
+                Expression::Number(_) | Expression::Number64(_) | Expression::Float(_) => {
+                    codegen.build_const_number(input)?
+                }
+                _ => context.match_ast(*input.clone(), &mut visitor, codegen)?,
+            };
+            codegen.new_print(expression_value.as_ref())?;
             return Ok(expression_value);
         }
         Err(anyhow!("unable to visit print stmt"))
@@ -669,6 +1687,177 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         Err(anyhow!("unable to visit print stmt"))
     }
 
+    fn visit_sum_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Sum(list) = left {
+            let list_value = context.match_ast(*list.clone(), &mut visitor, codegen)?;
+            return codegen.new_sum(list_value.as_ref());
+        }
+        Err(anyhow!("unable to visit sum stmt"))
+    }
+
+    fn visit_avg_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Avg(list) = left {
+            let list_value = context.match_ast(*list.clone(), &mut visitor, codegen)?;
+            return codegen.new_avg(list_value.as_ref());
+        }
+        Err(anyhow!("unable to visit avg stmt"))
+    }
+
+    fn visit_fold_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        if let Expression::Fold(list, init, func_name) = left {
+            //TODO: fix this so its an associated function
+            return codegen.new_fold(context, *list.clone(), *init.clone(), func_name.clone());
+        }
+        Err(anyhow!("unable to visit fold stmt"))
+    }
+
+    fn visit_windows_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Windows(list, size) = left {
+            return codegen.new_windows(context, *list.clone(), *size.clone(), &mut visitor);
+        }
+        Err(anyhow!("unable to visit windows stmt"))
+    }
+
+    fn visit_clamp_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Clamp(value, lo, hi) = left {
+            let value = context.match_ast(*value.clone(), &mut visitor, codegen)?;
+            let lo = context.match_ast(*lo.clone(), &mut visitor, codegen)?;
+            let hi = context.match_ast(*hi.clone(), &mut visitor, codegen)?;
+            return codegen.new_clamp(value.as_ref(), lo.as_ref(), hi.as_ref());
+        }
+        Err(anyhow!("unable to visit clamp stmt"))
+    }
+
+    fn visit_lerp_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Lerp(a, b, t) = left {
+            let a = context.match_ast(*a.clone(), &mut visitor, codegen)?;
+            let b = context.match_ast(*b.clone(), &mut visitor, codegen)?;
+            let t = context.match_ast(*t.clone(), &mut visitor, codegen)?;
+            return codegen.new_lerp(a.as_ref(), b.as_ref(), t.as_ref());
+        }
+        Err(anyhow!("unable to visit lerp stmt"))
+    }
+
+    fn visit_exit_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Exit(code) = left {
+            let code = context.match_ast(*code.clone(), &mut visitor, codegen)?;
+            return codegen.new_exit(code.as_ref());
+        }
+        Err(anyhow!("unable to visit exit stmt"))
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Assert(cond) = left {
+            let message = match context.span {
+                Some(span) => format!("assertion failed at {}: {}", span, cond),
+                None => format!("assertion failed: {}", cond),
+            };
+            let cond = context.match_ast(*cond.clone(), &mut visitor, codegen)?;
+            return codegen.new_assert(cond.as_ref(), &message);
+        }
+        Err(anyhow!("unable to visit assert stmt"))
+    }
+
+    fn visit_match_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Match(scrutinee, arms) = left {
+            return codegen.new_match_stmt(context, *scrutinee.clone(), arms.clone(), &mut visitor);
+        }
+        Err(anyhow!("unable to visit match stmt"))
+    }
+
+    fn visit_switch_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Switch(scrutinee, cases, default) = left {
+            return codegen.new_switch_stmt(
+                context,
+                *scrutinee.clone(),
+                cases.clone(),
+                *default.clone(),
+                &mut visitor,
+            );
+        }
+        Err(anyhow!("unable to visit switch stmt"))
+    }
+
+    fn visit_read_line_stmt(&mut self, codegen: &LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>> {
+        codegen.new_read_line()
+    }
+
+    fn visit_read_int_stmt(&mut self, codegen: &LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>> {
+        codegen.new_read_int()
+    }
+
+    fn visit_format_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>> {
+        let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
+        if let Expression::Format(format, args) = left {
+            return codegen.new_format(context, format.clone(), args.clone(), &mut visitor);
+        }
+        Err(anyhow!("unable to visit format stmt"))
+    }
+
     fn visit_return_stmt(
         &mut self,
         left: &Expression,
@@ -678,6 +1867,7 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
         let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
         if let Expression::ReturnStmt(input) = left {
             let expression_value = context.match_ast(*input.clone(), &mut visitor, codegen)?;
+            context.escape_string(expression_value.as_ref());
             codegen.build_ret(expression_value.get_value());
             return Ok(Box::new(ReturnType {}));
         }
@@ -686,23 +1876,73 @@ impl Visitor<Box<dyn TypeBase>> for LLVMCodegenVisitor {
 }
 
 impl LLVMCodegenVisitor {
+    // the value of a let or an assignment, an if used as a value joins the values of its
+    // branches
+    fn build_assigned_value(
+        value: Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+    ) -> Result<Box<dyn TypeBase>> {
+        match value {
+            Expression::IfStmt(condition, if_stmt, else_stmt) => {
+                codegen.new_if_expr(context, *condition, *if_stmt, *else_stmt, visitor)
+            }
+            _ => context.match_ast(value, visitor, codegen),
+        }
+    }
+
+    // a string built by the value itself, like `"a"` or `"a" + name` as concatenating appends
+    // to the left string, rather than one another variable can also be bound to
+    fn is_new_string(value: &Expression) -> bool {
+        match value {
+            Expression::String(_) => true,
+            Expression::Binary(lhs, op, _) if op == "+" => Self::is_new_string(lhs),
+            Expression::Grouping(value) => Self::is_new_string(value),
+            _ => false,
+        }
+    }
+
+    // i32 and i64 operands are widened by the builder, anything else has to match exactly
+    fn is_compatible_binary_type(lhs_type: &BaseTypes, rhs_type: &BaseTypes) -> bool {
+        match (lhs_type, rhs_type) {
+            (BaseTypes::Number | BaseTypes::Number64, BaseTypes::Number | BaseTypes::Number64) => {
+                true
+            }
+            _ => lhs_type == rhs_type,
+        }
+    }
+
     fn get_list_init_func_name(first_type: &BaseTypes) -> &str {
         match first_type {
             BaseTypes::String => "createStringList",
             BaseTypes::Number => "create_int32_tList",
+            BaseTypes::Number64 => "create_int64_tList",
             _ => {
                 unimplemented!("type {:?} is unimplemented", first_type)
             }
         }
     }
 
-    fn add_args_to_function(&self, codegen: &mut LLVMCodegenBuilder, context: &mut ASTContext, visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>, args: &[Expression], call_args: &mut Vec<LLVMValueRef>) -> Result<()> {
+    // returns the type of each argument
+    fn add_args_to_function(
+        &self,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+        visitor: &mut Box<dyn Visitor<Box<dyn TypeBase>>>,
+        args: &[Expression],
+        call_args: &mut Vec<LLVMValueRef>,
+    ) -> Result<Vec<Box<dyn TypeBase>>> {
+        let mut arg_values = vec![];
         for arg in args.iter() {
             // build load args i.e if variable
             let ast_value =
                 context.match_ast(arg.clone(), visitor, codegen)?;
+            // the callee can hand the string back, so it isn't freed by the caller's block
+            context.escape_string(ast_value.as_ref());
             call_args.push(ast_value.get_value());
+            arg_values.push(ast_value);
         }
-        Ok(())
+        Ok(arg_values)
     }
 }
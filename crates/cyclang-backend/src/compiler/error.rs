@@ -0,0 +1,399 @@
+use crate::compiler::types::BaseTypes;
+use cyclang_parser::{Span, Type};
+use std::fmt;
+
+/// Errors found while compiling a program, each variant is a category a caller can match on
+/// by downcasting the `anyhow::Error` returned from `compile`. The errors `match_ast` raises
+/// have a variant of their own, the ones from the codegen helpers in the builder are
+/// reported as `Codegen`. `span` is where the statement the error was found in starts, when
+/// it's known
+#[derive(Debug, Clone, PartialEq)]
+pub enum CycloError {
+    UndefinedVariable {
+        name: String,
+        span: Option<Span>,
+    },
+    UndefinedFunction {
+        name: String,
+        span: Option<Span>,
+    },
+    UnknownType {
+        name: String,
+        span: Option<Span>,
+    },
+    UnknownStruct {
+        name: String,
+        span: Option<Span>,
+    },
+    UnknownField {
+        name: String,
+        field: String,
+        span: Option<Span>,
+    },
+    /// A value assigned to a variable doesn't match the variable's type annotation
+    TypeMismatch {
+        name: String,
+        expected: BaseTypes,
+        found: BaseTypes,
+        span: Option<Span>,
+    },
+    /// A struct field is initialised with a value of the wrong type
+    FieldTypeMismatch {
+        name: String,
+        field: String,
+        expected: Box<BaseTypes>,
+        found: Box<BaseTypes>,
+        span: Option<Span>,
+    },
+    /// The operands of a binary expression have incompatible types
+    OperandMismatch {
+        op: String,
+        lhs: String,
+        rhs: String,
+        expected: Box<BaseTypes>,
+        found: Box<BaseTypes>,
+        span: Option<Span>,
+    },
+    UnsupportedOperator {
+        op: String,
+        span: Option<Span>,
+    },
+    /// The branches of an if expression produce values of different types
+    BranchTypeMismatch {
+        expected: BaseTypes,
+        found: BaseTypes,
+        span: Option<Span>,
+    },
+    /// An integer divided by a literal zero
+    DivisionByZero {
+        expr: String,
+        span: Option<Span>,
+    },
+    /// A call to a function with several overloads where none takes the argument types
+    NoMatchingOverload {
+        name: String,
+        args: Vec<BaseTypes>,
+        span: Option<Span>,
+    },
+    /// A call to a function with a different number of arguments than it declares
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Option<Span>,
+    },
+    /// An argument to a function that isn't the type the function declares for it
+    ArgumentTypeMismatch {
+        name: String,
+        position: usize,
+        expected: BaseTypes,
+        found: BaseTypes,
+        span: Option<Span>,
+    },
+    /// A `cast` between types that can't be converted, like an integer to a string
+    InvalidCast {
+        from: BaseTypes,
+        to: BaseTypes,
+        span: Option<Span>,
+    },
+    /// A backslash in a string literal that doesn't start a known escape
+    InvalidEscape {
+        escape: String,
+        span: Option<Span>,
+    },
+    /// A struct or type alias defined with a name that's already taken
+    AlreadyDefined {
+        kind: String,
+        name: String,
+        span: Option<Span>,
+    },
+    /// A struct defined with two fields of the same name
+    DuplicateField {
+        name: String,
+        field: String,
+        span: Option<Span>,
+    },
+    /// A struct field with a type a struct can't hold, like a list
+    InvalidFieldType {
+        name: String,
+        field_type: Type,
+        span: Option<Span>,
+    },
+    /// A struct initialised without one of its fields
+    MissingField {
+        field: String,
+        expr: String,
+        span: Option<Span>,
+    },
+    /// A field read from a value that isn't a struct
+    InvalidFieldAccess {
+        field: String,
+        expr: String,
+        found: Box<BaseTypes>,
+        span: Option<Span>,
+    },
+    /// A method called on a type that doesn't have it
+    UnknownMethod {
+        method: String,
+        expr: String,
+        found: Box<BaseTypes>,
+        span: Option<Span>,
+    },
+    /// A `global` declared with a type other than i32, i64, float or bool
+    InvalidGlobalType {
+        name: String,
+        span: Option<Span>,
+    },
+    /// A `let` destructuring a value that isn't a tuple with a component for each name
+    InvalidDestructure {
+        names: Vec<String>,
+        found: Box<BaseTypes>,
+        span: Option<Span>,
+    },
+    /// `sizeof` of a type that has no size, like `None`
+    InvalidSizeOf {
+        size_type: Type,
+        span: Option<Span>,
+    },
+    /// `_` used anywhere but as a match pattern
+    MisplacedWildcard {
+        span: Option<Span>,
+    },
+    /// Any other error from codegen, with where the statement it was found in starts
+    Codegen {
+        message: String,
+        span: Option<Span>,
+    },
+    /// Every error the type checker found, when it found more than one
+    TypeErrors {
+        errors: Vec<CycloError>,
+    },
+}
+
+impl CycloError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CycloError::UndefinedVariable { span, .. }
+            | CycloError::UndefinedFunction { span, .. }
+            | CycloError::UnknownType { span, .. }
+            | CycloError::UnknownStruct { span, .. }
+            | CycloError::UnknownField { span, .. }
+            | CycloError::TypeMismatch { span, .. }
+            | CycloError::FieldTypeMismatch { span, .. }
+            | CycloError::OperandMismatch { span, .. }
+            | CycloError::UnsupportedOperator { span, .. }
+            | CycloError::BranchTypeMismatch { span, .. }
+            | CycloError::DivisionByZero { span, .. }
+            | CycloError::NoMatchingOverload { span, .. }
+            | CycloError::ArgumentCountMismatch { span, .. }
+            | CycloError::ArgumentTypeMismatch { span, .. }
+            | CycloError::InvalidCast { span, .. }
+            | CycloError::InvalidEscape { span, .. }
+            | CycloError::AlreadyDefined { span, .. }
+            | CycloError::DuplicateField { span, .. }
+            | CycloError::InvalidFieldType { span, .. }
+            | CycloError::MissingField { span, .. }
+            | CycloError::InvalidFieldAccess { span, .. }
+            | CycloError::UnknownMethod { span, .. }
+            | CycloError::InvalidGlobalType { span, .. }
+            | CycloError::InvalidDestructure { span, .. }
+            | CycloError::InvalidSizeOf { span, .. }
+            | CycloError::MisplacedWildcard { span }
+            | CycloError::Codegen { span, .. } => *span,
+            CycloError::TypeErrors { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for CycloError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CycloError::UndefinedVariable { name, .. } => write!(f, "Unknown variable {}", name),
+            CycloError::UndefinedFunction { name, .. } => {
+                write!(f, "call does not exist for function {:?}", name)
+            }
+            CycloError::UnknownType { name, .. } => write!(f, "unknown type `{}`", name),
+            CycloError::UnknownStruct { name, .. } => write!(f, "unknown struct `{}`", name),
+            CycloError::UnknownField { name, field, .. } => {
+                write!(f, "struct {} has no field `{}`", name, field)
+            }
+            CycloError::TypeMismatch {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "cannot assign {} to {} variable {}",
+                found, expected, name
+            ),
+            CycloError::FieldTypeMismatch {
+                name,
+                field,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "field `{}` of struct {} expected type {}, but found {}",
+                field, name, expected, found
+            ),
+            CycloError::OperandMismatch {
+                op,
+                lhs,
+                rhs,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "mismatched types in `{} {} {}`: operator `{}` expected `{}` to have type {} (inferred from `{}`), but it was inferred as {}",
+                lhs, op, rhs, op, rhs, expected, lhs, found
+            ),
+            CycloError::UnsupportedOperator { op, .. } => {
+                write!(f, "Operator: {} not implement", op)
+            }
+            CycloError::BranchTypeMismatch {
+                expected, found, ..
+            } => write!(
+                f,
+                "if branches must have the same type, expected {} but found {}",
+                expected, found
+            ),
+            CycloError::DivisionByZero { expr, .. } => {
+                write!(f, "division by zero in `{}`", expr)
+            }
+            CycloError::NoMatchingOverload { name, args, .. } => write!(
+                f,
+                "no overload of function {:?} takes ({})",
+                name,
+                args.iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            CycloError::ArgumentCountMismatch {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "function {} expects {} argument{}, got {}",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            CycloError::ArgumentTypeMismatch {
+                name,
+                position,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "argument {} of {} expects {}, got {}",
+                position, name, expected, found
+            ),
+            CycloError::InvalidCast { from, to, .. } => {
+                write!(f, "unable to cast {} to {}", from, to)
+            }
+            CycloError::InvalidEscape { escape, .. } => {
+                write!(f, "invalid escape `{}` in string literal", escape)
+            }
+            CycloError::AlreadyDefined { kind, name, .. } => {
+                write!(f, "{} `{}` is already defined", kind, name)
+            }
+            CycloError::DuplicateField { name, field, .. } => {
+                write!(f, "field `{}` is defined twice in struct {}", field, name)
+            }
+            CycloError::InvalidFieldType {
+                name, field_type, ..
+            } => write!(
+                f,
+                "type {} can't be used as a field of struct {}",
+                field_type, name
+            ),
+            CycloError::MissingField { field, expr, .. } => {
+                write!(f, "missing field `{}` in {}", field, expr)
+            }
+            CycloError::InvalidFieldAccess {
+                field, expr, found, ..
+            } => write!(
+                f,
+                "unable to access field `{}` on {} of type {}",
+                field, expr, found
+            ),
+            CycloError::UnknownMethod {
+                method,
+                expr,
+                found,
+                ..
+            } => write!(f, "type {} has no method `{}` in {}", found, method, expr),
+            CycloError::InvalidGlobalType { name, .. } => write!(
+                f,
+                "global `{}` can only be an i32, i64, float or bool",
+                name
+            ),
+            CycloError::InvalidDestructure { names, found, .. } => write!(
+                f,
+                "unable to destructure type {} into ({})",
+                found,
+                names.join(", ")
+            ),
+            CycloError::InvalidSizeOf { size_type, .. } => {
+                write!(f, "unable to take sizeof({})", size_type)
+            }
+            CycloError::MisplacedWildcard { .. } => {
+                write!(f, "`_` can only be used as a match pattern")
+            }
+            CycloError::Codegen { message, .. } => write!(f, "{}", message),
+            CycloError::TypeErrors { errors } => write!(
+                f,
+                "{}",
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        }?;
+        if let Some(span) = self.span() {
+            write!(f, " at {}", span)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CycloError {}
+
+/// Problems that don't stop a program compiling, `span` is where the top level statement the
+/// warning was found in starts, when it's known
+#[derive(Debug, Clone, PartialEq)]
+pub enum CycloWarning {
+    /// Statements after a `return` in the same block, they're never run so they aren't built
+    UnreachableCode { stmt: String, span: Option<Span> },
+}
+
+impl CycloWarning {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CycloWarning::UnreachableCode { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for CycloWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CycloWarning::UnreachableCode { stmt, .. } => {
+                write!(f, "unreachable code after return: `{}`", stmt)
+            }
+        }?;
+        if let Some(span) = self.span() {
+            write!(f, " at {}", span)?;
+        }
+        Ok(())
+    }
+}
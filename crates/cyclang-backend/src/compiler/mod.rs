@@ -1,31 +1,335 @@
 use crate::compiler::codegen::builder::LLVMCodegenBuilder;
 use crate::compiler::codegen::target::Target;
 use crate::compiler::context::{ASTContext, LLVMCodegenVisitor};
-use crate::compiler::types::TypeBase;
+use crate::compiler::error::{CycloError, CycloWarning};
+use crate::compiler::optimizer::fold_constants;
+use crate::compiler::type_checker::TypeChecker;
+use crate::compiler::types::{BaseTypes, TypeBase};
 use crate::compiler::visitor::Visitor;
-use anyhow::Result;
-use cyclang_parser::Expression;
+use anyhow::{anyhow, Result};
+use cyclang_parser::{parse_cyclo_program_spanned, Expression, Span, Spanned};
+use llvm_sys::core::LLVMTypeOf;
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use std::collections::{HashMap, HashSet};
 
 extern crate llvm_sys;
 pub mod cache;
 pub mod codegen;
 pub mod context;
+pub mod error;
+pub mod optimizer;
+pub mod type_checker;
+pub mod type_inferencer;
 pub mod types;
 pub mod visitor;
-#[derive(Debug, Clone, Copy)]
+/// How the compiled module is run once codegen has finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionMode {
+    /// JIT compile the module and run `main` in process with the LLVM execution engine
+    Jit,
+    /// Write the module to `bin/main.ll`, build it with clang and run `bin/main`,
+    /// returning its stdout
+    #[default]
+    Aot,
+}
+
+/// `CompileOptions::default()` runs the module ahead of time for the host with no
+/// optimization
+#[derive(Debug, Clone, Default)]
 pub struct CompileOptions {
-    pub is_execution_engine: bool,
+    pub execution_mode: ExecutionMode,
     pub target: Option<Target>,
+    /// Emit DWARF debug info for the compiled module
+    pub debug_info: bool,
+    /// LLVM optimization level from 0 (no optimization) to 3
+    pub optimization_level: u8,
+    /// In Aot mode, write `bin/main.o` with the LLVM target machine instead of `bin/main.ll`
+    pub emit_object: bool,
+    /// Print the LLVM IR to stdout before the module is run or written out
+    pub print_ir: bool,
+    /// Verify the module before it's optimized, always enabled in debug builds
+    pub verify_module: bool,
+    /// The file the program was read from, recorded in the debug info
+    pub source_file: Option<String>,
+}
+
+/// Parses and compiles a cyclang program. In `Aot` mode for the host the program is built
+/// under `bin/` and run, and what it printed is returned. In `Jit` mode the program prints
+/// straight to stdout, and a cross compiled binary is only linked, so the string is empty
+pub fn compile_string(src: &str, compile_options: Option<CompileOptions>) -> Result<String> {
+    let exprs = parse_cyclo_program_spanned(src)
+        .map_err(|e| anyhow!("unable to parse contents due to error: {}", e))?;
+    compile_spanned(exprs, compile_options)
 }
 
 pub fn compile(exprs: Vec<Expression>, compile_options: Option<CompileOptions>) -> Result<String> {
+    compile_statements(exprs.into_iter().map(|expr| (expr, None)), compile_options)
+}
+
+/// Like `compile`, errors found in a statement include where it starts in the source
+pub fn compile_spanned(
+    exprs: Vec<Spanned<Expression>>,
+    compile_options: Option<CompileOptions>,
+) -> Result<String> {
+    compile_statements(
+        exprs.into_iter().map(|expr| (expr.node, Some(expr.span))),
+        compile_options,
+    )
+}
+
+/// The warnings for a program, like statements after a `return` that are never run. They don't
+/// stop it compiling, so it's up to the caller to report them
+pub fn warnings_spanned(exprs: &[Spanned<Expression>]) -> Vec<CycloWarning> {
+    let exprs: Vec<(Expression, Option<Span>)> = exprs
+        .iter()
+        .map(|expr| (expr.node.clone(), Some(expr.span)))
+        .collect();
+    let mut type_checker = TypeChecker::new();
+    type_checker.check_program(&exprs);
+    type_checker.warnings()
+}
+
+fn compile_statements(
+    exprs: impl Iterator<Item = (Expression, Option<Span>)>,
+    compile_options: Option<CompileOptions>,
+) -> Result<String> {
+    let exprs: Vec<(Expression, Option<Span>)> = exprs.collect();
+    let mut errors = TypeChecker::new().check_program(&exprs);
+    match errors.len() {
+        0 => {}
+        1 => return Err(errors.remove(0).into()),
+        _ => return Err(CycloError::TypeErrors { errors }.into()),
+    }
+
     // output LLVM IR
     let mut ast_ctx = ASTContext::init()?;
     let mut visitor: Box<dyn Visitor<Box<dyn TypeBase>>> = Box::new(LLVMCodegenVisitor {});
     let mut codegen = LLVMCodegenBuilder::init(compile_options)?;
 
-    for expr in exprs {
-        ast_ctx.match_ast(expr, &mut visitor, &mut codegen)?;
+    let statements: Vec<Expression> = exprs.iter().map(|(expr, _)| expr.clone()).collect();
+    ast_ctx.declare_functions(&statements, &mut codegen)?;
+    for (expr, span) in exprs {
+        ast_ctx.span = span;
+        if let Some(span) = span {
+            codegen.set_debug_line(span.line);
+        }
+        ast_ctx
+            .match_ast(fold_constants(expr), &mut visitor, &mut codegen)
+            .map_err(|e| locate_error(e, span))?;
     }
     codegen.dispose_and_get_module_str()
 }
+
+// codegen errors that aren't a `CycloError`, like the ones from the builder helpers, are
+// wrapped in `CycloError::Codegen` with the span of their statement. One wrapped without a
+// span is given the span of the statement it's nested in
+fn locate_error(error: anyhow::Error, span: Option<Span>) -> anyhow::Error {
+    match error.downcast::<CycloError>() {
+        Ok(CycloError::Codegen {
+            message,
+            span: None,
+        }) => CycloError::Codegen { message, span }.into(),
+        Ok(error) => error.into(),
+        Err(error) => CycloError::Codegen {
+            message: error.to_string(),
+            span,
+        }
+        .into(),
+    }
+}
+
+/// A repl session that keeps the same module and AST context between statements, so variables
+/// and functions declared on earlier lines can be used on later ones
+pub struct ReplSession {
+    ast_ctx: ASTContext,
+    visitor: Box<dyn Visitor<Box<dyn TypeBase>>>,
+    codegen: LLVMCodegenBuilder,
+    line: u32,
+    // each line is built into a function of its own, so its variables are kept in globals
+    // and read back at the start of the next line
+    variables: HashMap<String, ReplVariable>,
+}
+
+#[derive(Clone)]
+struct ReplVariable {
+    global: LLVMValueRef,
+    llvm_type: LLVMTypeRef,
+    base_type: BaseTypes,
+}
+
+impl ReplSession {
+    pub fn init() -> Result<ReplSession> {
+        let mut codegen = LLVMCodegenBuilder::init(Some(CompileOptions {
+            execution_mode: ExecutionMode::Jit,
+            target: None,
+            debug_info: false,
+            optimization_level: 0,
+            emit_object: false,
+            print_ir: false,
+            verify_module: false,
+            source_file: None,
+        }))?;
+        codegen.enable_repl()?;
+        Ok(ReplSession {
+            ast_ctx: ASTContext::init()?,
+            visitor: Box::new(LLVMCodegenVisitor {}),
+            codegen,
+            line: 0,
+            variables: HashMap::new(),
+        })
+    }
+
+    /// Compiles the statements of a single repl line into a function and runs it, expressions
+    /// that produce a value print it along with its type. A line that fails to compile is
+    /// dropped and leaves the session as it was before it
+    pub fn eval(&mut self, exprs: Vec<Expression>) -> Result<()> {
+        self.line += 1;
+        let names: HashSet<String> = self
+            .ast_ctx
+            .var_cache
+            .read()
+            .snapshot()
+            .into_keys()
+            .collect();
+        match self.eval_line(exprs) {
+            Ok(variables) => {
+                self.variables = variables;
+                Ok(())
+            }
+            Err(e) => {
+                self.discard_line(&names);
+                Err(e)
+            }
+        }
+    }
+
+    fn eval_line(&mut self, exprs: Vec<Expression>) -> Result<HashMap<String, ReplVariable>> {
+        self.codegen.start_repl_statement(self.line)?;
+        self.load_variables()?;
+        for expr in exprs {
+            let is_value = is_repl_value(&expr);
+            let value = self.ast_ctx.match_ast(
+                fold_constants(expr),
+                &mut self.visitor,
+                &mut self.codegen,
+            )?;
+            if is_value && is_printable(&value.get_type()) {
+                self.codegen.new_repl_result(value.as_ref())?;
+            }
+        }
+        let variables = self.store_variables();
+        self.codegen.run_repl_statement(self.line)?;
+        Ok(variables)
+    }
+
+    fn load_variables(&mut self) -> Result<()> {
+        let mut bindings = self.ast_ctx.var_cache.write();
+        for (name, variable) in &self.variables {
+            let value = self
+                .codegen
+                .build_load(variable.global, variable.llvm_type, name);
+            let value = self
+                .codegen
+                .build_value_for_type(&variable.base_type, value, name)?;
+            bindings.replace(name, value);
+        }
+        Ok(())
+    }
+
+    // saves the variables at the end of the line to their globals. Functions are declared in
+    // the module and `global`s already live in it, the other values can't be kept so are
+    // dropped rather than left pointing into this line's function
+    fn store_variables(&mut self) -> HashMap<String, ReplVariable> {
+        let mut variables = HashMap::new();
+        let mut bindings = self.ast_ctx.var_cache.write();
+        for (name, value) in bindings.snapshot() {
+            let base_type = value.get_type();
+            if base_type == BaseTypes::Func || self.codegen.is_global(value.as_ref()) {
+                continue;
+            }
+            // the values that can be printed are the ones that fit in a global
+            if !is_printable(&base_type) {
+                bindings.del(&name);
+                continue;
+            }
+            let current = self.codegen.load_value(value.as_ref());
+            let variable = match self.variables.get(&name) {
+                Some(variable) if variable.base_type == base_type => variable.clone(),
+                _ => {
+                    let llvm_type = unsafe { LLVMTypeOf(current) };
+                    ReplVariable {
+                        global: self.codegen.new_repl_variable(&name, llvm_type),
+                        llvm_type,
+                        base_type,
+                    }
+                }
+            };
+            self.codegen.build_store(current, variable.global);
+            variables.insert(name, variable);
+        }
+        variables
+    }
+
+    // drops the functions and names a failed line added, the variables from earlier lines are
+    // still in their globals for the next line to read
+    fn discard_line(&mut self, names: &HashSet<String>) {
+        for function in self.codegen.discard_repl_statement() {
+            self.ast_ctx.func_cache.write().del_function(function);
+        }
+        self.ast_ctx.declared_functions.clear();
+        while self.ast_ctx.depth > 0 {
+            let depth = self.ast_ctx.depth;
+            self.ast_ctx.var_cache.write().del_locals(depth);
+            self.ast_ctx.func_cache.write().del_locals(depth);
+            self.ast_ctx.owned_strings.remove(&depth);
+            self.ast_ctx.decr();
+        }
+        let mut bindings = self.ast_ctx.var_cache.write();
+        for name in bindings.snapshot().into_keys() {
+            if !names.contains(&name) {
+                bindings.del(&name);
+            }
+        }
+    }
+}
+
+fn is_repl_value(expr: &Expression) -> bool {
+    if let Expression::Builtin(builtin) = expr {
+        return is_repl_value(builtin);
+    }
+    matches!(
+        expr,
+        Expression::Number(_)
+            | Expression::Number64(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bool(_)
+            | Expression::List(_)
+            | Expression::ListIndex(_, _)
+            | Expression::FieldAccess(_, _)
+            | Expression::Variable(_)
+            | Expression::Binary(_, _, _)
+            | Expression::Unary(_, _)
+            | Expression::Grouping(_)
+            | Expression::CallStmt(_, _)
+            | Expression::Len(_)
+            | Expression::Sum(_)
+            | Expression::Avg(_)
+            | Expression::Fold(_, _, _)
+            | Expression::Clamp(_, _, _)
+            | Expression::Lerp(_, _, _)
+            | Expression::Format(_, _)
+    )
+}
+
+fn is_printable(base_type: &BaseTypes) -> bool {
+    matches!(
+        base_type,
+        BaseTypes::Number
+            | BaseTypes::Number64
+            | BaseTypes::Float
+            | BaseTypes::Bool
+            | BaseTypes::String
+            | BaseTypes::List(_)
+    )
+}
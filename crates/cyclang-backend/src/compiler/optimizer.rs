@@ -0,0 +1,180 @@
+use cyclang_parser::{Expression, Type};
+
+/// Replaces arithmetic on integer literals with its result, so `1 + 2 * 3` is built as `7`.
+/// An i32 result that overflows becomes an i64, an i64 result wraps like the `add`, `sub` and
+/// `mul` instructions the expression would have been built with. Division by zero is left for
+/// codegen to report. `sizeof` a scalar is an i64 literal, so `sizeof(i64) * 4` is `32`
+pub fn fold_constants(expr: Expression) -> Expression {
+    let fold = |expr: Box<Expression>| Box::new(fold_constants(*expr));
+    let fold_all = |exprs: Vec<Expression>| -> Vec<Expression> {
+        exprs.into_iter().map(fold_constants).collect()
+    };
+    match expr {
+        Expression::Binary(lhs, op, rhs) => {
+            fold_binary(fold_constants(*lhs), op, fold_constants(*rhs))
+        }
+        Expression::Unary(op, value) => match (op.as_str(), fold_constants(*value)) {
+            ("~", Expression::Number(n)) => Expression::Number(!n),
+            ("~", Expression::Number64(n)) => Expression::Number64(!n),
+            (_, value) => Expression::Unary(op, Box::new(value)),
+        },
+        Expression::Grouping(value) => match fold_constants(*value) {
+            value @ (Expression::Number(_) | Expression::Number64(_)) => value,
+            value => Expression::Grouping(Box::new(value)),
+        },
+        Expression::List(items) => Expression::List(fold_all(items)),
+        Expression::ListIndex(list, index) => Expression::ListIndex(fold(list), fold(index)),
+        Expression::Slice(list, start, end) => {
+            Expression::Slice(fold(list), fold(start), fold(end))
+        }
+        Expression::ListAssign(name, index, value) => {
+            Expression::ListAssign(name, fold(index), fold(value))
+        }
+        Expression::Tuple(items) => Expression::Tuple(fold_all(items)),
+        Expression::StructInit(name, fields) => Expression::StructInit(
+            name,
+            fields
+                .into_iter()
+                .map(|(field, value)| (field, fold_constants(value)))
+                .collect(),
+        ),
+        Expression::FieldAccess(value, field) => Expression::FieldAccess(fold(value), field),
+        Expression::MethodCall(value, method, args) => {
+            Expression::MethodCall(fold(value), method, fold_all(args))
+        }
+        Expression::LetStmt(name, var_type, value) => {
+            Expression::LetStmt(name, var_type, fold(value))
+        }
+        Expression::GlobalStmt(name, var_type, value) => {
+            Expression::GlobalStmt(name, var_type, fold(value))
+        }
+        Expression::AssignStmt(name, value) => Expression::AssignStmt(name, fold(value)),
+        Expression::LetTupleStmt(names, value) => Expression::LetTupleStmt(names, fold(value)),
+        Expression::BlockStmt(exprs, spans) => Expression::BlockStmt(fold_all(exprs), spans),
+        Expression::FuncStmt(name, args, return_type, body, attribute) => {
+            Expression::FuncStmt(name, args, return_type, fold(body), attribute)
+        }
+        Expression::CallStmt(name, args) => Expression::CallStmt(name, fold_all(args)),
+        Expression::IfStmt(cond, then_block, else_block) => Expression::IfStmt(
+            fold(cond),
+            fold(then_block),
+            Box::new((*else_block).map(fold_constants)),
+        ),
+        Expression::WhileStmt(cond, body) => Expression::WhileStmt(fold(cond), fold(body)),
+        Expression::DoWhileStmt(body, cond) => Expression::DoWhileStmt(fold(body), fold(cond)),
+        Expression::RepeatUntilStmt(body, cond) => {
+            Expression::RepeatUntilStmt(fold(body), fold(cond))
+        }
+        Expression::LoopStmt(body) => Expression::LoopStmt(fold(body)),
+        Expression::ReturnStmt(value) => Expression::ReturnStmt(fold(value)),
+        Expression::ForStmt(name, start, end, step, body) => {
+            Expression::ForStmt(name, start, end, step, fold(body))
+        }
+        Expression::ReversedForStmt(name, start, end, body) => {
+            Expression::ReversedForStmt(name, start, end, fold(body))
+        }
+        Expression::ForEachStmt(name, list, reversed, body) => {
+            Expression::ForEachStmt(name, fold(list), reversed, fold(body))
+        }
+        Expression::Print(value) => Expression::Print(fold(value)),
+        Expression::Len(value) => Expression::Len(fold(value)),
+        Expression::Sum(value) => Expression::Sum(fold(value)),
+        Expression::Avg(value) => Expression::Avg(fold(value)),
+        Expression::Fold(list, init, func) => Expression::Fold(fold(list), fold(init), func),
+        Expression::Windows(list, size) => Expression::Windows(fold(list), fold(size)),
+        Expression::Clamp(value, lo, hi) => Expression::Clamp(fold(value), fold(lo), fold(hi)),
+        Expression::Lerp(a, b, t) => Expression::Lerp(fold(a), fold(b), fold(t)),
+        Expression::Exit(code) => Expression::Exit(fold(code)),
+        Expression::Assert(cond) => Expression::Assert(fold(cond)),
+        Expression::Builtin(builtin) => Expression::Builtin(fold(builtin)),
+        Expression::Match(value, arms) => Expression::Match(
+            fold(value),
+            arms.into_iter()
+                .map(|(pattern, arm)| (pattern, fold_constants(arm)))
+                .collect(),
+        ),
+        Expression::Switch(value, cases, default) => Expression::Switch(
+            fold(value),
+            cases
+                .into_iter()
+                .map(|(case, body)| (case, fold_constants(body)))
+                .collect(),
+            Box::new((*default).map(fold_constants)),
+        ),
+        Expression::Format(template, args) => Expression::Format(template, fold_all(args)),
+        Expression::Cast(cast_type, value) => Expression::Cast(cast_type, fold(value)),
+        Expression::Is(value, is_type) => Expression::Is(fold(value), is_type),
+        // the sizes of the scalars are the same on every target, the size of a pointer or a
+        // struct's layout depends on the target so those are left for codegen
+        Expression::SizeOf(size_type) => match size_type {
+            Type::i32 => Expression::Number64(4),
+            Type::i64 | Type::Float => Expression::Number64(8),
+            Type::Bool => Expression::Number64(1),
+            size_type => Expression::SizeOf(size_type),
+        },
+        Expression::Number(_)
+        | Expression::Number64(_)
+        | Expression::Float(_)
+        | Expression::String(_)
+        | Expression::Bool(_)
+        | Expression::Nil
+        | Expression::StructDef(_, _)
+        | Expression::Variable(_)
+        | Expression::FuncArg(_, _, _)
+        | Expression::ExternFunc(_, _, _)
+        | Expression::BreakStmt
+        | Expression::Wildcard
+        | Expression::ReadLine
+        | Expression::ReadInt
+        | Expression::TypeAlias(_, _)
+        | Expression::Import(_) => expr,
+    }
+}
+
+// an i32 and an i64 operand are widened to i64, like the builder does
+fn fold_binary(lhs: Expression, op: String, rhs: Expression) -> Expression {
+    let folded = match (&lhs, &rhs) {
+        // the exact result always fits in an i64, like a literal it's an i64 when it's too
+        // big for an i32
+        (Expression::Number(a), Expression::Number(b)) => {
+            fold_int(*a as i64, &op, *b as i64).map(|n| match i32::try_from(n) {
+                Ok(n) => Expression::Number(n),
+                Err(_) => Expression::Number64(n),
+            })
+        }
+        (Expression::Number64(a), Expression::Number(b)) => {
+            fold_int(*a, &op, *b as i64).map(Expression::Number64)
+        }
+        (Expression::Number(a), Expression::Number64(b)) => {
+            fold_int(*a as i64, &op, *b).map(Expression::Number64)
+        }
+        (Expression::Number64(a), Expression::Number64(b)) => {
+            fold_int(*a, &op, *b).map(Expression::Number64)
+        }
+        _ => None,
+    };
+    folded.unwrap_or_else(|| Expression::Binary(Box::new(lhs), op, Box::new(rhs)))
+}
+
+// `None` when the operator isn't arithmetic or the division would trap at runtime
+fn fold_int(a: i64, op: &str, b: i64) -> Option<i64> {
+    match op {
+        "+" => Some(a.wrapping_add(b)),
+        "-" => Some(a.wrapping_sub(b)),
+        "*" => Some(a.wrapping_mul(b)),
+        "/" => a.checked_div(b),
+        _ => None,
+    }
+}
+
+/// Drops the statements after the first `return` in a block, they'd be built after the block's
+/// terminator. The type checker has a `CycloWarning::UnreachableCode` for each one dropped
+pub fn prune_dead_code(mut stmts: Vec<Expression>) -> Vec<Expression> {
+    if let Some(return_index) = stmts
+        .iter()
+        .position(|stmt| matches!(stmt, Expression::ReturnStmt(_)))
+    {
+        stmts.truncate(return_index + 1);
+    }
+    stmts
+}
@@ -0,0 +1,622 @@
+use crate::compiler::cache::pick_overload;
+use crate::compiler::error::{CycloError, CycloWarning};
+use crate::compiler::type_inferencer::TypeInferencer;
+use crate::compiler::types::{get_base_type, BaseTypes};
+use cyclang_parser::{Expression, Span, Type};
+use std::collections::HashMap;
+
+/// The types of the variables in scope
+pub type TypeEnv = HashMap<String, Type>;
+
+// the argument types, return type and argument defaults of an overload of a function
+type Overload = (Vec<Type>, Type, Vec<Option<Expression>>);
+
+/// Walks the AST before codegen so type errors are reported together, before any LLVM IR is
+/// built. Types it can't infer are `Type::None`, which is treated as compatible with anything
+/// so the checker only rejects programs codegen would reject too.
+#[derive(Default)]
+pub struct TypeChecker {
+    // the overloads of the functions declared so far
+    functions: HashMap<String, Vec<Overload>>,
+    structs: HashMap<String, Vec<(String, Type)>>,
+    aliases: HashMap<String, Type>,
+    errors: Vec<CycloError>,
+    warnings: Vec<CycloWarning>,
+    // where the statement being checked starts
+    span: Option<Span>,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks every top level statement in order, returning all the errors found
+    pub fn check_program(&mut self, exprs: &[(Expression, Option<Span>)]) -> Vec<CycloError> {
+        let mut env = TypeEnv::new();
+        self.declare_functions(exprs.iter().map(|(expr, _)| expr));
+        for (expr, span) in exprs {
+            self.span = *span;
+            self.check_statement(expr, &mut env);
+        }
+        std::mem::take(&mut self.errors)
+    }
+
+    /// The warnings found by `check_program`, like statements after a `return`
+    pub fn warnings(&mut self) -> Vec<CycloWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Infers the type of an expression, checking its children against the types they're
+    /// used as
+    pub fn check(&mut self, expr: &Expression, env: &TypeEnv) -> Result<Type, CycloError> {
+        match expr {
+            Expression::Number(_) => Ok(Type::i32),
+            Expression::Number64(_) => Ok(Type::i64),
+            Expression::Float(_) => Ok(Type::Float),
+            Expression::String(_) => Ok(Type::String),
+            Expression::Bool(_) => Ok(Type::Bool),
+            Expression::Nil | Expression::Wildcard | Expression::BreakStmt => Ok(Type::None),
+            Expression::Import(_) | Expression::FuncArg(_, _, _) => Ok(Type::None),
+            Expression::ReadLine => Ok(Type::String),
+            Expression::ReadInt => Ok(Type::i32),
+            Expression::SizeOf(_) => Ok(Type::i64),
+            Expression::Cast(cast_type, value) => {
+                let value_type = self.check(value, env)?;
+                let cast_type = self.resolve_type(cast_type);
+                // a string is a pointer to its buffer, an integer can't be turned into one
+                let is_integer = |t: &Type| matches!(t, Type::i32 | Type::i64);
+                if (value_type == Type::String && is_integer(&cast_type))
+                    || (is_integer(&value_type) && cast_type == Type::String)
+                {
+                    if let (Some(from), Some(to)) =
+                        (get_base_type(&value_type), get_base_type(&cast_type))
+                    {
+                        return Err(CycloError::InvalidCast {
+                            from,
+                            to,
+                            span: self.span,
+                        });
+                    }
+                }
+                Ok(cast_type)
+            }
+            Expression::Is(value, _) => {
+                self.check(value, env)?;
+                Ok(Type::Bool)
+            }
+            Expression::Variable(name) => {
+                env.get(name).cloned().ok_or(CycloError::UndefinedVariable {
+                    name: name.clone(),
+                    span: self.span,
+                })
+            }
+            Expression::List(items) => {
+                let mut item_type = Type::None;
+                for item in items {
+                    // like codegen, integers are widened to i64 when any element is an i64
+                    match (&item_type, self.check(item, env)?) {
+                        (Type::i64, Type::i32) => {}
+                        (_, next_type) => item_type = next_type,
+                    }
+                }
+                Ok(Type::List(Box::new(item_type)))
+            }
+            Expression::Tuple(items) => Ok(Type::Tuple(
+                items
+                    .iter()
+                    .map(|item| self.check(item, env))
+                    .collect::<Result<Vec<Type>, CycloError>>()?,
+            )),
+            Expression::ListIndex(list, index) => {
+                let list_type = self.check(list, env)?;
+                self.check(index, env)?;
+                match list_type {
+                    Type::List(inner) => Ok(*inner),
+                    _ => Ok(Type::None),
+                }
+            }
+            Expression::Slice(list, start, end) => {
+                self.check(start, env)?;
+                self.check(end, env)?;
+                self.check(list, env)
+            }
+            Expression::ListAssign(_, index, value) => {
+                self.check(index, env)?;
+                self.check(value, env)
+            }
+            Expression::StructDef(name, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, field_type)| (field.clone(), self.resolve_type(field_type)))
+                    .collect();
+                self.structs.insert(name.clone(), fields);
+                Ok(Type::None)
+            }
+            Expression::StructInit(name, fields) => {
+                for (_, value) in fields {
+                    self.check(value, env)?;
+                }
+                Ok(Type::Struct(name.clone()))
+            }
+            Expression::FieldAccess(value, field) => match self.check(value, env)? {
+                Type::Struct(name) => Ok(self
+                    .structs
+                    .get(&name)
+                    .and_then(|fields| fields.iter().find(|(name, _)| name == field))
+                    .map(|(_, field_type)| field_type.clone())
+                    .unwrap_or(Type::None)),
+                _ => Ok(Type::None),
+            },
+            Expression::MethodCall(value, method, args) => {
+                let value_type = self.check(value, env)?;
+                for arg in args {
+                    self.check(arg, env)?;
+                }
+                match (value_type, method.as_str()) {
+                    (Type::String, "chars" | "split") => Ok(Type::List(Box::new(Type::String))),
+                    (Type::String, "to_upper" | "to_lower") => Ok(Type::String),
+                    (Type::String, "trim" | "trim_start" | "trim_end") => Ok(Type::String),
+                    (Type::List(_), "contains") => Ok(Type::Bool),
+                    _ => Ok(Type::None),
+                }
+            }
+            Expression::Binary(lhs, op, rhs) => self.check_binary(expr, lhs, op, rhs, env),
+            Expression::Unary(_, value) | Expression::Grouping(value) => self.check(value, env),
+            Expression::LetStmt(name, var_type, value)
+            | Expression::GlobalStmt(name, var_type, value) => {
+                let value_type = self.check_assigned_value(value, env)?;
+                let var_type = self.resolve_type(var_type);
+                TypeInferencer::new(env, self.span).infer_let(name, &var_type, value, value_type)
+            }
+            Expression::AssignStmt(name, value) => {
+                let value_type = self.check_assigned_value(value, env)?;
+                TypeInferencer::new(env, self.span).infer_assign(name, value_type)
+            }
+            Expression::LetTupleStmt(_, value) => self.check(value, env),
+            Expression::BlockStmt(exprs, spans) => {
+                let mut env = env.clone();
+                // functions declared in the block are only in scope until it ends
+                let functions = self.functions.clone();
+                self.declare_functions(exprs);
+                // a block built without spans reports errors at the statement it's in
+                let outer_span = self.span;
+                // the value of a block is the value of its last statement
+                let mut block_type = Type::None;
+                for (i, expr) in exprs.iter().enumerate() {
+                    self.span = spans.get(i).or(outer_span);
+                    block_type = self.check_statement(expr, &mut env);
+                    // statements after a return are dropped by prune_dead_code before codegen
+                    if let Expression::ReturnStmt(_) = expr {
+                        for (j, stmt) in exprs.iter().enumerate().skip(i + 1) {
+                            self.warnings.push(CycloWarning::UnreachableCode {
+                                stmt: stmt.to_string(),
+                                span: spans.get(j).or(outer_span),
+                            });
+                        }
+                        break;
+                    }
+                }
+                self.span = outer_span;
+                self.functions = functions;
+                Ok(block_type)
+            }
+            Expression::FuncStmt(name, args, return_type, body, _) => {
+                // registered before the body is checked so the function can call itself
+                self.declare_function(name, args, return_type);
+                let mut env = env.clone();
+                // a default is checked where it's defined, it only sees the arguments before it
+                let mut earlier_args = TypeEnv::new();
+                for arg in args {
+                    if let Expression::FuncArg(arg_name, arg_type, default) = arg {
+                        let arg_type = self.resolve_type(arg_type);
+                        if let Some(default) = default {
+                            let default_type = self.check(default, &earlier_args)?;
+                            TypeInferencer::new(&earlier_args, self.span).infer_let(
+                                arg_name,
+                                &arg_type,
+                                default,
+                                default_type,
+                            )?;
+                        }
+                        earlier_args.insert(arg_name.clone(), arg_type.clone());
+                        env.insert(arg_name.clone(), arg_type);
+                    }
+                }
+                self.check(body, &env)?;
+                Ok(Type::None)
+            }
+            Expression::ExternFunc(name, args, return_type) => {
+                let arg_types = args
+                    .iter()
+                    .map(|(_, arg_type)| self.resolve_type(arg_type))
+                    .collect();
+                let return_type = self.resolve_type(return_type);
+                let overloads = self.functions.entry(name.clone()).or_default();
+                overloads.retain(|(existing, _, _)| *existing != arg_types);
+                overloads.push((arg_types, return_type, vec![]));
+                Ok(Type::None)
+            }
+            Expression::CallStmt(name, args) => {
+                if let Some(overloads) = self.functions.get(name).cloned() {
+                    let mut arg_types = vec![];
+                    for arg in args {
+                        arg_types.push(self.check(arg, env)?);
+                    }
+                    // like codegen, the defaults of a function with one overload fill in the
+                    // trailing arguments a call leaves out, they were checked against their
+                    // argument types where the function is defined
+                    if let [(param_types, _, defaults)] = overloads.as_slice() {
+                        let default_types =
+                            param_types.iter().zip(defaults).skip(args.len()).map_while(
+                                |(param_type, default)| default.as_ref().map(|_| param_type),
+                            );
+                        arg_types.extend(default_types.cloned());
+                    }
+                    return self.resolve_overload(name, &overloads, &arg_types);
+                }
+                match Expression::from_builtin_call(name, args) {
+                    Some(builtin) => self.check(&builtin, env),
+                    None => Err(CycloError::UndefinedFunction {
+                        name: name.clone(),
+                        span: self.span,
+                    }),
+                }
+            }
+            Expression::IfStmt(cond, then_block, else_block) => {
+                self.check(cond, env)?;
+                self.check(then_block, env)?;
+                if let Some(else_block) = &**else_block {
+                    self.check(else_block, env)?;
+                }
+                Ok(Type::None)
+            }
+            Expression::WhileStmt(cond, body)
+            | Expression::DoWhileStmt(body, cond)
+            | Expression::RepeatUntilStmt(body, cond) => {
+                self.check(body, env)?;
+                self.check(cond, env)?;
+                Ok(Type::None)
+            }
+            Expression::LoopStmt(body) => {
+                self.check(body, env)?;
+                Ok(Type::None)
+            }
+            Expression::ReturnStmt(value) => self.check(value, env),
+            Expression::ForStmt(name, _, _, _, body)
+            | Expression::ReversedForStmt(name, _, _, body) => {
+                let mut env = env.clone();
+                env.insert(name.clone(), Type::i32);
+                self.check(body, &env)?;
+                Ok(Type::None)
+            }
+            Expression::ForEachStmt(name, list, _, body) => {
+                let item_type = match self.check(list, env)? {
+                    Type::List(inner) => *inner,
+                    _ => Type::None,
+                };
+                let mut env = env.clone();
+                env.insert(name.clone(), item_type);
+                self.check(body, &env)?;
+                Ok(Type::None)
+            }
+            Expression::Print(_)
+            | Expression::Len(_)
+            | Expression::Sum(_)
+            | Expression::Avg(_)
+            | Expression::Windows(_, _)
+            | Expression::Clamp(_, _, _)
+            | Expression::Lerp(_, _, _)
+            | Expression::Exit(_)
+            | Expression::Assert(_) => match expr.as_builtin_call() {
+                // a user function shadows the builtin with the same name
+                Some((name, args)) if self.functions.contains_key(name) => {
+                    self.check(&Expression::CallStmt(name.to_string(), args), env)
+                }
+                _ => self.check_builtin(expr, env),
+            },
+            Expression::Builtin(builtin) => self.check_builtin(builtin, env),
+            Expression::Fold(list, init, _) => {
+                self.check(list, env)?;
+                self.check(init, env)
+            }
+            Expression::Match(value, arms) => {
+                self.check(value, env)?;
+                for (_, arm) in arms {
+                    self.check(arm, env)?;
+                }
+                Ok(Type::None)
+            }
+            Expression::Switch(value, cases, default) => {
+                self.check(value, env)?;
+                for (_, case) in cases {
+                    self.check(case, env)?;
+                }
+                if let Some(default) = default.as_ref() {
+                    self.check(default, env)?;
+                }
+                Ok(Type::None)
+            }
+            Expression::Format(_, args) => {
+                for arg in args {
+                    self.check(arg, env)?;
+                }
+                Ok(Type::String)
+            }
+            Expression::TypeAlias(name, alias_type) => {
+                let alias_type = self.resolve_type(alias_type);
+                self.aliases.insert(name.clone(), alias_type);
+                Ok(Type::None)
+            }
+        }
+    }
+
+    // checks a statement in a block, recording its error rather than stopping so every
+    // statement is checked, and adds any variables it declares to the block's scope. Returns
+    // the statement's type, a return leaves the block so it doesn't give the block a value
+    fn check_statement(&mut self, expr: &Expression, env: &mut TypeEnv) -> Type {
+        match self.check(expr, env) {
+            Ok(value_type) => match expr {
+                // a let shadows a variable with the same name until the end of the block
+                Expression::LetStmt(name, _, _) | Expression::GlobalStmt(name, _, _) => {
+                    env.insert(name.clone(), value_type.clone());
+                    value_type
+                }
+                Expression::AssignStmt(name, _) if !env.contains_key(name) => {
+                    env.insert(name.clone(), value_type.clone());
+                    value_type
+                }
+                Expression::LetTupleStmt(names, _) => {
+                    let component_types = match value_type {
+                        Type::Tuple(component_types) if component_types.len() == names.len() => {
+                            component_types
+                        }
+                        _ => vec![Type::None; names.len()],
+                    };
+                    for (name, component_type) in names.iter().zip(component_types.iter()) {
+                        env.entry(name.clone()).or_insert(component_type.clone());
+                    }
+                    Type::Tuple(component_types)
+                }
+                Expression::ReturnStmt(_) => Type::None,
+                _ => value_type,
+            },
+            Err(e) => {
+                self.errors.push(e);
+                // declare the variable anyway so its uses don't report it as undefined
+                match expr {
+                    Expression::LetStmt(name, _, _) | Expression::GlobalStmt(name, _, _) => {
+                        env.insert(name.clone(), Type::None);
+                    }
+                    Expression::AssignStmt(name, _) => {
+                        env.entry(name.clone()).or_insert(Type::None);
+                    }
+                    _ => {}
+                }
+                Type::None
+            }
+        }
+    }
+
+    // the value of a let or an assignment, which can be an if expression
+    fn check_assigned_value(
+        &mut self,
+        value: &Expression,
+        env: &TypeEnv,
+    ) -> Result<Type, CycloError> {
+        match value {
+            Expression::IfStmt(cond, then_block, else_block) => {
+                self.check_if_expr(cond, then_block, (**else_block).as_ref(), env)
+            }
+            _ => self.check(value, env),
+        }
+    }
+
+    // an if with an else used as a value, it has the type both its branches produce
+    fn check_if_expr(
+        &mut self,
+        cond: &Expression,
+        then_block: &Expression,
+        else_block: Option<&Expression>,
+        env: &TypeEnv,
+    ) -> Result<Type, CycloError> {
+        self.check(cond, env)?;
+        let then_type = self.check(then_block, env)?;
+        let else_type = match else_block {
+            Some(else_block) => self.check(else_block, env)?,
+            None => Type::None,
+        };
+        TypeInferencer::new(env, self.span).infer_if(then_type, else_type)
+    }
+
+    fn check_builtin(&mut self, expr: &Expression, env: &TypeEnv) -> Result<Type, CycloError> {
+        match expr {
+            Expression::Print(value) => self.check(value, env),
+            Expression::Len(value) => {
+                self.check(value, env)?;
+                Ok(Type::i32)
+            }
+            Expression::Sum(value) => {
+                self.check(value, env)?;
+                Ok(Type::i64)
+            }
+            Expression::Avg(value) => {
+                self.check(value, env)?;
+                Ok(Type::Float)
+            }
+            Expression::Windows(list, size) => {
+                let list_type = self.check(list, env)?;
+                self.check(size, env)?;
+                Ok(Type::List(Box::new(list_type)))
+            }
+            Expression::Clamp(a, b, c) | Expression::Lerp(a, b, c) => {
+                for value in [a, b, c] {
+                    self.check(value, env)?;
+                }
+                Ok(Type::Float)
+            }
+            Expression::Exit(code) | Expression::Assert(code) => {
+                self.check(code, env)?;
+                Ok(Type::None)
+            }
+            _ => self.check(expr, env),
+        }
+    }
+
+    fn check_binary(
+        &mut self,
+        expr: &Expression,
+        lhs: &Expression,
+        op: &str,
+        rhs: &Expression,
+        env: &TypeEnv,
+    ) -> Result<Type, CycloError> {
+        if op == "/" && matches!(rhs, Expression::Number(0) | Expression::Number64(0)) {
+            return Err(CycloError::DivisionByZero {
+                expr: expr.to_string(),
+                span: self.span,
+            });
+        }
+        let lhs_type = self.check(lhs, env)?;
+        let rhs_type = self.check(rhs, env)?;
+        if let (Some(expected), Some(found)) = (get_base_type(&lhs_type), get_base_type(&rhs_type))
+        {
+            let is_integer = |t: &Type| matches!(t, Type::i32 | Type::i64);
+            if expected != found && !(is_integer(&lhs_type) && is_integer(&rhs_type)) {
+                return Err(CycloError::OperandMismatch {
+                    op: op.to_string(),
+                    lhs: lhs.to_string(),
+                    rhs: rhs.to_string(),
+                    expected: Box::new(expected),
+                    found: Box::new(found),
+                    span: self.span,
+                });
+            }
+        }
+        match op {
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => Ok(Type::Bool),
+            _ if lhs_type == Type::i64 || rhs_type == Type::i64 => Ok(Type::i64),
+            _ => Ok(lhs_type),
+        }
+    }
+
+    // like codegen, the functions defined in a block can be called from anywhere in it
+    fn declare_functions<'a>(&mut self, exprs: impl IntoIterator<Item = &'a Expression>) {
+        for expr in exprs {
+            if let Expression::FuncStmt(name, args, return_type, _, _) = expr {
+                // an alias or struct may not be declared yet, so a function using one is only
+                // declared where it's defined
+                let uses_alias = args.iter().any(|arg| match arg {
+                    Expression::FuncArg(_, arg_type, _) => has_alias(arg_type),
+                    _ => false,
+                });
+                if !uses_alias && !has_alias(return_type) {
+                    self.declare_function(name, args, return_type);
+                }
+            }
+        }
+    }
+
+    fn declare_function(&mut self, name: &str, args: &[Expression], return_type: &Type) {
+        let return_type = self.resolve_type(return_type);
+        let arg_types = args
+            .iter()
+            .filter_map(|arg| match arg {
+                Expression::FuncArg(_, arg_type, _) => Some(self.resolve_type(arg_type)),
+                _ => None,
+            })
+            .collect();
+        let defaults = args
+            .iter()
+            .filter_map(|arg| match arg {
+                Expression::FuncArg(_, _, default) => Some(default.as_deref().cloned()),
+                _ => None,
+            })
+            .collect();
+        // a redeclared signature keeps its place, overloads are picked in declaration order
+        let overloads = self.functions.entry(name.to_string()).or_default();
+        match overloads
+            .iter_mut()
+            .find(|(existing, _, _)| *existing == arg_types)
+        {
+            Some(overload) => *overload = (arg_types, return_type, defaults),
+            None => overloads.push((arg_types, return_type, defaults)),
+        }
+    }
+
+    // the return type of the overload a call with `arg_types` picks, the same one
+    // `Overloads::resolve` picks in codegen
+    fn resolve_overload(
+        &self,
+        name: &str,
+        overloads: &[Overload],
+        arg_types: &[Type],
+    ) -> Result<Type, CycloError> {
+        if let [(params, return_type, _)] = overloads {
+            if params.len() != arg_types.len() {
+                return Err(CycloError::ArgumentCountMismatch {
+                    name: name.to_string(),
+                    expected: params.len(),
+                    found: arg_types.len(),
+                    span: self.span,
+                });
+            }
+            // an argument whose type couldn't be inferred is left to codegen
+            for (position, (param, arg)) in params.iter().zip(arg_types).enumerate() {
+                match (get_base_type(param), get_base_type(arg)) {
+                    (Some(BaseTypes::Number64), Some(BaseTypes::Number)) => {}
+                    (Some(expected), Some(found)) if expected != found => {
+                        return Err(CycloError::ArgumentTypeMismatch {
+                            name: name.to_string(),
+                            position: position + 1,
+                            expected,
+                            found,
+                            span: self.span,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(return_type.clone());
+        }
+        let arg_base_types: Vec<BaseTypes> =
+            match arg_types.iter().map(get_base_type).collect::<Option<_>>() {
+                Some(arg_base_types) => arg_base_types,
+                // an argument that couldn't be inferred could match any of them
+                None => return Ok(Type::None),
+            };
+        pick_overload(overloads, |(params, _, _)| params, &arg_base_types)
+            .map(|(_, return_type, _)| return_type.clone())
+            .ok_or(CycloError::NoMatchingOverload {
+                name: name.to_string(),
+                args: arg_base_types,
+                span: self.span,
+            })
+    }
+
+    // struct names are parsed as aliases, an alias that isn't known yet is left for codegen
+    // to report
+    fn resolve_type(&self, input_type: &Type) -> Type {
+        match input_type {
+            Type::Alias(name) if self.structs.contains_key(name) => Type::Struct(name.clone()),
+            Type::Alias(name) => self.aliases.get(name).cloned().unwrap_or(Type::None),
+            Type::List(inner) => Type::List(Box::new(self.resolve_type(inner))),
+            Type::Tuple(component_types) => Type::Tuple(
+                component_types
+                    .iter()
+                    .map(|component_type| self.resolve_type(component_type))
+                    .collect(),
+            ),
+            _ => input_type.clone(),
+        }
+    }
+}
+
+fn has_alias(value_type: &Type) -> bool {
+    match value_type {
+        Type::Alias(_) => true,
+        Type::List(inner) => has_alias(inner),
+        Type::Tuple(component_types) => component_types.iter().any(has_alias),
+        _ => false,
+    }
+}
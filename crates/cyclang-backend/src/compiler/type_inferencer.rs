@@ -0,0 +1,84 @@
+use crate::compiler::error::CycloError;
+use crate::compiler::type_checker::TypeEnv;
+use crate::compiler::types::get_base_type;
+use cyclang_parser::{Expression, Span, Type};
+
+/// Works out the type a `let` or an assignment gives its variable. A `let` without an
+/// annotation takes the type of its value, an assignment keeps the type the variable already
+/// has so later uses of the variable are checked against it
+pub struct TypeInferencer<'a> {
+    env: &'a TypeEnv,
+    span: Option<Span>,
+}
+
+impl<'a> TypeInferencer<'a> {
+    pub fn new(env: &'a TypeEnv, span: Option<Span>) -> Self {
+        Self { env, span }
+    }
+
+    /// The type of `name` after `let name: annotation = value`, `value_type` is the type
+    /// inferred for `value`
+    pub fn infer_let(
+        &self,
+        name: &str,
+        annotation: &Type,
+        value: &Expression,
+        value_type: Type,
+    ) -> Result<Type, CycloError> {
+        match annotation {
+            Type::None => Ok(value_type),
+            // an i32 literal annotated as i64 is built as an i64
+            Type::i64 if matches!(value, Expression::Number(_)) => Ok(Type::i64),
+            _ => self.expect_type(name, annotation.clone(), &value_type),
+        }
+    }
+
+    /// The type of `name` after `name = value`, assigning to a variable that doesn't exist
+    /// yet declares it
+    pub fn infer_assign(&self, name: &str, value_type: Type) -> Result<Type, CycloError> {
+        match self.env.get(name) {
+            Some(var_type) => self.expect_type(name, var_type.clone(), &value_type),
+            None => Ok(value_type),
+        }
+    }
+
+    fn expect_type(
+        &self,
+        name: &str,
+        var_type: Type,
+        value_type: &Type,
+    ) -> Result<Type, CycloError> {
+        if let (Some(expected), Some(found)) = (get_base_type(&var_type), get_base_type(value_type))
+        {
+            if expected != found {
+                return Err(CycloError::TypeMismatch {
+                    name: name.to_string(),
+                    expected,
+                    found,
+                    span: self.span,
+                });
+            }
+        }
+        Ok(var_type)
+    }
+
+    /// The type of `if (cond) { .. } else { .. }` used as a value, both branches have to
+    /// produce the same type
+    pub fn infer_if(&self, then_type: Type, else_type: Type) -> Result<Type, CycloError> {
+        if let (Some(expected), Some(found)) =
+            (get_base_type(&then_type), get_base_type(&else_type))
+        {
+            if expected != found {
+                return Err(CycloError::BranchTypeMismatch {
+                    expected,
+                    found,
+                    span: self.span,
+                });
+            }
+        }
+        match then_type {
+            Type::None => Ok(else_type),
+            _ => Ok(then_type),
+        }
+    }
+}
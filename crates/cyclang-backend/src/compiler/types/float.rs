@@ -0,0 +1,36 @@
+use crate::compiler::types::{BaseTypes, TypeBase};
+
+extern crate llvm_sys;
+use crate::compiler::codegen::builder::LLVMCodegenBuilder;
+use anyhow::anyhow;
+use anyhow::Result;
+use llvm_sys::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct FloatType {
+    pub llvm_value: LLVMValueRef,
+    pub llvm_value_pointer: Option<LLVMValueRef>,
+    pub name: String,
+}
+
+impl TypeBase for FloatType {
+    fn get_value(&self) -> LLVMValueRef {
+        self.llvm_value
+    }
+    fn get_ptr(&self) -> Option<LLVMValueRef> {
+        self.llvm_value_pointer
+    }
+    // printFloat formats with a fixed algorithm rather than printf so output is stable across libcs
+    fn print(&self, codegen: &mut LLVMCodegenBuilder) -> Result<()> {
+        let value = self.get_value_for_printf(codegen);
+        let print_float_func = codegen
+            .llvm_func_cache
+            .get("printFloat")
+            .ok_or(anyhow!("unable to find printFloat function"))?;
+        codegen.build_call(print_float_func, vec![value], 1, "");
+        Ok(())
+    }
+    fn get_type(&self) -> BaseTypes {
+        BaseTypes::Float
+    }
+}
@@ -1,6 +1,6 @@
 extern crate llvm_sys;
 use crate::compiler::types::{BaseTypes, TypeBase};
-use cyclang_parser::Type;
+use cyclang_parser::{Expression, Type};
 use llvm_sys::prelude::*;
 
 // FuncType -> Exposes the Call Func (i.e after function has been executed)
@@ -8,6 +8,11 @@ use llvm_sys::prelude::*;
 #[derive(Clone)]
 pub struct FuncType {
     pub return_type: Type,
+    // the name and default value of each argument, a call can leave out trailing arguments
+    // with one
+    pub default_args: Vec<(String, Option<Expression>)>,
+    // an `extern fn` is passed the buffer of a string argument rather than the string
+    pub is_extern: bool,
     pub llvm_type: LLVMTypeRef,
     pub llvm_func: LLVMValueRef,
 }
@@ -27,4 +32,12 @@ impl TypeBase for FuncType {
     fn get_return_type(&self) -> Type {
         self.return_type.clone()
     }
+
+    fn get_default_args(&self) -> Vec<(String, Option<Expression>)> {
+        self.default_args.clone()
+    }
+
+    fn is_extern(&self) -> bool {
+        self.is_extern
+    }
 }
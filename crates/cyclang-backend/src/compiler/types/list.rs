@@ -61,6 +61,8 @@ fn get_c_print_fn_name(base_type: BaseTypes) -> &'static str {
     match base_type {
         BaseTypes::String => "printStringList",
         BaseTypes::Number => "printInt32List",
+        BaseTypes::Number64 => "printInt64List",
+        BaseTypes::List(inner_type) if *inner_type == BaseTypes::Number => "printInt32ListList",
         _ => {
             unreachable!("No print function set up for type {:?}", base_type)
         }
@@ -71,6 +73,8 @@ fn get_c_len_fn_name(base_type: BaseTypes) -> &'static str {
     match base_type {
         BaseTypes::String => "lenStringList",
         BaseTypes::Number => "lenInt32List",
+        BaseTypes::Number64 => "lenInt64List",
+        BaseTypes::List(inner_type) if *inner_type == BaseTypes::Number => "lenInt32ListList",
         _ => {
             unreachable!("No print function set up for type {:?}", base_type)
         }
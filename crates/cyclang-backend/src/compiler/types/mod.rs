@@ -3,16 +3,20 @@
 //TODO: address these lints
 
 pub mod bool;
+pub mod float;
 pub mod func;
 pub mod list;
 pub mod num;
 pub mod num64;
 pub mod return_type;
 pub mod string;
+pub mod struct_type;
+pub mod tuple;
 pub mod void;
 
 use llvm_sys::core::LLVMGetValueName;
 use std::ffi::CStr;
+use std::fmt;
 
 use dyn_clone::DynClone;
 extern crate libc;
@@ -21,11 +25,12 @@ use libc::c_char;
 extern crate llvm_sys;
 use crate::compiler::codegen::builder::LLVMCodegenBuilder;
 use crate::compiler::codegen::{
-    int1_ptr_type, int1_type, int32_ptr_type, int32_type, int64_ptr_type, int64_type, int8_ptr_type,
+    float_ptr_type, float_type, int1_ptr_type, int1_type, int32_ptr_type, int32_type,
+    int64_ptr_type, int64_type, int8_ptr_type,
 };
 use anyhow::anyhow;
 use anyhow::Result;
-use cyclang_parser::Type;
+use cyclang_parser::{Expression, Type};
 use llvm_sys::prelude::*;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -33,13 +38,42 @@ pub enum BaseTypes {
     String,
     Number,
     Number64,
+    Float,
     Bool,
     List(Box<BaseTypes>),
+    Tuple(Vec<BaseTypes>),
+    Struct(String),
     Func,
     Void,
     Return,
 }
 
+impl fmt::Display for BaseTypes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaseTypes::String => write!(f, "string"),
+            BaseTypes::Number => write!(f, "i32"),
+            BaseTypes::Number64 => write!(f, "i64"),
+            BaseTypes::Float => write!(f, "float"),
+            BaseTypes::Bool => write!(f, "bool"),
+            BaseTypes::List(inner) => write!(f, "List<{}>", inner),
+            BaseTypes::Tuple(components) => write!(
+                f,
+                "({})",
+                components
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            BaseTypes::Struct(name) => write!(f, "{}", name),
+            BaseTypes::Func => write!(f, "fn"),
+            BaseTypes::Void => write!(f, "void"),
+            BaseTypes::Return => write!(f, "return"),
+        }
+    }
+}
+
 pub trait TypeBase: DynClone {
     fn get_name(&self) -> *const c_char {
         unsafe { LLVMGetValueName(self.get_value()) }
@@ -69,9 +103,10 @@ pub trait TypeBase: DynClone {
     }
 
     fn print(&self, codegen: &mut LLVMCodegenBuilder) -> Result<()> {
+        let value = self.get_value_for_printf(codegen);
         let print_args: Vec<LLVMValueRef> = vec![
-            codegen.get_printf_str(self.get_type()),
-            self.get_value_for_printf(codegen),
+            codegen.get_printf_str_for_value(self.get_type(), value),
+            value,
         ];
         let print_func = codegen
             .llvm_func_cache
@@ -92,6 +127,7 @@ pub trait TypeBase: DynClone {
             BaseTypes::Bool => int1_type(),
             BaseTypes::Number => int32_type(),
             BaseTypes::Number64 => int64_type(),
+            BaseTypes::Float => float_type(),
             _ => {
                 unreachable!("LLVMType for Type {:?} not found", self.get_type())
             }
@@ -103,6 +139,7 @@ pub trait TypeBase: DynClone {
             BaseTypes::Bool => int1_ptr_type(),
             BaseTypes::Number => int32_ptr_type(),
             BaseTypes::Number64 => int64_ptr_type(),
+            BaseTypes::Float => float_ptr_type(),
             _ => {
                 unreachable!("LLVMType for Type {:?} not found", self.get_type())
             }
@@ -111,6 +148,40 @@ pub trait TypeBase: DynClone {
     fn get_return_type(&self) -> Type {
         unimplemented!()
     }
+    fn get_default_args(&self) -> Vec<(String, Option<Expression>)> {
+        vec![]
+    }
+    fn is_extern(&self) -> bool {
+        false
+    }
 }
 
 dyn_clone::clone_trait_object!(TypeBase);
+
+// the type a value of a scalar type annotation is built as, used for the fields of
+// tuples and structs which are stored by value
+pub fn get_scalar_base_type(value_type: &Type) -> Option<BaseTypes> {
+    match value_type {
+        Type::i32 => Some(BaseTypes::Number),
+        Type::i64 => Some(BaseTypes::Number64),
+        Type::Float => Some(BaseTypes::Float),
+        Type::Bool => Some(BaseTypes::Bool),
+        Type::String => Some(BaseTypes::String),
+        _ => None,
+    }
+}
+
+// the type a value must have to match a type annotation, `None` for an unannotated value
+pub fn get_base_type(value_type: &Type) -> Option<BaseTypes> {
+    match value_type {
+        Type::List(inner) => Some(BaseTypes::List(Box::new(get_base_type(inner)?))),
+        Type::Tuple(component_types) => Some(BaseTypes::Tuple(
+            component_types
+                .iter()
+                .map(get_base_type)
+                .collect::<Option<Vec<BaseTypes>>>()?,
+        )),
+        Type::Struct(name) => Some(BaseTypes::Struct(name.clone())),
+        _ => get_scalar_base_type(value_type),
+    }
+}
@@ -1,9 +1,11 @@
 use crate::compiler::types::{BaseTypes, TypeBase};
 
 extern crate llvm_sys;
+use anyhow::anyhow;
 use anyhow::Result;
 
 use crate::compiler::codegen::builder::LLVMCodegenBuilder;
+use crate::compiler::codegen::int32_type;
 use llvm_sys::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -12,6 +14,89 @@ pub struct StringType {
     pub llvm_value: LLVMValueRef,
     pub llvm_value_pointer: Option<LLVMValueRef>,
 }
+
+// StringType values are always a pointer to the stdlib `struct.StringType`, all
+// construction, concatenation and printing goes through the stringInit, stringAdd
+// and stringPrint runtime helpers registered in load_string_helper_funcs. The runtime
+// helpers take a pointer and a length rather than a null terminated string, so strings
+// can contain any UTF-8 including null bytes
+impl StringType {
+    // stringInit copies the literal into its own buffer, so the constant global can be shared
+    pub fn init(codegen: &mut LLVMCodegenBuilder, name: &str, value: &[u8]) -> Result<Self> {
+        let string_init_func = codegen
+            .llvm_func_cache
+            .get("stringInit")
+            .ok_or(anyhow!("unable to find stringInit function"))?;
+        let string_ptr = codegen.build_global_string(value);
+        let length = codegen.const_int(int32_type(), value.len() as u64, 0);
+        let return_value = codegen.build_call(
+            string_init_func,
+            vec![string_ptr, length],
+            2,
+            "stringInitExample",
+        );
+        Ok(StringType {
+            name: name.to_string(),
+            llvm_value: return_value,
+            llvm_value_pointer: Some(return_value),
+        })
+    }
+
+    // stringAdd appends rhs onto the lhs buffer, so the result is lhs itself
+    pub fn add(
+        codegen: &LLVMCodegenBuilder,
+        lhs: Box<dyn TypeBase>,
+        rhs: Box<dyn TypeBase>,
+    ) -> Result<Box<dyn TypeBase>> {
+        if lhs.get_type() != BaseTypes::String || rhs.get_type() != BaseTypes::String {
+            return Err(anyhow!(
+                "unable to add type {} to type {}",
+                rhs.get_type(),
+                lhs.get_type()
+            ));
+        }
+        let string_add_func = codegen
+            .llvm_func_cache
+            .get("stringAdd")
+            .ok_or(anyhow!("unable to find stringAdd function"))?;
+        let args = vec![
+            lhs.get_value(),
+            codegen.load_string_buffer(rhs.as_ref())?,
+            codegen.load_string_length(rhs.as_ref())?,
+        ];
+        codegen.build_call(string_add_func, args, 3, "");
+        Ok(lhs)
+    }
+
+    // reassignment empties lhs and appends rhs to it with stringAdd, so lhs keeps its own
+    // buffer and reuses it when rhs fits, stringAdd frees it when it has to grow. lhs never
+    // shares a buffer with rhs that a later stringAdd could resize out from under it
+    pub fn assign(
+        codegen: &mut LLVMCodegenBuilder,
+        lhs: Box<dyn TypeBase>,
+        rhs: Box<dyn TypeBase>,
+    ) -> Result<Box<dyn TypeBase>> {
+        let lhs_ptr = lhs.get_ptr().ok_or(anyhow!(
+            "unable to get pointer for string {}",
+            lhs.get_name_as_str()
+        ))?;
+        let string_add_func = codegen
+            .llvm_func_cache
+            .get("stringAdd")
+            .ok_or(anyhow!("unable to find stringAdd function"))?;
+        // rhs is read before lhs is emptied, stringAdd uses memmove so rhs can be lhs itself
+        let args = vec![
+            lhs_ptr,
+            codegen.load_string_buffer(rhs.as_ref())?,
+            codegen.load_string_length(rhs.as_ref())?,
+        ];
+        let zero = codegen.const_int(int32_type(), 0, 0);
+        codegen.store_string_length(lhs.as_ref(), zero)?;
+        codegen.build_call(string_add_func, args, 3, "");
+        Ok(lhs)
+    }
+}
+
 impl TypeBase for StringType {
     fn get_value(&self) -> LLVMValueRef {
         self.llvm_value
@@ -25,7 +110,10 @@ impl TypeBase for StringType {
         }
     }
     fn print(&self, codegen: &mut LLVMCodegenBuilder) -> Result<()> {
-        let string_print_func = codegen.llvm_func_cache.get("stringPrint").unwrap();
+        let string_print_func = codegen
+            .llvm_func_cache
+            .get("stringPrint")
+            .ok_or(anyhow!("unable to find stringPrint function"))?;
         let lhs_value = self.get_value();
         let args = vec![lhs_value];
         codegen.build_call(string_print_func, args, 1, "");
@@ -0,0 +1,45 @@
+extern crate llvm_sys;
+use crate::compiler::types::{BaseTypes, TypeBase};
+use llvm_sys::prelude::*;
+
+// StructLayout -> the fields of a `struct` definition in declaration order, the index
+// of a field is its index in the LLVM struct
+#[derive(Debug, Clone)]
+pub struct StructLayout {
+    pub name: String,
+    pub fields: Vec<(String, BaseTypes)>,
+    pub llvm_type: LLVMTypeRef,
+}
+
+impl StructLayout {
+    pub fn get_field(&self, field: &str) -> Option<(usize, &BaseTypes)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, (name, _))| name == field)
+            .map(|(i, (_, field_type))| (i, field_type))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructType {
+    pub name: String,
+    pub llvm_value: LLVMValueRef,
+    pub llvm_value_pointer: LLVMValueRef,
+    pub llvm_type: LLVMTypeRef,
+}
+
+impl TypeBase for StructType {
+    fn get_value(&self) -> LLVMValueRef {
+        self.llvm_value
+    }
+    fn get_ptr(&self) -> Option<LLVMValueRef> {
+        Some(self.llvm_value_pointer)
+    }
+    fn get_type(&self) -> BaseTypes {
+        BaseTypes::Struct(self.name.clone())
+    }
+    fn get_llvm_type(&self) -> LLVMTypeRef {
+        self.llvm_type
+    }
+}
@@ -0,0 +1,61 @@
+extern crate llvm_sys;
+use crate::compiler::types::{get_base_type, BaseTypes, TypeBase};
+use anyhow::anyhow;
+use anyhow::Result;
+use cyclang_parser::Type;
+use llvm_sys::prelude::*;
+
+// TupleType -> an anonymous LLVM struct with a field per component, the components
+// are kept alongside so they can be read back without extracting them from the struct
+#[derive(Clone)]
+pub struct TupleType {
+    pub llvm_value: LLVMValueRef,
+    pub llvm_value_pointer: LLVMValueRef,
+    pub llvm_type: LLVMTypeRef,
+    pub components: Vec<Box<dyn TypeBase>>,
+}
+
+impl TypeBase for TupleType {
+    fn get_value(&self) -> LLVMValueRef {
+        self.llvm_value
+    }
+    fn get_ptr(&self) -> Option<LLVMValueRef> {
+        Some(self.llvm_value_pointer)
+    }
+    fn get_type(&self) -> BaseTypes {
+        BaseTypes::Tuple(self.components.iter().map(|c| c.get_type()).collect())
+    }
+    fn get_llvm_type(&self) -> LLVMTypeRef {
+        self.llvm_type
+    }
+}
+
+// the scalars a tuple can hold, both for a tuple built from values and one named in a type
+pub fn check_component_type(component_type: &BaseTypes) -> Result<()> {
+    match component_type {
+        BaseTypes::Number
+        | BaseTypes::Number64
+        | BaseTypes::Float
+        | BaseTypes::Bool
+        | BaseTypes::String => Ok(()),
+        _ => Err(anyhow!(
+            "type {} can't be used as a tuple component",
+            component_type
+        )),
+    }
+}
+
+// maps a tuple type annotation, i.e. a function return type, to the types its components are built as
+pub fn get_component_types(component_types: &[Type]) -> Result<Vec<BaseTypes>> {
+    component_types
+        .iter()
+        .map(|component_type| {
+            let base_type = get_base_type(component_type).ok_or(anyhow!(
+                "type {} can't be used as a tuple component",
+                component_type
+            ))?;
+            check_component_type(&base_type)?;
+            Ok(base_type)
+        })
+        .collect()
+}
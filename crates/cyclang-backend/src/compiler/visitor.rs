@@ -7,7 +7,11 @@ use cyclang_parser::Expression;
 pub trait Visitor<T> {
     fn visit_number(&mut self, expression: &Expression, codegen: &LLVMCodegenBuilder) -> Result<T>;
 
-    fn visit_string(&mut self, expression: &Expression, codegen: &LLVMCodegenBuilder) -> Result<T>;
+    fn visit_string(
+        &mut self,
+        expression: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+    ) -> Result<T>;
 
     fn visit_bool(&mut self, expression: &Expression, codegen: &LLVMCodegenBuilder) -> Result<T>;
 
@@ -25,6 +29,41 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_tuple_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_struct_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_struct_init_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_field_access_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_method_call_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_list_index_expr(
         &mut self,
         left: &Expression,
@@ -32,6 +71,13 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_slice_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_list_assign_expr(
         &mut self,
         left: &Expression,
@@ -48,6 +94,13 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_unary_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_grouping_stmt(
         &mut self,
         left: Expression,
@@ -62,6 +115,20 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_assign_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_let_tuple_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_block_stmt(
         &mut self,
         left: &Expression,
@@ -83,6 +150,41 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_extern_func(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_global_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_sizeof_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_cast_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_is_expr(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_if_stmt(
         &mut self,
         left: &Expression,
@@ -97,6 +199,29 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_do_while_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_repeat_until_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_loop_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_break_stmt(&mut self, codegen: &mut LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>>;
+
     fn visit_for_loop_stmt(
         &mut self,
         left: &Expression,
@@ -104,6 +229,20 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_reversed_for_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_for_each_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_print_stmt(
         &mut self,
         left: &Expression,
@@ -118,6 +257,87 @@ pub trait Visitor<T> {
         context: &mut ASTContext,
     ) -> Result<Box<dyn TypeBase>>;
 
+    fn visit_sum_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_avg_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_fold_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_windows_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_clamp_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_lerp_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_exit_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_assert_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_match_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_switch_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_read_line_stmt(&mut self, codegen: &LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_read_int_stmt(&mut self, codegen: &LLVMCodegenBuilder) -> Result<Box<dyn TypeBase>>;
+
+    fn visit_format_stmt(
+        &mut self,
+        left: &Expression,
+        codegen: &mut LLVMCodegenBuilder,
+        context: &mut ASTContext,
+    ) -> Result<Box<dyn TypeBase>>;
+
     fn visit_return_stmt(
         &mut self,
         left: &Expression,
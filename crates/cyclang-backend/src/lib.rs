@@ -1 +1,3 @@
 pub mod compiler;
+
+pub use compiler::{compile_string, CompileOptions, ExecutionMode};
@@ -0,0 +1,1136 @@
+//! A tree-walking interpreter, it evaluates `Expression`s directly in Rust without building
+//! any LLVM IR so cyclang scripts can run where LLVM isn't available. Values behave like they
+//! do in the compiled program, an i32 wraps at 32 bits and an i64 at 64
+use anyhow::{anyhow, Result};
+use cyclang_parser::{unescape, Expression, Type};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+/// The result of evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Int64(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+    Struct(String, Vec<(String, Value)>),
+    Nil,
+    /// The value of a statement, such as a loop or a `let`, that doesn't produce one
+    Void,
+}
+
+impl fmt::Display for Value {
+    // matches what the compiled program prints for the same value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Int64(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) => write!(f, "{}", s),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match item {
+                        Value::String(s) => write!(f, "\"{}\"", s)?,
+                        item => write!(f, "{}", item)?,
+                    }
+                }
+                write!(f, "]")
+            }
+            Value::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
+            Value::Struct(name, fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            Value::Nil => write!(f, "nil"),
+            Value::Void => Ok(()),
+        }
+    }
+}
+
+/// Evaluates `expr` and returns its value, anything it prints is written to stdout
+pub fn interpret(expr: &Expression) -> Result<Value> {
+    let mut interpreter = Interpreter::new();
+    let value = interpreter.eval(expr);
+    print!("{}", interpreter.output);
+    value
+}
+
+/// Evaluates each statement of a program in order and returns what it printed. A program that
+/// calls `exit` returns an `Exit` error with the code, the caller decides what to do with it
+pub fn interpret_program(exprs: &[Expression]) -> Result<String> {
+    let mut interpreter = Interpreter::new();
+    for expr in exprs {
+        interpreter.eval(expr)?;
+    }
+    Ok(interpreter.output)
+}
+
+/// The error a program that calls `exit(code)` stops with, the interpreter never ends the
+/// process itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exit {
+    pub code: i32,
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "program exited with code {}", self.code)
+    }
+}
+
+impl std::error::Error for Exit {}
+
+// how control leaves a statement, `break` and `return` unwind to the loop or call handling them
+// and `exit` unwinds all the way out of the program
+enum Flow {
+    Next(Value),
+    Break,
+    Return(Value),
+    Exit(i32),
+}
+
+struct Function {
+    args: Vec<(String, Type, Option<Expression>)>,
+    return_type: Type,
+    body: Expression,
+}
+
+pub struct Interpreter {
+    // the innermost scope is last, a block pushes a scope and pops it when it ends
+    scopes: Vec<HashMap<String, Value>>,
+    // declared with `global`, every function sees them whatever scopes it has
+    globals: HashMap<String, Value>,
+    funcs: HashMap<String, Function>,
+    output: String,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            globals: HashMap::new(),
+            funcs: HashMap::new(),
+            output: String::new(),
+        }
+    }
+
+    /// Everything printed so far
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Evaluates `expr`, an `exit` in it stops evaluation with an `Exit` error
+    pub fn eval(&mut self, expr: &Expression) -> Result<Value> {
+        match self.exec(expr)? {
+            Flow::Next(value) | Flow::Return(value) => Ok(value),
+            Flow::Break => Err(anyhow!("`break` can only be used inside a loop")),
+            Flow::Exit(code) => Err(Exit { code }.into()),
+        }
+    }
+
+    fn exec(&mut self, expr: &Expression) -> Result<Flow> {
+        let value = match expr {
+            Expression::Number(n) => Value::Int(*n),
+            Expression::Number64(n) => Value::Int64(*n),
+            Expression::Float(n) => Value::Float(*n),
+            Expression::String(s) => {
+                // only the quotes around the literal are dropped, an escaped quote is kept
+                let s = s
+                    .strip_prefix('"')
+                    .and_then(|s| s.strip_suffix('"'))
+                    .unwrap_or(s);
+                let s = unescape(s)
+                    .map_err(|escape| anyhow!("invalid escape `{}` in string literal", escape))?;
+                Value::String(s)
+            }
+            Expression::Bool(b) => Value::Bool(*b),
+            Expression::Nil => Value::Nil,
+            Expression::List(items) => Value::List(self.eval_all(items)?),
+            Expression::Tuple(items) => Value::Tuple(self.eval_all(items)?),
+            Expression::ListIndex(list, index) => {
+                let list = self.eval(list)?;
+                let index = self.eval(index)?;
+                self.index(&list, &index)?.clone()
+            }
+            Expression::Slice(list, start, end) => {
+                let list = self.eval(list)?;
+                let start = self.eval(start)?;
+                let end = self.eval(end)?;
+                Self::slice(list, &start, &end)?
+            }
+            Expression::ListAssign(name, index, value) => {
+                let index = self.eval(index)?;
+                let value = self.eval(value)?;
+                let len = match self.lookup(name)? {
+                    Value::List(items) => items.len(),
+                    other => return Err(anyhow!("unable to index into {}", other)),
+                };
+                let i = Self::list_position(&index, len)?;
+                if let Some(Value::List(items)) = self.lookup_mut(name) {
+                    items[i] = value;
+                }
+                Value::Void
+            }
+            Expression::StructDef(_, _) | Expression::TypeAlias(_, _) => Value::Void,
+            Expression::ExternFunc(name, _, _) => {
+                return Err(anyhow!(
+                    "extern function `{}` can't be linked by the interpreter",
+                    name
+                ))
+            }
+            Expression::StructInit(name, fields) => {
+                let mut values = vec![];
+                for (field, value) in fields {
+                    values.push((field.clone(), self.eval(value)?));
+                }
+                Value::Struct(name.clone(), values)
+            }
+            Expression::FieldAccess(value, field) => match self.eval(value)? {
+                Value::Struct(name, fields) => fields
+                    .into_iter()
+                    .find(|(name, _)| name == field)
+                    .map(|(_, value)| value)
+                    .ok_or(anyhow!("struct {} has no field {}", name, field))?,
+                other => return Err(anyhow!("unable to access field {} of {}", field, other)),
+            },
+            Expression::MethodCall(target, method, args) => {
+                let value = self.eval(target)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                match (value, method.as_str(), args.as_slice()) {
+                    // like stringChars, each byte is a string of its own
+                    (Value::String(s), "chars", []) => Value::List(
+                        s.bytes()
+                            .map(|byte| Value::String((byte as char).to_string()))
+                            .collect(),
+                    ),
+                    // like strtok_r, any of the delimiter's characters separate parts and
+                    // empty parts are dropped
+                    (Value::String(s), "split", [Value::String(delimiter)]) => {
+                        let parts: Vec<&str> = if delimiter.is_empty() {
+                            vec![s.as_str()]
+                        } else {
+                            s.split(delimiter.as_str()).collect()
+                        };
+                        Value::List(
+                            parts
+                                .into_iter()
+                                .filter(|part| !part.is_empty())
+                                .map(|part| Value::String(part.to_string()))
+                                .collect(),
+                        )
+                    }
+                    (Value::String(s), "to_upper", []) => Value::String(s.to_ascii_uppercase()),
+                    (Value::String(s), "to_lower", []) => Value::String(s.to_ascii_lowercase()),
+                    // like the stringTrim runtime helpers only space, tab and newline are stripped
+                    (Value::String(s), "trim" | "trim_start" | "trim_end", []) => {
+                        let is_space = |c: char| matches!(c, ' ' | '\t' | '\n');
+                        let trimmed = match method.as_str() {
+                            "trim" => s.trim_matches(is_space),
+                            "trim_start" => s.trim_start_matches(is_space),
+                            _ => s.trim_end_matches(is_space),
+                        };
+                        Value::String(trimmed.to_string())
+                    }
+                    (Value::List(items), "contains", [needle]) => {
+                        Value::Bool(items.contains(needle))
+                    }
+                    // sorts the variable's list in place, like the listSortI32 runtime helper
+                    (Value::List(mut items), "sort" | "sort_desc", [])
+                        if items.iter().all(|item| matches!(item, Value::Int(_))) =>
+                    {
+                        items.sort_by_key(|item| match item {
+                            Value::Int(n) => *n,
+                            _ => 0,
+                        });
+                        if method == "sort_desc" {
+                            items.reverse();
+                        }
+                        if let Expression::Variable(name) = target.as_ref() {
+                            if let Some(list) = self.lookup_mut(name) {
+                                *list = Value::List(items);
+                            }
+                        }
+                        Value::Void
+                    }
+                    (value, _, _) => return Err(anyhow!("{} has no method `{}`", value, method)),
+                }
+            }
+            Expression::Variable(name) => self.lookup(name)?.clone(),
+            Expression::Binary(lhs, op, rhs) => match Self::constant_int(expr) {
+                Some(value) => value,
+                None => {
+                    let lhs = self.eval(lhs)?;
+                    let rhs = self.eval(rhs)?;
+                    Self::binary(lhs, op, rhs)?
+                }
+            },
+            Expression::Unary(op, value) => match (op.as_str(), self.eval(value)?) {
+                ("~", Value::Int(n)) => Value::Int(!n),
+                ("~", Value::Int64(n)) => Value::Int64(!n),
+                (op, value) => return Err(anyhow!("unable to apply {} to {}", op, value)),
+            },
+            Expression::Grouping(value) | Expression::Builtin(value) => self.eval(value)?,
+            Expression::LetStmt(name, let_type, value) => {
+                let value = Self::as_declared(self.eval(value)?, let_type);
+                self.declare(name, value);
+                Value::Void
+            }
+            Expression::GlobalStmt(name, global_type, value) => {
+                let value = Self::as_declared(self.eval(value)?, global_type);
+                self.globals.insert(name.clone(), value);
+                Value::Void
+            }
+            Expression::AssignStmt(name, value) => {
+                let value = self.eval(value)?;
+                match self.lookup_mut(name) {
+                    // a variable keeps the type it was declared with
+                    Some(var @ Value::Int64(_)) => *var = Self::as_declared(value, &Type::i64),
+                    Some(var) => *var = value,
+                    None => self.declare(name, value),
+                }
+                Value::Void
+            }
+            Expression::LetTupleStmt(names, value) => match self.eval(value)? {
+                Value::Tuple(items) if items.len() == names.len() => {
+                    for (name, item) in names.iter().zip(items) {
+                        self.declare(name, item);
+                    }
+                    Value::Void
+                }
+                other => {
+                    return Err(anyhow!(
+                        "unable to destructure {} into ({})",
+                        other,
+                        names.join(", ")
+                    ))
+                }
+            },
+            Expression::BlockStmt(stmts, _) => {
+                self.scopes.push(HashMap::new());
+                let flow = self.exec_block(stmts);
+                self.scopes.pop();
+                return flow;
+            }
+            Expression::FuncArg(..) | Expression::Wildcard => Value::Void,
+            Expression::FuncStmt(name, args, return_type, body, _) => {
+                let args = args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        Expression::FuncArg(name, arg_type, default) => {
+                            Some((name.clone(), arg_type.clone(), default.as_deref().cloned()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let body = *body.clone();
+                let return_type = return_type.clone();
+                self.funcs.insert(
+                    name.clone(),
+                    Function {
+                        args,
+                        return_type,
+                        body,
+                    },
+                );
+                Value::Void
+            }
+            Expression::CallStmt(name, args) => {
+                if !self.funcs.contains_key(name) {
+                    if let Some(builtin) = Expression::from_builtin_call(name, args) {
+                        return self.exec(&builtin);
+                    }
+                }
+                let args = self.eval_all(args)?;
+                self.call(name, args)?
+            }
+            Expression::IfStmt(cond, then_block, else_block) => {
+                if self.eval_bool(cond)? {
+                    return self.exec(then_block);
+                }
+                match else_block.as_ref() {
+                    Some(else_block) => return self.exec(else_block),
+                    None => Value::Void,
+                }
+            }
+            Expression::WhileStmt(cond, body) => {
+                while self.eval_bool(cond)? {
+                    match self.exec(body)? {
+                        Flow::Next(_) => {}
+                        Flow::Break => break,
+                        flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+                    }
+                }
+                Value::Void
+            }
+            Expression::DoWhileStmt(body, cond) => {
+                loop {
+                    match self.exec(body)? {
+                        Flow::Next(_) => {}
+                        Flow::Break => break,
+                        flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+                    }
+                    if !self.eval_bool(cond)? {
+                        break;
+                    }
+                }
+                Value::Void
+            }
+            Expression::RepeatUntilStmt(body, cond) => {
+                loop {
+                    match self.exec(body)? {
+                        Flow::Next(_) => {}
+                        Flow::Break => break,
+                        flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+                    }
+                    if self.eval_bool(cond)? {
+                        break;
+                    }
+                }
+                Value::Void
+            }
+            Expression::LoopStmt(body) => loop {
+                match self.exec(body)? {
+                    Flow::Next(_) => {}
+                    Flow::Break => break Value::Void,
+                    flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+                }
+            },
+            Expression::BreakStmt => return Ok(Flow::Break),
+            Expression::ReturnStmt(value) => return Ok(Flow::Return(self.eval(value)?)),
+            Expression::ForStmt(name, start, end, step, body) => {
+                // the counter only lives for the loop
+                self.scopes.push(HashMap::new());
+                self.declare(name, Value::Int(*start));
+                let flow = self.exec_for(name, *end as i64, *step, body);
+                self.scopes.pop();
+                return flow;
+            }
+            Expression::ReversedForStmt(name, start, end, body) => {
+                self.scopes.push(HashMap::new());
+                // counts down from end - 1 to start, the bounds are compared as i64 so they
+                // can't overflow at the ends of i32
+                if *end > *start {
+                    self.declare(name, Value::Int(end - 1));
+                }
+                let flow = match *end > *start {
+                    true => self.exec_for(name, *start as i64 - 1, -1, body),
+                    false => Ok(Flow::Next(Value::Void)),
+                };
+                self.scopes.pop();
+                return flow;
+            }
+            Expression::ForEachStmt(name, list, reversed, body) => {
+                let mut items = match self.eval(list)? {
+                    Value::List(items) => items,
+                    other => return Err(anyhow!("unable to loop over {}", other)),
+                };
+                if *reversed {
+                    items.reverse();
+                }
+                for item in items {
+                    self.scopes.push(HashMap::new());
+                    self.declare(name, item);
+                    let flow = self.exec(body);
+                    self.scopes.pop();
+                    match flow? {
+                        Flow::Next(_) => {}
+                        Flow::Break => break,
+                        flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+                    }
+                }
+                Value::Void
+            }
+            Expression::Print(value) => {
+                let value = self.eval(value)?;
+                match value {
+                    // stringPrint wraps a printed string in quotes
+                    Value::String(s) => self.output.push_str(&format!("\"{}\"\n", s)),
+                    value => self.output.push_str(&format!("{}\n", value)),
+                }
+                Value::Void
+            }
+            Expression::Len(value) => match self.eval(value)? {
+                Value::List(items) => Value::Int(items.len() as i32),
+                Value::String(s) => Value::Int(s.len() as i32),
+                other => return Err(anyhow!("unable to get the length of {}", other)),
+            },
+            Expression::Sum(list) => {
+                let items = self.eval_list(list)?;
+                Value::Int64(Self::sum("sum", &items)?)
+            }
+            // like the compiled program, the average of an empty list is NaN
+            Expression::Avg(list) => {
+                let items = self.eval_list(list)?;
+                Value::Float(Self::sum("avg", &items)? as f64 / items.len() as f64)
+            }
+            Expression::Fold(list, init, func) => {
+                let items = self.eval_list(list)?;
+                let mut acc = self.eval(init)?;
+                for item in items {
+                    acc = self.call(func, vec![acc, item])?;
+                }
+                acc
+            }
+            Expression::Windows(list, size) => {
+                let items = self.eval_list(list)?;
+                let size = match self.eval(size)? {
+                    Value::Int(n) if n > 0 => n as usize,
+                    Value::Int64(n) if n > 0 => n as usize,
+                    other => return Err(anyhow!("window size must be positive, found {}", other)),
+                };
+                Value::List(
+                    items
+                        .windows(size)
+                        .map(|window| Value::List(window.to_vec()))
+                        .collect(),
+                )
+            }
+            Expression::Clamp(value, lo, hi) => {
+                let value = self.eval_float("clamp", value)?;
+                let lo = self.eval_float("clamp", lo)?;
+                let hi = self.eval_float("clamp", hi)?;
+                Value::Float(value.max(lo).min(hi))
+            }
+            Expression::Lerp(a, b, t) => {
+                let a = self.eval_float("lerp", a)?;
+                let b = self.eval_float("lerp", b)?;
+                let t = self.eval_float("lerp", t)?;
+                Value::Float(a + (b - a) * t)
+            }
+            Expression::Exit(code) => match self.eval(code)? {
+                Value::Int(code) => return Ok(Flow::Exit(code)),
+                code => return Err(anyhow!("unable to exit with {}", code)),
+            },
+            Expression::Assert(cond) => {
+                if !self.eval_bool(cond)? {
+                    return Err(anyhow!("assertion failed: {}", cond));
+                }
+                Value::Void
+            }
+            Expression::Match(value, arms) => {
+                let value = self.eval(value)?;
+                for (pattern, arm) in arms {
+                    let matched = match pattern {
+                        Expression::Wildcard => true,
+                        pattern => self.eval(pattern)? == value,
+                    };
+                    if matched {
+                        return self.exec(arm);
+                    }
+                }
+                Value::Void
+            }
+            Expression::Switch(value, cases, default) => {
+                let value = match self.eval(value)? {
+                    Value::Int(n) => n as i64,
+                    Value::Int64(n) => n,
+                    value => {
+                        return Err(anyhow!(
+                            "unable to switch on {}, switch cases are integers",
+                            value
+                        ))
+                    }
+                };
+                for (case, body) in cases {
+                    if *case as i64 == value {
+                        return self.exec(body);
+                    }
+                }
+                match default.as_ref() {
+                    Some(default) => return self.exec(default),
+                    None => Value::Void,
+                }
+            }
+            Expression::ReadLine => Value::String(Self::read_line()?),
+            Expression::ReadInt => {
+                let line = Self::read_line()?;
+                Value::Int(line.trim().parse().unwrap_or(0))
+            }
+            Expression::Format(template, args) => {
+                let args = self.eval_all(args)?;
+                Value::String(Self::format(template, args)?)
+            }
+            // the sizes the compiler gives these types on a 64 bit target, the interpreter has no
+            // layout for the others
+            Expression::SizeOf(size_type) => match size_type {
+                Type::i32 => Value::Int64(4),
+                Type::i64 | Type::Float => Value::Int64(8),
+                Type::Bool => Value::Int64(1),
+                _ => {
+                    return Err(anyhow!(
+                        "unable to take sizeof({}) in the interpreter",
+                        size_type
+                    ))
+                }
+            },
+            Expression::Cast(cast_type, value) => {
+                let value = self.eval(value)?;
+                Self::cast(value, cast_type)?
+            }
+            Expression::Is(value, is_type) => {
+                let value = self.eval(value)?;
+                Value::Bool(Self::is_type(&value, is_type))
+            }
+            Expression::Import(path) => {
+                return Err(anyhow!("unable to import {:?} in the interpreter", path))
+            }
+        };
+        Ok(Flow::Next(value))
+    }
+
+    // the value of a block is the value of its last statement
+    fn exec_block(&mut self, stmts: &[Expression]) -> Result<Flow> {
+        let mut value = Value::Void;
+        for stmt in stmts {
+            match self.exec(stmt)? {
+                Flow::Next(next) => value = next,
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next(value))
+    }
+
+    fn exec_for(&mut self, name: &str, end: i64, step: i32, body: &Expression) -> Result<Flow> {
+        loop {
+            let counter = match self.lookup(name)? {
+                Value::Int(n) => *n as i64,
+                other => return Err(anyhow!("for loop counter {} is {}", name, other)),
+            };
+            let in_range = if step < 0 {
+                counter > end
+            } else {
+                counter < end
+            };
+            if !in_range {
+                return Ok(Flow::Next(Value::Void));
+            }
+            match self.exec(body)? {
+                Flow::Next(_) => {}
+                Flow::Break => return Ok(Flow::Next(Value::Void)),
+                flow @ (Flow::Return(_) | Flow::Exit(_)) => return Ok(flow),
+            }
+            if let Some(Value::Int(n)) = self.lookup_mut(name) {
+                *n = n.wrapping_add(step);
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str, mut args: Vec<Value>) -> Result<Value> {
+        let (params, return_type, body) = match self.funcs.get(name) {
+            Some(func) => (
+                func.args.clone(),
+                func.return_type.clone(),
+                func.body.clone(),
+            ),
+            None => return Err(anyhow!("call does not exist for function {:?}", name)),
+        };
+        // trailing arguments the caller left out take their default values, a default only sees
+        // the arguments before it rather than the caller's variables
+        for (arg_name, arg_type, default) in params.iter().skip(args.len()) {
+            let Some(default) = default else {
+                break;
+            };
+            let frame = params
+                .iter()
+                .zip(&args)
+                .map(|((name, arg_type, _), arg)| {
+                    (name.clone(), Self::as_declared(arg.clone(), arg_type))
+                })
+                .collect();
+            let caller_scopes = std::mem::replace(&mut self.scopes, vec![frame]);
+            let value = self.eval(default);
+            self.scopes = caller_scopes;
+            let value = Self::as_declared(value?, arg_type);
+            if !Self::is_type(&value, arg_type) {
+                return Err(anyhow!(
+                    "default value of argument {} is {}, expected {}",
+                    arg_name,
+                    value,
+                    arg_type
+                ));
+            }
+            args.push(value);
+        }
+        if params.len() != args.len() {
+            return Err(anyhow!(
+                "function {} expects {} argument{}, got {}",
+                name,
+                params.len(),
+                if params.len() == 1 { "" } else { "s" },
+                args.len()
+            ));
+        }
+        // a function only sees its arguments, the caller's variables are restored afterwards
+        let frame = params
+            .into_iter()
+            .zip(args)
+            .map(|((name, arg_type, _), arg)| (name, Self::as_declared(arg, &arg_type)))
+            .collect();
+        let caller_scopes = std::mem::replace(&mut self.scopes, vec![frame]);
+        let flow = self.exec(&body);
+        self.scopes = caller_scopes;
+        match flow? {
+            Flow::Next(value) | Flow::Return(value) => Ok(Self::as_declared(value, &return_type)),
+            Flow::Break => Err(anyhow!("`break` can only be used inside a loop")),
+            Flow::Exit(code) => Err(Exit { code }.into()),
+        }
+    }
+
+    // a value takes the type it's declared with, like the compiler an i32 given where an i64 is
+    // declared is widened so `is` and arithmetic see an i64
+    fn as_declared(value: Value, declared_type: &Type) -> Value {
+        match (value, declared_type) {
+            (Value::Int(n), Type::i64) => Value::Int64(n as i64),
+            (Value::List(items), Type::List(inner)) => Value::List(
+                items
+                    .into_iter()
+                    .map(|item| Self::as_declared(item, inner))
+                    .collect(),
+            ),
+            (value, _) => value,
+        }
+    }
+
+    // an i32 and an i64 operand are widened to i64 like the compiler does, any other operands
+    // have to be the same type
+    fn binary(lhs: Value, op: &str, rhs: Value) -> Result<Value> {
+        if op == "^" {
+            return Err(anyhow!("^ is not implemented yet"));
+        }
+        let value = match (lhs, rhs) {
+            (Value::Int(a), Value::Int(b)) => match op {
+                "+" => Value::Int(a.wrapping_add(b)),
+                "-" => Value::Int(a.wrapping_sub(b)),
+                "*" => Value::Int(a.wrapping_mul(b)),
+                "/" if b == 0 => return Err(anyhow!("division by zero")),
+                "/" => Value::Int(a.wrapping_div(b)),
+                _ => Self::compare(&a, op, &b)?,
+            },
+            (Value::Int64(a), Value::Int64(b)) => match op {
+                "+" => Value::Int64(a.wrapping_add(b)),
+                "-" => Value::Int64(a.wrapping_sub(b)),
+                "*" => Value::Int64(a.wrapping_mul(b)),
+                "/" if b == 0 => return Err(anyhow!("division by zero")),
+                "/" => Value::Int64(a.wrapping_div(b)),
+                _ => Self::compare(&a, op, &b)?,
+            },
+            (Value::Int(a), Value::Int64(b)) => {
+                Self::binary(Value::Int64(a as i64), op, Value::Int64(b))?
+            }
+            (Value::Int64(a), Value::Int(b)) => {
+                Self::binary(Value::Int64(a), op, Value::Int64(b as i64))?
+            }
+            (Value::Float(a), Value::Float(b)) => match op {
+                "+" => Value::Float(a + b),
+                "-" => Value::Float(a - b),
+                "*" => Value::Float(a * b),
+                "/" => Value::Float(a / b),
+                _ => Self::compare(&a, op, &b)?,
+            },
+            (Value::String(a), Value::String(b)) => match op {
+                "+" => Value::String(a + &b),
+                _ => Self::compare(&a, op, &b)?,
+            },
+            (lhs, rhs) if std::mem::discriminant(&lhs) == std::mem::discriminant(&rhs) => {
+                match op {
+                    "==" => Value::Bool(lhs == rhs),
+                    "!=" => Value::Bool(lhs != rhs),
+                    _ => return Err(anyhow!("unable to apply {} to {} and {}", op, lhs, rhs)),
+                }
+            }
+            (lhs, rhs) => {
+                return Err(anyhow!(
+                    "mismatched types, unable to apply {} to {} and {}",
+                    op,
+                    lhs,
+                    rhs
+                ))
+            }
+        };
+        Ok(value)
+    }
+
+    // arithmetic on integer literals is folded by the compiler before the program runs, the
+    // exact result of i32 literals is an i64 when it's too big for an i32 rather than wrapping
+    fn constant_int(expr: &Expression) -> Option<Value> {
+        match expr {
+            Expression::Number(n) => Some(Value::Int(*n)),
+            Expression::Number64(n) => Some(Value::Int64(*n)),
+            Expression::Grouping(value) => Self::constant_int(value),
+            Expression::Unary(op, value) if op == "~" => match Self::constant_int(value)? {
+                Value::Int(n) => Some(Value::Int(!n)),
+                Value::Int64(n) => Some(Value::Int64(!n)),
+                _ => None,
+            },
+            Expression::Binary(lhs, op, rhs) => {
+                let lhs = Self::constant_int(lhs)?;
+                let rhs = Self::constant_int(rhs)?;
+                let fold = |a: i64, b: i64| match op.as_str() {
+                    "+" => Some(a.wrapping_add(b)),
+                    "-" => Some(a.wrapping_sub(b)),
+                    "*" => Some(a.wrapping_mul(b)),
+                    "/" => a.checked_div(b),
+                    _ => None,
+                };
+                match (lhs, rhs) {
+                    (Value::Int(a), Value::Int(b)) => {
+                        fold(a as i64, b as i64).map(|n| match i32::try_from(n) {
+                            Ok(n) => Value::Int(n),
+                            Err(_) => Value::Int64(n),
+                        })
+                    }
+                    (Value::Int(a), Value::Int64(b)) => fold(a as i64, b).map(Value::Int64),
+                    (Value::Int64(a), Value::Int(b)) => fold(a, b as i64).map(Value::Int64),
+                    (Value::Int64(a), Value::Int64(b)) => fold(a, b).map(Value::Int64),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn compare<T: PartialOrd>(a: &T, op: &str, b: &T) -> Result<Value> {
+        let result = match op {
+            "==" => a == b,
+            "!=" => a != b,
+            "<" => a < b,
+            "<=" => a <= b,
+            ">" => a > b,
+            ">=" => a >= b,
+            _ => return Err(anyhow!("unknown operator {}", op)),
+        };
+        Ok(Value::Bool(result))
+    }
+
+    // like the compiler, only a List<i32> can be summed and the total is an i64
+    fn sum(op: &str, items: &[Value]) -> Result<i64> {
+        items.iter().try_fold(0i64, |acc, item| match item {
+            Value::Int(n) => Ok(acc.wrapping_add(*n as i64)),
+            item => Err(anyhow!(
+                "{} is only implemented for List<i32>, found {}",
+                op,
+                item
+            )),
+        })
+    }
+
+    // printf style, `%d`, `%s` and `%f` are replaced by the arguments in order
+    fn format(template: &str, args: Vec<Value>) -> Result<String> {
+        let mut args = args.into_iter();
+        let mut formatted = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+            match (chars.next(), args.next()) {
+                (Some('%'), _) => formatted.push('%'),
+                (Some('f'), Some(Value::Float(n))) => formatted.push_str(&format!("{:.6}", n)),
+                (Some('d' | 's'), Some(arg)) => formatted.push_str(&arg.to_string()),
+                (specifier, _) => {
+                    return Err(anyhow!(
+                        "format string {:?} has an unsupported specifier {:?}",
+                        template,
+                        specifier
+                    ))
+                }
+            }
+        }
+        Ok(formatted)
+    }
+
+    fn read_line() -> Result<String> {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    // the same conversions as the compiler, a cast from i64 to i32 truncates it to 32 bits
+    fn cast(value: Value, cast_type: &Type) -> Result<Value> {
+        Ok(match (value, cast_type) {
+            (Value::Int(n), Type::i32) => Value::Int(n),
+            (Value::Int(n), Type::i64) => Value::Int64(n as i64),
+            (Value::Int(n), Type::Float) => Value::Float(n as f64),
+            (Value::Int(n), Type::Bool) => Value::Bool(n != 0),
+            (Value::Int64(n), Type::i32) => Value::Int(n as i32),
+            (Value::Int64(n), Type::i64) => Value::Int64(n),
+            (Value::Int64(n), Type::Float) => Value::Float(n as f64),
+            (Value::Int64(n), Type::Bool) => Value::Bool(n != 0),
+            (Value::Float(n), Type::i32) => Value::Int(n as i32),
+            (Value::Float(n), Type::i64) => Value::Int64(n as i64),
+            (Value::Float(n), Type::Float) => Value::Float(n),
+            (Value::Bool(b), Type::i32) => Value::Int(b as i32),
+            (Value::Bool(b), Type::i64) => Value::Int64(b as i64),
+            (Value::Bool(b), Type::Bool) => Value::Bool(b),
+            (Value::String(s), Type::String) => Value::String(s),
+            (value, _) => return Err(anyhow!("unable to cast {} to {}", value, cast_type)),
+        })
+    }
+
+    fn is_type(value: &Value, is_type: &Type) -> bool {
+        match (value, is_type) {
+            (Value::Int(_), Type::i32)
+            | (Value::Int64(_), Type::i64)
+            | (Value::Float(_), Type::Float)
+            | (Value::Bool(_), Type::Bool)
+            | (Value::String(_), Type::String) => true,
+            (Value::List(items), Type::List(inner)) => {
+                items.iter().all(|item| Self::is_type(item, inner))
+            }
+            (Value::Tuple(items), Type::Tuple(component_types)) => {
+                items.len() == component_types.len()
+                    && items
+                        .iter()
+                        .zip(component_types)
+                        .all(|(item, component_type)| Self::is_type(item, component_type))
+            }
+            (Value::Struct(name, _), Type::Struct(struct_name) | Type::Alias(struct_name)) => {
+                name == struct_name
+            }
+            _ => false,
+        }
+    }
+
+    fn eval_all(&mut self, exprs: &[Expression]) -> Result<Vec<Value>> {
+        exprs.iter().map(|expr| self.eval(expr)).collect()
+    }
+
+    fn eval_list(&mut self, expr: &Expression) -> Result<Vec<Value>> {
+        match self.eval(expr)? {
+            Value::List(items) => Ok(items),
+            other => Err(anyhow!("expected a list, found {}", other)),
+        }
+    }
+
+    fn eval_bool(&mut self, expr: &Expression) -> Result<bool> {
+        match self.eval(expr)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(anyhow!("expected a bool condition, found {}", other)),
+        }
+    }
+
+    fn eval_float(&mut self, op: &str, expr: &Expression) -> Result<f64> {
+        match self.eval(expr)? {
+            Value::Float(n) => Ok(n),
+            other => Err(anyhow!(
+                "{} is only implemented for floats, found {}",
+                op,
+                other
+            )),
+        }
+    }
+
+    fn index<'v>(&self, list: &'v Value, index: &Value) -> Result<&'v Value> {
+        match list {
+            Value::List(items) => Ok(&items[Self::list_position(index, items.len())?]),
+            other => Err(anyhow!("unable to index into {}", other)),
+        }
+    }
+
+    // the bounds are clamped to the list like the compiler does, so a slice past the end stops
+    // at the end and a start after the end is an empty list
+    fn slice(list: Value, start: &Value, end: &Value) -> Result<Value> {
+        let mut items = match list {
+            Value::List(items) => items,
+            other => return Err(anyhow!("unable to slice {}", other)),
+        };
+        let (start, end) = match (Self::as_i64(start), Self::as_i64(end)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(anyhow!("slice bounds must be integers")),
+        };
+        let len = items.len() as i64;
+        if start < 0 || start > end || end > len {
+            return Err(anyhow!(
+                "slice bounds {}..{} out of range for length {}",
+                start,
+                end,
+                len
+            ));
+        }
+        Ok(Value::List(
+            items.drain(start as usize..end as usize).collect(),
+        ))
+    }
+
+    fn list_position(index: &Value, len: usize) -> Result<usize> {
+        match Self::as_i64(index) {
+            Some(i) if i >= 0 && (i as usize) < len => Ok(i as usize),
+            _ => Err(anyhow!(
+                "index {} is out of bounds for a list of length {}",
+                index,
+                len
+            )),
+        }
+    }
+
+    fn as_i64(value: &Value) -> Option<i64> {
+        match value {
+            Value::Int(n) => Some(*n as i64),
+            Value::Int64(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Result<&Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.globals.get(name))
+            .ok_or(anyhow!("Undefined variable: {}", name))
+    }
+
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .or_else(|| self.globals.get_mut(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cyclang_parser::parse_cyclo_program;
+
+    #[test]
+    fn test_interpret_arithmetic() {
+        let exprs = parse_cyclo_program("1 + 2 * 3;").expect("unable to parse contents");
+        assert_eq!(interpret(&exprs[0]).unwrap(), Value::Int(7));
+    }
+
+    #[test]
+    fn test_interpret_string_concat() {
+        let exprs =
+            parse_cyclo_program(r#""hello " + "world";"#).expect("unable to parse contents");
+        assert_eq!(
+            interpret(&exprs[0]).unwrap(),
+            Value::String("hello world".to_string())
+        );
+    }
+
+    #[test]
+    fn test_interpret_i32_wraps() {
+        let input = r#"
+        let x: i32 = 2147483647;
+        print(x + 1);
+        let y = 4294967296;
+        print(y + 1);
+        print(2147483647 + 1);
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap(),
+            "-2147483648\n4294967297\n2147483648\n"
+        );
+    }
+
+    #[test]
+    fn test_interpret_pow_is_not_implemented() {
+        let exprs = parse_cyclo_program("let x = 2; print(x ^ 3);").expect("unable to parse");
+        assert_eq!(
+            interpret_program(&exprs).unwrap_err().to_string(),
+            "^ is not implemented yet"
+        );
+    }
+
+    #[test]
+    fn test_interpret_int_and_float_mismatch() {
+        let exprs = parse_cyclo_program("let x = 1; print(x + 1.5);").expect("unable to parse");
+        assert!(interpret_program(&exprs)
+            .unwrap_err()
+            .to_string()
+            .starts_with("mismatched types"));
+    }
+
+    #[test]
+    fn test_interpret_string_chars_and_escapes() {
+        let input = r#"
+        let s = "a\"b";
+        print(s.chars());
+        print(len(s));
+        print("x,y".split(","));
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap(),
+            "[\"a\", \"\"\", \"b\"]\n3\n[\"x\", \"y\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_interpret_is_uses_declared_type() {
+        let input = r#"
+        let x = 5;
+        let y: i64 = 5;
+        fn widen(i64 n) -> i64 {
+            return n;
+        }
+        print(x is i32);
+        print(y is i64);
+        print(y is i32);
+        print(widen(x) is i64);
+        y = 7;
+        print(y is i64);
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap(),
+            "true\ntrue\nfalse\ntrue\ntrue\n"
+        );
+    }
+
+    #[test]
+    fn test_interpret_exit_stops_the_program() {
+        let input = r#"
+        fn stop(i32 code) {
+            exit(code);
+        }
+        print(1);
+        for i in 0..3 {
+            stop(3);
+        }
+        print(2);
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        let mut interpreter = Interpreter::new();
+        let error = exprs
+            .iter()
+            .try_for_each(|expr| interpreter.eval(expr).map(|_| ()))
+            .unwrap_err();
+        assert_eq!(error.downcast_ref::<Exit>(), Some(&Exit { code: 3 }));
+        assert_eq!(interpreter.output(), "1\n");
+    }
+}
@@ -2,51 +2,417 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
-use std::num::ParseIntError;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::num::{ParseFloatError, ParseIntError};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 #[derive(Parser)]
 #[grammar = "../grammar/cyclo.pest"]
 struct CycloParser;
 
-#[derive(Debug, Clone, PartialEq)]
+// Binary operator precedence, lowest first: comparisons bind loosest,
+// then +/-, then * and /, with ^ binding tightest (and to the right)
+fn pratt_parser() -> &'static PrattParser<Rule> {
+    static PRATT_PARSER: OnceLock<PrattParser<Rule>> = OnceLock::new();
+    PRATT_PARSER.get_or_init(|| {
+        PrattParser::new()
+            .op(Op::infix(Rule::eqeq, Assoc::Left)
+                | Op::infix(Rule::neq, Assoc::Left)
+                | Op::infix(Rule::gte, Assoc::Left)
+                | Op::infix(Rule::lte, Assoc::Left)
+                | Op::infix(Rule::gt, Assoc::Left)
+                | Op::infix(Rule::lt, Assoc::Left))
+            .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::subtract, Assoc::Left))
+            .op(Op::infix(Rule::multiply, Assoc::Left) | Op::infix(Rule::divide, Assoc::Left))
+            .op(Op::infix(Rule::power, Assoc::Right))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(non_camel_case_types)]
 pub enum Type {
     None,
     i32,
     i64,
+    Float,
     String,
     Bool,
     List(Box<Type>),
+    Tuple(Vec<Type>),
+    Struct(String),
+    Alias(String),
+}
+
+/// The line and column a statement starts at in its source file, both counting from 1, and
+/// how many characters of source the statement covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: u32,
+    pub col: u32,
+    pub len: u32,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+/// A top level statement together with where it starts in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+/// Where each statement of a block starts in the source, in the same order as the statements.
+/// Spans are left out when comparing blocks, so a block built by hand equals the parsed one
+#[derive(Debug, Clone, Default)]
+pub struct BlockSpans(pub Vec<Span>);
+
+impl BlockSpans {
+    pub fn get(&self, index: usize) -> Option<Span> {
+        self.0.get(index).copied()
+    }
+}
+
+impl PartialEq for BlockSpans {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// Attributes written as `#[...]` before a function declaration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FuncAttribute {
+    Inline,
+    NoInline,
+    Export,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Number(i32),
     Number64(i64),
+    Float(f64),
     String(String),
     Bool(bool),
     Nil,
     List(Vec<Expression>),
     ListIndex(Box<Expression>, Box<Expression>),
+    // `list[start..end]`, a new list of the elements from start up to end
+    Slice(Box<Expression>, Box<Expression>, Box<Expression>),
     ListAssign(String, Box<Expression>, Box<Expression>),
+    Tuple(Vec<Expression>),
+    StructDef(String, Vec<(String, Type)>),
+    StructInit(String, Vec<(String, Expression)>),
+    FieldAccess(Box<Expression>, String),
+    // `value.method(args)`
+    MethodCall(Box<Expression>, String, Vec<Expression>),
     Variable(String),
     Binary(Box<Expression>, String, Box<Expression>),
+    Unary(String, Box<Expression>),
     Grouping(Box<Expression>),
     LetStmt(String, Type, Box<Expression>),
-    BlockStmt(Vec<Expression>),
-    FuncArg(String, Type),
-    FuncStmt(String, Vec<Expression>, Type, Box<Expression>),
+    // `name = value` without `let`, assigns to the variable in the nearest scope
+    AssignStmt(String, Box<Expression>),
+    LetTupleStmt(Vec<String>, Box<Expression>),
+    BlockStmt(Vec<Expression>, BlockSpans),
+    // `type name = default`, the default is used when a call leaves the argument out
+    FuncArg(String, Type, Option<Box<Expression>>),
+    FuncStmt(
+        String,
+        Vec<Expression>,
+        Type,
+        Box<Expression>,
+        Option<FuncAttribute>,
+    ),
+    // `extern fn name(args) -> type;`, a function without a body that's resolved when linking
+    ExternFunc(String, Vec<(String, Type)>, Type),
+    // `global name: type = value;`, a variable shared by every function
+    GlobalStmt(String, Type, Box<Expression>),
     CallStmt(String, Vec<Expression>),
     IfStmt(Box<Expression>, Box<Expression>, Box<Option<Expression>>),
     WhileStmt(Box<Expression>, Box<Expression>),
+    DoWhileStmt(Box<Expression>, Box<Expression>),
+    RepeatUntilStmt(Box<Expression>, Box<Expression>),
+    LoopStmt(Box<Expression>),
+    BreakStmt,
     ReturnStmt(Box<Expression>),
     ForStmt(String, i32, i32, i32, Box<Expression>),
+    // `for i in reversed(start..end)`, with the range's own bounds
+    ReversedForStmt(String, i32, i32, Box<Expression>),
+    ForEachStmt(String, Box<Expression>, bool, Box<Expression>),
     Print(Box<Expression>),
     Len(Box<Expression>),
+    Sum(Box<Expression>),
+    Avg(Box<Expression>),
+    Fold(Box<Expression>, Box<Expression>, String),
+    Windows(Box<Expression>, Box<Expression>),
+    Clamp(Box<Expression>, Box<Expression>, Box<Expression>),
+    Lerp(Box<Expression>, Box<Expression>, Box<Expression>),
+    // `exit(code)`, ends the program with the exit code
+    Exit(Box<Expression>),
+    // `assert(cond)`, exits with code 1 when the condition is false
+    Assert(Box<Expression>),
+    // `builtin.name(...)`, calls the builtin even when a user function shadows it
+    Builtin(Box<Expression>),
+    Match(Box<Expression>, Vec<(Expression, Expression)>),
+    Wildcard,
+    // `switch (value) { case 1 { ... } default { ... } }`, the i32 cases and the optional default
+    Switch(
+        Box<Expression>,
+        Vec<(i32, Expression)>,
+        Box<Option<Expression>>,
+    ),
+    ReadLine,
+    ReadInt,
+    Format(String, Vec<Expression>),
+    SizeOf(Type),
+    // `cast<type>(value)`, converts the value to the type
+    Cast(Type, Box<Expression>),
+    // `value is type`, true when the value has the type
+    Is(Box<Expression>, Type),
+    TypeAlias(String, Type),
+    Import(String),
+}
+
+impl fmt::Display for FuncAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuncAttribute::Inline => write!(f, "#[inline]"),
+            FuncAttribute::NoInline => write!(f, "#[noinline]"),
+            FuncAttribute::Export => write!(f, "#[export]"),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::None => write!(f, "none"),
+            Type::i32 => write!(f, "i32"),
+            Type::i64 => write!(f, "i64"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "string"),
+            Type::Bool => write!(f, "bool"),
+            Type::List(inner) => write!(f, "List<{}>", inner),
+            Type::Tuple(types) => write!(
+                f,
+                "({})",
+                types
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Type::Struct(name) => write!(f, "{}", name),
+            Type::Alias(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// Renders an expression back into (roughly) the source it was parsed from,
+// block bodies are elided so this stays short enough for error messages
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(n) => write!(f, "{}", n),
+            Expression::Number64(n) => write!(f, "{}", n),
+            Expression::Float(n) => write!(f, "{:?}", n),
+            Expression::String(s) => write!(f, "{}", s),
+            Expression::Bool(b) => write!(f, "{}", b),
+            Expression::Nil => write!(f, "nil"),
+            Expression::List(exprs) => write!(f, "[{}]", join_exprs(exprs)),
+            Expression::ListIndex(list, index) => write!(f, "{}[{}]", list, index),
+            Expression::Slice(list, start, end) => write!(f, "{}[{}..{}]", list, start, end),
+            Expression::ListAssign(var, index, value) => {
+                write!(f, "{}[{}] = {}", var, index, value)
+            }
+            Expression::Tuple(exprs) => write!(f, "({})", join_exprs(exprs)),
+            Expression::StructDef(name, _) => write!(f, "struct {} {{ ... }}", name),
+            Expression::StructInit(name, fields) => write!(
+                f,
+                "{} {{ {} }}",
+                name,
+                fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expression::FieldAccess(value, field) => write!(f, "{}.{}", value, field),
+            Expression::MethodCall(value, method, args) => write!(
+                f,
+                "{}.{}({})",
+                value,
+                method,
+                args.iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Binary(lhs, op, rhs) => write!(f, "{} {} {}", lhs, op, rhs),
+            Expression::Unary(op, expr) => write!(f, "{}{}", op, expr),
+            Expression::Grouping(expr) => write!(f, "({})", expr),
+            Expression::LetStmt(name, Type::None, value) => write!(f, "let {} = {}", name, value),
+            Expression::LetStmt(name, let_type, value) => {
+                write!(f, "let {}: {} = {}", name, let_type, value)
+            }
+            Expression::AssignStmt(name, value) => write!(f, "{} = {}", name, value),
+            Expression::GlobalStmt(name, global_type, value) => {
+                write!(f, "global {}: {} = {}", name, global_type, value)
+            }
+            Expression::LetTupleStmt(names, value) => {
+                write!(f, "let ({}) = {}", names.join(", "), value)
+            }
+            Expression::BlockStmt(..) => write!(f, "{{ ... }}"),
+            Expression::FuncArg(name, arg_type, None) => write!(f, "{} {}", arg_type, name),
+            Expression::FuncArg(name, arg_type, Some(default)) => {
+                write!(f, "{} {} = {}", arg_type, name, default)
+            }
+            Expression::FuncStmt(name, args, return_type, _, attribute) => {
+                if let Some(attribute) = attribute {
+                    write!(f, "{} ", attribute)?;
+                }
+                match return_type {
+                    Type::None => write!(f, "fn {}({}) {{ ... }}", name, join_exprs(args)),
+                    _ => write!(
+                        f,
+                        "fn {}({}) -> {} {{ ... }}",
+                        name,
+                        join_exprs(args),
+                        return_type
+                    ),
+                }
+            }
+            Expression::ExternFunc(name, args, return_type) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|(name, arg_type)| format!("{} {}", arg_type, name))
+                    .collect();
+                match return_type {
+                    Type::None => write!(f, "extern fn {}({})", name, args.join(", ")),
+                    _ => write!(
+                        f,
+                        "extern fn {}({}) -> {}",
+                        name,
+                        args.join(", "),
+                        return_type
+                    ),
+                }
+            }
+            Expression::CallStmt(name, args) => write!(f, "{}({})", name, join_exprs(args)),
+            Expression::IfStmt(cond, _, _) => write!(f, "if ({}) {{ ... }}", cond),
+            Expression::WhileStmt(cond, _) => write!(f, "while ({}) {{ ... }}", cond),
+            Expression::DoWhileStmt(_, cond) => write!(f, "do {{ ... }} while ({})", cond),
+            Expression::RepeatUntilStmt(_, cond) => write!(f, "repeat {{ ... }} until ({})", cond),
+            Expression::LoopStmt(_) => write!(f, "loop {{ ... }}"),
+            Expression::BreakStmt => write!(f, "break"),
+            Expression::ReturnStmt(expr) => write!(f, "return {}", expr),
+            Expression::ForStmt(var, start, end, step, _) => {
+                let (cmp, incr) = if *step < 0 { (">", "--") } else { ("<", "++") };
+                write!(
+                    f,
+                    "for (let {} = {}; {} {} {}; {}{}) {{ ... }}",
+                    var, start, var, cmp, end, var, incr
+                )
+            }
+            Expression::ReversedForStmt(var, start, end, _) => {
+                write!(f, "for {} in reversed({}..{}) {{ ... }}", var, start, end)
+            }
+            Expression::ForEachStmt(var, list, true, _) => {
+                write!(f, "for {} in reversed({}) {{ ... }}", var, list)
+            }
+            Expression::ForEachStmt(var, list, false, _) => {
+                write!(f, "for {} in {} {{ ... }}", var, list)
+            }
+            Expression::Print(expr) => write!(f, "print({})", expr),
+            Expression::Len(expr) => write!(f, "len({})", expr),
+            Expression::Sum(expr) => write!(f, "sum({})", expr),
+            Expression::Avg(expr) => write!(f, "avg({})", expr),
+            Expression::Fold(list, init, func_name) => {
+                write!(f, "fold({}, {}, {})", list, init, func_name)
+            }
+            Expression::Windows(list, size) => write!(f, "windows({}, {})", list, size),
+            Expression::Clamp(value, lo, hi) => write!(f, "clamp({}, {}, {})", value, lo, hi),
+            Expression::Lerp(a, b, t) => write!(f, "lerp({}, {}, {})", a, b, t),
+            Expression::Exit(code) => write!(f, "exit({})", code),
+            Expression::Assert(cond) => write!(f, "assert({})", cond),
+            Expression::Builtin(builtin) => write!(f, "builtin.{}", builtin),
+            Expression::Match(scrutinee, _) => write!(f, "match {} {{ ... }}", scrutinee),
+            Expression::Wildcard => write!(f, "_"),
+            Expression::Switch(scrutinee, _, _) => write!(f, "switch ({}) {{ ... }}", scrutinee),
+            Expression::ReadLine => write!(f, "read_line()"),
+            Expression::ReadInt => write!(f, "read_int()"),
+            Expression::Format(format, args) if args.is_empty() => {
+                write!(f, "format(\"{}\")", format)
+            }
+            Expression::Format(format, args) => {
+                write!(f, "format(\"{}\", {})", format, join_exprs(args))
+            }
+            Expression::SizeOf(size_type) => write!(f, "sizeof({})", size_type),
+            Expression::Cast(cast_type, value) => write!(f, "cast<{}>({})", cast_type, value),
+            Expression::Is(value, is_type) => write!(f, "{} is {}", value, is_type),
+            Expression::Import(path) => write!(f, "import \"{}\"", path),
+            Expression::TypeAlias(name, alias_type) => write!(f, "type {} = {}", name, alias_type),
+        }
+    }
+}
+
+fn join_exprs(exprs: &[Expression]) -> String {
+    exprs
+        .iter()
+        .map(|expr| expr.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
 }
 
 impl Expression {
+    /// The name and arguments of a call to a builtin that a user function can shadow
+    pub fn as_builtin_call(&self) -> Option<(&'static str, Vec<Expression>)> {
+        match self {
+            Expression::Print(value) => Some(("print", vec![*value.clone()])),
+            Expression::Len(value) => Some(("len", vec![*value.clone()])),
+            Expression::Sum(value) => Some(("sum", vec![*value.clone()])),
+            Expression::Avg(value) => Some(("avg", vec![*value.clone()])),
+            Expression::Windows(list, size) => {
+                Some(("windows", vec![*list.clone(), *size.clone()]))
+            }
+            Expression::Clamp(value, lo, hi) => {
+                Some(("clamp", vec![*value.clone(), *lo.clone(), *hi.clone()]))
+            }
+            Expression::Lerp(a, b, t) => Some(("lerp", vec![*a.clone(), *b.clone(), *t.clone()])),
+            Expression::Exit(code) => Some(("exit", vec![*code.clone()])),
+            Expression::Assert(cond) => Some(("assert", vec![*cond.clone()])),
+            _ => None,
+        }
+    }
+
+    /// The builtin a call by name refers to, the inverse of `as_builtin_call`
+    pub fn from_builtin_call(name: &str, args: &[Expression]) -> Option<Self> {
+        match (name, args) {
+            ("print", [value]) => Some(Self::new_print_stmt(value.clone())),
+            ("len", [value]) => Some(Self::new_len_stmt(value.clone())),
+            ("sum", [value]) => Some(Self::new_sum_stmt(value.clone())),
+            ("avg", [value]) => Some(Self::new_avg_stmt(value.clone())),
+            ("windows", [list, size]) => Some(Self::new_windows_stmt(list.clone(), size.clone())),
+            ("clamp", [value, lo, hi]) => {
+                Some(Self::new_clamp_stmt(value.clone(), lo.clone(), hi.clone()))
+            }
+            ("lerp", [a, b, t]) => Some(Self::new_lerp_stmt(a.clone(), b.clone(), t.clone())),
+            ("exit", [code]) => Some(Self::new_exit_stmt(code.clone())),
+            ("assert", [cond]) => Some(Self::new_assert_stmt(cond.clone())),
+            _ => None,
+        }
+    }
+
     fn new_number(n: i32) -> Self {
         Self::Number(n)
     }
@@ -54,6 +420,10 @@ impl Expression {
         Self::Number64(n)
     }
 
+    fn new_float(n: f64) -> Self {
+        Self::Float(n)
+    }
+
     fn new_string(s: String) -> Self {
         Self::String(s)
     }
@@ -74,10 +444,34 @@ impl Expression {
         Self::ListIndex(Box::new(list), Box::new(index))
     }
 
+    fn new_slice(list: Expression, start: Expression, end: Expression) -> Self {
+        Self::Slice(Box::new(list), Box::new(start), Box::new(end))
+    }
+
     fn new_list_assign(var: String, index: Expression, value: Expression) -> Self {
         Self::ListAssign(var, Box::new(index), Box::new(value))
     }
 
+    fn new_tuple(tuple: Vec<Expression>) -> Self {
+        Self::Tuple(tuple)
+    }
+
+    fn new_struct_def(name: String, fields: Vec<(String, Type)>) -> Self {
+        Self::StructDef(name, fields)
+    }
+
+    fn new_struct_init(name: String, fields: Vec<(String, Expression)>) -> Self {
+        Self::StructInit(name, fields)
+    }
+
+    fn new_field_access(value: Expression, field: String) -> Self {
+        Self::FieldAccess(Box::new(value), field)
+    }
+
+    fn new_method_call(value: Expression, method: String, args: Vec<Expression>) -> Self {
+        Self::MethodCall(Box::new(value), method, args)
+    }
+
     fn new_nil() -> Self {
         Self::Nil
     }
@@ -90,8 +484,16 @@ impl Expression {
         Self::LetStmt(name, let_type, Box::new(value))
     }
 
-    fn new_block_stmt(exprs: Vec<Expression>) -> Self {
-        Self::BlockStmt(exprs)
+    fn new_assign_stmt(name: String, value: Expression) -> Self {
+        Self::AssignStmt(name, Box::new(value))
+    }
+
+    fn new_let_tuple_stmt(names: Vec<String>, value: Expression) -> Self {
+        Self::LetTupleStmt(names, Box::new(value))
+    }
+
+    fn new_block_stmt(exprs: Vec<Expression>, spans: Vec<Span>) -> Self {
+        Self::BlockStmt(exprs, BlockSpans(spans))
     }
 
     fn new_if_stmt(
@@ -110,6 +512,18 @@ impl Expression {
         Self::WhileStmt(Box::new(condition), Box::new(while_block_expr))
     }
 
+    fn new_do_while_stmt(do_block_expr: Expression, condition: Expression) -> Self {
+        Self::DoWhileStmt(Box::new(do_block_expr), Box::new(condition))
+    }
+
+    fn new_repeat_until_stmt(repeat_block_expr: Expression, condition: Expression) -> Self {
+        Self::RepeatUntilStmt(Box::new(repeat_block_expr), Box::new(condition))
+    }
+
+    fn new_loop_stmt(loop_block_expr: Expression) -> Self {
+        Self::LoopStmt(Box::new(loop_block_expr))
+    }
+
     fn new_for_stmt(
         var_name: String,
         start: i32,
@@ -120,17 +534,44 @@ impl Expression {
         Self::ForStmt(var_name, start, end, step, Box::new(for_block_expr))
     }
 
+    fn new_reversed_for_stmt(
+        var_name: String,
+        start: i32,
+        end: i32,
+        for_block_expr: Expression,
+    ) -> Self {
+        Self::ReversedForStmt(var_name, start, end, Box::new(for_block_expr))
+    }
+
+    fn new_for_each_stmt(
+        var_name: String,
+        list: Expression,
+        reversed: bool,
+        for_block_expr: Expression,
+    ) -> Self {
+        Self::ForEachStmt(var_name, Box::new(list), reversed, Box::new(for_block_expr))
+    }
+
     fn new_func_stmt(
         name: String,
         args: Vec<Expression>,
         return_type: Type,
         body: Expression,
+        attribute: Option<FuncAttribute>,
     ) -> Self {
-        Self::FuncStmt(name, args, return_type, Box::new(body))
+        Self::FuncStmt(name, args, return_type, Box::new(body), attribute)
     }
 
-    fn new_func_arg(name: String, arg_type: Type) -> Self {
-        Self::FuncArg(name, arg_type)
+    fn new_extern_func(name: String, args: Vec<(String, Type)>, return_type: Type) -> Self {
+        Self::ExternFunc(name, args, return_type)
+    }
+
+    fn new_global_stmt(name: String, global_type: Type, value: Expression) -> Self {
+        Self::GlobalStmt(name, global_type, Box::new(value))
+    }
+
+    fn new_func_arg(name: String, arg_type: Type, default: Option<Expression>) -> Self {
+        Self::FuncArg(name, arg_type, default.map(Box::new))
     }
 
     fn new_call_stmt(name: String, args: Vec<Expression>) -> Self {
@@ -141,15 +582,91 @@ impl Expression {
         Self::Print(Box::new(value))
     }
 
+    fn new_sizeof_stmt(size_type: Type) -> Self {
+        Self::SizeOf(size_type)
+    }
+
+    fn new_cast_stmt(cast_type: Type, value: Expression) -> Self {
+        Self::Cast(cast_type, Box::new(value))
+    }
+
+    fn new_is_expr(value: Expression, is_type: Type) -> Self {
+        Self::Is(Box::new(value), is_type)
+    }
+
     fn new_len_stmt(value: Expression) -> Self {
         Self::Len(Box::new(value))
     }
 
+    fn new_sum_stmt(value: Expression) -> Self {
+        Self::Sum(Box::new(value))
+    }
+
+    fn new_avg_stmt(value: Expression) -> Self {
+        Self::Avg(Box::new(value))
+    }
+
+    fn new_import(path: String) -> Self {
+        Self::Import(path)
+    }
+
+    fn new_windows_stmt(list: Expression, size: Expression) -> Self {
+        Self::Windows(Box::new(list), Box::new(size))
+    }
+
+    fn new_clamp_stmt(value: Expression, lo: Expression, hi: Expression) -> Self {
+        Self::Clamp(Box::new(value), Box::new(lo), Box::new(hi))
+    }
+
+    fn new_lerp_stmt(a: Expression, b: Expression, t: Expression) -> Self {
+        Self::Lerp(Box::new(a), Box::new(b), Box::new(t))
+    }
+
+    fn new_exit_stmt(code: Expression) -> Self {
+        Self::Exit(Box::new(code))
+    }
+
+    fn new_assert_stmt(cond: Expression) -> Self {
+        Self::Assert(Box::new(cond))
+    }
+
+    fn new_type_alias(name: String, alias_type: Type) -> Self {
+        Self::TypeAlias(name, alias_type)
+    }
+
+    fn new_format_stmt(format: String, args: Vec<Expression>) -> Self {
+        Self::Format(format, args)
+    }
+
+    fn new_fold_stmt(list: Expression, init: Expression, func_name: String) -> Self {
+        Self::Fold(Box::new(list), Box::new(init), func_name)
+    }
+
+    fn new_match_stmt(scrutinee: Expression, arms: Vec<(Expression, Expression)>) -> Self {
+        Self::Match(Box::new(scrutinee), arms)
+    }
+
+    fn new_switch_stmt(
+        scrutinee: Expression,
+        cases: Vec<(i32, Expression)>,
+        default: Option<Expression>,
+    ) -> Self {
+        Self::Switch(Box::new(scrutinee), cases, Box::new(default))
+    }
+
     fn new_return_stmt(value: Expression) -> Self {
         Self::ReturnStmt(Box::new(value))
     }
 }
 
+fn get_func_attribute(pair: pest::iterators::Pair<Rule>) -> FuncAttribute {
+    match pair.into_inner().next().map(|p| p.as_rule()) {
+        Some(Rule::noinline_attribute) => FuncAttribute::NoInline,
+        Some(Rule::export_attribute) => FuncAttribute::Export,
+        _ => FuncAttribute::Inline,
+    }
+}
+
 fn get_type(next: pest::iterators::Pair<Rule>) -> Type {
     let mut inner_pairs = next.into_inner();
     let next = inner_pairs.next().unwrap();
@@ -158,14 +675,129 @@ fn get_type(next: pest::iterators::Pair<Rule>) -> Type {
         Rule::bool_type => Type::Bool,
         Rule::i32_type => Type::i32,
         Rule::i64_type => Type::i64,
+        Rule::float_type => Type::Float,
         Rule::list_type => {
             let list_inner_type = get_type(next);
             Type::List(Box::new(list_inner_type))
         }
+        Rule::tuple_type => Type::Tuple(
+            next.into_inner()
+                .filter(|p| p.as_rule() == Rule::type_name)
+                .map(get_type)
+                .collect(),
+        ),
+        Rule::alias_type => Type::Alias(next.as_str().to_string()),
         _ => Type::None,
     }
 }
 
+// a default value is built without the caller's variables, so it can only use literals and the
+// arguments that come before it
+fn is_default_value(expr: &Expression, earlier_args: &[&str]) -> bool {
+    match expr {
+        Expression::Number(_)
+        | Expression::Number64(_)
+        | Expression::Float(_)
+        | Expression::String(_)
+        | Expression::Bool(_)
+        | Expression::Nil => true,
+        Expression::Variable(name) => earlier_args.contains(&name.as_str()),
+        Expression::List(items) | Expression::Tuple(items) => items
+            .iter()
+            .all(|item| is_default_value(item, earlier_args)),
+        Expression::Unary(_, operand) | Expression::Grouping(operand) => {
+            is_default_value(operand, earlier_args)
+        }
+        Expression::Binary(lhs, _, rhs) => {
+            is_default_value(lhs, earlier_args) && is_default_value(rhs, earlier_args)
+        }
+        _ => false,
+    }
+}
+
+fn parse_range_bound(
+    pair: pest::iterators::Pair<Rule>,
+) -> Result<i32, Box<pest::error::Error<Rule>>> {
+    let span = pair.as_span();
+    pair.as_str()
+        .replace(' ', "")
+        .parse()
+        .map_err(|e: ParseIntError| {
+            Box::new(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: e.to_string(),
+                },
+                span,
+            ))
+        })
+}
+
+// replaces `\u{...}` escapes in a string literal with the unicode scalar value they name,
+// other escapes are left as they are for codegen to replace
+fn parse_unicode_escapes(
+    pair: &pest::iterators::Pair<Rule>,
+) -> Result<String, Box<pest::error::Error<Rule>>> {
+    let input = pair.as_str();
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('\\') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if !rest.starts_with("\\u{") {
+            // the escaped character is skipped too, so the `u` in `\\u{...}` isn't an escape
+            let end = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        let escape = &rest[3..];
+        let decoded = escape.find('}').and_then(|end| {
+            let value = u32::from_str_radix(&escape[..end], 16).ok()?;
+            Some((char::from_u32(value)?, end))
+        });
+        let Some((value, end)) = decoded else {
+            return Err(Box::new(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("invalid unicode escape in {}", input),
+                },
+                pair.as_span(),
+            )));
+        };
+        // a decoded backslash is escaped again so codegen doesn't read it as an escape
+        if value == '\\' {
+            output.push('\\');
+        }
+        output.push(value);
+        rest = &escape[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Replaces the `\n`, `\t`, `\r`, `\\` and `\"` escapes in a string literal with the characters
+/// they stand for, `\u{...}` escapes have already been replaced by the parser. Any other escape
+/// is returned as the error, `\0` included since a string can't hold a NUL byte
+pub fn unescape(s: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            other => return Err(format!("\\{}", other.map(String::from).unwrap_or_default())),
+        };
+        output.push(escaped);
+    }
+    Ok(output)
+}
+
 fn parse_expression(
     pair: pest::iterators::Pair<Rule>,
 ) -> Result<Expression, Box<pest::error::Error<Rule>>> {
@@ -190,12 +822,23 @@ fn parse_expression(
                 Ok(n) => Ok(Expression::new_number(n)),
             }
         }
+        Rule::float => {
+            let n: f64 = pair.as_str().parse().map_err(|e: ParseFloatError| {
+                pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: e.to_string(),
+                    },
+                    pair.as_span(),
+                )
+            })?;
+            Ok(Expression::new_float(n))
+        }
         Rule::name => {
             let s = pair.as_str().to_string().replace(' ', "");
             Ok(Expression::new_variable(s))
         }
         Rule::string => {
-            let s = pair.as_str().to_string();
+            let s = parse_unicode_escapes(&pair)?;
             Ok(Expression::new_string(s))
         }
         Rule::bool => match pair.as_str() {
@@ -209,20 +852,88 @@ fn parse_expression(
             ))),
         },
         Rule::nil => Ok(Expression::new_nil()),
-        Rule::binary => {
+        Rule::binary => pratt_parser()
+            .map_primary(parse_expression)
+            .map_infix(|left, op, right| {
+                Ok(Expression::new_binary(
+                    left?,
+                    op.as_str().to_string(),
+                    right?,
+                ))
+            })
+            .parse(pair.into_inner()),
+        Rule::unary => {
             let mut inner_pairs = pair.into_inner();
-            let next = inner_pairs.next().unwrap();
-            let left = parse_expression(next)?;
             let op = inner_pairs.next().unwrap().as_str().to_string();
-            let right = parse_expression(inner_pairs.next().unwrap())?;
-            Ok(Expression::new_binary(left, op, right))
+            let expr = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::Unary(op, Box::new(expr)))
         }
         Rule::grouping => {
             let inner_pair = pair.into_inner().next().unwrap();
             parse_expression(inner_pair).map(|expr| Expression::Grouping(Box::new(expr)))
         }
-        Rule::let_stmt => {
+        Rule::tuple => {
+            let mut tuple = vec![];
+            for next in pair.into_inner().filter(|p| p.as_rule() != Rule::comma) {
+                tuple.push(parse_expression(next)?);
+            }
+            Ok(Expression::new_tuple(tuple))
+        }
+        Rule::struct_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let name = inner_pairs.next().unwrap().as_str().replace(' ', "");
+            let mut fields = vec![];
+            for field in inner_pairs.filter(|p| p.as_rule() == Rule::struct_field) {
+                let mut field_pairs = field.into_inner();
+                let field_type = get_type(field_pairs.next().unwrap());
+                let field_name = field_pairs.next().unwrap().as_str().trim().to_string();
+                fields.push((field_name, field_type));
+            }
+            Ok(Expression::new_struct_def(name, fields))
+        }
+        Rule::struct_init => {
             let mut inner_pairs = pair.into_inner();
+            let name = inner_pairs.next().unwrap().as_str().replace(' ', "");
+            let mut fields = vec![];
+            for field in inner_pairs.filter(|p| p.as_rule() == Rule::struct_init_field) {
+                let mut field_pairs = field.into_inner().filter(|p| p.as_rule() != Rule::colon);
+                let field_name = field_pairs.next().unwrap().as_str().replace(' ', "");
+                let value = parse_expression(field_pairs.next().unwrap())?;
+                fields.push((field_name, value));
+            }
+            Ok(Expression::new_struct_init(name, fields))
+        }
+        Rule::field_access => {
+            let mut inner_pairs = pair.into_inner();
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            let field = inner_pairs.next().unwrap().as_str().replace(' ', "");
+            Ok(Expression::new_field_access(value, field))
+        }
+        Rule::method_call => {
+            let mut inner_pairs = pair.into_inner();
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            let method = inner_pairs.next().unwrap().as_str().to_string();
+            let args = inner_pairs
+                .filter(|p| p.as_rule() != Rule::comma)
+                .map(parse_expression)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Expression::new_method_call(value, method, args))
+        }
+        Rule::let_tuple_stmt => {
+            let inner_pairs: Vec<_> = pair
+                .into_inner()
+                .filter(|p| !matches!(p.as_rule(), Rule::comma | Rule::equal))
+                .collect();
+            let (value, names) = inner_pairs.split_last().unwrap();
+            let names = names.iter().map(|p| p.as_str().replace(' ', "")).collect();
+            let value = parse_expression(value.clone())?;
+            Ok(Expression::new_let_tuple_stmt(names, value))
+        }
+        Rule::let_stmt => {
+            let mut inner_pairs = pair.into_inner().peekable();
+            let is_declaration = inner_pairs
+                .next_if(|pair| pair.as_rule() == Rule::let_keyword)
+                .is_some();
             let name = inner_pairs
                 .next()
                 .unwrap()
@@ -237,6 +948,10 @@ fn parse_expression(
                 inner_pairs.next();
             }
             let value = parse_expression(inner_pairs.next().unwrap())?;
+            // an annotated assignment declares the variable like `let` does
+            if !is_declaration && let_type == Type::None {
+                return Ok(Expression::new_assign_stmt(name, value));
+            }
             Ok(Expression::new_let_stmt(name, let_type, value))
         }
         Rule::expression => {
@@ -255,13 +970,142 @@ fn parse_expression(
             let value = parse_expression(inner_pair)?;
             Ok(Expression::new_print_stmt(value))
         }
+        Rule::builtin_stmt => {
+            let inner_pair = pair.into_inner().next().unwrap();
+            let builtin = parse_expression(inner_pair)?;
+            Ok(Expression::Builtin(Box::new(builtin)))
+        }
+        Rule::sizeof_stmt => {
+            let inner_pair = pair.into_inner().next().unwrap();
+            Ok(Expression::new_sizeof_stmt(get_type(inner_pair)))
+        }
+        Rule::cast_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let cast_type = get_type(inner_pairs.next().unwrap());
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_cast_stmt(cast_type, value))
+        }
+        Rule::is_expr => {
+            let mut inner_pairs = pair
+                .into_inner()
+                .filter(|p| p.as_rule() != Rule::is_keyword);
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            let is_type = get_type(inner_pairs.next().unwrap());
+            Ok(Expression::new_is_expr(value, is_type))
+        }
         Rule::len_stmt => {
             let inner_pair = pair.into_inner().next().unwrap();
             let value = parse_expression(inner_pair)?;
             Ok(Expression::new_len_stmt(value))
         }
+        Rule::sum_stmt => {
+            let inner_pair = pair.into_inner().next().unwrap();
+            let value = parse_expression(inner_pair)?;
+            Ok(Expression::new_sum_stmt(value))
+        }
+        Rule::avg_stmt => {
+            let inner_pair = pair.into_inner().next().unwrap();
+            let value = parse_expression(inner_pair)?;
+            Ok(Expression::new_avg_stmt(value))
+        }
+        Rule::fold_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let list = parse_expression(inner_pairs.next().unwrap())?;
+            let init = parse_expression(inner_pairs.next().unwrap())?;
+            let func_name = inner_pairs.next().unwrap().as_str().to_string();
+            Ok(Expression::new_fold_stmt(list, init, func_name))
+        }
+        Rule::import_stmt => {
+            let path = pair.into_inner().next().unwrap().as_str();
+            Ok(Expression::new_import(path[1..path.len() - 1].to_string()))
+        }
+        Rule::windows_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let list = parse_expression(inner_pairs.next().unwrap())?;
+            let size = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_windows_stmt(list, size))
+        }
+        Rule::clamp_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            let lo = parse_expression(inner_pairs.next().unwrap())?;
+            let hi = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_clamp_stmt(value, lo, hi))
+        }
+        Rule::lerp_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let a = parse_expression(inner_pairs.next().unwrap())?;
+            let b = parse_expression(inner_pairs.next().unwrap())?;
+            let t = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_lerp_stmt(a, b, t))
+        }
+        Rule::match_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let scrutinee = parse_expression(inner_pairs.next().unwrap())?;
+            let mut arms = vec![];
+            for arm in inner_pairs {
+                let mut arm_pairs = arm.into_inner();
+                let pattern = parse_expression(arm_pairs.next().unwrap())?;
+                let body = parse_expression(arm_pairs.next().unwrap())?;
+                arms.push((pattern, body));
+            }
+            Ok(Expression::new_match_stmt(scrutinee, arms))
+        }
+        Rule::wildcard => Ok(Expression::Wildcard),
+        Rule::switch_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let scrutinee = parse_expression(inner_pairs.next().unwrap())?;
+            let mut cases = vec![];
+            let mut default = None;
+            for case in inner_pairs {
+                let rule = case.as_rule();
+                let mut case_pairs = case.into_inner();
+                if rule == Rule::switch_default {
+                    default = Some(parse_expression(case_pairs.next().unwrap())?);
+                    continue;
+                }
+                let value_pair = case_pairs.next().unwrap();
+                let value: i32 = value_pair.as_str().parse().map_err(|e: ParseIntError| {
+                    pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: e.to_string(),
+                        },
+                        value_pair.as_span(),
+                    )
+                })?;
+                let body = parse_expression(case_pairs.next().unwrap())?;
+                cases.push((value, body));
+            }
+            Ok(Expression::new_switch_stmt(scrutinee, cases, default))
+        }
+        Rule::read_line_stmt => Ok(Expression::ReadLine),
+        Rule::read_int_stmt => Ok(Expression::ReadInt),
+        Rule::type_alias_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::equal);
+            let name = inner_pairs.next().unwrap().as_str().to_string();
+            let alias_type = get_type(inner_pairs.next().unwrap());
+            Ok(Expression::new_type_alias(name, alias_type))
+        }
+        Rule::format_stmt => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::comma);
+            let format = parse_unicode_escapes(&inner_pairs.next().unwrap())?;
+            let format = format[1..format.len() - 1].to_string();
+            let mut args = vec![];
+            for arg in inner_pairs {
+                args.push(parse_expression(arg)?);
+            }
+            Ok(Expression::new_format_stmt(format, args))
+        }
         Rule::func_stmt => {
+            let span = pair.as_span();
             let mut inner_pairs = pair.into_inner();
+            let mut attribute = None;
+            if inner_pairs
+                .peek()
+                .is_some_and(|p| p.as_rule() == Rule::func_attribute)
+            {
+                attribute = Some(get_func_attribute(inner_pairs.next().unwrap()));
+            }
             let name = inner_pairs.next().unwrap().as_str().to_string();
 
             // Does this handle no args?
@@ -274,6 +1118,46 @@ fn parse_expression(
                 let args: pest::iterators::Pair<'_, Rule> = inner_pairs.next().unwrap();
                 func_args.push(parse_expression(args)?);
             }
+            // a call can only leave out trailing arguments, so once an argument has a default
+            // every argument after it needs one
+            let first_default = func_args
+                .iter()
+                .position(|arg| matches!(arg, Expression::FuncArg(_, _, Some(_))));
+            if let Some(first_default) = first_default {
+                if let Some(Expression::FuncArg(arg_name, _, _)) = func_args[first_default..]
+                    .iter()
+                    .find(|arg| matches!(arg, Expression::FuncArg(_, _, None)))
+                {
+                    return Err(Box::new(pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!(
+                                "argument {} needs a default value, it comes after an argument with one",
+                                arg_name
+                            ),
+                        },
+                        span,
+                    )));
+                }
+            }
+            let mut earlier_args = vec![];
+            for arg in &func_args {
+                if let Expression::FuncArg(arg_name, _, default) = arg {
+                    if let Some(default) = default {
+                        if !is_default_value(default, &earlier_args) {
+                            return Err(Box::new(pest::error::Error::new_from_span(
+                                pest::error::ErrorVariant::CustomError {
+                                    message: format!(
+                                        "the default value of argument {} can only use literals and the arguments before it",
+                                        arg_name
+                                    ),
+                                },
+                                span,
+                            )));
+                        }
+                    }
+                    earlier_args.push(arg_name.as_str());
+                }
+            }
 
             let mut func_type = Type::None;
             // Get function type or default to none
@@ -287,9 +1171,51 @@ fn parse_expression(
             }
             let inner = inner_pairs.next().unwrap();
             let body = parse_expression(inner)?;
-            let func = Expression::new_func_stmt(name, func_args, func_type, body);
+            let func = Expression::new_func_stmt(name, func_args, func_type, body, attribute);
             Ok(func)
         }
+        Rule::extern_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let name = inner_pairs.next().unwrap().as_str().to_string();
+            let mut args = vec![];
+            let mut return_type = Type::None;
+            for next in inner_pairs {
+                match next.as_rule() {
+                    Rule::func_arg => {
+                        let span = next.as_span();
+                        match parse_expression(next)? {
+                            Expression::FuncArg(arg_name, arg_type, None) => {
+                                args.push((arg_name, arg_type))
+                            }
+                            Expression::FuncArg(_, _, Some(_)) => {
+                                return Err(Box::new(pest::error::Error::new_from_span(
+                                    pest::error::ErrorVariant::CustomError {
+                                        message:
+                                            "extern function arguments can't have default values"
+                                                .to_string(),
+                                    },
+                                    span,
+                                )))
+                            }
+                            _ => unreachable!("a func_arg is always parsed as a FuncArg"),
+                        }
+                    }
+                    Rule::type_name => return_type = get_type(next),
+                    _ => {}
+                }
+            }
+            Ok(Expression::new_extern_func(name, args, return_type))
+        }
+        Rule::global_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            inner_pairs.next();
+            let name = inner_pairs.next().unwrap().as_str().to_string();
+            inner_pairs.next();
+            let global_type = get_type(inner_pairs.next().unwrap());
+            inner_pairs.next();
+            let value = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_global_stmt(name, global_type, value))
+        }
         Rule::func_arg => {
             let mut inner_pairs = pair.clone().into_inner();
             while inner_pairs.peek().map_or(false, |p| {
@@ -315,7 +1241,14 @@ fn parse_expression(
                             pair.as_span(),
                         )));
                     }
-                    return Ok(Expression::new_func_arg(arg_name, arg_type));
+                    // `= default` after the name
+                    let default = match inner_pairs.find(|p| p.as_rule() != Rule::equal) {
+                        Some(default) if default.as_rule() != Rule::comma => {
+                            Some(parse_expression(default)?)
+                        }
+                        _ => None,
+                    };
+                    return Ok(Expression::new_func_arg(arg_name, arg_type, default));
                 }
             }
             unreachable!("Unable to parse args {:?}", inner_pairs)
@@ -329,6 +1262,7 @@ fn parse_expression(
                 p.as_rule() == Rule::comma
                     || p.as_rule() == Rule::binary
                     || p.as_rule() == Rule::literal
+                    || p.as_rule() == Rule::field_access
                     || p.as_rule() == Rule::name
             }) {
                 let next = inner_pairs.next().unwrap();
@@ -343,16 +1277,18 @@ fn parse_expression(
         Rule::block_stmt => {
             let inner_pairs = pair.into_inner();
             let mut expressions = Vec::new();
+            let mut spans = Vec::new();
 
             for inner_pair in inner_pairs {
                 let rule = inner_pair.as_rule();
                 if rule == Rule::semicolon {
                     continue;
                 }
+                spans.push(span_of(&inner_pair));
                 expressions.push(parse_expression(inner_pair)?);
             }
 
-            Ok(Expression::new_block_stmt(expressions))
+            Ok(Expression::new_block_stmt(expressions, spans))
         }
         Rule::if_stmt => {
             let mut inner_pairs = pair.into_inner();
@@ -393,6 +1329,57 @@ fn parse_expression(
                 var_name, start, end, step, block_stmt,
             ))
         }
+        Rule::range_for_stmt => {
+            let span = pair.as_span();
+            let mut inner_pairs = pair.into_inner();
+            let var_name = inner_pairs.next().unwrap().as_str().to_string();
+            let range_pair = inner_pairs.next().unwrap();
+            if range_pair.as_rule() == Rule::reversed_range {
+                let mut bounds = range_pair.into_inner();
+                let start = parse_range_bound(bounds.next().unwrap())?;
+                let end = parse_range_bound(bounds.next().unwrap())?;
+                let block_stmt = parse_expression(inner_pairs.next().unwrap())?;
+                return Ok(Expression::new_reversed_for_stmt(
+                    var_name, start, end, block_stmt,
+                ));
+            }
+            let start = parse_range_bound(range_pair)?;
+            let end = parse_range_bound(inner_pairs.next().unwrap())?;
+
+            let mut step = 1;
+            let mut block_pair = inner_pairs.next().unwrap();
+            if block_pair.as_rule() == Rule::range_step {
+                step = parse_range_bound(block_pair.into_inner().next().unwrap())?;
+                block_pair = inner_pairs.next().unwrap();
+            }
+            if step == 0 {
+                return Err(Box::new(pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: "range step must not be zero".to_string(),
+                    },
+                    span,
+                )));
+            }
+            let block_stmt = parse_expression(block_pair)?;
+            Ok(Expression::new_for_stmt(
+                var_name, start, end, step, block_stmt,
+            ))
+        }
+        Rule::list_for_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let var_name = inner_pairs.next().unwrap().as_str().to_string();
+            let list_pair = inner_pairs.next().unwrap();
+            let reversed = list_pair.as_rule() == Rule::reversed_list;
+            let list = if reversed {
+                parse_expression(list_pair.into_inner().next().unwrap())?
+            } else {
+                parse_expression(list_pair)?
+            };
+            let block_stmt = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_for_each_stmt(
+                var_name, list, reversed, block_stmt,
+            ))
+        }
         Rule::return_stmt => {
             let inner_pairs = pair.into_inner().next().unwrap();
             let expr = parse_expression(inner_pairs)?;
@@ -404,6 +1391,24 @@ fn parse_expression(
             let while_block_expr = parse_expression(inner_pairs.next().unwrap())?;
             Ok(Expression::new_while_stmt(cond, while_block_expr))
         }
+        Rule::do_while_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let do_block_expr = parse_expression(inner_pairs.next().unwrap())?;
+            let cond = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_do_while_stmt(do_block_expr, cond))
+        }
+        Rule::repeat_until_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let repeat_block_expr = parse_expression(inner_pairs.next().unwrap())?;
+            let cond = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_repeat_until_stmt(repeat_block_expr, cond))
+        }
+        Rule::loop_stmt => {
+            let mut inner_pairs = pair.into_inner();
+            let loop_block_expr = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_loop_stmt(loop_block_expr))
+        }
+        Rule::break_stmt => Ok(Expression::BreakStmt),
         Rule::list => {
             let mut inner_pairs = pair.into_inner();
             let mut list = vec![];
@@ -427,6 +1432,13 @@ fn parse_expression(
             let index_expr = parse_expression(inner_pairs.next().unwrap())?;
             Ok(Expression::new_list_index(array_expr, index_expr))
         }
+        Rule::slice_expr => {
+            let mut inner_pairs = pair.into_inner().filter(|p| p.as_rule() != Rule::lbracket);
+            let list_expr = parse_expression(inner_pairs.next().unwrap())?;
+            let start = parse_expression(inner_pairs.next().unwrap())?;
+            let end = parse_expression(inner_pairs.next().unwrap())?;
+            Ok(Expression::new_slice(list_expr, start, end))
+        }
         Rule::index_stmt => {
             let mut inner_pairs = pair.into_inner();
             let mut array_expr_inner = inner_pairs.next().unwrap().into_inner();
@@ -454,9 +1466,18 @@ fn parse_expression(
     }
 }
 
+fn span_of(pair: &pest::iterators::Pair<Rule>) -> Span {
+    let (line, col) = pair.line_col();
+    Span {
+        line: line as u32,
+        col: col as u32,
+        len: pair.as_str().chars().count() as u32,
+    }
+}
+
 fn parse_program(
     pair: pest::iterators::Pair<Rule>,
-) -> Result<Vec<Expression>, Box<pest::error::Error<Rule>>> {
+) -> Result<Vec<Spanned<Expression>>, Box<pest::error::Error<Rule>>> {
     let mut expr_vec = vec![];
     for stmt_pair in pair.into_inner() {
         match stmt_pair.as_rule() {
@@ -464,15 +1485,18 @@ fn parse_program(
                 continue;
             }
             _ => {
-                let expr = parse_expression(stmt_pair)?;
-                expr_vec.push(expr);
+                let span = span_of(&stmt_pair);
+                let node = parse_expression(stmt_pair)?;
+                expr_vec.push(Spanned { node, span });
             }
         }
     }
     Ok(expr_vec)
 }
 
-pub fn parse_cyclo_program(input: &str) -> Result<Vec<Expression>, Box<pest::error::Error<Rule>>> {
+fn parse_cyclo_source(
+    input: &str,
+) -> Result<Vec<Spanned<Expression>>, Box<pest::error::Error<Rule>>> {
     match CycloParser::parse(Rule::expression_list, input) {
         Ok(mut pairs) => {
             // TODO: only returns first pair
@@ -486,6 +1510,124 @@ pub fn parse_cyclo_program(input: &str) -> Result<Vec<Expression>, Box<pest::err
     unreachable!("parse function program")
 }
 
+// Tracks the files seen while resolving `import` statements
+#[derive(Default)]
+struct ImportContext {
+    // files that are part of the current chain of imports, seeing one again is circular
+    importing: HashSet<PathBuf>,
+    // files that have already been merged, importing them again is a no-op
+    imported: HashSet<PathBuf>,
+}
+
+fn import_error(message: String) -> Box<pest::error::Error<Rule>> {
+    Box::new(pest::error::Error::new_from_pos(
+        pest::error::ErrorVariant::CustomError { message },
+        pest::Position::from_start(""),
+    ))
+}
+
+fn parse_cyclo_path(
+    path: &Path,
+    context: &mut ImportContext,
+) -> Result<Vec<Spanned<Expression>>, Box<pest::error::Error<Rule>>> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| import_error(format!("unable to read {:?}: {}", path, e)))?;
+    if context.importing.contains(&path) {
+        return Err(import_error(format!("circular import of {:?}", path)));
+    }
+    if context.imported.contains(&path) {
+        return Ok(vec![]);
+    }
+    let input = fs::read_to_string(&path)
+        .map_err(|e| import_error(format!("unable to read {:?}: {}", path, e)))?;
+    let path_name = path.to_string_lossy().to_string();
+    let exprs = parse_cyclo_source(&input).map_err(|e| Box::new(e.with_path(&path_name)))?;
+
+    context.importing.insert(path.clone());
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let exprs = resolve_imports(exprs, base_dir, context)?;
+    context.importing.remove(&path);
+    context.imported.insert(path);
+    Ok(exprs)
+}
+
+// Replaces each `import "path"` with the top level function and type declarations of
+// the imported file, paths are relative to the directory of the importing file
+fn resolve_imports(
+    exprs: Vec<Spanned<Expression>>,
+    base_dir: &Path,
+    context: &mut ImportContext,
+) -> Result<Vec<Spanned<Expression>>, Box<pest::error::Error<Rule>>> {
+    let mut resolved = vec![];
+    for expr in exprs {
+        match expr.node {
+            Expression::Import(path) => {
+                let imported = parse_cyclo_path(&base_dir.join(path), context)?;
+                resolved.extend(imported.into_iter().filter(|expr| {
+                    matches!(
+                        expr.node,
+                        Expression::FuncStmt(..)
+                            | Expression::ExternFunc(..)
+                            | Expression::TypeAlias(..)
+                    )
+                }));
+            }
+            _ => resolved.push(expr),
+        }
+    }
+    Ok(resolved)
+}
+
+// Names rules after the tokens a user would write, so `expected semicolon` reads as `expected `;``
+fn rename_rule(rule: &Rule) -> String {
+    match rule {
+        Rule::semicolon => "`;`".to_string(),
+        Rule::colon => "`:`".to_string(),
+        Rule::comma => "`,`".to_string(),
+        Rule::equal => "`=`".to_string(),
+        Rule::arrow => "`->`".to_string(),
+        Rule::lbracket => "`[`".to_string(),
+        Rule::rbracket => "`]`".to_string(),
+        Rule::EOI => "end of input".to_string(),
+        _ => format!("{:?}", rule).replace('_', " "),
+    }
+}
+
+// Formats a parse error with the file path, line and column of the offending token and the
+// source line with a caret underneath it
+fn format_parse_error(error: pest::error::Error<Rule>) -> String {
+    error.renamed_rules(rename_rule).to_string()
+}
+
+fn strip_spans(exprs: Vec<Spanned<Expression>>) -> Vec<Expression> {
+    exprs.into_iter().map(|expr| expr.node).collect()
+}
+
+/// Parses a program from a string, imports are resolved relative to the current directory.
+/// Errors are returned formatted for display
+pub fn parse_cyclo_program(input: &str) -> Result<Vec<Expression>, String> {
+    parse_cyclo_program_spanned(input).map(strip_spans)
+}
+
+/// Like `parse_cyclo_program`, keeping where each top level statement starts
+pub fn parse_cyclo_program_spanned(input: &str) -> Result<Vec<Spanned<Expression>>, String> {
+    parse_cyclo_source(input)
+        .and_then(|exprs| resolve_imports(exprs, Path::new("."), &mut ImportContext::default()))
+        .map_err(|e| format_parse_error(*e))
+}
+
+/// Parses the program in the file at `path`, imports are resolved relative to its directory.
+/// Errors are returned formatted for display
+pub fn parse_cyclo_file(path: &Path) -> Result<Vec<Expression>, String> {
+    parse_cyclo_file_spanned(path).map(strip_spans)
+}
+
+/// Like `parse_cyclo_file`, keeping where each top level statement starts
+pub fn parse_cyclo_file_spanned(path: &Path) -> Result<Vec<Spanned<Expression>>, String> {
+    parse_cyclo_path(path, &mut ImportContext::default()).map_err(|e| format_parse_error(*e))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -515,6 +1657,12 @@ mod test {
         assert!(parse_cyclo_program(input).is_err());
     }
 
+    #[test]
+    fn test_parse_name_with_whitespace_err() {
+        let input = r#"print(foo bar);"#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
     #[test]
     fn test_parse_number_expression() {
         let input = r#"555;"#;
@@ -527,6 +1675,43 @@ mod test {
         assert!(parse_cyclo_program(input).is_ok());
     }
 
+    #[test]
+    fn test_parse_mul_binds_tighter_than_add() {
+        let output = parse_cyclo_program(r#"1 + 2 * 3;"#);
+        let expected = Expression::new_binary(
+            Number(1),
+            "+".into(),
+            Expression::new_binary(Number(2), "*".into(), Number(3)),
+        );
+        assert_eq!(output.unwrap(), vec![expected]);
+    }
+
+    #[test]
+    fn test_parse_sub_is_left_associative() {
+        let output = parse_cyclo_program(r#"2 * 3 + 1 - 4;"#);
+        let expected = Expression::new_binary(
+            Expression::new_binary(
+                Expression::new_binary(Number(2), "*".into(), Number(3)),
+                "+".into(),
+                Number(1),
+            ),
+            "-".into(),
+            Number(4),
+        );
+        assert_eq!(output.unwrap(), vec![expected]);
+    }
+
+    #[test]
+    fn test_parse_comparison_binds_loosest() {
+        let output = parse_cyclo_program(r#"x + 1 == y * 2;"#);
+        let expected = Expression::new_binary(
+            Expression::new_binary(Variable("x".into()), "+".into(), Number(1)),
+            "==".into(),
+            Expression::new_binary(Variable("y".into()), "*".into(), Number(2)),
+        );
+        assert_eq!(output.unwrap(), vec![expected]);
+    }
+
     #[test]
     fn test_parse_number_expression_add_grouping() {
         let input = r#"(555 + 555) + (555 + 555);"#;
@@ -560,6 +1745,14 @@ mod test {
         assert!(parse_cyclo_program(input).is_err());
     }
 
+    #[test]
+    fn test_parse_float_expression() {
+        let output = parse_cyclo_program(r#"0.1 + -0.2;"#);
+        let expected =
+            Expression::new_binary(Expression::Float(0.1), "+".into(), Expression::Float(-0.2));
+        assert_eq!(output.unwrap(), vec![expected]);
+    }
+
     #[test]
     fn test_parse_nil() {
         let input = r#"nil;"#;
@@ -740,8 +1933,7 @@ mod test {
     #[test]
     fn test_parse_let_stmt_list_string() {
         let input = r#"let value: List<string> = ["1", "2", "3", "4"];"#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let list_expr = Expression::List(vec![
             Expression::String("\"1\"".to_string()),
             Expression::String("\"2\"".to_string()),
@@ -758,8 +1950,7 @@ mod test {
     #[test]
     fn test_parse_let_stmt_list_of_lists_bool() {
         let input = r#"let value: List<List<bool>> = [[true,false],[true,false]];"#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let list_expr = Expression::List(vec![Expression::Bool(true), Expression::Bool(false)]);
         let list_of_list_expr = Expression::List(vec![list_expr.clone(), list_expr]);
         let list_type = Type::List(Box::new(Type::Bool));
@@ -776,8 +1967,7 @@ mod test {
     #[test]
     fn test_parse_let_stmt_list_of_lists_int() {
         let input = r#"let value: List<List<i32>> = [[1,2],[1,2]];"#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let list_expr = Expression::List(vec![Number(1), Number(2)]);
         let list_of_list_expr = Expression::List(vec![list_expr.clone(), list_expr]);
         let list_type = Type::List(Box::new(Type::i32));
@@ -828,6 +2018,38 @@ mod test {
         assert!(parse_cyclo_program(input).is_ok());
     }
 
+    #[test]
+    fn test_block_comments() {
+        let input = r#"
+        /* a comment
+           spanning lines */
+        let value = 1 /* inline */ + 2;
+        fn add(i32 a, /* the second */ i32 b) -> i32 {
+            return a + b; // trailing
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "value".into(),
+                Type::None,
+                Box::new(Expression::Binary(
+                    Box::new(Number(1)),
+                    "+".into(),
+                    Box::new(Number(2))
+                ))
+            )
+        );
+        assert_eq!(output.len(), 2);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment_is_error() {
+        let input = "let value = 1; /* never closed";
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
     #[test]
     fn test_parse_multi_line_stmt() {
         let input = "
@@ -888,8 +2110,7 @@ mod test {
         }
         print(get_ten([[1,2],[1,3]]));
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         assert!(output.is_ok());
     }
 
@@ -901,8 +2122,7 @@ mod test {
         }
         let var = get_ten();
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "get_ten".into(),
             [].to_vec(),
@@ -920,11 +2140,10 @@ mod test {
             return value;
         }
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "get_value".into(),
-            [FuncArg("value".into(), Type::i32)].to_vec(),
+            [FuncArg("value".into(), Type::i32, None)].to_vec(),
             Type::i32,
             vec![Expression::ReturnStmt(Box::new(Expression::Variable(
                 "value".into(),
@@ -941,11 +2160,10 @@ mod test {
             return value;
         }
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "get_value".into(),
-            [FuncArg("value".into(), Type::String)].to_vec(),
+            [FuncArg("value".into(), Type::String, None)].to_vec(),
             Type::String,
             vec![Expression::ReturnStmt(Box::new(Expression::Variable(
                 "value".into(),
@@ -965,13 +2183,12 @@ mod test {
             return x + y;
         }
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "add".into(),
             [
-                FuncArg("x".into(), Type::i32),
-                FuncArg("y".into(), Type::i32),
+                FuncArg("x".into(), Type::i32, None),
+                FuncArg("y".into(), Type::i32, None),
             ]
             .to_vec(),
             Type::i32,
@@ -992,8 +2209,8 @@ mod test {
         return_type: Type,
         block_stmt: Vec<Expression>,
     ) -> Expression {
-        let body = Expression::BlockStmt(block_stmt);
-        Expression::new_func_stmt(name, args, return_type, body)
+        let body = Expression::new_block_stmt(block_stmt, vec![]);
+        Expression::new_func_stmt(name, args, return_type, body, None)
     }
 
     #[test]
@@ -1004,8 +2221,7 @@ mod test {
         }
         let val = hello_world();
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "hello_world".into(),
             [].to_vec(),
@@ -1026,8 +2242,7 @@ mod test {
         }
         let val = hello_world();
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "hello_bool".into(),
             [].to_vec(),
@@ -1038,6 +2253,52 @@ mod test {
         assert!(output.unwrap().contains(&func_expr))
     }
 
+    #[test]
+    fn test_block_statement_spans() {
+        let input = "fn f() {\n    let a = 1;\n    if (a > 0) {\n      print(a);\n    }\n}";
+        let exprs = parse_cyclo_program_spanned(input).unwrap();
+        let (body, if_block) = match &exprs[0].node {
+            Expression::FuncStmt(_, _, _, body, _) => match body.as_ref() {
+                Expression::BlockStmt(stmts, spans) => match &stmts[1] {
+                    Expression::IfStmt(_, if_block, _) => (spans.clone(), if_block.clone()),
+                    _ => panic!("expected an if statement"),
+                },
+                _ => panic!("expected a block"),
+            },
+            _ => panic!("expected a function"),
+        };
+        let lines: Vec<(u32, u32)> = body.0.iter().map(|span| (span.line, span.col)).collect();
+        assert_eq!(lines, [(2, 5), (3, 5)]);
+        match *if_block {
+            Expression::BlockStmt(_, spans) => assert_eq!(spans.get(0).unwrap().line, 4),
+            _ => panic!("expected a block"),
+        }
+    }
+
+    #[test]
+    fn test_fn_return_call() {
+        let input = r#"
+        fn is_even(i32 n) -> bool {
+            return is_odd(n - 1);
+        }
+        "#;
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
+        let func_expr = build_basic_func_ast(
+            "is_even".into(),
+            vec![FuncArg("n".into(), Type::i32, None)],
+            Type::Bool,
+            vec![Expression::ReturnStmt(Box::new(Expression::CallStmt(
+                "is_odd".into(),
+                vec![Expression::Binary(
+                    Box::new(Variable("n".into())),
+                    "-".into(),
+                    Box::new(Number(1)),
+                )],
+            )))],
+        );
+        assert_eq!(output.unwrap(), vec![func_expr]);
+    }
+
     #[test]
     fn test_fn_return_call_fn_binary_add() {
         let input = r#"
@@ -1046,13 +2307,12 @@ mod test {
         }
         let val = sum_square(x,y);
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         let func_expr = build_basic_func_ast(
             "sum_square".into(),
             [
-                FuncArg("x".into(), Type::i32),
-                FuncArg("y".into(), Type::i32),
+                FuncArg("x".into(), Type::i32, None),
+                FuncArg("y".into(), Type::i32, None),
             ]
             .to_vec(),
             Type::i32,
@@ -1083,8 +2343,7 @@ mod test {
         }
         fib(20);
         "#;
-        let output: Result<Vec<Expression>, Box<pest::error::Error<Rule>>> =
-            parse_cyclo_program(input);
+        let output: Result<Vec<Expression>, String> = parse_cyclo_program(input);
         assert!(output.is_ok());
         // assert!(output.unwrap().contains(&func_expr)); to test?
     }
@@ -1158,6 +2417,415 @@ mod test {
         assert!(parse_cyclo_program(input).is_ok());
     }
     #[test]
+    fn test_compare_call_stmts() {
+        let input = r#"
+        print(is_ready() == is_done());
+        let same = is_ready() != is_done();
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        let expected = Expression::Binary(
+            Box::new(Expression::CallStmt("is_ready".into(), vec![])),
+            "==".into(),
+            Box::new(Expression::CallStmt("is_done".into(), vec![])),
+        );
+        assert_eq!(output[0], Expression::Print(Box::new(expected)));
+        match &output[1] {
+            Expression::LetStmt(_, _, rhs) => {
+                assert!(matches!(**rhs, Expression::Binary(_, _, _)))
+            }
+            other => panic!("expected let statement, got {:?}", other),
+        }
+    }
+    #[test]
+    fn test_bitwise_not() {
+        let input = r#"print(~5 + ~x);"#;
+        let output = parse_cyclo_program(input).unwrap();
+        let expected = Expression::Binary(
+            Box::new(Expression::Unary("~".into(), Box::new(Number(5)))),
+            "+".into(),
+            Box::new(Expression::Unary(
+                "~".into(),
+                Box::new(Expression::Variable("x".into())),
+            )),
+        );
+        assert_eq!(output[0], Expression::Print(Box::new(expected)));
+    }
+    #[test]
+    fn test_func_attributes() {
+        let input = r#"
+        #[inline]
+        fn add_one(i32 x) -> i32 {
+            return x + 1;
+        }
+        #[noinline]
+        fn log() {
+            print("log");
+        }
+        #[export]
+        fn add(i32 a, i32 b) -> i32 {
+            return a + b;
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert!(matches!(
+            &output[0],
+            Expression::FuncStmt(_, _, _, _, Some(FuncAttribute::Inline))
+        ));
+        assert!(matches!(
+            &output[1],
+            Expression::FuncStmt(_, _, _, _, Some(FuncAttribute::NoInline))
+        ));
+        assert!(matches!(
+            &output[2],
+            Expression::FuncStmt(_, _, _, _, Some(FuncAttribute::Export))
+        ));
+    }
+    #[test]
+    fn test_tuple_return_and_destructure() {
+        let input = r#"
+        fn divmod(i32 a, i32 b) -> (i32, i32) {
+            return (a / b, a - (a / b) * b);
+        }
+        let (q, r) = divmod(7, 2);
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        match &output[0] {
+            Expression::FuncStmt(_, _, return_type, _, _) => {
+                assert_eq!(return_type, &Type::Tuple(vec![Type::i32, Type::i32]))
+            }
+            expr => panic!("expected a function, got {:?}", expr),
+        }
+        assert_eq!(
+            output[1],
+            Expression::LetTupleStmt(
+                vec!["q".into(), "r".into()],
+                Box::new(Expression::CallStmt(
+                    "divmod".into(),
+                    vec![Expression::Number(7), Expression::Number(2)]
+                ))
+            )
+        );
+    }
+    #[test]
+    fn test_struct_def_init_and_field_access() {
+        let input = r#"
+        struct Point {
+            i32 x,
+            i32 y
+        }
+        let point = Point { x: 1, y: 2 };
+        print(point.x);
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::StructDef(
+                "Point".into(),
+                vec![("x".into(), Type::i32), ("y".into(), Type::i32)]
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::LetStmt(
+                "point".into(),
+                Type::None,
+                Box::new(Expression::StructInit(
+                    "Point".into(),
+                    vec![
+                        ("x".into(), Expression::Number(1)),
+                        ("y".into(), Expression::Number(2))
+                    ]
+                ))
+            )
+        );
+        assert_eq!(
+            output[2],
+            Expression::Print(Box::new(Expression::FieldAccess(
+                Box::new(Expression::Variable("point".into())),
+                "x".into()
+            )))
+        );
+    }
+    #[test]
+    fn test_sum_and_avg_stmt() {
+        let input = r#"
+        let total = sum(values);
+        print(avg(values));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "total".into(),
+                Type::None,
+                Box::new(Expression::Sum(Box::new(Expression::Variable(
+                    "values".into()
+                ))))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::Avg(Box::new(Expression::Variable(
+                "values".into()
+            )))))
+        );
+    }
+    #[test]
+    fn test_clamp_and_lerp_stmt() {
+        let input = r#"
+        let x = clamp(value, 0.0, 1.0);
+        print(lerp(0.0, 10.0, t) == 5.0);
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "x".into(),
+                Type::None,
+                Box::new(Expression::Clamp(
+                    Box::new(Expression::Variable("value".into())),
+                    Box::new(Expression::Float(0.0)),
+                    Box::new(Expression::Float(1.0)),
+                ))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::Binary(
+                Box::new(Expression::Lerp(
+                    Box::new(Expression::Float(0.0)),
+                    Box::new(Expression::Float(10.0)),
+                    Box::new(Expression::Variable("t".into())),
+                )),
+                "==".into(),
+                Box::new(Expression::Float(5.0)),
+            )))
+        );
+    }
+    #[test]
+    fn test_float_type() {
+        let input = r#"
+        fn half(float x) -> float {
+            return x / 2.0;
+        }
+        let y: float = half(5.0);
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert!(matches!(
+            &output[0],
+            Expression::FuncStmt(_, args, Type::Float, _, None)
+                if args == &vec![Expression::FuncArg("x".into(), Type::Float, None)]
+        ));
+        assert!(matches!(&output[1], Expression::LetStmt(_, Type::Float, _)));
+    }
+    #[test]
+    fn test_string_unicode_escape() {
+        let output = parse_cyclo_program(r#"let face = "\u{1F600}";"#).unwrap();
+        let Expression::LetStmt(_, _, value) = &output[0] else {
+            panic!("expected a let statement, got {:?}", output[0]);
+        };
+        assert_eq!(**value, Expression::String("\"\u{1F600}\"".into()));
+        let Expression::String(value) = value.as_ref() else {
+            unreachable!()
+        };
+        // the emoji is 4 bytes of UTF-8 between the quotes
+        assert_eq!(value.len(), 6);
+        assert!(parse_cyclo_program(r#"let bad = "\u{D800}";"#).is_err());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r#"a\nb\tc\rd\\e\"f"#).unwrap(), "a\nb\tc\rd\\e\"f");
+        assert_eq!(unescape(r"a\qb").unwrap_err(), "\\q");
+        assert_eq!(unescape(r"a\0b").unwrap_err(), "\\0");
+        assert_eq!(unescape("a\\").unwrap_err(), "\\");
+    }
+    #[test]
+    fn test_reversed_for_stmt() {
+        let input = r#"
+        for x in reversed([1, 2, 3]) {
+            print(x);
+        }
+        for i in reversed(0..3) {
+            print(i);
+        }
+        for name in names {
+            print(name);
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        let print_x = Expression::new_block_stmt(
+            vec![Expression::Print(Box::new(Variable("x".into())))],
+            vec![],
+        );
+        assert_eq!(
+            output[0],
+            Expression::ForEachStmt(
+                "x".into(),
+                Box::new(Expression::List(vec![Number(1), Number(2), Number(3)])),
+                true,
+                Box::new(print_x)
+            )
+        );
+        assert!(matches!(
+            &output[1],
+            Expression::ReversedForStmt(_, 0, 3, _)
+        ));
+        assert!(matches!(
+            &output[2],
+            Expression::ForEachStmt(_, list, false, _) if **list == Variable("names".into())
+        ));
+    }
+    #[test]
+    fn test_parse_error_shows_location_and_caret() {
+        let input = "let a = 1;\nlet b = ;";
+        let error = parse_cyclo_program(input).unwrap_err();
+        assert!(error.contains("--> 2:"));
+        assert!(error.contains("let b = ;"));
+        assert!(error.contains('^'));
+    }
+
+    #[test]
+    fn test_builtin_stmt() {
+        let input = r#"
+        let total = builtin.sum(values);
+        print(builtin.len(values));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        let builtin_sum = Expression::Builtin(Box::new(Expression::Sum(Box::new(
+            Expression::Variable("values".into()),
+        ))));
+        assert_eq!(
+            output[0],
+            Expression::LetStmt("total".into(), Type::None, Box::new(builtin_sum))
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::Builtin(Box::new(Expression::Len(
+                Box::new(Expression::Variable("values".into()))
+            )))))
+        );
+    }
+
+    #[test]
+    fn test_statement_spans() {
+        let input = "let a = 1;\n\n  print(a);";
+        let output = parse_cyclo_program_spanned(input).unwrap();
+        assert_eq!(
+            output[0].span,
+            Span {
+                line: 1,
+                col: 1,
+                len: 9
+            }
+        );
+        assert_eq!(
+            output[1].span,
+            Span {
+                line: 3,
+                col: 3,
+                len: 8
+            }
+        );
+        assert_eq!(output[1].span.to_string(), "3:3");
+    }
+
+    #[test]
+    fn test_let_if_expression() {
+        let input = r#"
+        let x = if (a > 1) { 1; } else { 2; };
+        "#;
+        let output = parse_cyclo_program(input);
+        let if_expr = Expression::new_if_stmt(
+            Expression::new_binary(Variable("a".into()), ">".into(), Number(1)),
+            Expression::new_block_stmt(vec![Number(1)], vec![]),
+            Some(Expression::new_block_stmt(vec![Number(2)], vec![])),
+        );
+        let let_stmt_expr = Expression::LetStmt("x".into(), Type::None, Box::new(if_expr));
+        assert!(output.is_ok());
+        assert!(output.unwrap().contains(&let_stmt_expr))
+    }
+
+    #[test]
+    fn test_block_ending_in_expression() {
+        let input = r#"
+        let x = if (a > 1) { let b = 2; b * 5 } else { 5 };
+        "#;
+        let output = parse_cyclo_program(input);
+        let if_expr = Expression::new_if_stmt(
+            Expression::new_binary(Variable("a".into()), ">".into(), Number(1)),
+            Expression::new_block_stmt(
+                vec![
+                    Expression::new_let_stmt("b".into(), Type::None, Number(2)),
+                    Expression::new_binary(Variable("b".into()), "*".into(), Number(5)),
+                ],
+                vec![],
+            ),
+            Some(Expression::new_block_stmt(vec![Number(5)], vec![])),
+        );
+        let let_stmt_expr = Expression::LetStmt("x".into(), Type::None, Box::new(if_expr));
+        assert!(output.is_ok());
+        assert!(output.unwrap().contains(&let_stmt_expr))
+    }
+
+    #[test]
+    fn test_let_declares_and_assignment_assigns() {
+        let input = r#"
+        let x = 1;
+        x = 2;
+        letter = 3;
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output,
+            vec![
+                Expression::new_let_stmt("x".into(), Type::None, Number(1)),
+                Expression::new_assign_stmt("x".into(), Number(2)),
+                Expression::new_assign_stmt("letter".into(), Number(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_do_while_stmt() {
+        let input = r#"
+        do
+        {
+            print("hello");
+        } while (value);
+        "#;
+        assert!(parse_cyclo_program(input).is_ok());
+    }
+    #[test]
+    fn test_repeat_until_stmt() {
+        let input = r#"
+        repeat {
+            count = count + 1;
+        } until (count == 3);
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert!(matches!(&output[0], Expression::RepeatUntilStmt(_, _)));
+    }
+    #[test]
+    fn test_loop_stmt_with_break() {
+        let input = r#"
+        loop {
+            count = count + 1;
+            if (count == 3) {
+                break;
+            }
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert!(matches!(&output[0], Expression::LoopStmt(_)));
+    }
+    #[test]
+    fn test_break_prefix_is_a_name() {
+        let input = r#"breakfast = 1;"#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert!(matches!(&output[0], Expression::AssignStmt(_, _)));
+    }
+    #[test]
     fn test_for_loop_stmt() {
         let input = r#"
         for (let i = 0; i < 20; i++)
@@ -1179,6 +2847,263 @@ mod test {
         assert!(parse_cyclo_program(input).is_ok());
     }
 
+    #[test]
+    fn test_fold_stmt() {
+        let input = r#"
+        let total = fold([1, 2, 3], 0, add);
+        "#;
+        let output = parse_cyclo_program(input);
+        let fold_expr = Expression::Fold(
+            Box::new(Expression::List(vec![Number(1), Number(2), Number(3)])),
+            Box::new(Number(0)),
+            "add".into(),
+        );
+        let let_stmt_expr = Expression::LetStmt("total".into(), Type::None, Box::new(fold_expr));
+        assert!(output.is_ok());
+        assert!(output.unwrap().contains(&let_stmt_expr))
+    }
+
+    #[test]
+    fn test_display_expression() {
+        let output = parse_cyclo_program(r#"let value: List<i32> = [1, 2] + get(x, y - 1);"#);
+        assert!(output.is_ok());
+        assert_eq!(
+            output.unwrap()[0].to_string(),
+            "let value: List<i32> = [1, 2] + get(x, y - 1)"
+        );
+    }
+
+    #[test]
+    fn test_match_stmt() {
+        let input = r#"
+        let value = match x {
+            1 => 10,
+            true => y + 1,
+            "two" => 20,
+            _ => 0,
+        };
+        "#;
+        let output = parse_cyclo_program(input);
+        let match_expr = Expression::Match(
+            Box::new(Variable("x".into())),
+            vec![
+                (Number(1), Number(10)),
+                (
+                    Expression::Bool(true),
+                    Expression::new_binary(Variable("y".into()), "+".into(), Number(1)),
+                ),
+                (Expression::String("\"two\"".into()), Number(20)),
+                (Expression::Wildcard, Number(0)),
+            ],
+        );
+        let let_stmt_expr = Expression::LetStmt("value".into(), Type::None, Box::new(match_expr));
+        assert!(output.is_ok());
+        assert!(output.unwrap().contains(&let_stmt_expr))
+    }
+
+    #[test]
+    fn test_match_stmt_with_block_arms() {
+        let input = r#"
+        match n {
+            0 => { print("zero"); },
+            _ => print(n)
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_ok());
+    }
+
+    #[test]
+    fn test_switch_stmt() {
+        let input = r#"
+        switch (x) {
+            case 1 { print(10); }
+            case -2 { print(20); }
+            default { print(0); }
+        }
+        "#;
+        let output = parse_cyclo_program(input);
+        let switch_expr = Expression::Switch(
+            Box::new(Variable("x".into())),
+            vec![
+                (
+                    1,
+                    Expression::new_block_stmt(
+                        vec![Expression::new_print_stmt(Number(10))],
+                        vec![],
+                    ),
+                ),
+                (
+                    -2,
+                    Expression::new_block_stmt(
+                        vec![Expression::new_print_stmt(Number(20))],
+                        vec![],
+                    ),
+                ),
+            ],
+            Box::new(Some(Expression::new_block_stmt(
+                vec![Expression::new_print_stmt(Number(0))],
+                vec![],
+            ))),
+        );
+        assert_eq!(output, Ok(vec![switch_expr]));
+    }
+
+    #[test]
+    fn test_read_line_and_read_int() {
+        let input = r#"
+        let name = read_line();
+        let doubled = 2 * read_int();
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        let exprs = output.unwrap();
+        assert!(exprs.contains(&Expression::LetStmt(
+            "name".into(),
+            Type::None,
+            Box::new(Expression::ReadLine)
+        )));
+        assert!(exprs.contains(&Expression::LetStmt(
+            "doubled".into(),
+            Type::None,
+            Box::new(Expression::new_binary(
+                Number(2),
+                "*".into(),
+                Expression::ReadInt
+            ))
+        )));
+    }
+
+    #[test]
+    fn test_range_for_stmt_with_step() {
+        let input = r#"
+        for i in 0..10 step 2 {
+            print(i);
+        }
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        let for_stmt = Expression::ForStmt(
+            "i".into(),
+            0,
+            10,
+            2,
+            Box::new(Expression::new_block_stmt(
+                vec![Expression::Print(Box::new(Variable("i".into())))],
+                vec![],
+            )),
+        );
+        assert!(output.unwrap().contains(&for_stmt));
+    }
+
+    #[test]
+    fn test_range_for_stmt_defaults_to_step_one() {
+        let input = r#"
+        for i in 0..3 {
+            print(i);
+        }
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        assert!(matches!(
+            output.unwrap().first(),
+            Some(Expression::ForStmt(_, 0, 3, 1, _))
+        ));
+    }
+
+    #[test]
+    fn test_range_for_stmt_zero_step_is_error() {
+        let input = r#"
+        for i in 0..10 step 0 {
+            print(i);
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
+    #[test]
+    fn test_format_stmt() {
+        let input = r#"
+        let s = format("%d + %d = %s", 2, x, name);
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        let format_expr = Expression::Format(
+            "%d + %d = %s".into(),
+            vec![Number(2), Variable("x".into()), Variable("name".into())],
+        );
+        let let_stmt_expr = Expression::LetStmt("s".into(), Type::None, Box::new(format_expr));
+        assert!(output.unwrap().contains(&let_stmt_expr));
+    }
+
+    #[test]
+    fn test_type_alias_stmt() {
+        let input = r#"
+        type Meters = i32;
+        type Path = List<Meters>;
+        let distance: Meters = 5;
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        let exprs = output.unwrap();
+        assert!(exprs.contains(&Expression::TypeAlias("Meters".into(), Type::i32)));
+        assert!(exprs.contains(&Expression::TypeAlias(
+            "Path".into(),
+            Type::List(Box::new(Type::Alias("Meters".into())))
+        )));
+        assert!(exprs.contains(&Expression::LetStmt(
+            "distance".into(),
+            Type::Alias("Meters".into()),
+            Box::new(Number(5))
+        )));
+    }
+
+    #[test]
+    fn test_windows_stmt() {
+        let input = r#"
+        let pairs = windows([1,2,3,4], 2);
+        "#;
+        let output = parse_cyclo_program(input);
+        assert!(output.is_ok());
+        let windows_expr = Expression::Windows(
+            Box::new(Expression::List(vec![
+                Number(1),
+                Number(2),
+                Number(3),
+                Number(4),
+            ])),
+            Box::new(Number(2)),
+        );
+        let let_stmt_expr = Expression::LetStmt("pairs".into(), Type::None, Box::new(windows_expr));
+        assert!(output.unwrap().contains(&let_stmt_expr));
+    }
+
+    #[test]
+    fn test_import_stmt() {
+        let input = r#"import "lib/math.cyclo";"#;
+        let pair = CycloParser::parse(Rule::expression_list, input)
+            .unwrap()
+            .next()
+            .unwrap();
+        let output = strip_spans(parse_program(pair).unwrap());
+        assert_eq!(output, vec![Expression::Import("lib/math.cyclo".into())]);
+    }
+
+    #[test]
+    fn test_import_missing_file_is_error() {
+        let input = r#"import "does/not/exist.cyclo";"#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
+    #[test]
+    fn test_import_stmt_only_at_top_level() {
+        let input = r#"
+        fn f() {
+            import "lib/math.cyclo";
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
     #[test]
     fn test_access_and_set_value_in_list() {
         let input = r#"
@@ -1187,4 +3112,390 @@ mod test {
         "#;
         assert!(parse_cyclo_program(input).is_ok());
     }
+
+    #[test]
+    fn test_extern_stmt() {
+        let input = r#"
+        extern fn abs(i32 x) -> i32;
+        extern fn srand(i32 seed);
+        print(abs(0 - 5));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::ExternFunc("abs".into(), vec![("x".into(), Type::i32)], Type::i32)
+        );
+        assert_eq!(
+            output[1],
+            Expression::ExternFunc("srand".into(), vec![("seed".into(), Type::i32)], Type::None)
+        );
+        assert_eq!(output[0].to_string(), "extern fn abs(i32 x) -> i32");
+    }
+
+    #[test]
+    fn test_extern_stmt_only_at_top_level() {
+        let input = r#"
+        fn f() {
+            extern fn abs(i32 x) -> i32;
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
+    #[test]
+    fn test_global_stmt() {
+        let input = r#"
+        global counter: i32 = 0;
+        fn bump() {
+            counter = counter + 1;
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::GlobalStmt("counter".into(), Type::i32, Box::new(Expression::Number(0)))
+        );
+        assert_eq!(output[0].to_string(), "global counter: i32 = 0");
+        let input = r#"
+        fn f() {
+            global counter: i32 = 0;
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_err());
+    }
+
+    #[test]
+    fn test_sizeof_stmt() {
+        let input = r#"
+        let a = sizeof(i32);
+        print(sizeof(List<i64>));
+        let b = sizeof(Point) * 2;
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "a".into(),
+                Type::None,
+                Box::new(Expression::SizeOf(Type::i32))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::SizeOf(Type::List(Box::new(
+                Type::i64
+            )))))
+        );
+        assert_eq!(
+            output[2],
+            Expression::LetStmt(
+                "b".into(),
+                Type::None,
+                Box::new(Expression::Binary(
+                    Box::new(Expression::SizeOf(Type::Alias("Point".into()))),
+                    "*".into(),
+                    Box::new(Number(2))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_cast_stmt() {
+        let input = r#"
+        let a = cast<i64>(x);
+        print(cast<float>(a + 1));
+        let b = cast<i32>(y) * 2;
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "a".into(),
+                Type::None,
+                Box::new(Expression::Cast(Type::i64, Box::new(Variable("x".into()))))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::Cast(
+                Type::Float,
+                Box::new(Expression::new_binary(
+                    Variable("a".into()),
+                    "+".into(),
+                    Number(1)
+                ))
+            )))
+        );
+        assert_eq!(
+            output[2],
+            Expression::LetStmt(
+                "b".into(),
+                Type::None,
+                Box::new(Expression::new_binary(
+                    Expression::Cast(Type::i32, Box::new(Variable("y".into()))),
+                    "*".into(),
+                    Number(2)
+                ))
+            )
+        );
+        assert_eq!(output[0].to_string(), "let a = cast<i64>(x)");
+    }
+
+    #[test]
+    fn test_is_expr() {
+        let input = r#"
+        let a = x is i64;
+        print(f(1) is List<i32>);
+        if (x is Point) {
+            print(x);
+        }
+        let island = 1;
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "a".into(),
+                Type::None,
+                Box::new(Expression::Is(Box::new(Variable("x".into())), Type::i64))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::Print(Box::new(Expression::Is(
+                Box::new(Expression::CallStmt("f".into(), vec![Number(1)])),
+                Type::List(Box::new(Type::i32))
+            )))
+        );
+        assert_eq!(output[1].to_string(), "print(f(1) is List<i32>)");
+        assert_eq!(
+            output[3],
+            Expression::LetStmt("island".into(), Type::None, Box::new(Number(1)))
+        );
+    }
+
+    #[test]
+    fn test_slice_expr() {
+        let input = r#"
+        let s = [1,2,3,4,5][1..4];
+        print(len(values[start..end]));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "s".into(),
+                Type::None,
+                Box::new(Expression::Slice(
+                    Box::new(Expression::List(vec![
+                        Number(1),
+                        Number(2),
+                        Number(3),
+                        Number(4),
+                        Number(5)
+                    ])),
+                    Box::new(Number(1)),
+                    Box::new(Number(4))
+                ))
+            )
+        );
+        assert_eq!(output[1].to_string(), "print(len(values[start..end]))");
+    }
+
+    #[test]
+    fn test_slice_expr_bounds_are_expressions() {
+        let output = parse_cyclo_program("print(xs[i..i + 2]);").unwrap();
+        assert_eq!(
+            output[0],
+            Expression::Print(Box::new(Expression::Slice(
+                Box::new(Expression::Variable("xs".into())),
+                Box::new(Expression::Variable("i".into())),
+                Box::new(Expression::Binary(
+                    Box::new(Expression::Variable("i".into())),
+                    "+".into(),
+                    Box::new(Number(2))
+                ))
+            )))
+        );
+    }
+
+    #[test]
+    fn test_exit_call_is_builtin() {
+        let output = parse_cyclo_program("exit(3);").unwrap();
+        assert_eq!(
+            output[0],
+            Expression::CallStmt("exit".into(), vec![Number(3)])
+        );
+        assert_eq!(
+            Expression::from_builtin_call("exit", &[Number(3)]),
+            Some(Expression::Exit(Box::new(Number(3))))
+        );
+    }
+
+    #[test]
+    fn test_string_escaped_quote() {
+        let output = parse_cyclo_program(r#"let s = "say \"hi\"\n";"#).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "s".into(),
+                Type::None,
+                Box::new(Expression::String(r#""say \"hi\"\n""#.into()))
+            )
+        );
+        // the escaped backslash means `u{41}` isn't a unicode escape
+        let output = parse_cyclo_program(r#"print("\\u{41}");"#).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::Print(Box::new(Expression::String(r#""\\u{41}""#.into())))
+        );
+    }
+
+    #[test]
+    fn test_assert_call_is_builtin() {
+        let output = parse_cyclo_program("assert(x == 1);").unwrap();
+        let cond = Expression::Binary(
+            Box::new(Expression::Variable("x".into())),
+            "==".into(),
+            Box::new(Number(1)),
+        );
+        assert_eq!(
+            output[0],
+            Expression::CallStmt("assert".into(), vec![cond.clone()])
+        );
+        assert_eq!(
+            Expression::from_builtin_call("assert", std::slice::from_ref(&cond)),
+            Some(Expression::Assert(Box::new(cond)))
+        );
+    }
+
+    #[test]
+    fn test_method_call() {
+        let input = r#"
+        let c = s.chars();
+        for ch in "hi".chars() {
+            print(ch);
+        }
+        print(len(s.chars()));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "c".into(),
+                Type::None,
+                Box::new(Expression::MethodCall(
+                    Box::new(Expression::Variable("s".into())),
+                    "chars".into(),
+                    vec![]
+                ))
+            )
+        );
+        assert_eq!(
+            output[1],
+            Expression::ForEachStmt(
+                "ch".into(),
+                Box::new(Expression::MethodCall(
+                    Box::new(Expression::String("\"hi\"".into())),
+                    "chars".into(),
+                    vec![]
+                )),
+                false,
+                Box::new(Expression::new_block_stmt(
+                    vec![Expression::Print(Box::new(Expression::Variable(
+                        "ch".into()
+                    )))],
+                    vec![]
+                ))
+            )
+        );
+        assert_eq!(output[2].to_string(), "print(len(s.chars()))");
+    }
+
+    #[test]
+    fn test_method_call_with_args() {
+        let input = r#"
+        let parts = "a,b".split(",");
+        print(len(s.split(sep)));
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::LetStmt(
+                "parts".into(),
+                Type::None,
+                Box::new(Expression::MethodCall(
+                    Box::new(Expression::String("\"a,b\"".into())),
+                    "split".into(),
+                    vec![Expression::String("\",\"".into())]
+                ))
+            )
+        );
+        assert_eq!(output[1].to_string(), "print(len(s.split(sep)))");
+    }
+
+    #[test]
+    fn test_func_default_args() {
+        let input = r#"
+        fn greet(string greeting, string name = "world", i32 times = 1) {
+            print(name);
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        match &output[0] {
+            Expression::FuncStmt(_, args, _, _, _) => assert_eq!(
+                args,
+                &vec![
+                    FuncArg("greeting".into(), Type::String, None),
+                    FuncArg(
+                        "name".into(),
+                        Type::String,
+                        Some(Box::new(Expression::String("\"world\"".into())))
+                    ),
+                    FuncArg("times".into(), Type::i32, Some(Box::new(Number(1)))),
+                ]
+            ),
+            other => panic!("expected a function, got {:?}", other),
+        }
+        let input = r#"
+        fn greet(string name = "world", i32 times) {
+            print(name);
+        }
+        "#;
+        let error = parse_cyclo_program(input).unwrap_err();
+        assert!(error.contains("argument times needs a default value"));
+        let input = r#"
+        fn add(i32 x, i32 y = x + 1) -> i32 {
+            return x + y;
+        }
+        "#;
+        assert!(parse_cyclo_program(input).is_ok());
+        for args in [
+            "i32 x = y + 1, i32 y = 1",
+            "i32 x, i32 y = z + 1",
+            "i32 x, i32 y = f(x) + 1",
+        ] {
+            let input = format!("fn add({}) {{ print(x); }}", args);
+            let error = parse_cyclo_program(&input).unwrap_err();
+            assert!(error.contains("can only use literals and the arguments before it"));
+        }
+    }
+
+    #[test]
+    fn test_method_call_stmt() {
+        let input = r#"
+        values.sort();
+        print(values);
+        if (values.contains(3)) {
+            print(3);
+        }
+        "#;
+        let output = parse_cyclo_program(input).unwrap();
+        assert_eq!(
+            output[0],
+            Expression::MethodCall(Box::new(Variable("values".into())), "sort".into(), vec![])
+        );
+        assert_eq!(output[2].to_string(), "if (values.contains(3)) { ... }");
+    }
 }
@@ -0,0 +1,215 @@
+use cyclang_parser::Expression;
+
+// Formats an expression tree with one node per line, each node prints its variant name and
+// any names or literal values it holds, its children are indented two spaces per depth level
+pub fn format_expr(e: &Expression, indent: usize) -> String {
+    let pad = "  ".repeat(indent);
+    let (label, children): (String, Vec<&Expression>) = match e {
+        Expression::Number(n) => (format!("Number {}", n), vec![]),
+        Expression::Number64(n) => (format!("Number64 {}", n), vec![]),
+        Expression::Float(n) => (format!("Float {}", n), vec![]),
+        Expression::String(s) => (format!("String {}", s), vec![]),
+        Expression::Bool(b) => (format!("Bool {}", b), vec![]),
+        Expression::Nil => ("Nil".to_string(), vec![]),
+        Expression::List(items) => ("List".to_string(), items.iter().collect()),
+        Expression::ListIndex(list, index) => {
+            ("ListIndex".to_string(), vec![list.as_ref(), index.as_ref()])
+        }
+        Expression::Slice(list, start, end) => (
+            "Slice".to_string(),
+            vec![list.as_ref(), start.as_ref(), end.as_ref()],
+        ),
+        Expression::ListAssign(name, index, value) => (
+            format!("ListAssign {}", name),
+            vec![index.as_ref(), value.as_ref()],
+        ),
+        Expression::Tuple(items) => ("Tuple".to_string(), items.iter().collect()),
+        Expression::StructDef(name, fields) => {
+            let fields = fields
+                .iter()
+                .map(|(field, field_type)| format!("{}: {}", field, field_type))
+                .collect::<Vec<String>>()
+                .join(", ");
+            (format!("StructDef {} {{ {} }}", name, fields), vec![])
+        }
+        Expression::StructInit(name, fields) => {
+            let mut output = format!("{}StructInit {}\n", pad, name);
+            for (field, value) in fields {
+                output.push_str(&format!("{}  Field {}\n", pad, field));
+                output.push_str(&format_expr(value, indent + 2));
+            }
+            return output;
+        }
+        Expression::FieldAccess(value, field) => {
+            (format!("FieldAccess .{}", field), vec![value.as_ref()])
+        }
+        Expression::MethodCall(value, method, args) => (
+            format!("MethodCall .{}()", method),
+            std::iter::once(value.as_ref()).chain(args.iter()).collect(),
+        ),
+        Expression::Variable(name) => (format!("Variable {}", name), vec![]),
+        Expression::Binary(lhs, op, rhs) => {
+            (format!("Binary {}", op), vec![lhs.as_ref(), rhs.as_ref()])
+        }
+        Expression::Unary(op, value) => (format!("Unary {}", op), vec![value.as_ref()]),
+        Expression::Grouping(value) => ("Grouping".to_string(), vec![value.as_ref()]),
+        Expression::LetStmt(name, var_type, value) => (
+            format!("LetStmt {}: {}", name, var_type),
+            vec![value.as_ref()],
+        ),
+        Expression::GlobalStmt(name, var_type, value) => (
+            format!("GlobalStmt {}: {}", name, var_type),
+            vec![value.as_ref()],
+        ),
+        Expression::AssignStmt(name, value) => {
+            (format!("AssignStmt {}", name), vec![value.as_ref()])
+        }
+        Expression::LetTupleStmt(names, value) => (
+            format!("LetTupleStmt ({})", names.join(", ")),
+            vec![value.as_ref()],
+        ),
+        Expression::BlockStmt(exprs, _) => ("BlockStmt".to_string(), exprs.iter().collect()),
+        Expression::FuncArg(name, arg_type, default) => (
+            format!("FuncArg {}: {}", name, arg_type),
+            default.iter().map(|default| default.as_ref()).collect(),
+        ),
+        Expression::FuncStmt(name, args, return_type, body, attribute) => {
+            let attribute = match attribute {
+                Some(attribute) => format!(" {}", attribute),
+                None => String::new(),
+            };
+            let mut children: Vec<&Expression> = args.iter().collect();
+            children.push(body);
+            (
+                format!("FuncStmt {} -> {}{}", name, return_type, attribute),
+                children,
+            )
+        }
+        Expression::ExternFunc(name, args, return_type) => {
+            let args: Vec<String> = args
+                .iter()
+                .map(|(arg_name, arg_type)| format!("{}: {}", arg_name, arg_type))
+                .collect();
+            (
+                format!(
+                    "ExternFunc {}({}) -> {}",
+                    name,
+                    args.join(", "),
+                    return_type
+                ),
+                vec![],
+            )
+        }
+        Expression::CallStmt(name, args) => (format!("CallStmt {}", name), args.iter().collect()),
+        Expression::IfStmt(cond, then_block, else_block) => {
+            let mut children = vec![cond.as_ref(), then_block.as_ref()];
+            if let Some(else_block) = &**else_block {
+                children.push(else_block);
+            }
+            ("IfStmt".to_string(), children)
+        }
+        Expression::WhileStmt(cond, body) => {
+            ("WhileStmt".to_string(), vec![cond.as_ref(), body.as_ref()])
+        }
+        Expression::DoWhileStmt(body, cond) => (
+            "DoWhileStmt".to_string(),
+            vec![body.as_ref(), cond.as_ref()],
+        ),
+        Expression::RepeatUntilStmt(body, cond) => (
+            "RepeatUntilStmt".to_string(),
+            vec![body.as_ref(), cond.as_ref()],
+        ),
+        Expression::LoopStmt(body) => ("LoopStmt".to_string(), vec![body.as_ref()]),
+        Expression::BreakStmt => ("BreakStmt".to_string(), vec![]),
+        Expression::ReturnStmt(value) => ("ReturnStmt".to_string(), vec![value.as_ref()]),
+        Expression::ForStmt(name, start, end, step, body) => (
+            format!("ForStmt {} in {}..{} step {}", name, start, end, step),
+            vec![body.as_ref()],
+        ),
+        Expression::ReversedForStmt(name, start, end, body) => (
+            format!("ReversedForStmt {} in {}..{}", name, start, end),
+            vec![body.as_ref()],
+        ),
+        Expression::ForEachStmt(name, list, reversed, body) => {
+            let label = if *reversed { "reversed " } else { "" };
+            (
+                format!("ForEachStmt {} in {}", name, label),
+                vec![list.as_ref(), body.as_ref()],
+            )
+        }
+        Expression::Print(value) => ("Print".to_string(), vec![value.as_ref()]),
+        Expression::Len(value) => ("Len".to_string(), vec![value.as_ref()]),
+        Expression::Sum(value) => ("Sum".to_string(), vec![value.as_ref()]),
+        Expression::Avg(value) => ("Avg".to_string(), vec![value.as_ref()]),
+        Expression::Fold(list, init, func) => {
+            (format!("Fold {}", func), vec![list.as_ref(), init.as_ref()])
+        }
+        Expression::Windows(list, size) => {
+            ("Windows".to_string(), vec![list.as_ref(), size.as_ref()])
+        }
+        Expression::Clamp(value, lo, hi) => (
+            "Clamp".to_string(),
+            vec![value.as_ref(), lo.as_ref(), hi.as_ref()],
+        ),
+        Expression::Lerp(a, b, t) => ("Lerp".to_string(), vec![a.as_ref(), b.as_ref(), t.as_ref()]),
+        Expression::Exit(code) => ("Exit".to_string(), vec![code.as_ref()]),
+        Expression::Assert(cond) => ("Assert".to_string(), vec![cond.as_ref()]),
+        Expression::Builtin(builtin) => ("Builtin".to_string(), vec![builtin.as_ref()]),
+        Expression::Match(value, arms) => {
+            let mut output = format!("{}Match\n", pad);
+            output.push_str(&format_expr(value, indent + 1));
+            for (pattern, arm) in arms {
+                output.push_str(&format!("{}  Arm\n", pad));
+                output.push_str(&format_expr(pattern, indent + 2));
+                output.push_str(&format_expr(arm, indent + 2));
+            }
+            return output;
+        }
+        Expression::Switch(value, cases, default) => {
+            let mut output = format!("{}Switch\n", pad);
+            output.push_str(&format_expr(value, indent + 1));
+            for (case, body) in cases {
+                output.push_str(&format!("{}  Case {}\n", pad, case));
+                output.push_str(&format_expr(body, indent + 2));
+            }
+            if let Some(default) = &**default {
+                output.push_str(&format!("{}  Default\n", pad));
+                output.push_str(&format_expr(default, indent + 2));
+            }
+            return output;
+        }
+        Expression::Wildcard => ("Wildcard".to_string(), vec![]),
+        Expression::ReadLine => ("ReadLine".to_string(), vec![]),
+        Expression::ReadInt => ("ReadInt".to_string(), vec![]),
+        Expression::Format(template, args) => {
+            (format!("Format {}", template), args.iter().collect())
+        }
+        Expression::TypeAlias(name, alias_type) => {
+            (format!("TypeAlias {} = {}", name, alias_type), vec![])
+        }
+        Expression::SizeOf(size_type) => (format!("SizeOf {}", size_type), vec![]),
+        Expression::Cast(cast_type, value) => (format!("Cast {}", cast_type), vec![value.as_ref()]),
+        Expression::Is(value, is_type) => (format!("Is {}", is_type), vec![value.as_ref()]),
+        Expression::Import(path) => (format!("Import {}", path), vec![]),
+    };
+    let mut output = format!("{}{}\n", pad, label);
+    for child in children {
+        output.push_str(&format_expr(child, indent + 1));
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cyclang_parser::parse_cyclo_program;
+
+    #[test]
+    fn test_format_expr_indents_children() {
+        let exprs = parse_cyclo_program("let a = 1 + 2;").unwrap();
+        assert_eq!(
+            format_expr(&exprs[0], 0),
+            "LetStmt a: none\n  Binary +\n    Number 1\n    Number 2\n"
+        );
+    }
+}
@@ -1,15 +1,20 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use cyclang_backend::compiler;
 use cyclang_backend::compiler::codegen::target::Target;
-use cyclang_backend::compiler::CompileOptions;
-use cyclang_parser::parse_cyclo_program;
-use std::fs;
+use cyclang_backend::compiler::{CompileOptions, ExecutionMode};
+use cyclang_interpreter::{Exit, Interpreter};
+use cyclang_parser::{parse_cyclo_file, parse_cyclo_file_spanned, Expression, Spanned};
+use std::fmt::Display;
+use std::path::Path;
 use std::process::exit;
 use text_colorizer::Colorize;
+mod debug;
 mod repl;
 
 #[derive(Parser, Debug)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(short, long)]
     version: bool,
     #[arg(short, long)]
@@ -18,31 +23,106 @@ struct Args {
     target: Option<String>,
     #[arg(short, long)]
     emit_llvm_ir: bool,
+    #[arg(short = 'g', long)]
+    debug_info: bool,
+    #[arg(
+        short = 'O',
+        long,
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..=3)
+    )]
+    optimization_level: u8,
+    #[arg(long)]
+    emit_object: bool,
+    #[arg(long)]
+    repl: bool,
+    #[arg(long)]
+    print_ast: bool,
+    #[arg(long)]
+    print_ir: bool,
+    #[arg(long)]
+    verify_module: bool,
+    #[arg(long)]
+    interpret: bool,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Evaluate statements one line at a time, variables and functions are kept between lines
+    Repl,
+}
+
+// a --target that isn't valid exits rather than compiling for the host
 fn get_target(target: Option<String>) -> Option<Target> {
-    if let Some(target) = target {
-        return Target::from_target_name(&target);
+    let target = target?;
+    match Target::from_triple(&target) {
+        Ok(target) => Some(target),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1)
+        }
     }
-    None
 }
 
+// only the tests compile from a string, the cli reads programs from files
+#[cfg(test)]
 fn compile_output_from_string(
     contents: String,
-    is_execution_engine: bool,
+    execution_mode: ExecutionMode,
     target: Option<String>,
 ) -> String {
-    let compile_options = Some(CompileOptions {
-        is_execution_engine,
+    let compile_options = CompileOptions {
+        execution_mode,
         target: get_target(target),
-    });
-    match parse_cyclo_program(&contents) {
+        debug_info: false,
+        optimization_level: 0,
+        emit_object: false,
+        print_ir: false,
+        verify_module: false,
+        source_file: None,
+    };
+    compile_output(
+        cyclang_parser::parse_cyclo_program_spanned(&contents),
+        compile_options,
+        false,
+    )
+}
+
+fn compile_output_from_file(
+    filename: &str,
+    compile_options: CompileOptions,
+    print_ast: bool,
+) -> String {
+    compile_output(
+        parse_cyclo_file_spanned(Path::new(filename)),
+        compile_options,
+        print_ast,
+    )
+}
+
+fn compile_output(
+    parsed: Result<Vec<Spanned<Expression>>, impl Display>,
+    compile_options: CompileOptions,
+    print_ast: bool,
+) -> String {
+    let compile_options = Some(compile_options);
+    match parsed {
         // loop through expression, if type var then store
 
-        Ok(exprs) => compiler::compile(exprs, compile_options).unwrap_or_else(|e| {
-            eprintln!("unable to compile contents due to error: {}", e);
-            exit(1)
-        }),
+        Ok(exprs) => {
+            if print_ast {
+                for expr in &exprs {
+                    eprint!("{}", debug::format_expr(&expr.node, 0));
+                }
+            }
+            for warning in compiler::warnings_spanned(&exprs) {
+                eprintln!("warning: {}", warning);
+            }
+            compiler::compile_spanned(exprs, compile_options).unwrap_or_else(|e| {
+                eprintln!("unable to compile contents due to error: {}", e);
+                exit(1)
+            })
+        }
         Err(e) => {
             eprintln!("unable to parse contents due to error: {}", e);
             exit(1)
@@ -50,6 +130,26 @@ fn compile_output_from_string(
     }
 }
 
+// runs the program without LLVM, for hosts where it isn't available
+fn interpret_file(filename: &str) {
+    let exprs = parse_cyclo_file(Path::new(filename)).unwrap_or_else(|e| {
+        eprintln!("unable to parse contents due to error: {}", e);
+        exit(1)
+    });
+    let mut interpreter = Interpreter::new();
+    let result = exprs.iter().try_for_each(|expr| interpreter.eval(expr).map(|_| ()));
+    // what the program printed before it exited or failed is still shown
+    print!("{}", interpreter.output());
+    if let Err(e) = result {
+        // like the compiled program, the process ends with the code the program exits with
+        if let Some(Exit { code }) = e.downcast_ref::<Exit>() {
+            exit(*code)
+        }
+        eprintln!("unable to interpret contents due to error: {}", e);
+        exit(1)
+    }
+}
+
 fn main() {
     let args = Args::parse();
     if args.version {
@@ -57,9 +157,32 @@ fn main() {
         println!("{} {}", "cyclang".italic(), version.italic());
         return;
     }
+    if args.repl || matches!(args.command, Some(Command::Repl)) {
+        repl::run();
+        return;
+    }
     if let Some(filename) = args.file {
-        let contents = fs::read_to_string(filename).expect("Failed to read file");
-        compile_output_from_string(contents, !args.emit_llvm_ir, args.target);
+        if args.interpret {
+            interpret_file(&filename);
+            return;
+        }
+        // cross compiled modules can't be run in process
+        let execution_mode = if args.emit_llvm_ir || args.emit_object || args.target.is_some() {
+            ExecutionMode::Aot
+        } else {
+            ExecutionMode::Jit
+        };
+        let compile_options = CompileOptions {
+            execution_mode,
+            target: get_target(args.target),
+            debug_info: args.debug_info,
+            optimization_level: args.optimization_level,
+            emit_object: args.emit_object,
+            print_ir: args.print_ir,
+            verify_module: args.verify_module,
+            source_file: Some(filename.clone()),
+        };
+        compile_output_from_file(&filename, compile_options, args.print_ast);
         return;
     }
     repl::run();
@@ -68,9 +191,82 @@ fn main() {
 #[cfg(test)]
 mod test {
     use super::*;
+    use cyclang_backend::compiler::codegen::target::{TargetArch, TargetInfo};
+    use cyclang_interpreter::interpret_program;
+    use cyclang_parser::{parse_cyclo_file, parse_cyclo_program, parse_cyclo_program_spanned, Type};
     //Note: Integration tests for parsing and compiling output
     fn compile_output_from_string_test(contents: String) -> String {
-        compile_output_from_string(contents, false, None)
+        compile_output_from_string(contents, ExecutionMode::Aot, None)
+    }
+
+    fn compile_error_from_string_test(contents: String) -> String {
+        let exprs = parse_cyclo_program(&contents).expect("unable to parse contents");
+        let compile_options = Some(CompileOptions {
+            execution_mode: ExecutionMode::Aot,
+            target: None,
+            debug_info: false,
+            optimization_level: 0,
+            emit_object: false,
+            print_ir: false,
+            verify_module: false,
+            source_file: None,
+        });
+        match compiler::compile(exprs, compile_options) {
+            Ok(output) => panic!("expected compile error, got output {:?}", output),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    // compiles contents in Aot mode and returns the LLVM IR written to bin/main.ll
+    fn compile_ir_from_string_test(contents: String) -> String {
+        compile_output_from_string(contents, ExecutionMode::Aot, None);
+        std::fs::read_to_string("bin/main.ll").expect("unable to read bin/main.ll")
+    }
+
+    // compiles contents for a cross compile target and returns bin/main.o, which is written
+    // before it's linked. Linking needs the target's own linker, so the result is only
+    // checked when has_tool finds it
+    fn compile_target_object_test(
+        contents: &str,
+        target: &str,
+    ) -> (Vec<u8>, Result<String, String>) {
+        let exprs = parse_cyclo_program(contents).expect("unable to parse contents");
+        let compile_options = CompileOptions {
+            target: Some(Target::from_triple(target).expect("unable to find target")),
+            ..Default::default()
+        };
+        let linked = compiler::compile(exprs, Some(compile_options)).map_err(|e| e.to_string());
+        let object = std::fs::read("bin/main.o").expect("unable to read bin/main.o");
+        (object, linked)
+    }
+
+    fn has_tool(name: &str) -> bool {
+        std::process::Command::new(name)
+            .arg("--version")
+            .output()
+            .is_ok()
+    }
+
+    // compiles contents to bin/main and then reruns the binary with stdin piped in,
+    // the first run from compile_output_from_string reads from an empty stdin
+    fn compile_output_with_stdin_test(contents: String, stdin: &str) -> String {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        compile_output_from_string(contents, ExecutionMode::Aot, None);
+        let mut child = Command::new("bin/main")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("unable to run bin/main");
+        child
+            .stdin
+            .take()
+            .expect("unable to open stdin")
+            .write_all(stdin.as_bytes())
+            .expect("unable to write to stdin");
+        let output = child.wait_with_output().expect("unable to read stdout");
+        String::from_utf8_lossy(&output.stdout).to_string()
     }
 
     #[test]
@@ -80,6 +276,31 @@ mod test {
         assert_eq!(output, "12\n");
     }
 
+    #[test]
+    fn test_compile_print_float_addition_is_deterministic() {
+        let input = r#"print(0.1 + 0.2);"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0.3\n");
+    }
+
+    #[test]
+    fn test_compile_print_float_keeps_one_decimal_place() {
+        let input = r#"
+        print(2.0);
+        print(-1.5 * 2.0);
+        print(10.0 / 4.0);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2.0\n-3.0\n2.5\n");
+    }
+
+    #[test]
+    fn test_compile_float_comparison() {
+        let input = r#"print(0.5 < 0.25);"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "false\n");
+    }
+
     #[test]
     fn test_compile_print_add_string_expression() {
         let input = r#"print("hello" + " world");"#;
@@ -87,6 +308,18 @@ mod test {
         assert_eq!(output, "\"hello world\"\n");
     }
 
+    #[test]
+    fn test_compile_add_string_variables_and_print() {
+        let input = r#"
+        let greeting = "hello";
+        let name = " world";
+        let message = greeting + name + "!";
+        print(message);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"hello world!\"\n");
+    }
+
     #[test]
     fn test_compile_print_bool_expression() {
         let input = r#"print(true);"#;
@@ -194,6 +427,43 @@ mod test {
         assert_eq!(output, "5\n");
     }
 
+    #[test]
+    fn test_compile_add_string_and_number_type_mismatch() {
+        let input = r#"print("hello" + 1);"#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "mismatched types in `\"hello\" + 1`: operator `+` expected `1` to have type string (inferred from `\"hello\"`), but it was inferred as i32"
+        );
+    }
+
+    #[test]
+    fn test_compile_precedence_mul_before_add() {
+        let input = r#"
+        print(1 + 2 * 3);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "7\n");
+    }
+
+    #[test]
+    fn test_compile_precedence_mul_then_add() {
+        let input = r#"
+        print(2 * 3 + 1);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "7\n");
+    }
+
+    #[test]
+    fn test_compile_precedence_comparison_loosest() {
+        let input = r#"
+        print(1 + 2 * 3 == 7);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\n");
+    }
+
     #[test]
     fn test_compile_eqeq_true_number() {
         let input = r#"
@@ -275,7 +545,7 @@ mod test {
         print(listFnExample(["one", "two"] + ["three", "four"]));
         "#;
         let output = compile_output_from_string_test(input.to_string());
-        assert_eq!(output, "[\"one\",\"two\",\"three\",\"four\"]");
+        assert_eq!(output, "[\"one\", \"two\", \"three\", \"four\"]\n");
     }
 
     #[test]
@@ -287,7 +557,7 @@ mod test {
         print(listFnExample([1,2,3,4]));
         "#;
         let output = compile_output_from_string_test(input.to_string());
-        assert_eq!(output, "[1,2,3,4]");
+        assert_eq!(output, "[1, 2, 3, 4]\n");
     }
 
     #[test]
@@ -645,6 +915,41 @@ mod test {
         assert_eq!(output, "10\n19\n27\n34\n40\n45\n49\n52\n54\n55\n");
     }
 
+    #[test]
+    fn test_compile_range_for_loop_with_step() {
+        let input = r#"
+        for i in 0..10 step 2 {
+            print(i);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0\n2\n4\n6\n8\n");
+    }
+
+    #[test]
+    fn test_compile_range_for_loop_with_step_sum() {
+        let input = r#"
+        let sum = 0;
+        for i in 0..10 step 3 {
+            sum = sum + i;
+            print(sum);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0\n3\n9\n18\n");
+    }
+
+    #[test]
+    fn test_compile_range_for_loop_without_step() {
+        let input = r#"
+        for i in 0..3 {
+            print(i);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0\n1\n2\n");
+    }
+
     #[test]
     fn test_compile_function_return_int() {
         let input = r#"
@@ -794,6 +1099,49 @@ mod test {
         assert_eq!(output, "6765\n");
     }
 
+    #[test]
+    fn test_mutual_recursion_fn() {
+        let input = r#"
+        fn is_even(i32 n) -> bool {
+            if (n == 0) {
+                return true;
+            }
+            return is_odd(n - 1);
+        }
+        fn is_odd(i32 n) -> bool {
+            if (n == 0) {
+                return false;
+            }
+            return is_even(n - 1);
+        }
+        print(is_even(10));
+        print(is_odd(10));
+        print(is_odd(7));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\ntrue\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_call_fn_defined_later() {
+        let input = r#"
+        fn outer(i32 x) -> i32 {
+            fn double(i32 y) -> i32 {
+                return halve(y) * 4;
+            }
+            fn halve(i32 y) -> i32 {
+                return y / 2;
+            }
+            return double(x);
+        }
+        print(outer(10));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "20\n");
+    }
+
     #[test]
     fn test_while_loop_in_fn_with_var() {
         let input = r#"
@@ -836,4 +1184,2581 @@ mod test {
         let output = compile_output_from_string_test(input.to_string());
         assert_eq!(output, "true\n");
     }
+
+    #[test]
+    fn test_fold_sum() {
+        let input = r#"
+        fn add(i32 acc, i32 x) -> i32 {
+            return acc + x;
+        }
+        print(fold([1,2,3,4], 0, add));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "10\n");
+    }
+
+    #[test]
+    fn test_fold_product() {
+        let input = r#"
+        fn mul(i32 acc, i32 x) -> i32 {
+            return acc * x;
+        }
+        let product = fold([1,2,3,4,5], 1, mul);
+        print(product);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "120\n");
+    }
+
+    #[test]
+    fn test_fold_string_concat() {
+        let input = r#"
+        fn concat(string acc, string x) -> string {
+            return acc + x;
+        }
+        print(fold(["a","b","c"], "", concat));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"abc\"\n");
+    }
+
+    #[test]
+    fn test_fold_calls_get_int32_value_for_i32_elements() {
+        // get_int32_tValue returns the element itself, which fold passes straight to the function
+        let input = r#"
+        fn add(i32 acc, i32 x) -> i32 {
+            return acc + x;
+        }
+        print(fold([1,2,3], 0, add));
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        assert!(ir.contains("call i32 @get_int32_tValue("));
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "6\n");
+    }
+
+    #[test]
+    fn test_fold_function_signature_errors() {
+        let input = r#"
+        fn inc(i32 x) -> i32 {
+            return x + 1;
+        }
+        print(fold([1,2,3], 0, inc));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "fold function \"inc\" must take the accumulator and a list element"
+        );
+        let input = r#"
+        fn concat(string acc, string x) -> string {
+            return acc + x;
+        }
+        print(fold([1,2,3], 0, concat));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "fold function \"concat\" must take the accumulator and a list element"
+        );
+        let input = r#"
+        fn count(i32 acc, i32 x) -> bool {
+            return acc < x;
+        }
+        print(fold([1,2,3], 0, count));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "fold function \"count\" must return the type of the accumulator"
+        );
+    }
+
+    #[test]
+    fn test_compile_match_number_with_wildcard() {
+        let input = r#"
+        let n = 2;
+        let x = match n {
+            1 => 10,
+            2 => 20,
+            _ => 0,
+        };
+        print(x);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "20\n");
+    }
+
+    #[test]
+    fn test_compile_match_falls_through_to_wildcard() {
+        let input = r#"
+        let n = 5;
+        print(match n { 1 => 10, _ => n * 2 });
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "10\n");
+    }
+
+    #[test]
+    fn test_compile_match_bool() {
+        let input = r#"
+        let flag = 1 < 2;
+        let x = match flag {
+            true => "yes",
+            false => "no",
+        };
+        print(x);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"yes\"\n");
+    }
+
+    #[test]
+    fn test_compile_match_string_patterns() {
+        let input = r#"
+        let s = "world";
+        let x = match s { "hello" => 1, "world" => 2, _ => 0 };
+        print(x);
+        let y = match "other" { "hello" => 1, "world" => 2, _ => 0 };
+        print(y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2\n0\n");
+    }
+
+    #[test]
+    fn test_compile_match_read_line() {
+        let input = r#"
+        print(match read_line() { "yes" => true, _ => false });
+        "#;
+        let output = compile_output_with_stdin_test(input.to_string(), "yes\n");
+        assert_eq!(output, "true\n");
+    }
+
+    #[test]
+    fn test_compile_match_arms_type_mismatch() {
+        let input = r#"
+        let n = 1;
+        let x = match n {
+            1 => 10,
+            _ => "other",
+        };
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "match arms must all have the same type, expected i32 but found string"
+        );
+    }
+
+    #[test]
+    fn test_compile_match_void_and_value_arms() {
+        let input = r#"
+        let x = 1;
+        match x { 1 => print(1), _ => 5 }
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "match arms must all have the same type, expected void but found i32"
+        );
+    }
+
+    #[test]
+    fn test_compile_match_value_without_wildcard() {
+        let input = r#"
+        let n = 3;
+        let x = match n { 1 => 10, 2 => 20 };
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "a match with a value needs a wildcard `_` arm");
+        // a match without a value does nothing when no arm matches
+        let input = r#"
+        let n = 3;
+        match n { 1 => print(10), 2 => print(20) }
+        print(n);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn test_compile_switch() {
+        let input = r#"
+        let x = 2;
+        switch (x) {
+            case 1 { print(10); }
+            case 2 { print(20); }
+            case 3 { print(30); }
+            default { print(0); }
+        }
+        switch (x + 5) {
+            case 1 { print(10); }
+            default { print(0); }
+        }
+        switch (x) {
+            case 3 { print(30); }
+        }
+        print(x);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "20\n0\n2\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_switch_duplicate_case() {
+        let input = r#"
+        let x = 2;
+        switch (x) {
+            case 1 { print(10); }
+            case 1 { print(20); }
+        }
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "duplicate switch case 1");
+    }
+
+    #[test]
+    fn test_compile_read_int_and_print_doubled() {
+        let input = r#"
+        let n = read_int();
+        print(n * 2);
+        "#;
+        let output = compile_output_with_stdin_test(input.to_string(), "21\n");
+        assert_eq!(output, "42\n");
+    }
+
+    #[test]
+    fn test_compile_read_line_and_print() {
+        let input = r#"
+        let name = read_line();
+        print("hello " + name);
+        "#;
+        let output = compile_output_with_stdin_test(input.to_string(), "world\n");
+        assert_eq!(output, "\"hello world\"\n");
+    }
+
+    #[test]
+    fn test_compile_format_numbers() {
+        let input = r#"print(format("%d + %d", 2, 3));"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"2 + 3\"\n");
+    }
+
+    #[test]
+    fn test_compile_format_string_and_variable() {
+        let input = r#"
+        let name = "world";
+        let count = 3;
+        let message = format("hello %s x%d", name, count);
+        print(message);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"hello world x3\"\n");
+    }
+
+    #[test]
+    fn test_compile_format_specifier_count_mismatch() {
+        let input = r#"print(format("%d + %d", 2));"#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "format string \"%d + %d\" has 2 format specifiers but 1 arguments were given"
+        );
+    }
+
+    #[test]
+    fn test_compile_format_specifier_type_mismatch() {
+        let input = r#"print(format("%d", "two"));"#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "format specifier `%d` expected `\"two\"` to have type i32, but it was inferred as string"
+        );
+    }
+
+    #[test]
+    fn test_compile_aot_mode_returns_binary_output() {
+        let input = r#"print(1 + 1);"#;
+        let output = compile_output_from_string(input.to_string(), ExecutionMode::Aot, None);
+        assert_eq!(output, "2\n");
+    }
+
+    #[test]
+    fn test_compile_jit_mode_runs_in_process() {
+        // the execution engine writes to this process's stdout and nothing is returned, so the
+        // program is run by this test again in a child process to capture what it printed
+        if std::env::var("CYCLO_JIT_TEST").is_ok() {
+            let input = r#"let x = 1 + 1; print("jit"); print(x);"#;
+            let output = compile_output_from_string(input.to_string(), ExecutionMode::Jit, None);
+            assert_eq!(output, "");
+            return;
+        }
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "test::test_compile_jit_mode_runs_in_process", "--nocapture"])
+            .env("CYCLO_JIT_TEST", "1")
+            .output()
+            .expect("unable to rerun the test");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.lines().any(|line| line == "\"jit\""));
+        assert!(stdout.lines().any(|line| line == "2"));
+    }
+
+    #[test]
+    fn test_compile_print_ir_prints_module() {
+        // the IR is printed to this process's stdout, see test_compile_jit_mode_runs_in_process
+        if std::env::var("CYCLO_PRINT_IR_TEST").is_ok() {
+            let exprs = parse_cyclo_program("print(7);").expect("unable to parse contents");
+            let compile_options = CompileOptions {
+                execution_mode: ExecutionMode::Jit,
+                print_ir: true,
+                ..Default::default()
+            };
+            compiler::compile(exprs, Some(compile_options)).expect("unable to compile");
+            return;
+        }
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "test::test_compile_print_ir_prints_module",
+                "--nocapture",
+            ])
+            .env("CYCLO_PRINT_IR_TEST", "1")
+            .output()
+            .expect("unable to rerun the test");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.lines().any(|line| line.starts_with("define void @main()")));
+        assert!(stdout.lines().any(|line| line == "7"));
+    }
+
+    #[test]
+    fn test_compile_verify_module_accepts_valid_module() {
+        let input = r#"
+        fn greet(string name) -> string {
+            return "hello " + name;
+        }
+        let total = 0;
+        for (let i = 0; i < 3; i++) {
+            total = total + i;
+        }
+        print(greet("cyclo"));
+        print(total);
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        let compile_options = CompileOptions {
+            verify_module: true,
+            ..Default::default()
+        };
+        let output = compiler::compile(exprs, Some(compile_options)).expect("unable to compile");
+        assert_eq!(output, "\"hello cyclo\"\n3\n");
+    }
+
+    #[test]
+    fn test_compile_defaults_to_aot_mode() {
+        assert_eq!(ExecutionMode::default(), ExecutionMode::Aot);
+        let exprs = parse_cyclo_program(r#"print(3);"#).expect("unable to parse contents");
+        let output = compiler::compile(exprs, None).expect("unable to compile");
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn test_compile_type_alias() {
+        let input = r#"
+        type Meters = i32;
+        let distance: Meters = 5;
+        let total: i32 = distance + 10;
+        print(total);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "15\n");
+    }
+
+    #[test]
+    fn test_compile_type_alias_in_function() {
+        let input = r#"
+        type Meters = i32;
+        fn double(Meters m) -> Meters {
+            return m * 2;
+        }
+        print(double(21));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "42\n");
+    }
+
+    #[test]
+    fn test_compile_type_alias_in_let_and_function() {
+        let input = r#"
+        type Celsius = i32;
+        fn warmer(Celsius temp) -> Celsius {
+            return temp + 5;
+        }
+        let today: Celsius = 20;
+        print(warmer(today));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "25\n");
+    }
+
+    #[test]
+    fn test_compile_unknown_type_alias() {
+        let input = r#"
+        let distance: Meters = 5;
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "unknown type `Meters`");
+    }
+
+    #[test]
+    fn test_compile_windows() {
+        let input = r#"print(windows([1,2,3,4], 2));"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "[[1, 2], [2, 3], [3, 4]]\n");
+    }
+
+    #[test]
+    fn test_compile_windows_len() {
+        let input = r#"
+        let values = [1,2,3,4,5];
+        let triples = windows(values, 3);
+        print(len(triples));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn test_compile_windows_larger_than_list() {
+        let input = r#"print(windows([1,2], 3));"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "[]\n");
+    }
+
+    // writes each (name, contents) pair into a fresh directory under the system temp dir
+    fn write_test_files(dir_name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(dir_name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("unable to create test dir");
+        for (name, contents) in files {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).expect("unable to create test dir");
+            std::fs::write(path, contents).expect("unable to write test file");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_compile_import_functions_and_types() {
+        let dir = write_test_files(
+            "cyclang_test_import",
+            &[
+                (
+                    "math.cyclo",
+                    r#"
+                    type Meters = i32;
+                    fn double(Meters m) -> Meters {
+                        return m * 2;
+                    }
+                    print("not run");
+                    "#,
+                ),
+                (
+                    "main.cyclo",
+                    r#"
+                    import "math.cyclo";
+                    let distance: Meters = 21;
+                    print(double(distance));
+                    "#,
+                ),
+            ],
+        );
+        let main = dir.join("main.cyclo");
+        let compile_options = CompileOptions {
+            execution_mode: ExecutionMode::Aot,
+            target: None,
+            debug_info: false,
+            optimization_level: 0,
+            emit_object: false,
+            print_ir: false,
+            verify_module: false,
+            source_file: None,
+        };
+        let output = compile_output_from_file(main.to_str().unwrap(), compile_options, false);
+        assert_eq!(output, "42\n");
+    }
+
+    #[test]
+    fn test_compile_circular_import_is_error() {
+        let dir = write_test_files(
+            "cyclang_test_circular_import",
+            &[
+                ("a.cyclo", r#"import "b.cyclo";"#),
+                ("b.cyclo", r#"import "a.cyclo";"#),
+            ],
+        );
+        let error = parse_cyclo_file(&dir.join("a.cyclo")).unwrap_err();
+        assert!(error.to_string().contains("circular import"));
+    }
+
+    #[test]
+    fn test_import_paths_are_relative_to_the_importing_file() {
+        let dir = write_test_files(
+            "cyclang_test_nested_import",
+            &[
+                ("lib/shapes.cyclo", r#"import "units.cyclo";"#),
+                ("lib/units.cyclo", r#"type Meters = i32;"#),
+                ("main.cyclo", r#"import "lib/shapes.cyclo";"#),
+            ],
+        );
+        let exprs = parse_cyclo_file(&dir.join("main.cyclo")).expect("unable to parse imports");
+        assert_eq!(exprs, vec![Expression::TypeAlias("Meters".into(), cyclang_parser::Type::i32)]);
+    }
+
+    #[test]
+    fn test_compile_do_while_stmt_runs_once_when_false() {
+        let input = r#"
+        let count = 0;
+        do {
+            count = count + 1;
+            print(count);
+        } while (count < 0);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n");
+    }
+
+    #[test]
+    fn test_compile_do_while_stmt_increment() {
+        let input = r#"
+        let val = 0;
+        do {
+            val = val + 1;
+        } while (val < 10);
+        print(val);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "10\n");
+    }
+
+    #[test]
+    fn test_compile_repeat_until_stmt() {
+        let input = r#"
+        let count = 0;
+        repeat {
+            count = count + 1;
+            print(count);
+        } until (count == 3);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n2\n3\n");
+    }
+
+    #[test]
+    fn test_compile_duplicate_string_literals_share_global() {
+        let input = r#"
+        print("hello");
+        let greeting = "hello";
+        print(greeting);
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        // the constant keeps its null terminator, stringInit is given the length separately
+        assert_eq!(
+            ir.matches("private unnamed_addr constant [6 x i8] c\"hello\\00\"")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compile_duplicate_string_literals_output() {
+        let input = r#"
+        let a = "hello";
+        let b = "hello";
+        print(a + b);
+        print(b);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"hellohello\"\n\"hello\"\n");
+    }
+
+    #[test]
+    fn test_compile_free_string_built_in_block() {
+        let input = r#"
+        fn greet() {
+            let greeting = "hello" + " world";
+            print(greeting);
+        }
+        greet();
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        assert!(ir.contains("call void @free("));
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"hello world\"\n");
+    }
+
+    #[test]
+    fn test_compile_string_escapes_block() {
+        let input = r#"
+        fn greeting(bool loud) -> string {
+            let name = "world";
+            let greeting = if (loud) {
+                let shout = "HELLO " + name;
+                shout;
+            } else {
+                "hello " + name;
+            };
+            return greeting;
+        }
+        print(greeting(true));
+        print(greeting(false));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"HELLO world\"\n\"hello world\"\n");
+    }
+
+    #[test]
+    fn test_compile_debug_info_emits_dwarf() {
+        let input = r#"
+        fn add(i32 a, i32 b) -> i32 {
+            return a + b;
+        }
+        let total = add(1, 2);
+        print(total);
+        "#;
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let compile_options = Some(CompileOptions {
+            execution_mode: ExecutionMode::Aot,
+            target: None,
+            debug_info: true,
+            optimization_level: 0,
+            emit_object: false,
+            print_ir: false,
+            verify_module: false,
+            source_file: Some("examples/add.cyclo".to_string()),
+        });
+        let output = compiler::compile_spanned(exprs, compile_options).expect("unable to compile");
+        assert_eq!(output, "3\n");
+
+        let ir = std::fs::read_to_string("bin/main.ll").expect("unable to read bin/main.ll");
+        assert!(ir.contains("!DICompileUnit("));
+        assert!(ir.contains("!DIFile(filename: \"add.cyclo\", directory: \"examples\")"));
+        assert!(ir.contains("!DISubprogram(name: \"main\""));
+        assert!(ir.contains("!DISubprogram(name: \"add\", scope: !"));
+        assert!(ir.contains("line: 2,"));
+        assert!(ir.contains("!DILocalVariable(name: \"a\""));
+        // the call to add is built while the statement on line 5 is
+        assert!(ir.contains("!DILocation(line: 5,"));
+    }
+
+    // returns the program output and the LLVM IR written to bin/main.ll
+    fn compile_with_optimization_level_test(
+        contents: &str,
+        optimization_level: u8,
+    ) -> (String, String) {
+        let exprs = parse_cyclo_program(contents).expect("unable to parse contents");
+        let compile_options = Some(CompileOptions {
+            execution_mode: ExecutionMode::Aot,
+            target: None,
+            debug_info: false,
+            optimization_level,
+            emit_object: false,
+            print_ir: false,
+            verify_module: false,
+            source_file: None,
+        });
+        let output = compiler::compile(exprs, compile_options).expect("unable to compile");
+        let ir = std::fs::read_to_string("bin/main.ll").expect("unable to read bin/main.ll");
+        (output, ir)
+    }
+
+    #[test]
+    fn test_compile_optimization_levels() {
+        let input = r#"
+        let a = 2;
+        let b = 3;
+        print(a + b);
+        "#;
+        let (output, unoptimized) = compile_with_optimization_level_test(input, 0);
+        assert_eq!(output, "5\n");
+        for level in 1..=3 {
+            let (output, optimized) = compile_with_optimization_level_test(input, level);
+            assert_eq!(output, "5\n");
+            assert!(
+                optimized.matches(" load ").count() < unoptimized.matches(" load ").count(),
+                "expected fewer loads at optimization level {}",
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_optimization_level_above_3_is_error() {
+        assert!(Args::try_parse_from(["cyclang", "-O", "4"]).is_err());
+        let exprs = parse_cyclo_program("print(1);").expect("unable to parse contents");
+        let compile_options = Some(CompileOptions {
+            optimization_level: 4,
+            ..Default::default()
+        });
+        let error = compiler::compile(exprs, compile_options).unwrap_err();
+        assert_eq!(error.to_string(), "optimization level 4 is not between 0 and 3");
+    }
+
+    // the body of the main function in the IR, skipping the stdlib functions linked into it
+    fn main_function_ir(ir: &str) -> &str {
+        let start = ir.find("@main(").expect("unable to find main");
+        let end = ir[start..].find("\n}\n").expect("unable to find end of main");
+        &ir[start..start + end]
+    }
+
+    #[test]
+    fn test_compile_o2_folds_constants() {
+        let input = r#"
+        let a = 2;
+        let b = 3;
+        print(a + b);
+        "#;
+        let (output, unoptimized) = compile_with_optimization_level_test(input, 0);
+        assert_eq!(output, "5\n");
+        let (output, optimized) = compile_with_optimization_level_test(input, 2);
+        assert_eq!(output, "5\n");
+        let unoptimized = main_function_ir(&unoptimized);
+        let optimized = main_function_ir(&optimized);
+        assert!(!optimized.contains(" add "));
+        assert!(optimized.contains("i32 5)"));
+        assert!(optimized.lines().count() < unoptimized.lines().count());
+    }
+
+    #[test]
+    fn test_compile_loop_stmt_with_break() {
+        let input = r#"
+        let count = 0;
+        loop {
+            count = count + 1;
+            if (count == 3) {
+                break;
+            }
+        }
+        print(count);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn test_compile_break_in_while_stmt() {
+        let input = r#"
+        let running = true;
+        let val = 0;
+        while (running) {
+            val = val + 1;
+            if (val == 5) {
+                break;
+            }
+        }
+        print(val);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "5\n");
+    }
+
+    #[test]
+    fn test_compile_break_outside_loop() {
+        let input = r#"
+        break;
+        "#;
+        let output = compile_error_from_string_test(input.to_string());
+        assert_eq!(output, "`break` can only be used inside a loop");
+    }
+
+    #[test]
+    fn test_compile_compare_bool_function_returns() {
+        let input = r#"
+        fn is_ready() -> bool {
+            return true;
+        }
+        fn is_done() -> bool {
+            return false;
+        }
+        print(is_ready() == is_done());
+        print(is_ready() != is_done());
+        print(is_ready() == is_ready());
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "false\ntrue\ntrue\n");
+    }
+
+    #[test]
+    fn test_compile_compare_bool_function_param() {
+        let input = r#"
+        fn same(bool a, bool b) -> bool {
+            return a == b;
+        }
+        print(same(true, true));
+        print(same(true, false));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\n");
+    }
+
+    #[test]
+    fn test_compile_bitwise_not() {
+        let input = r#"
+        print(~0);
+        print(~5);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "-1\n-6\n");
+    }
+
+    #[test]
+    fn test_compile_bitwise_not_variable_in_binary() {
+        let input = r#"
+        let x = 5;
+        let y = ~x + 1;
+        print(y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "-5\n");
+    }
+
+    #[test]
+    fn test_compile_bitwise_not_string_error() {
+        let input = r#"
+        let name = "cyclang";
+        print(~name);
+        "#;
+        let output = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "operator `~` expected `name` to have type i32 or i64, but it was inferred as string"
+        );
+    }
+
+    #[test]
+    fn test_compile_inline_attribute() {
+        let input = r#"
+        #[inline]
+        fn add_one(i32 x) -> i32 {
+            return x + 1;
+        }
+        let a = add_one(1);
+        let b = add_one(a);
+        print(b);
+        "#;
+        let (output, ir) = compile_with_optimization_level_test(input, 2);
+        assert_eq!(output, "3\n");
+        assert!(!ir.contains("call i32 @add_one"));
+    }
+
+    #[test]
+    fn test_compile_noinline_attribute() {
+        let input = r#"
+        #[noinline]
+        fn add_one(i32 x) -> i32 {
+            return x + 1;
+        }
+        print(add_one(1));
+        "#;
+        let (output, ir) = compile_with_optimization_level_test(input, 2);
+        assert_eq!(output, "2\n");
+        assert!(ir.contains("call i32 @add_one"));
+        assert!(ir.contains("noinline"));
+    }
+
+    #[test]
+    fn test_compile_emit_object_file() {
+        use std::process::Command;
+
+        let input = r#"
+        let x = 1 + 2;
+        print(x);
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        let compile_options = Some(CompileOptions {
+            execution_mode: ExecutionMode::Aot,
+            target: None,
+            debug_info: false,
+            optimization_level: 0,
+            emit_object: true,
+            print_ir: false,
+            verify_module: false,
+            source_file: None,
+        });
+        let output = compiler::compile(exprs, compile_options).expect("unable to compile");
+        assert_eq!(output, "3\n");
+
+        // cc drives ld with the C runtime start files, the stdlib is already in the object
+        let link = Command::new("cc")
+            .args(["bin/main.o", "-o", "bin/main_object"])
+            .output()
+            .expect("unable to link bin/main.o");
+        assert!(link.status.success(), "{}", String::from_utf8_lossy(&link.stderr));
+        let output = Command::new("bin/main_object").output().expect("unable to run binary");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+    }
+
+    #[test]
+    fn test_compile_sequential_fn_definitions() {
+        let input = r#"
+        fn add(i32 a, i32 b) -> i32 {
+            return a + b;
+        }
+        fn double(i32 a) -> i32 {
+            return a * 2;
+        }
+        let total = add(1, 2);
+        let doubled = double(5);
+        print(total);
+        print(doubled);
+        print("done");
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n10\n\"done\"\n");
+    }
+
+    #[test]
+    fn test_compile_tuple_return_destructure() {
+        let input = r#"
+        fn divmod(i32 a, i32 b) -> (i32, i32) {
+            return (a / b, a - (a / b) * b);
+        }
+        let (q, r) = divmod(7, 2);
+        print(q);
+        print(r);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n1\n");
+    }
+
+    #[test]
+    fn test_compile_tuple_with_float_component() {
+        let input = r#"
+        fn scaled(i32 a) -> (i32, float) {
+            return (a, 2.5);
+        }
+        let (a, factor) = scaled(3);
+        print(a);
+        print(factor);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n2.5\n");
+    }
+
+    #[test]
+    fn test_compile_tuple_destructure_wrong_arity() {
+        let input = r#"
+        let (a, b, c) = (1, 2);
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "unable to destructure type (i32, i32) into (a, b, c)");
+    }
+
+    #[test]
+    fn test_compile_wasm32_target() {
+        let input = r#"
+        let x = 1 + 2;
+        print(x);
+        "#;
+        let (object, linked) = compile_target_object_test(input, "wasm32");
+        // a wasm object file has the wasm magic and version 1
+        assert_eq!(&object[..8], b"\0asm\x01\0\0\0");
+        if has_tool("wasm-ld") {
+            assert_eq!(linked, Ok("".to_string()));
+            let wasm = std::fs::read("bin/main.wasm").expect("unable to read bin/main.wasm");
+            assert_eq!(&wasm[..8], b"\0asm\x01\0\0\0");
+        }
+    }
+
+    #[test]
+    fn test_compile_struct_field_access() {
+        let input = r#"
+        struct Point {
+            i32 x,
+            i32 y
+        }
+        let point = Point { x: 1, y: 2 };
+        print(point.x);
+        print(point.y);
+        print(point.x + point.y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n2\n3\n");
+    }
+
+    #[test]
+    fn test_compile_struct_missing_field() {
+        let input = r#"
+        struct Point {
+            i32 x,
+            i32 y
+        }
+        let point = Point { x: 1 };
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "missing field `y` in Point { x: 1 }");
+    }
+
+    #[test]
+    fn test_compile_aarch64_linux_target() {
+        let input = r#"
+        let x = 1 + 2;
+        print(x);
+        "#;
+        let (object, linked) = compile_target_object_test(input, "aarch64-linux");
+        // a relocatable ELF file with e_machine EM_AARCH64
+        assert_eq!(&object[..4], b"\x7fELF");
+        assert_eq!(u16::from_le_bytes([object[16], object[17]]), 1);
+        assert_eq!(u16::from_le_bytes([object[18], object[19]]), 183);
+        if has_tool("aarch64-linux-gnu-gcc") {
+            assert_eq!(linked, Ok("".to_string()));
+            let binary = std::fs::read("bin/main").expect("unable to read bin/main");
+            assert_eq!(&binary[..4], b"\x7fELF");
+            assert_eq!(u16::from_le_bytes([binary[18], binary[19]]), 183);
+        }
+    }
+
+    #[test]
+    fn test_compile_struct_return() {
+        let input = r#"
+        struct Point {
+            i32 x,
+            i32 y
+        }
+        fn make_point(i32 x, i32 y) -> Point {
+            return Point { x: x, y: y };
+        }
+        let point = make_point(3, 4);
+        print(point.y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "4\n");
+    }
+
+    #[test]
+    fn test_compile_sum_widens_to_i64() {
+        let input = r#"
+        let values = [2147483647, 2147483647, 2];
+        print(sum(values));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "4294967296\n");
+    }
+
+    #[test]
+    fn test_compile_avg() {
+        let input = r#"
+        let values = [1, 2, 3, 4];
+        print(avg(values));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2.5\n");
+    }
+
+    #[test]
+    fn test_compile_reassign_string_in_loop() {
+        let input = r#"
+        let word = "start";
+        for (let i = 0; i < 3; i++)
+        {
+            if (i == 1) {
+                word = "middle";
+            } else {
+                word = word + "!";
+            }
+        }
+        print(word);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"middle!\"\n");
+    }
+
+    #[test]
+    fn test_compile_reassign_string_reuses_buffer() {
+        let input = r#"
+        let word = "hello";
+        word = word;
+        print(word);
+        word = "hi";
+        print(word);
+        word = word + " there";
+        print(word);
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        // each literal is initialised once, reassigning doesn't build a temporary string
+        assert_eq!(ir.matches("@stringInit(ptr @str_const").count(), 3);
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"hello\"\n\"hi\"\n\"hi there\"\n");
+    }
+
+    #[test]
+    fn test_compile_string_less_than() {
+        let input = r#"print("apple" < "banana");"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\n");
+    }
+
+    #[test]
+    fn test_compile_string_greater_than() {
+        let input = r#"
+        let a = "a";
+        let b = "b";
+        print(b > a);
+        print(b <= a);
+        print(a != b);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\ntrue\n");
+    }
+
+    #[test]
+    fn test_compile_string_prefix_sorts_first() {
+        let input = r#"
+        print("ab" < "abc");
+        print("abc" > "ab");
+        print("ab" < "ab");
+        print("b" > "abc");
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\ntrue\nfalse\ntrue\n");
+    }
+
+    #[test]
+    fn test_compile_lerp() {
+        let input = r#"
+        print(lerp(0.0, 10.0, 0.5) == 5.0);
+        print(lerp(2.0, 4.0, 0.25));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\n2.5\n");
+    }
+
+    #[test]
+    fn test_compile_clamp_float() {
+        let input = r#"
+        print(clamp(1.5, 0.0, 1.0));
+        print(clamp(-0.5, 0.0, 1.0));
+        print(clamp(0.25, 0.0, 1.0));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1.0\n0.0\n0.25\n");
+    }
+
+    #[test]
+    fn test_compile_print_constant_without_alloca() {
+        let input = r#"
+        print(42);
+        print(2.5);
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        assert!(ir.contains("i32 42"));
+        assert!(!ir.contains("%num32 = alloca"));
+        assert!(!ir.contains("%float = alloca"));
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "42\n2.5\n");
+    }
+
+    #[test]
+    fn test_compile_float_typed_function() {
+        let input = r#"
+        fn half(float x) -> float {
+            return x / 2.0;
+        }
+        let y: float = half(5.0);
+        print(y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2.5\n");
+    }
+
+    #[test]
+    fn test_compile_string_unicode_escape() {
+        let input = r#"print("caf\u{E9} \u{1F600}");"#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"caf\u{E9} \u{1F600}\"\n");
+        assert_eq!(output.len(), "\"cafe \"\n".len() + 1 + 4);
+    }
+
+    #[test]
+    fn test_compile_for_each_reversed_list() {
+        let input = r#"
+        let values = [1, 2, 3];
+        for x in reversed(values) {
+            print(x);
+        }
+        for name in ["a", "b"] {
+            print(name);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n2\n1\n\"a\"\n\"b\"\n");
+    }
+
+    #[test]
+    fn test_compile_reversed_range() {
+        let input = r#"
+        for i in reversed(0..3) {
+            print(i);
+        }
+        for i in reversed(-2..0) {
+            print(i);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2\n1\n0\n-1\n-2\n");
+    }
+
+    #[test]
+    fn test_compile_reversed_range_at_i32_bounds() {
+        let input = r#"
+        for i in reversed(2147483646..2147483647) {
+            print(i);
+        }
+        for i in reversed(-2147483648..-2147483646) {
+            print(i);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2147483646\n-2147483647\n-2147483648\n");
+    }
+
+    #[test]
+    fn test_parse_file_error_shows_path_and_line() {
+        let dir = write_test_files(
+            "cyclang_test_parse_error",
+            &[("bad.cyclo", "let a = 1;\nprint(a\n")],
+        );
+        let error = parse_cyclo_file(&dir.join("bad.cyclo")).unwrap_err();
+        assert!(error.contains("bad.cyclo:2:"));
+        assert!(error.contains('^'));
+    }
+
+    #[test]
+    fn test_compile_let_i64_annotation_widens_literal() {
+        let input = r#"
+        let x: i64 = 5;
+        print(x);
+        x = 5000000000;
+        print(x);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "5\n5000000000\n");
+    }
+
+    #[test]
+    fn test_compile_let_annotation_mismatch_is_error() {
+        let input = r#"
+        let x: i64 = "hello";
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "cannot assign string to i64 variable x");
+    }
+
+    #[test]
+    fn test_compile_export_attribute() {
+        let input = r#"
+        #[export]
+        fn add(i32 a, i32 b) -> i32 {
+            return a + b;
+        }
+        print(add(1, 2));
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        assert!(ir.contains("define i32 @add("));
+        assert!(ir.contains("\"wasm-export-name\"=\"add\""));
+
+        let (_, linked) = compile_target_object_test(input, "wasm32");
+        if has_tool("wasm-ld") {
+            assert_eq!(linked, Ok("".to_string()));
+            let wasm = std::fs::read("bin/main.wasm").expect("unable to read bin/main.wasm");
+            let exports = wasm_export_names(&wasm);
+            assert!(exports.iter().any(|name| name == "add"));
+        }
+    }
+
+    // the names in the export section of a wasm binary
+    fn wasm_export_names(wasm: &[u8]) -> Vec<String> {
+        fn read_leb128(bytes: &[u8], pos: &mut usize) -> usize {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let byte = bytes[*pos];
+                *pos += 1;
+                value |= ((byte & 0x7f) as usize) << shift;
+                if byte & 0x80 == 0 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+        // sections follow the magic and version, each is an id and a length prefixed payload
+        let mut pos = 8;
+        let mut names = vec![];
+        while pos < wasm.len() {
+            let id = wasm[pos];
+            pos += 1;
+            let size = read_leb128(wasm, &mut pos);
+            let end = pos + size;
+            if id == 7 {
+                let count = read_leb128(wasm, &mut pos);
+                for _ in 0..count {
+                    let len = read_leb128(wasm, &mut pos);
+                    names.push(String::from_utf8_lossy(&wasm[pos..pos + len]).to_string());
+                    // the export kind and the index of what's exported
+                    pos += len + 1;
+                    read_leb128(wasm, &mut pos);
+                }
+            }
+            pos = end;
+        }
+        names
+    }
+
+    #[test]
+    fn test_compile_user_function_shadows_builtin() {
+        let input = r#"
+        fn sum(List<i32> values) -> i32 {
+            return len(values);
+        }
+        let values = [1, 2, 3];
+        print(sum(values));
+        print(builtin.sum(values));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n6\n");
+    }
+
+    #[test]
+    fn test_compile_errors_are_categorised() {
+        use cyclang_backend::compiler::error::CycloError;
+        use cyclang_backend::compiler::types::BaseTypes;
+
+        let compile = |input: &str| {
+            let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+            compiler::compile(exprs, None).expect_err("expected compile error")
+        };
+        let error = compile("print(missing);");
+        assert_eq!(
+            error.downcast_ref::<CycloError>(),
+            Some(&CycloError::UndefinedVariable {
+                name: "missing".to_string(),
+                span: None,
+            })
+        );
+        let error = compile("let x: i64 = true;");
+        assert_eq!(
+            error.downcast_ref::<CycloError>(),
+            Some(&CycloError::TypeMismatch {
+                name: "x".to_string(),
+                expected: BaseTypes::Number64,
+                found: BaseTypes::Bool,
+                span: None,
+            })
+        );
+        let error = compile("print(1 / 0);");
+        assert_eq!(error.to_string(), "division by zero in `1 / 0`");
+        assert!(matches!(
+            error.downcast_ref::<CycloError>(),
+            Some(CycloError::DivisionByZero { .. })
+        ));
+        let error = compile("let (a, b, c) = (1, 2);");
+        assert_eq!(
+            error.downcast_ref::<CycloError>(),
+            Some(&CycloError::InvalidDestructure {
+                names: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                found: Box::new(BaseTypes::Tuple(vec![BaseTypes::Number, BaseTypes::Number])),
+                span: None,
+            })
+        );
+        let error = compile("struct Point { i32 x, i32 x }");
+        assert!(matches!(
+            error.downcast_ref::<CycloError>(),
+            Some(CycloError::DuplicateField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_target_triples() {
+        let error = Target::from_triple("not-a-real-triple").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid target triple not-a-real-triple"));
+        let target = Target::from_triple("x86_64-pc-linux-gnu").unwrap();
+        assert_eq!(target.arch, TargetArch::x86_64);
+        assert_eq!(target.get_llvm_target_name(), "x86_64-pc-linux-gnu");
+        let target = Target::from_triple("wasm32-unknown-unknown").unwrap();
+        assert_eq!(target.arch, TargetArch::wasm);
+        assert_eq!(target.get_llvm_target_name(), "wasm32-unknown-unknown");
+        let error = Target::from_triple("arm32")
+            .unwrap()
+            .initialize()
+            .unwrap_err();
+        assert!(error.to_string().contains("is not supported"));
+    }
+
+    #[test]
+    fn test_invalid_target_exits() {
+        // get_target exits the process, see test_compile_jit_mode_runs_in_process
+        if std::env::var("CYCLO_TARGET_TEST").is_ok() {
+            get_target(Some("not-a-real-triple".to_string()));
+            return;
+        }
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "test::test_invalid_target_exits", "--nocapture"])
+            .env("CYCLO_TARGET_TEST", "1")
+            .output()
+            .expect("unable to rerun the test");
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("invalid target triple not-a-real-triple"));
+    }
+
+    #[test]
+    fn test_compile_error_shows_statement_location() {
+        let input = "let a = 1;\nprint(a);\n  print(missing);";
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let error = compiler::compile_spanned(exprs, None).expect_err("expected compile error");
+        assert_eq!(error.to_string(), "Unknown variable missing at 3:3");
+    }
+
+    #[test]
+    fn test_compile_error_in_function_shows_statement_location() {
+        let input = "fn f() {\n  let a = 1;\n  if (a > 0) {\n    print(missing);\n  }\n}\nf();";
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let error = compiler::compile_spanned(exprs, None).expect_err("expected compile error");
+        assert_eq!(error.to_string(), "Unknown variable missing at 4:5");
+    }
+
+    #[test]
+    fn test_type_checker_reports_every_error() {
+        use cyclang_backend::compiler::error::CycloError;
+        let input = "let a: i64 = \"x\";\nprint(b);\nfn f(i32 x) -> i32 { return x + \"s\"; }";
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let error = compiler::compile_spanned(exprs, None).expect_err("expected compile error");
+        match error.downcast_ref::<CycloError>() {
+            Some(CycloError::TypeErrors { errors }) => {
+                assert_eq!(errors.len(), 3);
+                assert!(matches!(errors[0], CycloError::TypeMismatch { .. }));
+                assert!(matches!(errors[1], CycloError::UndefinedVariable { .. }));
+                assert!(matches!(errors[2], CycloError::OperandMismatch { .. }));
+            }
+            _ => panic!("expected type errors, got {}", error),
+        }
+        assert!(error.to_string().contains("Unknown variable b at 2:1"));
+    }
+
+    #[test]
+    fn test_unreachable_code_after_return() {
+        use cyclang_backend::compiler::optimizer::prune_dead_code;
+        let block = parse_cyclo_program("{ return 1; print(\"dead\"); print(2); }").unwrap();
+        let stmts = match &block[0] {
+            Expression::BlockStmt(stmts, _) => stmts.clone(),
+            _ => panic!("expected a block"),
+        };
+        assert_eq!(stmts.len(), 3);
+        assert_eq!(prune_dead_code(stmts.clone()), stmts[..1].to_vec());
+        let stmts = parse_cyclo_program("print(1); print(2);").unwrap();
+        assert_eq!(prune_dead_code(stmts.clone()), stmts);
+
+        let input = r#"
+        fn f() -> i32 {
+            return 1;
+            print("dead");
+        }
+        print(f());
+        "#;
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let warnings = compiler::warnings_spanned(&exprs);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<String>>(),
+            ["unreachable code after return: `print(\"dead\")` at 4:13"]
+        );
+        let (output, ir) = compile_with_optimization_level_test(input, 0);
+        assert_eq!(output, "1\n");
+        assert!(!ir.contains("dead"));
+    }
+
+    #[test]
+    fn test_compile_let_if_expression() {
+        let input = r#"
+        let a = 3;
+        let x = if (a > 1) { 10; } else { 20; };
+        let y = if (a > 5) { "big"; } else { "small"; };
+        print(x + 1);
+        print(y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "11\n\"small\"\n");
+    }
+
+    #[test]
+    fn test_let_type_inference_errors() {
+        let error = compile_error_from_string_test(
+            "let x = if (true) { 1; } else { \"one\"; };".to_string(),
+        );
+        assert_eq!(
+            error,
+            "if branches must have the same type, expected i32 but found string"
+        );
+        // a branch without a value can't be mixed with one that has a value
+        let error = compile_error_from_string_test(
+            "let x = if (true) { print(1); } else { 1; };".to_string(),
+        );
+        assert_eq!(
+            error,
+            "if branches must have the same type, expected void but found i32"
+        );
+        // the type inferred from the first let is used for the reassignment
+        let error = compile_error_from_string_test("let x = 1;\nx = \"one\";".to_string());
+        assert_eq!(error, "cannot assign string to i32 variable x");
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        use cyclang_backend::compiler::optimizer::fold_constants;
+        let fold = |input: &str| -> Vec<Expression> {
+            parse_cyclo_program(input)
+                .expect("unable to parse contents")
+                .into_iter()
+                .map(fold_constants)
+                .collect()
+        };
+        assert_eq!(
+            fold("print(1 + 2 * 3); print((10 - 4) / 2); print(2147483647 + 1);"),
+            vec![
+                Expression::Print(Box::new(Expression::Number(7))),
+                Expression::Print(Box::new(Expression::Number(3))),
+                Expression::Print(Box::new(Expression::Number64(2147483648))),
+            ]
+        );
+        assert_eq!(
+            fold("let n = sizeof(i64) * 4 + sizeof(bool);"),
+            vec![Expression::LetStmt(
+                "n".into(),
+                cyclang_parser::Type::None,
+                Box::new(Expression::Number64(33))
+            )]
+        );
+        // variables, division by zero and sizes that depend on the target are left for codegen
+        let unfolded = fold("print(a + 1); print(1 / 0); print(sizeof(string));");
+        assert_eq!(
+            unfolded,
+            parse_cyclo_program("print(a + 1); print(1 / 0); print(sizeof(string));").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compile_folds_constants_before_codegen() {
+        let input = r#"
+        print(1 + 2 * 3);
+        "#;
+        let (output, ir) = compile_with_optimization_level_test(input, 0);
+        assert_eq!(output, "7\n");
+        let ir = main_function_ir(&ir);
+        assert!(ir.contains("i32 7"));
+        assert!(!ir.contains(" mul "));
+    }
+
+    #[test]
+    fn test_compile_block_value_from_last_expression() {
+        let input = r#"
+        let a = 3;
+        let x = if (a > 1) { 5 } else { a };
+        print(x + 1);
+        let y = if (a > 5) { 1 } else { let b = 4; b * 2 };
+        print(y);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "6\n8\n");
+    }
+
+    #[test]
+    fn test_compile_shadowing_in_nested_blocks() {
+        let input = r#"
+        let x = 1;
+        {
+            let x = 2;
+            print(x)
+        }
+        print(x);
+        {
+            let x = "inner";
+            print(x);
+            x = "assigned";
+            print(x);
+        }
+        print(x);
+        {
+            x = 3;
+        }
+        print(x);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2\n1\n\"inner\"\n\"assigned\"\n1\n3\n");
+    }
+
+    #[test]
+    fn test_compile_print_uses_value_width() {
+        let input = r#"
+        print(4000000000);
+        let big = 2000000000;
+        print(big + 5000000000);
+        print(0 - 5000000000);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "4000000000\n7000000000\n-5000000000\n");
+    }
+
+    #[test]
+    fn test_compile_outer_variable_restored_after_inner_declaration() {
+        let input = r#"
+        let total = 10;
+        let flag = true;
+        if (flag) {
+            let total = "inner";
+            if (flag) {
+                let total = 3;
+                print(total);
+            }
+            print(total);
+        }
+        print(total + 1);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n\"inner\"\n11\n");
+    }
+
+    #[test]
+    fn test_compile_constant_overflow_promotes_to_i64() {
+        let input = r#"
+        print(2000000000 + 2000000000);
+        print(0 - 2000000000 - 2000000000);
+        print(100000 * 100000);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "4000000000\n-4000000000\n10000000000\n");
+    }
+
+    #[test]
+    fn test_compile_for_loop_counter_assigned_in_body() {
+        let input = r#"
+        for (let i = 0; i < 10; i++) {
+            if (i == 2) {
+                i = i + 3;
+            }
+            if (i == 8) {
+                break;
+            }
+            print(i);
+        }
+        for (let j = 3; j > 0; j--) {
+            print(j);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0\n1\n5\n6\n7\n3\n2\n1\n");
+    }
+
+    #[test]
+    fn test_compile_for_loop_counter_shadows_outer_variable() {
+        let input = r#"
+        let i = 100;
+        for (let i = 0; i < 2; i++) {
+            print(i);
+        }
+        print(i);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "0\n1\n100\n");
+    }
+
+    #[test]
+    fn test_variable_cache_rename() {
+        use cyclang_backend::compiler::cache::VariableCache;
+        use cyclang_backend::compiler::types::num::NumberType;
+        let number = |name: &str| NumberType {
+            name: name.to_string(),
+            llvm_value: std::ptr::null_mut(),
+            llvm_value_pointer: None,
+        };
+        let cache = VariableCache::new();
+        cache.write().set("i", Box::new(number("outer")), 0);
+        cache.write().set("i", Box::new(number("inner")), 1);
+        cache.write().rename("i", "i.0");
+        assert_eq!(cache.read().get("i.0").unwrap().get_name_as_str(), "inner");
+        assert_eq!(cache.read().get("i").unwrap().get_name_as_str(), "outer");
+        // the renamed binding still goes when the depth it was set at ends
+        cache.write().del_locals(1);
+        assert!(cache.read().get("i.0").is_none());
+        assert_eq!(cache.read().get("i").unwrap().get_name_as_str(), "outer");
+    }
+
+    #[test]
+    fn test_interpret_program_matches_compiled_output() {
+        let input = r#"
+        fn fib(i32 n) -> i32 {
+            if (n < 2) {
+                return n;
+            }
+            return fib(n - 1) + fib(n - 2);
+        }
+        let total = 0;
+        for (let i = 0; i < 5; i++) {
+            total = total + i;
+        }
+        print(total);
+        print(fib(10));
+        print("a" + "b");
+        print(avg([1, 2, 3, 4]));
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        let output = interpret_program(&exprs).unwrap();
+        assert_eq!(output, compile_output_from_string_test(input.to_string()));
+        assert_eq!(output, "10\n55\n\"ab\"\n2.5\n");
+    }
+
+    #[test]
+    fn test_compile_string() {
+        let output = cyclang_backend::compile_string("print(6 * 7);", Some(Default::default()));
+        assert_eq!(output.unwrap(), "42\n");
+    }
+
+    #[test]
+    fn test_compile_string_parse_error() {
+        let error = cyclang_backend::compile_string("print(6 *", None).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unable to parse contents due to error"));
+    }
+
+    #[test]
+    fn test_compile_if_merges_variables_assigned_in_branches() {
+        let input = r#"
+        let x = 1;
+        let big = false;
+        for (let i = 0; i < 4; i++) {
+            if (i > 1) {
+                x = x * 10;
+                big = true;
+                if (i == 3) {
+                    x = x + 1;
+                }
+            } else {
+                x = x + i;
+            }
+            print(x);
+        }
+        print(big);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n2\n20\n201\ntrue\n");
+    }
+
+    #[test]
+    fn test_codegen_error_shows_statement_location() {
+        let input = "let a = 1;\n  let (b, c, d) = (a, 2);";
+        let exprs = parse_cyclo_program_spanned(input).expect("unable to parse contents");
+        let error = compiler::compile_spanned(exprs, None).expect_err("expected compile error");
+        assert_eq!(
+            error.to_string(),
+            "unable to destructure type (i32, i32) into (b, c, d) at 2:3"
+        );
+    }
+
+    #[test]
+    fn test_compile_function_overloads() {
+        let input = r#"
+        fn scale(i32 x) -> i32 {
+            return x + 1;
+        }
+        fn scale(i64 x) -> i64 {
+            return x * 2;
+        }
+        let small = 20;
+        let big = 3000000000;
+        print(scale(small));
+        print(scale(big));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "21\n6000000000\n");
+    }
+
+    #[test]
+    fn test_compile_widened_overload_in_declaration_order() {
+        let input = r#"
+        fn pick(i64 x, i32 y) -> i32 {
+            return 1;
+        }
+        fn pick(i32 x, i64 y) -> i64 {
+            return 2;
+        }
+        let a = 5;
+        let picked = pick(a, a);
+        print(picked);
+        print(picked is i32);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\ntrue\n");
+    }
+
+    #[test]
+    fn test_compile_no_matching_overload() {
+        let input = r#"
+        fn scale(i32 x) -> i32 {
+            return x + 1;
+        }
+        fn scale(i64 x) -> i64 {
+            return x * 2;
+        }
+        print(scale("x"));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "no overload of function \"scale\" takes (string)");
+    }
+
+    #[test]
+    fn test_compile_call_with_declared_arity() {
+        let input = r#"
+        fn add(i32 x, i32 y) -> i32 {
+            return x + y;
+        }
+        print(add(1, 2));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn test_compile_call_with_wrong_arity() {
+        let input = r#"
+        fn add(i32 x, i32 y) -> i32 {
+            return x + y;
+        }
+        print(add(1));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "function add expects 2 arguments, got 1");
+        let input = r#"
+        fn inc(i32 x) -> i32 {
+            return x + 1;
+        }
+        print(inc(1, 2));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "function inc expects 1 argument, got 2");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap_err().to_string(),
+            "function inc expects 1 argument, got 2"
+        );
+    }
+
+    #[test]
+    fn test_compile_call_with_declared_arg_types() {
+        let input = r#"
+        fn describe(i32 count, string label) -> string {
+            print(count);
+            return label;
+        }
+        fn widen(i64 x) -> i64 {
+            return x * 2;
+        }
+        print(describe(2, "items"));
+        print(widen(21));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2\n\"items\"\n42\n");
+    }
+
+    #[test]
+    fn test_compile_call_with_wrong_arg_type() {
+        let input = r#"
+        fn double(i32 x) -> i32 {
+            return x * 2;
+        }
+        print(double("two"));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "argument 1 of double expects i32, got string");
+        let input = r#"
+        fn label(string name, i32 count) -> i32 {
+            return count;
+        }
+        print(label("a", true));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "argument 2 of label expects i32, got bool");
+    }
+
+    #[test]
+    fn test_compile_nested_function() {
+        let input = r#"
+        fn outer(i32 x) -> i32 {
+            fn inner(i32 y) -> i32 {
+                return y * 2;
+            }
+            return inner(x) + 1;
+        }
+        print(outer(3));
+        print(outer(10));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "7\n21\n");
+    }
+
+    #[test]
+    fn test_compile_nested_function_out_of_scope() {
+        let input = r#"
+        fn outer(i32 x) -> i32 {
+            fn inner(i32 y) -> i32 {
+                return y * 2;
+            }
+            return inner(x);
+        }
+        print(outer(3));
+        print(inner(3));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "call does not exist for function \"inner\"");
+    }
+
+    #[test]
+    fn test_compile_global_shared_between_functions() {
+        let input = r#"
+        global counter: i32 = 0;
+        fn bump() {
+            counter = counter + 1;
+        }
+        fn bump_twice() {
+            counter = counter + 2;
+        }
+        bump();
+        bump_twice();
+        bump();
+        if (counter > 3) {
+            counter = counter * 10;
+        }
+        print(counter);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "40\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_global_literal_is_initializer() {
+        // a literal is set as the initializer, any other value is stored where it's declared
+        let input = r#"
+        global limit: i32 = 5;
+        global verbose: bool = true;
+        let start = 2;
+        global offset: i32 = start;
+        print(limit + offset);
+        "#;
+        let ir = compile_ir_from_string_test(input.to_string());
+        assert!(ir.contains("@limit = internal global i32 5"));
+        assert!(ir.contains("@verbose = internal global i1 true"));
+        assert!(ir.contains("@offset = internal global i32 0"));
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "7\n");
+    }
+
+    #[test]
+    fn test_compile_default_args() {
+        let input = r#"
+        fn greet(string name = "world") {
+            print(name);
+        }
+        fn add(i32 x, i32 y = 10) -> i32 {
+            return x + y;
+        }
+        greet();
+        greet("bob");
+        print(add(1));
+        print(add(1, 2));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"world\"\n\"bob\"\n11\n3\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_default_args_missing_required() {
+        let input = r#"
+        fn add(i32 x, i32 y = 10) -> i32 {
+            return x + y;
+        }
+        print(add());
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "function add expects 2 arguments, got 0");
+    }
+
+    #[test]
+    fn test_compile_default_args_only_see_earlier_args() {
+        let input = r#"
+        fn add(i32 x, i32 y = x + 1) -> i32 {
+            return x + y;
+        }
+        fn twice(i32 x) -> i32 {
+            return add(x * 2);
+        }
+        let x = 100;
+        print(add(1));
+        print(twice(3));
+        print(add(1, 1));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n13\n2\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_default_arg_type_mismatch() {
+        let input = r#"
+        fn greet(i32 times = "twice") {
+            print(times);
+        }
+        greet();
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "cannot assign string to i32 variable times");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        let error = interpret_program(&exprs).unwrap_err().to_string();
+        assert!(error.contains("default value of argument times"));
+    }
+
+    #[test]
+    fn test_compile_extern_functions() {
+        let input = r#"
+        extern fn abs(i32 x) -> i32;
+        extern fn labs(i64 x) -> i64;
+        let big: i64 = 0 - 3000000000;
+        print(abs(0 - 5));
+        print(labs(big));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "5\n3000000000\n");
+    }
+
+    #[test]
+    fn test_compile_extern_function_string_arg() {
+        let input = r#"
+        extern fn strlen(string s) -> i64;
+        let s = "hello";
+        print(strlen(s));
+        print(strlen(""));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "5\n0\n");
+    }
+
+    #[test]
+    fn test_compile_extern_function_string_return_is_error() {
+        let input = r#"
+        extern fn getenv(string name) -> string;
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "extern function `getenv` can only return i32, i64, float and bool"
+        );
+    }
+
+    #[test]
+    fn test_compile_extern_function_conflicting_type_is_error() {
+        let input = r#"
+        fn twice(i32 x) -> i32 {
+            return x * 2;
+        }
+        extern fn twice(i64 x) -> i64;
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(
+            error,
+            "extern function `twice` doesn't match the function `twice` already defined"
+        );
+    }
+
+    #[test]
+    fn test_compile_sizeof() {
+        let input = r#"
+        struct Pair {
+            i32 a,
+            i64 b
+        }
+        print(sizeof(i32));
+        print(sizeof(i64));
+        print(sizeof(float));
+        print(sizeof(bool));
+        print(sizeof(Pair));
+        print(sizeof((i32, i32)));
+        let n = sizeof(i64) * 2;
+        print(n);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "4\n8\n8\n1\n16\n8\n16\n");
+    }
+
+    #[test]
+    fn test_compile_cast() {
+        let input = r#"
+        let a = 7;
+        let b = cast<i64>(a) * 1000000000;
+        print(b);
+        print(cast<i32>(b));
+        print(cast<float>(a) / 2.0);
+        print(cast<i32>(2.9));
+        print(cast<i32>(true));
+        print(cast<bool>(0));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "7000000000\n-1589934592\n3.5\n2\n1\nfalse\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_cast_unsupported_is_error() {
+        let input = r#"
+        print(cast<i32>("7"));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "unable to cast string to i32");
+        let input = r#"
+        let address = 4096;
+        print(cast<string>(cast<i64>(address)));
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert_eq!(error, "unable to cast i64 to string");
+    }
+
+    #[test]
+    fn test_compile_void_calls_are_discarded() {
+        let input = r#"
+        fn greet(i32 n) {
+            print(n);
+        }
+        greet(1);
+        greet(2);
+        let n = 3;
+        if (n == 3) {
+            greet(n);
+        }
+        greet(4);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n2\n3\n4\n");
+    }
+
+    #[test]
+    fn test_compile_call_does_not_bind_function_name() {
+        let input = r#"
+        fn double(i32 n) -> i32 {
+            return n * 2;
+        }
+        print(double(2));
+        print(double);
+        "#;
+        let error = compile_error_from_string_test(input.to_string());
+        assert!(error.contains("Unknown variable double"));
+    }
+
+    #[test]
+    fn test_compile_for_each_sums_list() {
+        let input = r#"
+        let total = 0;
+        for x in [10, 20, 30] {
+            total = total + x;
+        }
+        print(total);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "60\n");
+    }
+
+    #[test]
+    fn test_compile_is_expr() {
+        let input = r#"
+        fn half(i32 n) -> float {
+            return cast<float>(n) / 2.0;
+        }
+        let x = 5;
+        print(x is i32);
+        print(x is i64);
+        print(half(3) is float);
+        print([1, 2] is List<i32>);
+        if (x is i32) {
+            print("int");
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\ntrue\ntrue\n\"int\"\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_is_expr_uses_declared_type() {
+        let input = r#"
+        let y: i64 = 5;
+        print(y is i64);
+        print(y is i32);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_list_slice() {
+        let input = r#"
+        let s = [1, 2, 3, 4, 5][1..4];
+        print(len(s));
+        print(s[0]);
+        let names = ["a", "b", "c"];
+        for name in names[1..3] {
+            print(name);
+        }
+        print(len(names[2..2]));
+        let i = 1;
+        print(s[i - 1..i + 1]);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n2\n\"b\"\n\"c\"\n0\n[2, 3]\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_list_slice_out_of_range() {
+        let input = r#"
+        let names = ["a", "b", "c"];
+        print(len(names[1..3]));
+        print(names[1..10]);
+        print(len(names));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "2\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap_err().to_string(),
+            "slice bounds 1..10 out of range for length 3"
+        );
+    }
+
+    #[test]
+    fn test_compile_list_i64_index() {
+        let input = r#"
+        let values = [5000000000, 6000000000];
+        print(values[1]);
+        values[0] = 7;
+        print(values);
+        print(len([1, 5000000000]));
+        for x in [1, 5000000000] {
+            print(x);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "6000000000\n[7, 6000000000]\n2\n1\n5000000000\n");
+    }
+
+    #[test]
+    fn test_target_info_matches_host() {
+        // strings and lists are pointers, so sizeof reads the pointer size from target_info
+        let input = r#"
+        print(sizeof(string));
+        print(sizeof(List<i32>));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        let pointer_size = std::mem::size_of::<usize>();
+        assert_eq!(output, format!("{}\n{}\n", pointer_size, pointer_size));
+
+        let target_info = TargetInfo::new(None).expect("unable to read the host layout");
+        assert_eq!(target_info.pointer_size as usize, pointer_size);
+        assert_eq!(target_info.is_little_endian, cfg!(target_endian = "little"));
+        assert_eq!(target_info.type_size(&Type::i64).unwrap(), 8);
+        assert_eq!(target_info.type_size(&Type::Bool).unwrap(), 1);
+        assert_eq!(target_info.type_size(&Type::String).unwrap() as usize, pointer_size);
+        assert_eq!(
+            target_info.type_alignment(&Type::i64).unwrap() as usize,
+            std::mem::align_of::<i64>()
+        );
+        let tuple = Type::Tuple(vec![Type::i32, Type::i64]);
+        assert_eq!(target_info.type_size(&tuple).unwrap(), 16);
+        assert!(target_info.type_size(&Type::Struct("Point".into())).is_err());
+    }
+
+    #[test]
+    fn test_jit_exit_sets_process_exit_code() {
+        // exit ends the process running the execution engine, so the program is run by this
+        // test again in a child process
+        if std::env::var("CYCLO_EXIT_TEST").is_ok() {
+            let input = r#"
+            print(41);
+            exit(3);
+            print(42);
+            "#;
+            compile_output_from_string(input.to_string(), ExecutionMode::Jit, None);
+            return;
+        }
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "test::test_jit_exit_sets_process_exit_code", "--nocapture"])
+            .env("CYCLO_EXIT_TEST", "1")
+            .output()
+            .expect("unable to rerun the test");
+        assert_eq!(output.status.code(), Some(3));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("41"));
+        assert!(!stdout.contains("42"));
+    }
+
+    #[test]
+    fn test_compile_string_escapes() {
+        let input = r#"
+        print("a\nb");
+        print("a\tb");
+        print("a\rb");
+        print("a\\b");
+        print("say \"hi\"");
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "\"a\nb\"\n\"a\tb\"\n\"a\rb\"\n\"a\\b\"\n\"say \"hi\"\"\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_string_escape_errors() {
+        let output = compile_error_from_string_test(r#"print("a\qb");"#.to_string());
+        assert_eq!(output, "invalid escape `\\q` in string literal");
+        let output = compile_error_from_string_test(r#"print("a\0b");"#.to_string());
+        assert_eq!(output, "invalid escape `\\0` in string literal");
+    }
+
+    #[test]
+    fn test_compile_string_unicode_and_null_bytes() {
+        let input = r#"
+        let greeting = "h\u{E9}llo w\u{F6}rld \u{1F389}";
+        print(greeting);
+        let bytes = "a\u{0}b";
+        print(bytes);
+        print(bytes == "a\u{0}c");
+        let joined = "\u{E9}" + bytes + "\u{1F389}";
+        print(joined);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "\"h\u{E9}llo w\u{F6}rld \u{1F389}\"\n\"a\0b\"\nfalse\n\"\u{E9}a\0b\u{1F389}\"\n"
+        );
+    }
+
+    #[test]
+    fn test_compile_assert_passes_silently() {
+        let input = r#"
+        assert(1 == 1);
+        print(1);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "1\n");
+    }
+
+    #[test]
+    fn test_compile_failed_assert_exits() {
+        // a failed assert ends the process running the execution engine, so the program is
+        // run by this test again in a child process rather than from the shared bin/main
+        let input = "print(41);\nassert(1 == 2);\nprint(42);";
+        if std::env::var("CYCLO_ASSERT_TEST").is_ok() {
+            compile_output_from_string(input.to_string(), ExecutionMode::Jit, None);
+            return;
+        }
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "test::test_compile_failed_assert_exits", "--nocapture"])
+            .env("CYCLO_ASSERT_TEST", "1")
+            .output()
+            .expect("unable to rerun the test");
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("41"));
+        assert!(!stdout.contains("42"));
+        assert!(!stdout.contains("assertion failed"));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("assertion failed at 2:1: 1 == 2"));
+
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap_err().to_string(),
+            "assertion failed: 1 == 2"
+        );
+    }
+
+    #[test]
+    fn test_compile_print_list() {
+        let input = r#"
+        print([1, 2, 3]);
+        print(["a", "b"]);
+        print([1, -1, 2]);
+        print(len([1, -1, 2]));
+        print([5000000000, -1]);
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "[1, 2, 3]\n[\"a\", \"b\"]\n[1, -1, 2]\n3\n[5000000000, -1]\n");
+        let exprs = parse_cyclo_program("print([1, 2, 3]);").expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), "[1, 2, 3]\n");
+    }
+
+    #[test]
+    fn test_compile_string_chars() {
+        let input = r#"
+        let s = "abc";
+        let c = s.chars();
+        print(len(c));
+        print(c);
+        for ch in "hi".chars() {
+            print(ch);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "3\n[\"a\", \"b\", \"c\"]\n\"h\"\n\"i\"\n");
+    }
+
+    #[test]
+    fn test_compile_string_split() {
+        let input = r#"
+        let parts = "a,b,,c".split(",");
+        print(len(parts));
+        print(parts);
+        print("".split(","));
+        for part in "x y".split(" ") {
+            print(part);
+        }
+        print("a, b,c".split(", "));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "3\n[\"a\", \"b\", \"c\"]\n[]\n\"x\"\n\"y\"\n[\"a\", \"b,c\"]\n"
+        );
+        let input = r#"
+        print(len("a,b,,c".split(",")));
+        for part in "x y".split(" ") {
+            print(part);
+        }
+        print("a, b,c".split(", "));
+        "#;
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(
+            interpret_program(&exprs).unwrap(),
+            "3\n\"x\"\n\"y\"\n[\"a\", \"b,c\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_compile_string_case() {
+        let input = r#"
+        let s = "Hello World 123";
+        print(s.to_upper());
+        print(s.to_lower());
+        print(s);
+        print("".to_upper());
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "\"HELLO WORLD 123\"\n\"hello world 123\"\n\"Hello World 123\"\n\"\"\n"
+        );
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_string_trim() {
+        let input = r#"
+        print(" \t\n ".trim());
+        print("  hi".trim());
+        print("hi \n".trim());
+        print(" \t hi there \n".trim());
+        print("  hi  ".trim_start());
+        print("  hi  ".trim_end());
+        print("hi".trim_start());
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "\"\"\n\"hi\"\n\"hi\"\n\"hi there\"\n\"hi  \"\n\"  hi\"\n\"hi\"\n"
+        );
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_list_sort() {
+        let input = r#"
+        let unsorted = [5, 3, 9, 1, 3];
+        unsorted.sort();
+        print(unsorted);
+        unsorted.sort_desc();
+        print(unsorted);
+        let sorted = [1, 2, 3];
+        sorted.sort();
+        print(sorted);
+        let single = [42];
+        single.sort_desc();
+        print(single);
+        print(len(single));
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(
+            output,
+            "[1, 3, 3, 5, 9]\n[9, 5, 3, 3, 1]\n[1, 2, 3]\n[42]\n1\n"
+        );
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_list_contains() {
+        let input = r#"
+        let values = [4, 8, 15, 16];
+        print(values.contains(15));
+        print(values.contains(5));
+        let x = 4;
+        print(values.contains(x));
+        let names = ["ann", "bob"];
+        print(names.contains("bob"));
+        print(names.contains("bo"));
+        if (names.contains("ann")) {
+            print(1);
+        }
+        "#;
+        let output = compile_output_from_string_test(input.to_string());
+        assert_eq!(output, "true\nfalse\ntrue\ntrue\nfalse\n1\n");
+        let exprs = parse_cyclo_program(input).expect("unable to parse contents");
+        assert_eq!(interpret_program(&exprs).unwrap(), output);
+    }
+
+    #[test]
+    fn test_compile_list_contains_wrong_type() {
+        let output = compile_error_from_string_test(
+            r#"let values = [1, 2]; print(values.contains("a"));"#.to_string(),
+        );
+        assert_eq!(output, "unable to check if List<i32> contains string");
+    }
+
+    #[test]
+    fn test_compile_unknown_method() {
+        let output = compile_error_from_string_test(r#"let x = 1; print(x.chars());"#.to_string());
+        assert_eq!(output, "type i32 has no method `chars` in x.chars()");
+    }
 }
@@ -1,6 +1,6 @@
-use crate::compiler::{self, CompileOptions};
-use anyhow::Result;
-use cyclang_parser::{parse_cyclo_program, Expression};
+use crate::compiler::ReplSession;
+use anyhow::{anyhow, Result};
+use cyclang_parser::parse_cyclo_program;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
 use rustyline::{Cmd, EventHandler, KeyCode, KeyEvent, Modifiers};
@@ -16,21 +16,26 @@ pub fn run() {
         EventHandler::Simple(Cmd::Newline),
     );
 
+    let mut session = match ReplSession::init() {
+        Ok(session) => session,
+        Err(e) => {
+            println!("{}", e.to_string().red());
+            return;
+        }
+    };
+
     loop {
         let line = rl.readline(">> ");
         match line {
             Ok(input) => match input.trim() {
                 "exit()" => break,
-                _ => match parse_and_compile(input.to_string(), &mut rl) {
-                    Ok(output) => {
-                        if !output.is_empty() {
-                            println!("{:?}", output)
-                        }
-                    }
-                    Err(e) => {
+                "" => continue,
+                _ => {
+                    let _ = rl.add_history_entry(input.as_str());
+                    if let Err(e) = parse_and_eval(&input, &mut session) {
                         println!("{}", e.to_string().red());
                     }
-                },
+                }
             },
             Err(ReadlineError::Interrupted) => {
                 println!("Did you want to exit? Type exit()");
@@ -47,26 +52,28 @@ pub fn run() {
     }
 }
 
-fn parse_and_compile(input: String, rl: &mut DefaultEditor) -> Result<String> {
-    let joined_history = rl
-        .history()
-        .iter()
-        .map(|s| &**s) // Convert &String to &str
-        .collect::<Vec<&str>>()
-        .join("\n");
+fn parse_and_eval(input: &str, session: &mut ReplSession) -> Result<()> {
+    // the trailing semicolon is optional for a single statement in the repl
+    let input = input.trim_end();
+    let exprs = if input.ends_with(';') || input.ends_with('}') {
+        parse_cyclo_program(input)
+    } else {
+        parse_cyclo_program(&format!("{};", input))
+    }
+    .map_err(|e| anyhow!(e))?;
+    session.eval(exprs)
+}
 
-    let final_string = format!("{}{}", joined_history, input);
-    let exprs = parse_cyclo_program(&final_string)?;
-    let compile_options = Some(CompileOptions {
-        is_execution_engine: true,
-        target: None,
-    });
-    let output = compiler::compile(exprs.clone(), compile_options)?;
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    for expr in parse_cyclo_program(&input)? {
-        if let Expression::LetStmt(_, _, _) | Expression::FuncStmt(_, _, _, _) = expr {
-            let _ = rl.add_history_entry(input.as_str());
-        }
+    #[test]
+    fn test_repl_state_persists_between_lines() {
+        let mut session = ReplSession::init().unwrap();
+        parse_and_eval("let a = 40;", &mut session).unwrap();
+        parse_and_eval("fn add_two(i32 x) -> i32 { return x + 2; }", &mut session).unwrap();
+        parse_and_eval("add_two(a)", &mut session).unwrap();
+        assert!(parse_and_eval("b + 1;", &mut session).is_err());
     }
-    Ok(output)
 }
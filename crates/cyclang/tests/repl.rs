@@ -0,0 +1,59 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// runs `cyclang repl` with `lines` piped to stdin, the JIT output is flushed after every line
+fn repl_output(lines: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cyclang"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("unable to start cyclang repl");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(lines.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn has_line(output: &str, expected: &str) -> bool {
+    output
+        .lines()
+        .any(|line| line.trim_start_matches(">> ") == expected)
+}
+
+#[test]
+fn test_repl_keeps_variables_between_lines() {
+    let output = repl_output("let x = 5\nprint(x + 1)\n");
+    assert!(has_line(&output, "6"), "unexpected output: {}", output);
+}
+
+#[test]
+fn test_repl_continues_after_parse_error() {
+    let output = repl_output("let x = 5\nlet = ;\nprint(x)\n");
+    assert!(has_line(&output, "5"), "unexpected output: {}", output);
+}
+
+#[test]
+fn test_repl_runs_each_line_once() {
+    let output = repl_output("print(\"once\")\nlet x = 1\nx = x + 1\nprint(x)\n");
+    let printed = output.matches("\"once\"").count();
+    assert_eq!(printed, 1, "unexpected output: {}", output);
+    assert!(has_line(&output, "2"), "unexpected output: {}", output);
+}
+
+#[test]
+fn test_repl_continues_after_compile_error() {
+    let output = repl_output("let x = 5\nlet y = x + z\nprint(x)\nprint(y)\n");
+    assert!(has_line(&output, "5"), "unexpected output: {}", output);
+    // the line that failed didn't declare y
+    assert!(
+        output.contains("Unknown variable y"),
+        "unexpected output: {}",
+        output
+    );
+}